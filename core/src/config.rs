@@ -1,91 +1,711 @@
-use std::path::PathBuf;
+use crate::features::model::FeatureType;
+use crate::features::pages::model::DatetimeFormat;
+use crate::io::{has_extension, DEFAULT_MAX_FILE_BYTES};
+use crate::io::path_utils::normalize_path;
+use crate::parser::model::PageFrontMatter;
+use anyhow::{bail, Context, Result};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// What a sync does when several files claim the same identifier.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    /// Every claimant is left out until the clash is fixed.
+    #[default]
+    Reject,
+    /// The first claimant by filename keeps the identifier and each later
+    /// one gets the first free `-2`, `-3`, ... suffix.
+    AppendSuffix,
+}
+
+impl CollisionPolicy {
+    /// Parses the names used by `COLLISION_POLICY`.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "reject" => Some(CollisionPolicy::Reject),
+            "append_suffix" => Some(CollisionPolicy::AppendSuffix),
+            _ => None,
+        }
+    }
+}
+
+/// How a page without an `identifier` in its frontmatter is named from its
+/// path relative to the pages dir.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IdentifierStrategy {
+    /// `blog/post.md` becomes `blog/post`.
+    #[default]
+    FullPath,
+    /// `blog/post.md` becomes `post`. Files sharing a stem in different
+    /// dirs collide, and `COLLISION_POLICY` decides between them.
+    Stem,
+    /// `blog/2024-01-31-post.md` becomes `blog/post`, and the date fills in
+    /// `created_datetime` when the frontmatter has none. Files not named
+    /// `YYYY-MM-DD-title` are named as `FullPath` would.
+    JekyllDate,
+}
+
+impl IdentifierStrategy {
+    /// Parses the names used by `IDENTIFIER_STRATEGY`.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "full_path" => Some(IdentifierStrategy::FullPath),
+            "stem" => Some(IdentifierStrategy::Stem),
+            "jekyll_date" => Some(IdentifierStrategy::JekyllDate),
+            _ => None,
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct ChasquiConfig {
     pub database_url: String,
+    /// Size of the read pool serving API lookups.
     pub max_connections: u32,
+    /// Size of the write pool used by syncs; SQLite runs one writer at a time.
+    pub max_write_connections: u32,
+    // pragmas applied to every pooled SQLite connection
+    pub sqlite_journal_mode: String,
+    pub sqlite_synchronous: String,
+    pub sqlite_busy_timeout_ms: u64,
+    pub sqlite_cache_size: i64,
+    pub sqlite_foreign_keys: bool,
 
     // Mount Points
     pub pages_dir: PathBuf,
     pub images_dir: PathBuf,
     pub audio_dir: PathBuf,
     pub videos_dir: PathBuf,
+    /// Further content roots, each laid out like the primary one
+    /// (`md/`, `images/`, `audio/`, `videos/`).
+    pub extra_content_dirs: Vec<PathBuf>,
+    /// File extensions read as pages, lowercased and without the dot.
+    pub content_extensions: Vec<String>,
+    /// Content files larger than this are skipped rather than read.
+    pub max_file_bytes: u64,
+    /// Extensions served verbatim under `/assets`, lowercased and without
+    /// the dot. Empty turns the passthrough off.
+    pub passthrough_extensions: Vec<String>,
+    /// Create missing content dirs at startup instead of waiting for them.
+    pub create_content_dirs: bool,
+    /// How many times startup re-checks for a missing page dir before
+    /// giving up.
+    pub content_dir_retries: u32,
 
     pub page_strip_extension: bool,
     pub asset_strip_extension: bool,
     pub serve_home: bool,
     pub home_identifier: String,
+    /// Page served, with a 404 status, for identifiers that don't exist.
+    /// Empty disables it.
+    pub not_found_identifier: String,
+    pub folder_index_pages: bool,
+    pub resolve_html_links: bool,
+    pub normalize_slashes: bool,
+    pub strict_links: bool,
     pub webhook_url: String,
     pub webhook_secret: String,
+    pub webhook_payload: Option<serde_json::Value>,
+    /// At most one build notification in flight; any asked for meanwhile
+    /// are merged into a single follow-up.
+    pub webhook_coalesce: bool,
     pub port: u16,
     pub nginx_media_prefixes: bool,
+    pub site_url: String,
+    pub base_path: String,
+    pub watch_debounce_ms: u64,
+    pub cors_allowed_origins: Vec<String>,
+    pub slugify_identifiers: bool,
+    pub collision_policy: CollisionPolicy,
+    pub identifier_strategy: IdentifierStrategy,
+    pub admin_token: String,
+    pub required_frontmatter: Vec<String>,
+    pub html_template_path: Option<PathBuf>,
+    /// Holds `{name}.html` templates that pages pick with `layout: name`.
+    pub html_layouts_dir: Option<PathBuf>,
+    pub created_datetime_aliases: Vec<String>,
+    pub modified_datetime_aliases: Vec<String>,
+    /// Keep each page file's atime as `accessed_datetime`. Off by default,
+    /// since `noatime`/`relatime` mounts leave it stale or unset.
+    pub track_accessed_datetime: bool,
+    /// Keep pages marked `published: false`, or with a `created_datetime`
+    /// still to come, from being served until they're out.
+    pub hide_unpublished_pages: bool,
+    pub max_request_body_bytes: usize,
+    pub max_list_pages: usize,
+    pub max_batch_pages: usize,
+    /// Default timestamp format of JSON pages; `?datetime_format=` overrides it.
+    pub json_datetime_format: DatetimeFormat,
+    pub compiled_cache_entries: usize,
+    pub dedupe_page_bodies: bool,
+    /// `max-age` in seconds for page responses.
+    pub page_cache_max_age: u64,
+    /// Pages unmodified for this many days get `stable_page_max_age`
+    /// instead; 0 turns that off.
+    pub stable_page_after_days: u64,
+    pub stable_page_max_age: u64,
+    /// `max-age` in seconds for page lists and feeds.
+    pub list_cache_max_age: u64,
+    pub soft_delete: bool,
+    pub tombstone_retention_days: u64,
+    pub git_repo: Option<PathBuf>,
+    pub git_ref: String,
+    pub git_poll_secs: u64,
+    /// Seconds between external link checks; 0 turns the checker off.
+    pub link_check_interval_secs: u64,
+    pub link_check_concurrency: usize,
+    pub link_check_timeout_secs: u64,
 }
 
 impl ChasquiConfig {
-    pub fn from_env() -> Self {
-        let database_url = std::env::var("DATABASE_URL")
-            .expect("Failed to determine DATABASE_URL from environment variables");
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
 
-        let max_connections = std::env::var("MAX_CONNECTIONS")
-            .ok()
+    /// Reads settings from a TOML file instead of the environment. Keys are
+    /// the environment variable names in lowercase, so `MAX_CONNECTIONS=20`
+    /// becomes `max_connections = 20`; lists may be written as arrays. A key
+    /// that isn't a setting is an error rather than silently ignored.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let file = read_config_file(path)?;
+        Self::from_checked_file(&file, path, |name| file_var(&file, name))
+    }
+
+    /// Like `from_file`, but any setting also present in the environment
+    /// takes the environment's value.
+    pub fn from_env_and_file(path: &Path) -> Result<Self> {
+        let file = read_config_file(path)?;
+        Self::from_checked_file(&file, path, |name| {
+            std::env::var(name).ok().or_else(|| file_var(&file, name))
+        })
+    }
+
+    // every name `from_vars` looks up is a setting, so a file key it never
+    // asked for is a typo or a setting that doesn't exist
+    fn from_checked_file(
+        file: &toml::Table,
+        path: &Path,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let looked_up = RefCell::new(HashSet::new());
+        let config = Self::from_vars(|name| {
+            looked_up.borrow_mut().insert(name.to_lowercase());
+            var(name)
+        })?;
+
+        let looked_up = looked_up.into_inner();
+        let unknown: Vec<&str> = file
+            .keys()
+            .filter(|key| !looked_up.contains(key.as_str()))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            bail!(
+                "Unknown settings in config file {}: {}",
+                path.display(),
+                unknown.join(", ")
+            );
+        }
+        Ok(config)
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let database_url = var("DATABASE_URL")
+            .context("DATABASE_URL is set neither in the environment nor in the config file")?;
+
+        let max_connections = var("MAX_CONNECTIONS")
             .and_then(|val| val.parse::<u32>().ok())
             .unwrap_or(15);
 
-        let content_root = std::env::var("CONTENT_DIR").unwrap_or_else(|_| "./content".to_string());
+        // more than one writer only ever waits on SQLite's write lock
+        let max_write_connections = var("MAX_WRITE_CONNECTIONS")
+            .and_then(|val| val.parse::<u32>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(1);
+
+        // WAL lets the watcher write while the API keeps reading
+        let sqlite_journal_mode =
+            var("SQLITE_JOURNAL_MODE").unwrap_or_else(|| "WAL".to_string());
+        let sqlite_synchronous =
+            var("SQLITE_SYNCHRONOUS").unwrap_or_else(|| "NORMAL".to_string());
+
+        let sqlite_busy_timeout_ms = var("SQLITE_BUSY_TIMEOUT_MS")
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(5000);
+
+        // negative values are KiB rather than pages, so this is 64 MiB
+        let sqlite_cache_size = var("SQLITE_CACHE_SIZE")
+            .and_then(|val| val.parse::<i64>().ok())
+            .unwrap_or(-64000);
 
-        let pages_dir = resolve_dir("PAGES_DIR", &format!("{}/md", content_root));
-        let images_dir = resolve_dir("IMAGES_DIR", &format!("{}/images", content_root));
-        let audio_dir = resolve_dir("AUDIO_DIR", &format!("{}/audio", content_root));
-        let videos_dir = resolve_dir("VIDEOS_DIR", &format!("{}/videos", content_root));
+        let sqlite_foreign_keys =
+            var("SQLITE_FOREIGN_KEYS").unwrap_or_else(|| "true".to_string()) == "true";
 
-        let page_strip_extension = std::env::var("DEFAULT_PAGE_IDENTIFIER_STRIP_EXTENSION")
-            .unwrap_or_else(|_| "true".to_string())
+        // CONTENT_DIRS is colon-separated; the first root is the primary one and
+        // honours the PAGES_DIR/IMAGES_DIR/... overrides below
+        let mut content_roots: Vec<String> = var("CONTENT_DIRS")
+            .unwrap_or_default()
+            .split(':')
+            .map(|root| root.trim().to_string())
+            .filter(|root| !root.is_empty())
+            .collect();
+        // read either way, so a config file holding both is still checked
+        let content_dir = var("CONTENT_DIR");
+        if content_roots.is_empty() {
+            content_roots.push(content_dir.unwrap_or_else(|| "./content".to_string()));
+        }
+        let content_root = content_roots.remove(0);
+        let extra_content_dirs = content_roots
+            .iter()
+            .map(|root| std::fs::canonicalize(root).unwrap_or_else(|_| PathBuf::from(root)))
+            .collect();
+
+        let pages_dir = resolve_dir(&var, "PAGES_DIR", &format!("{}/md", content_root));
+        let images_dir = resolve_dir(&var, "IMAGES_DIR", &format!("{}/images", content_root));
+        let audio_dir = resolve_dir(&var, "AUDIO_DIR", &format!("{}/audio", content_root));
+        let videos_dir = resolve_dir(&var, "VIDEOS_DIR", &format!("{}/videos", content_root));
+
+        // extensions read as pages; .mdx and friends are plain markdown, JSX included
+        let mut content_extensions: Vec<String> = parse_list_var(&var, "CONTENT_EXTENSIONS")
+            .into_iter()
+            .map(|ext| ext.trim_start_matches('.').to_lowercase())
+            .collect();
+        if content_extensions.is_empty() {
+            content_extensions.push("md".to_string());
+        }
+
+        // generous, since big pages are fine, but finite so a stray huge file
+        // can't be read into memory
+        let max_file_bytes = var("MAX_FILE_BYTES")
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_MAX_FILE_BYTES);
+
+        // data files beside the pages, e.g. json, served as-is; page
+        // extensions are left out so markdown is never sent raw
+        let passthrough_extensions: Vec<String> = parse_list_var(&var, "PASSTHROUGH_EXTENSIONS")
+            .into_iter()
+            .map(|ext| ext.trim_start_matches('.').to_lowercase())
+            .filter(|ext| !content_extensions.contains(ext))
+            .collect();
+
+        // off by default, so a mistyped path fails loudly rather than
+        // serving an empty dir
+        let create_content_dirs =
+            var("CREATE_CONTENT_DIRS").unwrap_or_else(|| "false".to_string()) == "true";
+
+        // a volume mounted just after the server starts is waited for
+        let content_dir_retries = var("CONTENT_DIR_RETRIES")
+            .and_then(|val| val.parse::<u32>().ok())
+            .unwrap_or(5);
+
+        let page_strip_extension = var("DEFAULT_PAGE_IDENTIFIER_STRIP_EXTENSION")
+            .unwrap_or_else(|| "true".to_string())
             == "true";
 
-        let asset_strip_extension = std::env::var("DEFAULT_ASSET_IDENTIFIER_STRIP_EXTENSION")
-            .unwrap_or_else(|_| "false".to_string())
+        let asset_strip_extension = var("DEFAULT_ASSET_IDENTIFIER_STRIP_EXTENSION")
+            .unwrap_or_else(|| "false".to_string())
             == "true";
 
-        let serve_home = std::env::var("ROUTER_SERVE_HOME_AT_DEFAULT")
-            .unwrap_or_else(|_| "true".to_string())
+        // ROUTER_SERVE_HOME_AT_DEFAULT is the original name and still honoured
+        let legacy_serve_home = var("ROUTER_SERVE_HOME_AT_DEFAULT");
+        let serve_home = var("SERVE_HOME")
+            .or(legacy_serve_home)
+            .unwrap_or_else(|| "true".to_string())
             == "true";
 
         let home_identifier =
-            std::env::var("HOME_IDENTIFIER").unwrap_or_else(|_| "index".to_string());
+            var("HOME_IDENTIFIER").unwrap_or_else(|| "index".to_string());
+
+        // misses render this page if it exists; set empty for a bare 404
+        let not_found_identifier =
+            var("NOT_FOUND_IDENTIFIER").unwrap_or_else(|| "404".to_string());
+
+        // `blog/index.md` becomes `blog` rather than `blog/index`
+        let folder_index_pages = var("FOLDER_INDEX_PAGES")
+            .unwrap_or_else(|| "false".to_string())
+            == "true";
 
-        let webhook_url = std::env::var("FRONTEND_WEBHOOK_URL")
-            .unwrap_or_else(|_| "http://127.0.0.1:4000/build".to_string());
+        // `/about/` and `//about` look up the `about` page
+        let normalize_slashes =
+            var("NORMALIZE_SLASHES").unwrap_or_else(|| "false".to_string()) == "true";
 
-        let webhook_secret = std::env::var("WEBHOOK_SECRET").unwrap_or_default();
+        // a page with an unresolved internal link is rejected from the sync
+        let strict_links =
+            var("STRICT_LINKS").unwrap_or_else(|| "false".to_string()) == "true";
 
-        let port = std::env::var("PORT")
-            .ok()
+        // treat `about.html` links as references to `about.md`
+        let resolve_html_links = var("RESOLVE_HTML_LINKS")
+            .unwrap_or_else(|| "false".to_string())
+            == "true";
+
+        let webhook_url = var("FRONTEND_WEBHOOK_URL")
+            .unwrap_or_else(|| "http://127.0.0.1:4000/build".to_string());
+
+        let webhook_secret = var("WEBHOOK_SECRET").unwrap_or_default();
+
+        // JSON body for build notifications; `"{{changes}}"` marks where the
+        // changed/deleted identifier lists go
+        let webhook_payload = var("WEBHOOK_PAYLOAD")
+            .filter(|raw| !raw.trim().is_empty())
+            .and_then(|raw| match serde_json::from_str(&raw) {
+                Ok(payload) => Some(payload),
+                Err(e) => {
+                    tracing::warn!(error = %e, "ignoring WEBHOOK_PAYLOAD that is not valid JSON");
+                    None
+                }
+            });
+
+        // hold notifications back while one is in flight, then send them as one
+        let webhook_coalesce = var("WEBHOOK_COALESCE").unwrap_or_else(|| "true".to_string()) == "true";
+
+        // empty disables the admin endpoints entirely
+        let admin_token = var("ADMIN_TOKEN").unwrap_or_default();
+
+        let port = var("PORT")
             .and_then(|val| val.parse::<u16>().ok())
             .unwrap_or(3000);
 
+        let site_url =
+            var("SITE_URL").unwrap_or_else(|| format!("http://127.0.0.1:{}", port));
+
+        // `/docs`, `docs/` and `/docs/` are all stored as `/docs`; empty means
+        // pages live at the site root
+        let base_path = var("BASE_PATH")
+            .map(|path| path.trim_matches('/').to_string())
+            .filter(|path| !path.is_empty())
+            .map(|path| format!("/{}", path))
+            .unwrap_or_default();
+
+        let watch_debounce_ms = var("WATCH_DEBOUNCE_MS")
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(1500);
+
+        let cors_allowed_origins = parse_list_var(&var, "CORS_ALLOWED_ORIGINS");
+
+        let slugify_identifiers = var("SLUGIFY_IDENTIFIERS")
+            .unwrap_or_else(|| "false".to_string())
+            == "true";
+
+        let collision_policy = match var("COLLISION_POLICY") {
+            Some(name) => CollisionPolicy::parse(&name).unwrap_or_else(|| {
+                tracing::warn!(policy = %name, "unknown COLLISION_POLICY, using reject");
+                CollisionPolicy::Reject
+            }),
+            None => CollisionPolicy::Reject,
+        };
+
+        let identifier_strategy = match var("IDENTIFIER_STRATEGY") {
+            Some(name) => IdentifierStrategy::parse(&name).unwrap_or_else(|| {
+                tracing::warn!(strategy = %name, "unknown IDENTIFIER_STRATEGY, using full_path");
+                IdentifierStrategy::FullPath
+            }),
+            None => IdentifierStrategy::FullPath,
+        };
+
+        let required_frontmatter = parse_list_var(&var, "REQUIRED_FRONTMATTER")
+            .into_iter()
+            .filter(|field| {
+                let known = PageFrontMatter::FIELDS.contains(&field.as_str());
+                if !known {
+                    tracing::warn!(field = %field, "ignoring unknown REQUIRED_FRONTMATTER field");
+                }
+                known
+            })
+            .collect();
+
+        let html_template_path = var("HTML_TEMPLATE_PATH")
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from);
+
+        let html_layouts_dir = var("HTML_LAYOUTS_DIR")
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from);
+
+        // e.g. `date,published` for Jekyll-style posts
+        let created_datetime_aliases = parse_list_var(&var, "CREATED_DATETIME_ALIASES");
+        let modified_datetime_aliases = parse_list_var(&var, "MODIFIED_DATETIME_ALIASES");
+
+        // atime is read at sync, so it's only as fresh as the last sync of a page
+        let track_accessed_datetime =
+            var("TRACK_ACCESSED_DATETIME").unwrap_or_else(|| "false".to_string()) == "true";
+
+        // judged per request, so a scheduled page goes live without a sync
+        let hide_unpublished_pages =
+            var("HIDE_UNPUBLISHED_PAGES").unwrap_or_else(|| "false".to_string()) == "true";
+
+        let max_request_body_bytes = var("MAX_REQUEST_BODY_BYTES")
+            .and_then(|val| val.parse::<usize>().ok())
+            .unwrap_or(1024 * 1024);
+
+        // the list endpoint never returns more than this many pages at once
+        let max_list_pages = var("MAX_LIST_PAGES")
+            .and_then(|val| val.parse::<usize>().ok())
+            .unwrap_or(1000);
+
+        // identifiers accepted by one POST /api/pages/batch request
+        let max_batch_pages = var("MAX_BATCH_PAGES")
+            .and_then(|val| val.parse::<usize>().ok())
+            .unwrap_or(50);
+
+        // `legacy` keeps the zone-less format older clients parse
+        let json_datetime_format = match var("JSON_DATETIME_FORMAT") {
+            Some(name) => DatetimeFormat::parse(&name).unwrap_or_else(|| {
+                tracing::warn!(format = %name, "unknown JSON_DATETIME_FORMAT, using legacy");
+                DatetimeFormat::Legacy
+            }),
+            None => DatetimeFormat::Legacy,
+        };
+
+        // compiled page bodies kept across syncs; 0 turns the cache off
+        let compiled_cache_entries = var("COMPILED_CACHE_ENTRIES")
+            .and_then(|val| val.parse::<usize>().ok())
+            .unwrap_or(0);
+
+        // Cache-Control max-age for pages, long-unchanged pages and lists/feeds
+        let page_cache_max_age = var("PAGE_CACHE_MAX_AGE")
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(300);
+        let stable_page_after_days = var("STABLE_PAGE_AFTER_DAYS")
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(0);
+        let stable_page_max_age = var("STABLE_PAGE_MAX_AGE")
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(86400);
+        let list_cache_max_age = var("LIST_CACHE_MAX_AGE")
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(60);
+
+        // identical page bodies share one row in `page_bodies`
+        let dedupe_page_bodies = var("DEDUPE_PAGE_BODIES")
+            .unwrap_or_else(|| "false".to_string())
+            == "true";
+
+        // deleted pages keep a tombstone row so their URLs answer 410 Gone
+        let soft_delete =
+            var("SOFT_DELETE").unwrap_or_else(|| "false".to_string()) == "true";
+
+        let tombstone_retention_days = var("TOMBSTONE_RETENTION_DAYS")
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(30);
+
+        // serve content from a git ref instead of the working tree
+        let git_repo = var("CONTENT_GIT_REPO")
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from);
+
+        let git_ref = var("CONTENT_GIT_REF").unwrap_or_else(|| "HEAD".to_string());
+
+        let git_poll_secs = var("CONTENT_GIT_POLL_SECS")
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(30);
+
+        // background HEAD requests to the external links in pages, reported
+        // at /admin/link-health
+        let link_check_interval_secs = var("LINK_CHECK_INTERVAL_SECS")
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        let link_check_concurrency = var("LINK_CHECK_CONCURRENCY")
+            .and_then(|val| val.parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(4);
+
+        let link_check_timeout_secs = var("LINK_CHECK_TIMEOUT_SECS")
+            .and_then(|val| val.parse::<u64>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(10);
+
         let nginx_media_prefixes =
-            std::env::var("NGINX_MEDIA_PREFIXES").unwrap_or_else(|_| "true".to_string()) == "true";
+            var("NGINX_MEDIA_PREFIXES").unwrap_or_else(|| "true".to_string()) == "true";
 
-        Self {
+        Ok(Self {
             database_url,
             max_connections,
+            max_write_connections,
+            sqlite_journal_mode,
+            sqlite_synchronous,
+            sqlite_busy_timeout_ms,
+            sqlite_cache_size,
+            sqlite_foreign_keys,
             pages_dir,
             images_dir,
             audio_dir,
             videos_dir,
+            extra_content_dirs,
+            content_extensions,
+            max_file_bytes,
+            passthrough_extensions,
+            create_content_dirs,
+            content_dir_retries,
             page_strip_extension,
             asset_strip_extension,
             serve_home,
             home_identifier,
+            not_found_identifier,
+            folder_index_pages,
+            resolve_html_links,
+            normalize_slashes,
+            strict_links,
             webhook_url,
             webhook_secret,
+            webhook_payload,
+            webhook_coalesce,
             port,
             nginx_media_prefixes,
+            site_url,
+            base_path,
+            watch_debounce_ms,
+            cors_allowed_origins,
+            slugify_identifiers,
+            collision_policy,
+            identifier_strategy,
+            admin_token,
+            required_frontmatter,
+            html_template_path,
+            html_layouts_dir,
+            created_datetime_aliases,
+            modified_datetime_aliases,
+            track_accessed_datetime,
+            hide_unpublished_pages,
+            max_request_body_bytes,
+            max_list_pages,
+            max_batch_pages,
+            json_datetime_format,
+            compiled_cache_entries,
+            dedupe_page_bodies,
+            page_cache_max_age,
+            stable_page_after_days,
+            stable_page_max_age,
+            list_cache_max_age,
+            soft_delete,
+            tombstone_retention_days,
+            git_repo,
+            git_ref,
+            git_poll_secs,
+            link_check_interval_secs,
+            link_check_concurrency,
+            link_check_timeout_secs,
+        })
+    }
+
+    /// Every watched directory paired with the feature type it holds, primary
+    /// root first.
+    pub fn mounts(&self) -> Vec<(PathBuf, FeatureType)> {
+        let mut mounts = vec![
+            (self.pages_dir.clone(), FeatureType::Page),
+            (self.images_dir.clone(), FeatureType::Image),
+            (self.audio_dir.clone(), FeatureType::Audio),
+            (self.videos_dir.clone(), FeatureType::Video),
+        ];
+
+        for root in &self.extra_content_dirs {
+            mounts.push((root.join("md"), FeatureType::Page));
+            mounts.push((root.join("images"), FeatureType::Image));
+            mounts.push((root.join("audio"), FeatureType::Audio));
+            mounts.push((root.join("videos"), FeatureType::Video));
+        }
+
+        mounts
+    }
+
+    /// The key `path` is tracked under: its path below `mount`, behind an
+    /// `@<dir name>/` prefix for mounts of an extra root, so the same
+    /// relative path under two roots stays two files. `None` when `path`
+    /// isn't below `mount`.
+    pub fn content_key(&self, path: &Path, mount: &Path) -> Option<String> {
+        let relative = normalize_path(path.strip_prefix(mount).ok()?);
+        let primary = [&self.pages_dir, &self.images_dir, &self.audio_dir, &self.videos_dir];
+        let extra_root = mount
+            .parent()
+            .filter(|_| !primary.iter().any(|dir| dir.as_path() == mount))
+            .and_then(|root| Some((root, self.extra_content_dirs.iter().position(|r| r == root)?)));
+
+        match extra_root {
+            Some((root, position)) => {
+                let name = root
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| (position + 1).to_string());
+                Some(format!("@{}/{}", name, relative))
+            }
+            None => Some(relative),
+        }
+    }
+
+    /// Page dirs that don't exist. Media dirs are optional, so a root
+    /// without `audio/` or `videos/` isn't reported.
+    pub fn missing_page_dirs(&self) -> Vec<PathBuf> {
+        self.mounts()
+            .into_iter()
+            .filter(|(dir, f_type)| *f_type == FeatureType::Page && !dir.is_dir())
+            .map(|(dir, _)| dir)
+            .collect()
+    }
+
+    /// Creates every mount dir that doesn't exist yet, media dirs included,
+    /// so all of them can be watched.
+    pub fn create_missing_dirs(&self) -> Result<()> {
+        for (dir, _) in self.mounts() {
+            if !dir.is_dir() {
+                std::fs::create_dir_all(&dir)
+                    .context(format!("Failed to create content dir {}", dir.display()))?;
+                tracing::info!(dir = %dir.display(), "created missing content dir");
+            }
         }
+        Ok(())
+    }
+
+    /// Whether `path` has one of the `CONTENT_EXTENSIONS`. Everything that
+    /// decides what counts as a page goes through this.
+    pub fn is_content_file(&self, path: &Path) -> bool {
+        has_extension(path, &self.content_extensions)
+    }
+
+    /// Whether `path` has one of the `PASSTHROUGH_EXTENSIONS`.
+    pub fn is_passthrough_file(&self, path: &Path) -> bool {
+        has_extension(path, &self.passthrough_extensions)
     }
 }
 
-fn resolve_dir(env_var: &str, default: &str) -> PathBuf {
-    let path_str = std::env::var(env_var).unwrap_or_else(|_| default.to_string());
+fn resolve_dir(var: impl Fn(&str) -> Option<String>, name: &str, default: &str) -> PathBuf {
+    let path_str = var(name).unwrap_or_else(|| default.to_string());
     std::fs::canonicalize(&path_str).unwrap_or_else(|_| PathBuf::from(path_str))
-}
\ No newline at end of file
+}
+fn parse_list_var(var: impl Fn(&str) -> Option<String>, name: &str) -> Vec<String> {
+    var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn read_config_file(path: &Path) -> Result<toml::Table> {
+    let raw = std::fs::read_to_string(path)
+        .context(format!("Failed to read config file {}", path.display()))?;
+    toml::from_str(&raw).context(format!("Failed to parse config file {}", path.display()))
+}
+
+// file values go through the same parsing as environment variables, so
+// they're flattened back into the strings an environment would hold
+fn file_var(file: &toml::Table, name: &str) -> Option<String> {
+    let value = file.get(&name.to_lowercase())?;
+    match value {
+        toml::Value::String(s) => Some(s.clone()),
+        toml::Value::Array(items) => {
+            let separator = if name == "CONTENT_DIRS" { ":" } else { "," };
+            let items: Vec<String> = items
+                .iter()
+                .map(|item| match item {
+                    toml::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect();
+            Some(items.join(separator))
+        }
+        // e.g. `webhook_payload` written as a table rather than a JSON string
+        toml::Value::Table(_) => serde_json::to_string(value).ok(),
+        other => Some(other.to_string()),
+    }
+}
@@ -0,0 +1,84 @@
+use std::path::{Path, PathBuf};
+
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+pub type ChasquiResult<T> = Result<T, ChasquiError>;
+
+/// What went wrong while turning content into stored features, so callers can
+/// tell a missing file from bad frontmatter from a database failure.
+#[derive(Debug, thiserror::Error)]
+pub enum ChasquiError {
+    #[error("failed to read {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: BoxError,
+    },
+
+    #[error("invalid frontmatter in {filename}: {source}")]
+    FrontmatterParse {
+        filename: String,
+        #[source]
+        source: BoxError,
+    },
+
+    #[error("failed to resolve links in {filename}: {source}")]
+    LinkResolution {
+        filename: String,
+        #[source]
+        source: BoxError,
+    },
+
+    /// A write was refused because an identifier is already live under
+    /// another file. Batched writes can't tell which row clashed, so this
+    /// lists every identifier in the failed statement.
+    #[error("identifier already claimed by another file: {}", identifiers.join(", "))]
+    Collision { identifiers: Vec<String> },
+
+    #[error("{context}: {source}")]
+    Database {
+        context: String,
+        #[source]
+        source: BoxError,
+    },
+
+    /// The content was read fine but isn't acceptable as a feature. Such
+    /// files are left out of the sync rather than failing it.
+    #[error("{filename}: {reason}")]
+    Validation { filename: String, reason: String },
+}
+
+impl ChasquiError {
+    pub fn io(path: &Path, source: impl Into<BoxError>) -> Self {
+        ChasquiError::Io {
+            path: path.to_path_buf(),
+            source: source.into(),
+        }
+    }
+
+    pub fn database(context: impl Into<String>, source: impl Into<BoxError>) -> Self {
+        ChasquiError::Database {
+            context: context.into(),
+            source: source.into(),
+        }
+    }
+
+    /// Stable snake_case name of the variant, for reports and logs.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ChasquiError::Io { .. } => "io",
+            ChasquiError::FrontmatterParse { .. } => "frontmatter_parse",
+            ChasquiError::LinkResolution { .. } => "link_resolution",
+            ChasquiError::Collision { .. } => "collision",
+            ChasquiError::Database { .. } => "database",
+            ChasquiError::Validation { .. } => "validation",
+        }
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl From<sqlx::Error> for ChasquiError {
+    fn from(e: sqlx::Error) -> Self {
+        ChasquiError::database("database query failed", e)
+    }
+}
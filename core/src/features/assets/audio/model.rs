@@ -2,6 +2,7 @@ use crate::features::assets::model::CommonAssetMetadata;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct AudioAsset {
     pub metadata: CommonAssetMetadata,
     pub bitrate_kbps: Option<u32>,
@@ -2,6 +2,7 @@ use crate::features::assets::model::CommonAssetMetadata;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ImageAsset {
     pub metadata: CommonAssetMetadata,
     pub width: Option<u32>,
@@ -4,14 +4,17 @@ use std::path::PathBuf;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CommonAssetMetadata {
     pub id: Uuid,
     pub filename: String,
     pub identifier: Option<String>,
     #[serde(skip_serializing)]
+    #[cfg_attr(feature = "openapi", schema(ignore))]
     pub file_path: PathBuf,
     pub content_hash: String,
     #[serde(skip_serializing)]
+    #[cfg_attr(feature = "openapi", schema(ignore))]
     pub new_path: Option<PathBuf>,
     pub bytes_size: u64,
     pub created_at: Option<NaiveDateTime>,
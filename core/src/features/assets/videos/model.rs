@@ -2,6 +2,7 @@ use crate::features::assets::model::CommonAssetMetadata;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct VideoAsset {
     pub metadata: CommonAssetMetadata,
     pub duration_seconds: Option<u32>,
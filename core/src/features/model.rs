@@ -13,6 +13,7 @@ pub enum Feature {
 }
 
 #[derive(Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(tag = "type", content = "data")]
 pub enum JsonFeature {
     Page(crate::features::pages::model::JsonPage),
@@ -1,5 +1,8 @@
+use crate::parser::markdown::{render_html, text_preview, PREVIEW_MAX_CHARS};
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -7,46 +10,171 @@ pub struct Page {
     pub identifier: String,
     pub filename: String,
     pub name: Option<String>,
+    pub description: Option<String>,
     pub md_content: String,
+    /// `md_content`'s excerpt as HTML, rendered once when the page is built
+    /// or loaded rather than for every response.
+    pub excerpt_html: Option<String>,
     pub content_hash: String,
+    pub body_hash: String,
     pub tags: Vec<String>,
+    pub metadata: BTreeMap<String, Value>,
+    /// Extra identifiers that redirect to this page.
+    pub aliases: Vec<String>,
+    /// Identifier or URL this page permanently redirects to.
+    pub redirect_to: Option<String>,
+    /// Set when the frontmatter was malformed and the page was built from
+    /// defaults instead.
+    pub frontmatter_error: Option<String>,
+    /// Filenames of the pages spliced into this one by includes, nested ones
+    /// too. Not stored, so only known for pages built since startup.
+    pub includes: Vec<String>,
     pub modified_datetime: Option<NaiveDateTime>,
     pub created_datetime: Option<NaiveDateTime>,
+    /// The file's last access time, as of its last sync. Only kept with
+    /// `TRACK_ACCESSED_DATETIME`, and `None` where the filesystem has none.
+    pub accessed_datetime: Option<NaiveDateTime>,
     pub file_path: PathBuf,
     pub new_path: Option<PathBuf>,
 }
 
+impl Page {
+    /// Whether the page is out by `now`: not marked `published: false` and
+    /// not dated later than `now`. A page without a `created_datetime` is
+    /// never held back by its date.
+    pub fn is_published_at(&self, now: NaiveDateTime) -> bool {
+        self.metadata.get("published") != Some(&Value::Bool(false))
+            && self.created_datetime.is_none_or(|created| created <= now)
+    }
+}
+
+/// A version a page file was saved with. `md_content` is only loaded when a
+/// single revision is asked for, not when listing a page's history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageRevision {
+    pub content_hash: String,
+    pub saved_at: NaiveDateTime,
+    pub md_content: Option<String>,
+}
+
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct JsonPage {
     pub identifier: String,
     pub filename: String,
     pub name: Option<String>,
+    pub description: Option<String>,
+    /// Everything before `<!-- more -->` rendered to HTML, or the
+    /// auto-excerpt when there's no such delimiter.
+    #[serde(default)]
+    pub excerpt_html: Option<String>,
     pub md_content: String,
     pub content_hash: String,
     pub tags: Vec<String>,
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub metadata: BTreeMap<String, Value>,
+    pub aliases: Vec<String>,
+    pub redirect_to: Option<String>,
+    #[serde(default)]
+    pub has_frontmatter_error: bool,
     pub modified_datetime: Option<String>,
     pub created_datetime: Option<String>,
+    /// Only present with `TRACK_ACCESSED_DATETIME`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accessed_datetime: Option<String>,
+    /// Bytes of HTML the page renders to; only with `?preview=true`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub html_length: Option<usize>,
+    /// The first few hundred characters of the page as plain text; only
+    /// with `?preview=true`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct JsonPageRevision {
+    pub content_hash: String,
+    pub saved_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub md_content: Option<String>,
+}
+
+/// How `JsonPage` writes its timestamps, all of which are UTC.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DatetimeFormat {
+    /// `2023-01-01 12:00:00`, with no zone marker.
+    #[default]
+    Legacy,
+    /// `2023-01-01T12:00:00Z`.
+    Rfc3339,
+}
+
+impl DatetimeFormat {
+    /// Parses the names used by `JSON_DATETIME_FORMAT` and `?datetime_format=`.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "legacy" => Some(DatetimeFormat::Legacy),
+            "rfc3339" => Some(DatetimeFormat::Rfc3339),
+            _ => None,
+        }
+    }
+
+    pub fn format(self, datetime: NaiveDateTime) -> String {
+        match self {
+            DatetimeFormat::Legacy => datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            DatetimeFormat::Rfc3339 => datetime.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        }
+    }
 }
 
 impl From<&Page> for JsonPage {
     fn from(page: &Page) -> Self {
-        let format = "%Y-%m-%d %H:%M:%S";
-        let modified_datetime = page
-            .modified_datetime
-            .map(|dt| dt.format(format).to_string());
-        let created_datetime = page
-            .created_datetime
-            .map(|dt| dt.format(format).to_string());
+        JsonPage::with_datetime_format(page, DatetimeFormat::Legacy)
+    }
+}
+
+impl JsonPage {
+    pub fn with_datetime_format(page: &Page, format: DatetimeFormat) -> Self {
+        let modified_datetime = page.modified_datetime.map(|dt| format.format(dt));
+        let created_datetime = page.created_datetime.map(|dt| format.format(dt));
+        let accessed_datetime = page.accessed_datetime.map(|dt| format.format(dt));
 
         JsonPage {
             identifier: page.identifier.clone(),
             filename: page.filename.clone(),
             name: page.name.clone(),
+            description: page.description.clone(),
+            excerpt_html: page.excerpt_html.clone(),
             md_content: page.md_content.clone(),
             content_hash: page.content_hash.clone(),
             tags: page.tags.clone(),
+            metadata: page.metadata.clone(),
+            aliases: page.aliases.clone(),
+            redirect_to: page.redirect_to.clone(),
+            has_frontmatter_error: page.frontmatter_error.is_some(),
             modified_datetime,
             created_datetime,
+            accessed_datetime,
+            html_length: None,
+            preview: None,
         }
     }
-}
\ No newline at end of file
+
+    /// Adds `html_length` and `preview`, for list views that show a page's
+    /// size and opening words without fetching its body.
+    pub fn with_preview(mut self, page: &Page) -> Self {
+        self.html_length = Some(render_html(&page.md_content).len());
+        self.preview = Some(text_preview(&page.md_content, PREVIEW_MAX_CHARS));
+        self
+    }
+}
+impl JsonPageRevision {
+    pub fn with_datetime_format(revision: &PageRevision, format: DatetimeFormat) -> Self {
+        JsonPageRevision {
+            content_hash: revision.content_hash.clone(),
+            saved_at: format.format(revision.saved_at),
+            md_content: revision.md_content.clone(),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::io::{ContentMetadata, ContentReader, PathStream, SyncFile};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Wraps another reader and remembers `read_to_string` and `get_metadata`
+/// results per path, so a file is fetched from the backend at most once while
+/// the cache lives. Meant to be scoped to a single sync batch; anything that
+/// changes a file mid-batch should `invalidate` it.
+pub struct CachingContentReader {
+    inner: Arc<dyn ContentReader>,
+    strings: Mutex<HashMap<PathBuf, String>>,
+    metadata: Mutex<HashMap<PathBuf, ContentMetadata>>,
+}
+
+impl CachingContentReader {
+    pub fn new(inner: Arc<dyn ContentReader>) -> Self {
+        Self {
+            inner,
+            strings: Mutex::new(HashMap::new()),
+            metadata: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn invalidate(&self, path: &Path) {
+        self.strings.lock().unwrap().remove(path);
+        self.metadata.lock().unwrap().remove(path);
+    }
+}
+
+#[async_trait]
+impl ContentReader for CachingContentReader {
+    async fn read_to_string(&self, path: &Path) -> Result<String> {
+        if let Some(content) = self.strings.lock().unwrap().get(path) {
+            return Ok(content.clone());
+        }
+
+        // errors are not cached; a failed read is retried next time
+        let content = self.inner.read_to_string(path).await?;
+        self.strings
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), content.clone());
+        Ok(content)
+    }
+
+    async fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        self.inner.read_bytes(path).await
+    }
+
+    async fn open_file(&self, path: &Path) -> Result<SyncFile> {
+        self.inner.open_file(path).await
+    }
+
+    async fn get_hash(&self, path: &Path) -> Result<String> {
+        self.inner.get_hash(path).await
+    }
+
+    async fn get_metadata(&self, path: &Path) -> Result<ContentMetadata> {
+        if let Some(metadata) = self.metadata.lock().unwrap().get(path) {
+            return Ok(metadata.clone());
+        }
+
+        let metadata = self.inner.get_metadata(path).await?;
+        self.metadata
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), metadata.clone());
+        Ok(metadata)
+    }
+
+    async fn list_all_files(&self, root: &Path) -> Result<Vec<PathBuf>> {
+        self.inner.list_all_files(root).await
+    }
+
+    async fn list_files_by_extension(&self, root: &Path, extension: String) {
+        self.inner.list_files_by_extension(root, extension).await
+    }
+
+    fn stream_markdown_files<'a>(&'a self, root: &'a Path, extensions: &'a [String]) -> PathStream<'a> {
+        self.inner.stream_markdown_files(root, extensions)
+    }
+}
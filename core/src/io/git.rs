@@ -0,0 +1,262 @@
+use crate::io::{
+    check_file_size, has_extension, ContentMetadata, ContentReader, PathStream, SyncFile,
+    DEFAULT_MAX_FILE_BYTES,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime};
+use futures_util::stream;
+use git2::{ObjectType, Repository, Sort, TreeWalkMode, TreeWalkResult};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub use git2::Oid;
+
+/// Reads content straight out of a git repository at a fixed ref, without a
+/// checkout. The ref's tree is mounted at `root_path`, so a blob at
+/// `md/post.md` is read through `{root_path}/md/post.md`.
+///
+/// The ref is resolved on every call, so advancing a branch is picked up by
+/// the next sync.
+pub struct GitContentReader {
+    repo: Mutex<Repository>,
+    reference: String,
+    root_path: PathBuf,
+    max_file_bytes: u64,
+    // last-change times per path, rebuilt whenever the ref moves
+    history: Mutex<Option<(Oid, HashMap<PathBuf, FileHistory>)>>,
+}
+
+#[derive(Clone, Copy)]
+struct FileHistory {
+    modified: NaiveDateTime,
+    created: NaiveDateTime,
+}
+
+impl GitContentReader {
+    pub fn open(repo_path: &Path, reference: &str, root_path: PathBuf) -> Result<Self> {
+        let repo = Repository::open(repo_path)
+            .context(format!("Failed to open git repository {}", repo_path.display()))?;
+
+        let reader = Self {
+            repo: Mutex::new(repo),
+            reference: reference.to_string(),
+            root_path,
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            history: Mutex::new(None),
+        };
+        reader.head_commit()?;
+        Ok(reader)
+    }
+
+    /// Blobs larger than this are refused instead of loaded.
+    pub fn with_max_file_bytes(mut self, max_file_bytes: u64) -> Self {
+        self.max_file_bytes = max_file_bytes;
+        self
+    }
+
+    /// The commit the configured ref currently points at.
+    pub fn head_commit(&self) -> Result<Oid> {
+        let repo = self.lock_repo()?;
+        let commit = repo
+            .revparse_single(&self.reference)
+            .and_then(|obj| obj.peel_to_commit())
+            .context(format!("Failed to resolve git ref {}", self.reference))?;
+        Ok(commit.id())
+    }
+
+    /// Paths whose blobs were removed between two commits, as content paths
+    /// below `root_path`.
+    pub fn deleted_paths(&self, from: Oid, to: Oid) -> Result<Vec<PathBuf>> {
+        let repo = self.lock_repo()?;
+        let old_tree = repo.find_commit(from)?.tree()?;
+        let new_tree = repo.find_commit(to)?.tree()?;
+        let diff = repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)?;
+
+        Ok(diff
+            .deltas()
+            .filter(|delta| delta.status() == git2::Delta::Deleted)
+            .filter_map(|delta| delta.old_file().path().map(|p| self.root_path.join(p)))
+            .collect())
+    }
+
+    fn lock_repo(&self) -> Result<std::sync::MutexGuard<'_, Repository>> {
+        self.repo
+            .lock()
+            .map_err(|_| anyhow!("git repository lock poisoned"))
+    }
+
+    fn tree_path<'a>(&self, path: &'a Path) -> Result<&'a Path> {
+        match path.strip_prefix(&self.root_path) {
+            Ok(rel) => Ok(rel),
+            Err(_) => bail!(
+                "Security Violation: Absolute path outside root: {:?}",
+                path
+            ),
+        }
+    }
+
+    fn blob_id(&self, repo: &Repository, path: &Path) -> Result<Oid> {
+        let rel = self.tree_path(path)?;
+        let tree = repo
+            .revparse_single(&self.reference)?
+            .peel_to_commit()?
+            .tree()?;
+        let entry = tree
+            .get_path(rel)
+            .context(format!("{} not found at {}", rel.display(), self.reference))?;
+        Ok(entry.id())
+    }
+
+    // read from the object header, without inflating the blob
+    fn blob_size(&self, path: &Path) -> Result<u64> {
+        let repo = self.lock_repo()?;
+        let id = self.blob_id(&repo, path)?;
+        let (size, _) = repo.odb()?.read_header(id)?;
+        Ok(size as u64)
+    }
+
+    fn read_blob(&self, path: &Path) -> Result<Vec<u8>> {
+        check_file_size(path, self.blob_size(path)?, self.max_file_bytes)?;
+        let repo = self.lock_repo()?;
+        let blob = repo
+            .find_blob(self.blob_id(&repo, path)?)
+            .context(format!("{} is not a file", path.display()))?;
+        Ok(blob.content().to_vec())
+    }
+
+    // Walks history once per ref position, keeping the newest and oldest
+    // commit that touched each path.
+    fn file_history(&self, rel: &Path) -> Result<Option<FileHistory>> {
+        let head = self.head_commit()?;
+        let mut cached = self
+            .history
+            .lock()
+            .map_err(|_| anyhow!("git history lock poisoned"))?;
+
+        if cached.as_ref().map(|(oid, _)| *oid) != Some(head) {
+            *cached = Some((head, self.build_history(head)?));
+        }
+
+        Ok(cached.as_ref().and_then(|(_, map)| map.get(rel).copied()))
+    }
+
+    fn build_history(&self, head: Oid) -> Result<HashMap<PathBuf, FileHistory>> {
+        let repo = self.lock_repo()?;
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(Sort::TIME)?;
+        revwalk.push(head)?;
+
+        let mut history: HashMap<PathBuf, FileHistory> = HashMap::new();
+        for oid in revwalk {
+            let commit = repo.find_commit(oid?)?;
+            let Some(time) = DateTime::from_timestamp(commit.time().seconds(), 0) else {
+                continue;
+            };
+            let time = time.naive_utc();
+
+            let tree = commit.tree()?;
+            let parent_tree = match commit.parents().next() {
+                Some(parent) => Some(parent.tree()?),
+                None => None,
+            };
+            let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+
+            for delta in diff.deltas() {
+                let Some(path) = delta.new_file().path() else {
+                    continue;
+                };
+                // newest commits come first, so the first sighting is the
+                // last modification and the final one the creation
+                history
+                    .entry(path.to_path_buf())
+                    .and_modify(|h| h.created = time)
+                    .or_insert(FileHistory {
+                        modified: time,
+                        created: time,
+                    });
+            }
+        }
+
+        Ok(history)
+    }
+
+    fn list_blobs(&self, root: &Path, extensions: Option<&[String]>) -> Result<Vec<PathBuf>> {
+        let repo = self.lock_repo()?;
+        let tree = repo
+            .revparse_single(&self.reference)?
+            .peel_to_commit()?
+            .tree()?;
+
+        let mut entries = Vec::new();
+        tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+            if entry.kind() != Some(ObjectType::Blob) {
+                return TreeWalkResult::Ok;
+            }
+            let Some(name) = entry.name() else {
+                return TreeWalkResult::Ok;
+            };
+
+            let path = self.root_path.join(dir).join(name);
+            let matches_ext = extensions.is_none_or(|exts| has_extension(&path, exts));
+            if path.starts_with(root) && matches_ext {
+                entries.push(path);
+            }
+            TreeWalkResult::Ok
+        })?;
+
+        Ok(entries)
+    }
+}
+
+#[async_trait]
+impl ContentReader for GitContentReader {
+    async fn read_to_string(&self, path: &Path) -> Result<String> {
+        let bytes = self.read_blob(path)?;
+        String::from_utf8(bytes).context(format!("{} is not valid UTF-8", path.display()))
+    }
+
+    async fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        self.read_blob(path)
+    }
+
+    async fn open_file(&self, path: &Path) -> Result<SyncFile> {
+        Ok(Box::new(Cursor::new(self.read_blob(path)?)))
+    }
+
+    async fn get_hash(&self, path: &Path) -> Result<String> {
+        let bytes = self.read_blob(path)?;
+        Ok(format!("{:016x}", xxhash_rust::xxh3::xxh3_64(&bytes)))
+    }
+
+    async fn get_metadata(&self, path: &Path) -> Result<ContentMetadata> {
+        let size = self.blob_size(path)?;
+        let history = self.file_history(self.tree_path(path)?)?;
+
+        Ok(ContentMetadata {
+            modified: history.map(|h| h.modified),
+            created: history.map(|h| h.created),
+            // a checkout's files are read by the reader itself, so git has no atime
+            accessed: None,
+            size,
+        })
+    }
+
+    async fn list_all_files(&self, root: &Path) -> Result<Vec<PathBuf>> {
+        self.list_blobs(root, None)
+    }
+
+    async fn list_files_by_extension(&self, _root: &Path, _extension: String) {}
+
+    // the tree walk is in-memory and holds the repo lock, so it runs to
+    // completion up front
+    fn stream_markdown_files<'a>(&'a self, root: &'a Path, extensions: &'a [String]) -> PathStream<'a> {
+        let paths = match self.list_blobs(root, Some(extensions)) {
+            Ok(paths) => paths.into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        };
+        Box::pin(stream::iter(paths))
+    }
+}
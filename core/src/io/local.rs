@@ -1,32 +1,50 @@
 use crate::io::{
-    verified_fs_metadata, verified_fs_read, verified_fs_read_to_string, verify_absolute_path,
-    ContentMetadata, ContentReader,
+    check_file_size, has_extension, open_without_atime, verified_fs_metadata, verified_fs_read, verified_fs_read_to_string, verify_absolute_path,
+    ContentMetadata, ContentReader, PathStream, VerifiedPath,
 };
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use futures_util::stream;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use tracing::warn;
 use walkdir::WalkDir;
 
+// paths a streamed walk may get ahead of its consumer by
+const WALK_CHANNEL_CAPACITY: usize = 256;
+
 pub struct LocalContentReader {
     pub root_path: PathBuf,
+    /// Files larger than this are refused by `read_to_string` and `read_bytes`.
+    pub max_file_bytes: u64,
+}
+
+impl LocalContentReader {
+    fn check_size(&self, path: &VerifiedPath) -> Result<()> {
+        let size = std::fs::metadata(path.as_path())?.len();
+        check_file_size(path.as_path(), size, self.max_file_bytes)
+    }
 }
 
 #[async_trait]
 impl ContentReader for LocalContentReader {
     async fn read_to_string(&self, path: &Path) -> Result<String> {
         let verified = verify_absolute_path(&self.root_path, path)?;
+        self.check_size(&verified)?;
         verified_fs_read_to_string(verified)
     }
 
     async fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
         let verified = verify_absolute_path(&self.root_path, path)?;
+        self.check_size(&verified)?;
         verified_fs_read(verified)
     }
 
     async fn open_file(&self, path: &Path) -> Result<crate::io::SyncFile> {
         let verified = verify_absolute_path(&self.root_path, path)?;
-        let file = std::fs::File::open(verified)?;
+        let file = open_without_atime(verified.as_path())?;
         Ok(Box::new(file))
     }
 
@@ -35,7 +53,7 @@ impl ContentReader for LocalContentReader {
         use xxhash_rust::xxh3::Xxh3;
 
         let verified = verify_absolute_path(&self.root_path, path)?;
-        let file = std::fs::File::open(verified)?;
+        let file = open_without_atime(verified.as_path())?;
         let mut reader = BufReader::with_capacity(64 * 1024, file);
         let mut hasher = Xxh3::new();
         let mut buffer = [0u8; 64 * 1024];
@@ -63,38 +81,137 @@ impl ContentReader for LocalContentReader {
             .created()
             .ok()
             .map(|t| DateTime::<Utc>::from(t).naive_utc());
+        let accessed = metadata
+            .accessed()
+            .ok()
+            .map(|t| DateTime::<Utc>::from(t).naive_utc());
 
         Ok(ContentMetadata {
             modified,
             created,
+            accessed,
             size: metadata.len(),
         })
     }
 
-    async fn list_markdown_files(&self, root: &Path) -> Result<Vec<PathBuf>> {
-        let mut entries = Vec::new();
-        for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
-            if entry.file_type().is_file()
-                && entry.path().extension().and_then(|s| s.to_str()) == Some("md")
-            {
-                entries.push(entry.into_path());
+    // the walk blocks on the filesystem, so it runs on the blocking pool and
+    // hands paths over a bounded channel, pausing whenever the consumer
+    // falls behind. It starts on the first poll and stops once the stream
+    // is dropped.
+    fn stream_markdown_files<'a>(&'a self, root: &'a Path, extensions: &'a [String]) -> PathStream<'a> {
+        let (tx, rx) = mpsc::channel(WALK_CHANNEL_CAPACITY);
+        let root = root.to_path_buf();
+        let extensions = extensions.to_vec();
+        let mut walk = Some(move || {
+            for path in FileWalk::new(&root).filter(|path| has_extension(path, &extensions)) {
+                if tx.blocking_send(path).is_err() {
+                    break;
+                }
             }
-        }
-        Ok(entries)
+        });
+
+        Box::pin(stream::unfold(rx, move |mut rx| {
+            if let Some(walk) = walk.take() {
+                tokio::task::spawn_blocking(walk);
+            }
+            async move { rx.recv().await.map(|path| (Ok(path), rx)) }
+        }))
     }
 
     async fn list_all_files(&self, root: &Path) -> Result<Vec<PathBuf>> {
-        let mut entries = Vec::new();
-        for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
-            if entry.file_type().is_file() {
-                entries.push(entry.into_path());
-            }
-        }
-        Ok(entries)
+        let root = root.to_path_buf();
+        Ok(tokio::task::spawn_blocking(move || FileWalk::new(&root).collect()).await?)
     }
 
     async fn list_files_by_extension(&self, _root: &Path, _extension: String) {
         // Implementation logic if needed, but SyncService will likely use list_all_files
         // or we can implement it similarly to list_markdown_files
     }
-}
\ No newline at end of file
+}
+
+// Follows symlinks so linked content is picked up, but skips loops and
+// anything whose real location lies outside `root`. A file reachable through
+// several paths is listed once, preferring a path without links in it, so an
+// alias never collides with the file it points at.
+//
+// Unlinked paths are yielded as the walk reaches them. Linked ones are held
+// back until the walk ends, since their unlinked twin may still turn up.
+struct FileWalk {
+    root: PathBuf,
+    real_root: PathBuf,
+    walker: Option<walkdir::IntoIter>,
+    seen: HashSet<PathBuf>,
+    linked: HashMap<PathBuf, PathBuf>,
+    deferred: std::vec::IntoIter<PathBuf>,
+}
+
+impl FileWalk {
+    fn new(root: &Path) -> Self {
+        let real_root = std::fs::canonicalize(root).ok();
+        Self {
+            root: root.to_path_buf(),
+            walker: real_root
+                .is_some()
+                .then(|| WalkDir::new(root).follow_links(true).into_iter()),
+            real_root: real_root.unwrap_or_default(),
+            seen: HashSet::new(),
+            linked: HashMap::new(),
+            deferred: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl Iterator for FileWalk {
+    type Item = PathBuf;
+
+    fn next(&mut self) -> Option<PathBuf> {
+        while let Some(entry) = self.walker.as_mut().and_then(|w| w.next()) {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    match e.loop_ancestor() {
+                        Some(ancestor) => warn!(
+                            path = ?e.path(),
+                            ancestor = %ancestor.display(),
+                            "skipping symlink loop"
+                        ),
+                        None => warn!(error = %e, "skipping unreadable path"),
+                    }
+                    continue;
+                }
+            };
+
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let real = match std::fs::canonicalize(entry.path()) {
+                Ok(real) if verify_absolute_path(&self.real_root, &real).is_ok() => real,
+                _ => {
+                    warn!(path = %entry.path().display(), "skipping path that resolves outside the content root");
+                    continue;
+                }
+            };
+
+            let unlinked = self
+                .real_root
+                .join(entry.path().strip_prefix(&self.root).unwrap_or(entry.path()));
+            if real != unlinked {
+                self.linked.entry(real).or_insert_with(|| entry.into_path());
+            } else if self.seen.insert(real) {
+                return Some(entry.into_path());
+            }
+        }
+
+        if self.walker.take().is_some() {
+            let linked = std::mem::take(&mut self.linked);
+            self.deferred = linked
+                .into_iter()
+                .filter(|(real, _)| !self.seen.contains(real))
+                .map(|(_, path)| path)
+                .collect::<Vec<_>>()
+                .into_iter();
+        }
+        self.deferred.next()
+    }
+}
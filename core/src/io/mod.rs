@@ -1,8 +1,14 @@
+use crate::error::ChasquiError;
 use anyhow::{bail, Result};
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
+use futures_util::stream::BoxStream;
+use futures_util::TryStreamExt;
+use std::io::Read;
 use std::path::{Component, Path, PathBuf};
 
+pub mod caching;
+pub mod git;
 pub mod local;
 pub mod path_utils;
 
@@ -21,11 +27,33 @@ impl AsRef<Path> for VerifiedPath {
 }
 
 pub fn verified_fs_read_to_string(path: VerifiedPath) -> Result<String> {
-    Ok(std::fs::read_to_string(path.as_path())?)
+    let mut content = String::new();
+    open_without_atime(path.as_path())?.read_to_string(&mut content)?;
+    Ok(content)
 }
 
 pub fn verified_fs_read(path: VerifiedPath) -> Result<Vec<u8>> {
-    Ok(std::fs::read(path.as_path())?)
+    let mut content = Vec::new();
+    open_without_atime(path.as_path())?.read_to_end(&mut content)?;
+    Ok(content)
+}
+
+/// Opens `path` for reading without bumping its access time where the OS
+/// allows it, so a page's `accessed` reflects readers other than us. Linux
+/// only grants this to the file's owner; anyone else gets a plain open.
+pub fn open_without_atime(path: &Path) -> std::io::Result<std::fs::File> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        let no_atime = std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NOATIME)
+            .open(path);
+        if let Ok(file) = no_atime {
+            return Ok(file);
+        }
+    }
+    std::fs::File::open(path)
 }
 
 pub fn verified_fs_metadata(path: VerifiedPath) -> Result<std::fs::Metadata> {
@@ -71,8 +99,15 @@ pub fn verify_relative_path(
     Ok(VerifiedPath(final_path))
 }
 
+// `starts_with` only compares components, so `root/../elsewhere` would pass
+// it; nothing below the root may step back up
 pub fn verify_absolute_path(root: &Path, absolute_path: &Path) -> Result<VerifiedPath> {
-    if absolute_path.starts_with(root) {
+    let below_root = absolute_path.strip_prefix(root).map(|rest| {
+        rest.components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    });
+
+    if below_root.unwrap_or(false) {
         Ok(VerifiedPath(absolute_path.to_path_buf()))
     } else {
         bail!(
@@ -84,6 +119,31 @@ pub fn verify_absolute_path(root: &Path, absolute_path: &Path) -> Result<Verifie
 
 pub type SyncFile = Box<dyn SyncStream>;
 
+/// Default for `MAX_FILE_BYTES`, 10 MiB.
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Refuses a file of `size` bytes when it is over `max_bytes`, so a stray
+/// huge file in the content dir is skipped instead of read into memory.
+pub fn check_file_size(path: &Path, size: u64, max_bytes: u64) -> Result<()> {
+    if size > max_bytes {
+        return Err(ChasquiError::Validation {
+            filename: path.display().to_string(),
+            reason: format!("{} bytes exceeds MAX_FILE_BYTES ({})", size, max_bytes),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Whether `path`'s extension is one of `extensions`, ignoring case.
+pub fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    path.extension()
+        .and_then(|s| s.to_str())
+        .is_some_and(|ext| extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+}
+
+pub type PathStream<'a> = BoxStream<'a, Result<PathBuf>>;
+
 pub trait SyncStream: std::io::Read + std::io::Seek + Send {}
 impl<T: std::io::Read + std::io::Seek + Send> SyncStream for T {}
 
@@ -98,13 +158,22 @@ pub trait ContentReader: Send + Sync {
     // to fulfill the old purpose of list_markdown_files
     async fn list_files_by_extension(&self, root: &Path, extension: String); // not String maybe idk
 
+    /// Markdown files below `root`, i.e. those with one of `extensions`,
+    /// yielded as the listing finds them so a slow or paginated backend
+    /// doesn't hold up the first path until the last one is known.
+    fn stream_markdown_files<'a>(&'a self, root: &'a Path, extensions: &'a [String]) -> PathStream<'a>;
+
     // TODO: sunset
-    async fn list_markdown_files(&self, root: &Path) -> Result<Vec<PathBuf>>;
+    async fn list_markdown_files(&self, root: &Path, extensions: &[String]) -> Result<Vec<PathBuf>> {
+        self.stream_markdown_files(root, extensions).try_collect().await
+    }
 }
 
 #[derive(Clone)]
 pub struct ContentMetadata {
     pub modified: Option<NaiveDateTime>,
     pub created: Option<NaiveDateTime>,
+    /// Last access time, where the reader and filesystem record one.
+    pub accessed: Option<NaiveDateTime>,
     pub size: u64,
 }
\ No newline at end of file
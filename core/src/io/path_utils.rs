@@ -1,9 +1,26 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use unicode_normalization::UnicodeNormalization;
 
 pub fn normalize_path<P: AsRef<Path>>(path: P) -> String {
     path.as_ref().to_string_lossy().replace("\\", "/")
 }
 
+/// Appended to a page's file name to name its sidecar metadata file, so
+/// `post.md` takes its metadata from `post.md.meta.yaml`.
+pub const SIDECAR_SUFFIX: &str = ".meta.yaml";
+
+pub fn sidecar_path(page: &Path) -> PathBuf {
+    let mut path = page.as_os_str().to_os_string();
+    path.push(SIDECAR_SUFFIX);
+    PathBuf::from(path)
+}
+
+/// The page a sidecar metadata file belongs to, or `None` if `path` isn't one.
+pub fn sidecar_page(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?.strip_suffix(SIDECAR_SUFFIX)?;
+    (!name.is_empty()).then(|| path.with_file_name(name))
+}
+
 pub fn normalize_logical_path<P: AsRef<Path>>(path: P) -> String {
     use std::path::Component;
     let mut components = Vec::new();
@@ -53,6 +70,42 @@ pub fn sanitize_identifier(id: &str) -> String {
     final_id.to_string()
 }
 
+/// Reduces an author-supplied identifier to a URL-safe slug: accents are
+/// stripped, ASCII alphanumerics are lowercased, and every other run of
+/// characters becomes a single hyphen. `/` separates segments and is kept.
+/// May return an empty string when nothing slug-worthy remains.
+pub fn slugify_identifier(id: &str) -> String {
+    if id.contains("..") {
+        return "".to_string();
+    }
+
+    let mut segments = Vec::new();
+
+    for segment in normalize_logical_path(id).split('/') {
+        let mut slug = String::with_capacity(segment.len());
+        let mut pending_dash = false;
+
+        // NFD splits "é" into "e" + a combining mark, which is then dropped
+        for c in segment.nfd() {
+            if c.is_ascii_alphanumeric() {
+                if pending_dash && !slug.is_empty() {
+                    slug.push('-');
+                }
+                slug.push(c.to_ascii_lowercase());
+                pending_dash = false;
+            } else if c.is_ascii() || c.is_whitespace() {
+                pending_dash = true;
+            }
+        }
+
+        if !slug.is_empty() {
+            segments.push(slug);
+        }
+    }
+
+    segments.join("/")
+}
+
 pub fn path_to_identifier(path: &Path, strip_extension: bool) -> String {
     let raw = if strip_extension {
         path.with_extension("").to_string_lossy().to_string()
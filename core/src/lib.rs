@@ -1,4 +1,5 @@
 pub mod config;
+pub mod error;
 pub mod features;
 pub mod io;
 pub mod notifier;
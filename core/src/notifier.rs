@@ -1,7 +1,43 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// Identifiers touched since the previous build notification, so frontends
+/// can rebuild incrementally.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildChanges {
+    pub changed: BTreeSet<String>,
+    pub deleted: BTreeSet<String>,
+}
+
+impl BuildChanges {
+    pub fn record_changed(&mut self, identifier: String) {
+        self.deleted.remove(&identifier);
+        self.changed.insert(identifier);
+    }
+
+    pub fn record_deleted(&mut self, identifier: String) {
+        self.changed.remove(&identifier);
+        self.deleted.insert(identifier);
+    }
+
+    /// Folds `other` in as if its changes happened after ours.
+    pub fn merge(&mut self, other: BuildChanges) {
+        for identifier in other.changed {
+            self.record_changed(identifier);
+        }
+        for identifier in other.deleted {
+            self.record_deleted(identifier);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.deleted.is_empty()
+    }
+}
 
 #[async_trait]
 pub trait ContentBuildNotifier: Send + Sync {
-    async fn notify(&self) -> Result<()>;
-}
\ No newline at end of file
+    async fn notify(&self, changes: &BuildChanges) -> Result<()>;
+}
@@ -1,9 +1,12 @@
+use crate::io::path_utils::slugify_identifier;
 use crate::parser::model::PageFrontMatter;
 use anyhow::Result;
-use gray_matter::{engine::YAML, Matter};
-use pulldown_cmark::{Event, Options as CmarkOptions, Parser, Tag};
+use gray_matter::{engine::{JSON, YAML}, Matter};
+use pulldown_cmark::{Event, HeadingLevel, Options as CmarkOptions, Parser, Tag, TagEnd};
 use pulldown_cmark_to_cmark::cmark;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::ops::Range;
 
 pub fn is_external_url(url: &str) -> bool {
     url.starts_with("http://")
@@ -17,7 +20,9 @@ pub fn apply_nginx_prefix(url: &str, enable_prefixes: bool) -> String {
         return url.to_string();
     }
 
-    let extension = url.rsplit('.').next().map(|s| s.to_lowercase());
+    // a fragment is not part of the file name, e.g. `/post#v1.2` is not media
+    let path = url.split('#').next().unwrap_or(url);
+    let extension = path.rsplit('.').next().map(|s| s.to_lowercase());
 
     let prefix_map = get_media_nginx_prefix_map();
 
@@ -51,7 +56,19 @@ fn get_media_nginx_prefix_map() -> HashMap<&'static str, &'static str> {
     map
 }
 
+// Editors on Windows save with a UTF-8 BOM and `\r\n` endings, neither of
+// which the fence search below expects.
+fn normalize_line_endings(md_content: &str) -> Cow<'_, str> {
+    let md_content = md_content.strip_prefix('\u{FEFF}').unwrap_or(md_content);
+    if md_content.contains('\r') {
+        Cow::Owned(md_content.replace("\r\n", "\n").replace('\r', "\n"))
+    } else {
+        Cow::Borrowed(md_content)
+    }
+}
+
 pub fn extract_frontmatter(md_content: &str, filename: &str) -> Result<(PageFrontMatter, String)> {
+    let md_content = &*normalize_line_endings(md_content);
     if !md_content.starts_with("---") {
         return Ok((PageFrontMatter::default(), md_content.to_string()));
     }
@@ -63,19 +80,31 @@ pub fn extract_frontmatter(md_content: &str, filename: &str) -> Result<(PageFron
         let frontmatter_block = &md_content[..body_start];
         let body_content = &md_content[body_start..];
 
-        let matter = Matter::<YAML>::new();
-        return match matter.parse::<PageFrontMatter>(frontmatter_block) {
+        // pipelines emit `---\n{ ... }\n---`; a leading brace selects the JSON engine
+        let is_json = md_content[3..closing_start].trim_start().starts_with('{');
+        let (engine, parsed) = if is_json {
+            ("JSON", Matter::<JSON>::new().parse::<PageFrontMatter>(frontmatter_block))
+        } else {
+            ("YAML", Matter::<YAML>::new().parse::<PageFrontMatter>(frontmatter_block))
+        };
+
+        return match parsed {
             Ok(parsed) => Ok((
                 parsed.data.unwrap_or_default(),
                 body_content.trim_start().to_string(),
             )),
             Err(e) => {
-                eprintln!(
-                    "Warning: Malformed YAML frontmatter in {}. Using defaults. Error: {}",
-                    filename, e
+                tracing::warn!(
+                    engine,
+                    filename,
+                    error = %e,
+                    "malformed frontmatter, using defaults"
                 );
                 Ok((
-                    PageFrontMatter::default(),
+                    PageFrontMatter {
+                        parse_error: Some(format!("{} frontmatter: {}", engine, e)),
+                        ..Default::default()
+                    },
                     body_content.trim_start().to_string(),
                 ))
             }
@@ -85,21 +114,70 @@ pub fn extract_frontmatter(md_content: &str, filename: &str) -> Result<(PageFron
     Ok((PageFrontMatter::default(), md_content.to_string()))
 }
 
+/// Whether `md_content` opens with a frontmatter block.
+pub fn has_frontmatter(md_content: &str) -> bool {
+    let md_content = &*normalize_line_endings(md_content);
+    md_content.starts_with("---") && md_content[3..].contains("---")
+}
+
+/// Reads a sidecar metadata file as the YAML frontmatter it stands in for,
+/// so a malformed one is reported the same way.
+pub fn parse_sidecar(yaml: &str, filename: &str) -> PageFrontMatter {
+    let block = format!("---\n{}\n---\n", yaml.trim_end());
+    extract_frontmatter(&block, filename)
+        .map(|(frontmatter, _)| frontmatter)
+        .unwrap_or_default()
+}
+
+/// How a link or image destination fared during `precompile_markdown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    External,
+    Internal,
+    /// A relative destination the resolver handed back unchanged.
+    Broken,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkResolution {
+    pub original: String,
+    /// What the resolver returned, before any nginx media prefix.
+    pub resolved: String,
+    pub kind: LinkKind,
+}
+
+impl LinkResolution {
+    pub fn new(original: &str, resolved: &str) -> Self {
+        let kind = if is_external_url(original) {
+            LinkKind::External
+        } else if resolved == original && !original.is_empty() && !original.starts_with('#') {
+            LinkKind::Broken
+        } else {
+            LinkKind::Internal
+        };
+
+        Self {
+            original: original.to_string(),
+            resolved: resolved.to_string(),
+            kind,
+        }
+    }
+}
+
+/// Rewrites every link and image destination through `resolver`, returning
+/// the markdown along with how each destination resolved, in document order.
 pub fn precompile_markdown<F>(
     markdown_content: &str,
     mut resolver: F,
     nginx_media_prefixes: bool,
-) -> Result<String>
+) -> Result<(String, Vec<LinkResolution>)>
 where
     F: FnMut(&str) -> String,
 {
-    let mut options = CmarkOptions::empty();
-    options.insert(CmarkOptions::ENABLE_STRIKETHROUGH);
-    options.insert(CmarkOptions::ENABLE_TABLES);
-
-    let parser = Parser::new_ext(markdown_content, options);
+    let parser = Parser::new_ext(markdown_content, cmark_options());
 
     let mut events: Vec<Event> = Vec::new();
+    let mut links = Vec::new();
 
     for event in parser {
         match event {
@@ -110,6 +188,7 @@ where
                 id,
             }) => {
                 let resolved_url = resolver(&dest_url);
+                links.push(LinkResolution::new(&dest_url, &resolved_url));
                 let prefixed_url = apply_nginx_prefix(&resolved_url, nginx_media_prefixes);
                 events.push(Event::Start(Tag::Link {
                     link_type,
@@ -125,6 +204,7 @@ where
                 id,
             }) => {
                 let resolved_url = resolver(&dest_url);
+                links.push(LinkResolution::new(&dest_url, &resolved_url));
                 let prefixed_url = apply_nginx_prefix(&resolved_url, nginx_media_prefixes);
                 events.push(Event::Start(Tag::Image {
                     link_type,
@@ -140,5 +220,256 @@ where
     let mut output = String::new();
     cmark(events.into_iter(), &mut output)?;
 
-    Ok(output)
-}
\ No newline at end of file
+    Ok((output, links))
+}
+
+/// Renders precompiled markdown to an HTML fragment.
+pub fn render_html(markdown_content: &str) -> String {
+    let mut output = String::new();
+    pulldown_cmark::html::push_html(&mut output, Parser::new_ext(markdown_content, cmark_options()));
+    output
+}
+
+/// Length of the auto-excerpt, in characters.
+pub const EXCERPT_MAX_CHARS: usize = 160;
+
+/// The markdown before the first `<!-- more -->` comment, which marks where
+/// a page's excerpt ends. One inside a code block doesn't count.
+pub fn explicit_excerpt(markdown_content: &str) -> Option<&str> {
+    Parser::new_ext(markdown_content, cmark_options())
+        .into_offset_iter()
+        .find(|(event, _)| match event {
+            Event::Html(html) | Event::InlineHtml(html) => is_more_delimiter(html),
+            _ => false,
+        })
+        .map(|(_, range)| markdown_content[..range.start].trim_end())
+}
+
+fn is_more_delimiter(html: &str) -> bool {
+    html.trim()
+        .strip_prefix("<!--")
+        .and_then(|rest| rest.strip_suffix("-->"))
+        .is_some_and(|comment| comment.trim().eq_ignore_ascii_case("more"))
+}
+
+/// The excerpt as HTML: everything before `<!-- more -->` rendered, or
+/// without one, the auto-excerpt as a single paragraph.
+pub fn excerpt_html(markdown_content: &str) -> Option<String> {
+    if let Some(excerpt) = explicit_excerpt(markdown_content) {
+        return Some(render_html(excerpt));
+    }
+
+    let text = generate_excerpt(markdown_content, EXCERPT_MAX_CHARS)?;
+    let mut output = String::new();
+    pulldown_cmark::html::push_html(
+        &mut output,
+        [
+            Event::Start(Tag::Paragraph),
+            Event::Text(text.into()),
+            Event::End(TagEnd::Paragraph),
+        ]
+        .into_iter(),
+    );
+    Some(output)
+}
+
+/// Plain-text excerpt from the first non-empty paragraph, cut on a word boundary.
+pub fn generate_excerpt(markdown_content: &str, max_chars: usize) -> Option<String> {
+    let mut in_paragraph = false;
+    let mut text = String::new();
+
+    for event in Parser::new_ext(markdown_content, cmark_options()) {
+        match event {
+            Event::Start(Tag::Paragraph) => in_paragraph = true,
+            Event::End(TagEnd::Paragraph) => {
+                if !text.trim().is_empty() {
+                    break;
+                }
+                in_paragraph = false;
+                text.clear();
+            }
+            event if in_paragraph => push_plain_text(&event, &mut text),
+            _ => {}
+        }
+    }
+
+    let collapsed = collapse_whitespace(&text);
+    if collapsed.is_empty() {
+        return None;
+    }
+
+    Some(truncate_on_word_boundary(&collapsed, max_chars))
+}
+
+/// Length of a `text_preview`, in characters.
+pub const PREVIEW_MAX_CHARS: usize = 200;
+
+/// The start of the page's plain text, cut on a word boundary. Parsing stops
+/// once there is enough, so a long page costs no more than a short one.
+pub fn text_preview(markdown_content: &str, max_chars: usize) -> String {
+    let mut text = String::new();
+    for event in Parser::new_ext(markdown_content, cmark_options()) {
+        push_plain_text(&event, &mut text);
+        // runs of whitespace collapse, so the raw length alone can't tell
+        if text.len() > 4 * max_chars && collapse_whitespace(&text).chars().count() > max_chars {
+            break;
+        }
+    }
+    truncate_on_word_boundary(&collapse_whitespace(&text), max_chars)
+}
+
+/// Markdown reduced to its words, e.g. for search indexing.
+pub fn markdown_to_text(markdown_content: &str) -> String {
+    let mut text = String::new();
+    for event in Parser::new_ext(markdown_content, cmark_options()) {
+        push_plain_text(&event, &mut text);
+    }
+    collapse_whitespace(&text)
+}
+
+/// Destinations of the links (not images) in `markdown_content`, in order.
+pub fn link_destinations(markdown_content: &str) -> Vec<String> {
+    Parser::new_ext(markdown_content, cmark_options())
+        .filter_map(|event| match event {
+            Event::Start(Tag::Link { dest_url, .. }) => Some(dest_url.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Destinations of the links and images in `markdown_content`, in the
+/// order `precompile_markdown` resolves them.
+pub fn link_and_image_destinations(markdown_content: &str) -> Vec<String> {
+    Parser::new_ext(markdown_content, cmark_options())
+        .filter_map(|event| match event {
+            Event::Start(Tag::Link { dest_url, .. }) | Event::Start(Tag::Image { dest_url, .. }) => {
+                Some(dest_url.to_string())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Anchor for a heading's text, slugged like an identifier. `.` and `/`
+/// separate words here instead of being path syntax.
+pub fn heading_slug(text: &str) -> String {
+    slugify_identifier(&text.replace(['.', '/'], " "))
+}
+
+/// The markdown from the heading whose anchor is `anchor` up to the next
+/// heading of the same or a higher level. Repeated headings get `-1`, `-2`,
+/// … suffixes in document order, so every anchor names one section.
+pub fn section_markdown<'a>(markdown_content: &'a str, anchor: &str) -> Option<&'a str> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut heading: Option<(HeadingLevel, usize, String)> = None;
+    let mut section: Option<(HeadingLevel, usize)> = None;
+
+    for (event, range) in Parser::new_ext(markdown_content, cmark_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                if let Some((found, start)) = section {
+                    if level <= found {
+                        return Some(&markdown_content[start..range.start]);
+                    }
+                }
+                heading = Some((level, range.start, String::new()));
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                let Some((level, start, text)) = heading.take() else {
+                    continue;
+                };
+                if section.is_some() {
+                    continue;
+                }
+
+                let slug = heading_slug(&text);
+                let count = seen.entry(slug.clone()).or_default();
+                let slug = match *count {
+                    0 => slug,
+                    n => format!("{}-{}", slug, n),
+                };
+                *count += 1;
+
+                if slug == anchor {
+                    section = Some((level, start));
+                }
+            }
+            event => {
+                if let Some((_, _, text)) = &mut heading {
+                    push_plain_text(&event, text);
+                }
+            }
+        }
+    }
+
+    section.map(|(_, start)| &markdown_content[start..])
+}
+
+/// Paragraphs made of nothing but an `{{include: target}}` directive, as
+/// their byte range in `markdown_content` and the trimmed target, in order.
+/// A directive inside a code block or running text is left alone.
+pub fn include_directives(markdown_content: &str) -> Vec<(Range<usize>, String)> {
+    Parser::new_ext(markdown_content, cmark_options())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Start(Tag::Paragraph) => {
+                let target = markdown_content[range.clone()]
+                    .trim()
+                    .strip_prefix("{{")?
+                    .strip_suffix("}}")?
+                    .trim()
+                    .strip_prefix("include:")?
+                    .trim();
+                (!target.is_empty()).then(|| (range, target.to_string()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn push_plain_text(event: &Event, text: &mut String) {
+    match event {
+        Event::Text(t) | Event::Code(t) => text.push_str(t),
+        Event::SoftBreak | Event::HardBreak => text.push(' '),
+        // inline spans run into their surroundings; blocks need a separator
+        Event::End(
+            TagEnd::Emphasis
+            | TagEnd::Strong
+            | TagEnd::Strikethrough
+            | TagEnd::Superscript
+            | TagEnd::Subscript
+            | TagEnd::Link
+            | TagEnd::Image,
+        ) => {}
+        Event::End(_) => text.push(' '),
+        _ => {}
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn truncate_on_word_boundary(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let cut: String = text.chars().take(max_chars).collect();
+    let trimmed = match cut.rfind(' ') {
+        Some(idx) if idx > 0 => &cut[..idx],
+        _ => cut.as_str(),
+    };
+
+    format!(
+        "{}…",
+        trimmed.trim_end_matches(|c: char| c.is_ascii_punctuation())
+    )
+}
+
+fn cmark_options() -> CmarkOptions {
+    let mut options = CmarkOptions::empty();
+    options.insert(CmarkOptions::ENABLE_STRIKETHROUGH);
+    options.insert(CmarkOptions::ENABLE_TABLES);
+    options
+}
@@ -1,10 +1,74 @@
 use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
 
 #[derive(Deserialize, Debug, Default, Clone)]
 pub struct PageFrontMatter {
     pub identifier: Option<String>,
     pub name: Option<String>,
+    pub description: Option<String>,
     pub tags: Option<Vec<String>>,
     pub modified_datetime: Option<String>,
     pub created_datetime: Option<String>,
-}
\ No newline at end of file
+    pub aliases: Option<Vec<String>>,
+    pub redirect_to: Option<String>,
+    /// Every field not listed above, kept as-is for themes and frontends.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+    /// Why the frontmatter block could not be parsed, in which case every
+    /// field above is left at its default.
+    #[serde(skip)]
+    pub parse_error: Option<String>,
+}
+impl PageFrontMatter {
+    pub const FIELDS: [&'static str; 8] = [
+        "identifier",
+        "name",
+        "description",
+        "tags",
+        "modified_datetime",
+        "created_datetime",
+        "aliases",
+        "redirect_to",
+    ];
+
+    /// Fills `created_datetime` / `modified_datetime` from the first alias
+    /// field present in `extra`, unless they were set explicitly.
+    pub fn apply_datetime_aliases(&mut self, created_aliases: &[String], modified_aliases: &[String]) {
+        if self.created_datetime.is_none() {
+            self.created_datetime = self.first_alias_value(created_aliases);
+        }
+        if self.modified_datetime.is_none() {
+            self.modified_datetime = self.first_alias_value(modified_aliases);
+        }
+    }
+
+    fn first_alias_value(&self, aliases: &[String]) -> Option<String> {
+        aliases.iter().find_map(|alias| match self.extra.get(alias)? {
+            Value::String(s) => Some(s.clone()),
+            _ => None,
+        })
+    }
+
+    /// Returns the fields from `required` that are absent or blank. An empty
+    /// tag list counts as missing.
+    pub fn missing_fields(&self, required: &[String]) -> Vec<String> {
+        let is_blank = |value: &Option<String>| value.as_deref().is_none_or(|v| v.trim().is_empty());
+
+        required
+            .iter()
+            .filter(|field| match field.as_str() {
+                "identifier" => is_blank(&self.identifier),
+                "name" => is_blank(&self.name),
+                "description" => is_blank(&self.description),
+                "tags" => self.tags.as_ref().is_none_or(|tags| tags.is_empty()),
+                "modified_datetime" => is_blank(&self.modified_datetime),
+                "created_datetime" => is_blank(&self.created_datetime),
+                "aliases" => self.aliases.as_ref().is_none_or(|aliases| aliases.is_empty()),
+                "redirect_to" => is_blank(&self.redirect_to),
+                _ => false,
+            })
+            .cloned()
+            .collect()
+    }
+}
@@ -1,4 +1,4 @@
-use crate::io::{ContentMetadata, ContentReader, SyncFile};
+use crate::io::{has_extension, ContentMetadata, ContentReader, PathStream, SyncFile};
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
@@ -72,6 +72,7 @@ impl MockContentReader {
                 metadata: ContentMetadata {
                     modified: None,
                     created: None,
+                    accessed: None,
                     size,
                 },
                 virtual_size: Some(size),
@@ -83,6 +84,11 @@ impl MockContentReader {
         self.add_file_with_metadata(path, content, None, None);
     }
 
+    pub fn remove_file(&self, path: &str) {
+        let mut files = self.files.lock().unwrap();
+        files.remove(Path::new(path));
+    }
+
     pub fn add_binary_file(&self, path: &str, content: Vec<u8>) {
         let mut files = self.files.lock().unwrap();
         let size = content.len() as u64;
@@ -93,6 +99,7 @@ impl MockContentReader {
                 metadata: ContentMetadata {
                     modified: None,
                     created: None,
+                    accessed: None,
                     size,
                 },
                 virtual_size: None,
@@ -113,7 +120,7 @@ impl MockContentReader {
             PathBuf::from(path),
             MockFile {
                 content: content.as_bytes().to_vec(),
-                metadata: ContentMetadata { modified, created, size },
+                metadata: ContentMetadata { modified, created, accessed: None, size },
                 virtual_size: None,
             },
         );
@@ -140,6 +147,7 @@ impl MockContentReader {
                 metadata: ContentMetadata {
                     modified,
                     created,
+                    accessed: None,
                     size: fs_metadata.len(),
                 },
                 virtual_size: None,
@@ -231,13 +239,16 @@ impl ContentReader for MockContentReader {
         Ok(meta)
     }
 
-    async fn list_markdown_files(&self, _root: &Path) -> Result<Vec<PathBuf>> {
+    fn stream_markdown_files<'a>(&'a self, root: &'a Path, extensions: &'a [String]) -> PathStream<'a> {
         let files = self.files.lock().unwrap();
-        Ok(files
+        let paths: Vec<Result<PathBuf>> = files
             .keys()
-            .filter(|p| p.extension().map_or(false, |e| e == "md"))
+            .filter(|p| p.starts_with(root))
+            .filter(|p| has_extension(p, extensions))
             .cloned()
-            .collect())
+            .map(Ok)
+            .collect();
+        Box::pin(futures_util::stream::iter(paths))
     }
 
     async fn list_all_files(&self, root: &Path) -> Result<Vec<PathBuf>> {
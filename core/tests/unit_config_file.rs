@@ -0,0 +1,95 @@
+use chasqui_core::config::ChasquiConfig;
+use std::fs;
+
+#[test]
+fn test_config_file_with_env_overrides() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("chasqui.toml");
+    fs::write(
+        &path,
+        r#"
+database_url = "sqlite:file.db"
+max_connections = 4
+strict_links = true
+base_path = "docs/"
+cors_allowed_origins = ["https://a.example", "https://b.example"]
+webhook_payload = { text = "{{changes}}" }
+"#,
+    )
+    .unwrap();
+
+    std::env::remove_var("MAX_CONNECTIONS");
+    let config = ChasquiConfig::from_file(&path).unwrap();
+    assert_eq!(config.database_url, "sqlite:file.db");
+    assert_eq!(config.max_connections, 4);
+    assert!(config.strict_links);
+    assert_eq!(config.base_path, "/docs");
+    assert_eq!(
+        config.cors_allowed_origins,
+        vec!["https://a.example", "https://b.example"]
+    );
+    assert_eq!(
+        config.webhook_payload,
+        Some(serde_json::json!({ "text": "{{changes}}" }))
+    );
+    // unset keys fall back to the usual defaults
+    assert_eq!(config.port, 3000);
+
+    std::env::set_var("MAX_CONNECTIONS", "9");
+    let layered = ChasquiConfig::from_env_and_file(&path).unwrap();
+    std::env::remove_var("MAX_CONNECTIONS");
+    assert_eq!(layered.max_connections, 9);
+    assert_eq!(layered.cors_allowed_origins.len(), 2);
+
+    assert!(ChasquiConfig::from_file(&dir.path().join("missing.toml")).is_err());
+}
+
+#[test]
+fn test_config_file_rejects_unknown_keys_and_missing_database_url() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("chasqui.toml");
+
+    fs::write(&path, "database_url = \"sqlite:file.db\"\nmax_conections = 4\n").unwrap();
+    let err = ChasquiConfig::from_file(&path).unwrap_err();
+    assert!(err.to_string().contains("max_conections"), "{}", err);
+
+    // settings read only as a fallback still count as known
+    fs::write(
+        &path,
+        "database_url = \"sqlite:file.db\"\ncontent_dirs = [\"./a\"]\ncontent_dir = \"./b\"\nrouter_serve_home_at_default = \"false\"\n",
+    )
+    .unwrap();
+    assert!(ChasquiConfig::from_file(&path).is_ok());
+
+    fs::write(&path, "max_connections = 4\n").unwrap();
+    let err = ChasquiConfig::from_file(&path).unwrap_err();
+    assert!(err.to_string().contains("DATABASE_URL"), "{}", err);
+}
+
+#[test]
+fn test_config_reports_and_creates_missing_content_dirs() {
+    let dir = tempfile::tempdir().unwrap();
+    let content = dir.path().join("content");
+    let path = dir.path().join("chasqui.toml");
+    fs::write(
+        &path,
+        format!(
+            "database_url = \"sqlite:file.db\"\ncontent_dir = \"{}\"\n",
+            content.display()
+        ),
+    )
+    .unwrap();
+
+    let config = ChasquiConfig::from_file(&path).unwrap();
+    assert!(!config.create_content_dirs);
+    assert_eq!(config.missing_page_dirs(), vec![content.join("md")]);
+
+    // media dirs are optional, so only the page dir counts as missing
+    fs::create_dir_all(content.join("md")).unwrap();
+    assert!(config.missing_page_dirs().is_empty());
+
+    config.create_missing_dirs().unwrap();
+    for media in ["images", "audio", "videos"] {
+        assert!(content.join(media).is_dir());
+    }
+}
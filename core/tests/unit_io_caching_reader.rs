@@ -0,0 +1,38 @@
+use chasqui_core::io::caching::CachingContentReader;
+use chasqui_core::io::ContentReader;
+use chasqui_core::testutil::MockContentReader;
+use std::path::Path;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_caching_reader_memoizes_until_invalidated() {
+    let inner = MockContentReader::new();
+    inner.add_file("/content/post.md", "first");
+    let reader = CachingContentReader::new(Arc::new(inner.clone()));
+    let path = Path::new("/content/post.md");
+
+    assert_eq!(reader.read_to_string(path).await.unwrap(), "first");
+    assert_eq!(reader.get_metadata(path).await.unwrap().size, 5);
+
+    inner.add_file("/content/post.md", "second!");
+    assert_eq!(reader.read_to_string(path).await.unwrap(), "first");
+    assert_eq!(reader.get_metadata(path).await.unwrap().size, 5);
+    // uncached calls still go straight to the backend
+    assert_eq!(reader.read_bytes(path).await.unwrap(), b"second!");
+
+    reader.invalidate(path);
+    assert_eq!(reader.read_to_string(path).await.unwrap(), "second!");
+    assert_eq!(reader.get_metadata(path).await.unwrap().size, 7);
+}
+
+#[tokio::test]
+async fn test_caching_reader_does_not_cache_errors() {
+    let inner = MockContentReader::new();
+    let reader = CachingContentReader::new(Arc::new(inner.clone()));
+    let path = Path::new("/content/late.md");
+
+    assert!(reader.read_to_string(path).await.is_err());
+
+    inner.add_file("/content/late.md", "arrived");
+    assert_eq!(reader.read_to_string(path).await.unwrap(), "arrived");
+}
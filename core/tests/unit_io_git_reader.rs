@@ -0,0 +1,95 @@
+use chasqui_core::io::git::GitContentReader;
+use chasqui_core::io::ContentReader;
+use chrono::DateTime;
+use git2::{Repository, Signature, Time};
+use std::path::{Path, PathBuf};
+
+// Commits `files` (path, contents) on top of HEAD at `seconds` since the epoch;
+// a `None` contents removes the path.
+fn commit(repo: &Repository, seconds: i64, files: &[(&str, Option<&str>)]) -> git2::Oid {
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let mut index = git2::Index::new().unwrap();
+    if let Some(parent) = &parent {
+        index.read_tree(&parent.tree().unwrap()).unwrap();
+    }
+    for (path, contents) in files {
+        match contents {
+            Some(contents) => {
+                let blob = repo.blob(contents.as_bytes()).unwrap();
+                let entry = git2::IndexEntry {
+                    ctime: git2::IndexTime::new(0, 0),
+                    mtime: git2::IndexTime::new(0, 0),
+                    dev: 0,
+                    ino: 0,
+                    mode: 0o100644,
+                    uid: 0,
+                    gid: 0,
+                    file_size: contents.len() as u32,
+                    id: blob,
+                    flags: 0,
+                    flags_extended: 0,
+                    path: path.as_bytes().to_vec(),
+                };
+                index.add(&entry).unwrap();
+            }
+            None => index.remove_path(Path::new(path)).unwrap(),
+        }
+    }
+    let tree = repo.find_tree(index.write_tree_to(repo).unwrap()).unwrap();
+
+    let sig = Signature::new("Author", "author@example.com", &Time::new(seconds, 0)).unwrap();
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, "update", &tree, &parents)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_git_reader_reads_ref_without_checkout() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Repository::init_bare(dir.path()).unwrap();
+
+    let first = commit(
+        &repo,
+        1_700_000_000,
+        &[("md/post.md", Some("# First")), ("md/old.md", Some("# Old"))],
+    );
+    let second = commit(
+        &repo,
+        1_700_086_400,
+        &[("md/post.md", Some("# Second")), ("md/old.md", None), ("images/a.png", Some("png"))],
+    );
+
+    let root = PathBuf::from("/content");
+    let reader = GitContentReader::open(dir.path(), "HEAD", root.clone()).unwrap();
+    let post = root.join("md/post.md");
+
+    assert_eq!(reader.read_to_string(&post).await.unwrap(), "# Second");
+    assert!(reader.read_to_string(&root.join("md/old.md")).await.is_err());
+    assert!(reader.read_to_string(Path::new("/elsewhere/md/post.md")).await.is_err());
+
+    let metadata = reader.get_metadata(&post).await.unwrap();
+    assert_eq!(metadata.size, 8);
+    assert_eq!(
+        metadata.modified,
+        DateTime::from_timestamp(1_700_086_400, 0).map(|t| t.naive_utc())
+    );
+    assert_eq!(
+        metadata.created,
+        DateTime::from_timestamp(1_700_000_000, 0).map(|t| t.naive_utc())
+    );
+
+    let mut pages = reader.list_markdown_files(&root.join("md"), &["md".to_string()]).await.unwrap();
+    pages.sort();
+    assert_eq!(pages, vec![post.clone()]);
+    assert_eq!(reader.list_all_files(&root).await.unwrap().len(), 2);
+
+    assert_eq!(reader.head_commit().unwrap(), second);
+    assert_eq!(
+        reader.deleted_paths(first, second).unwrap(),
+        vec![root.join("md/old.md")]
+    );
+
+    // a fixed ref keeps serving the old tree
+    let pinned = GitContentReader::open(dir.path(), &first.to_string(), root.clone()).unwrap();
+    assert_eq!(pinned.read_to_string(&post).await.unwrap(), "# First");
+}
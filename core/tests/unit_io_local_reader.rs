@@ -0,0 +1,153 @@
+use chasqui_core::io::local::LocalContentReader;
+use chasqui_core::io::{ContentReader, DEFAULT_MAX_FILE_BYTES};
+use std::fs;
+use std::path::PathBuf;
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_local_reader_skips_symlink_loops_and_escapes() {
+    use std::os::unix::fs::symlink;
+
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("content");
+    let outside = dir.path().join("outside");
+    fs::create_dir_all(root.join("blog")).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+
+    fs::write(root.join("blog/post.md"), "# Post").unwrap();
+    fs::write(outside.join("secret.md"), "# Secret").unwrap();
+
+    symlink(&root, root.join("blog/loop")).unwrap();
+    symlink(&outside, root.join("escape")).unwrap();
+    symlink(outside.join("secret.md"), root.join("leak.md")).unwrap();
+    symlink(root.join("blog/post.md"), root.join("alias.md")).unwrap();
+
+    let reader = LocalContentReader {
+        root_path: PathBuf::from("/"),
+        max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+    };
+
+    let mut pages = reader.list_markdown_files(&root, &["md".to_string()]).await.unwrap();
+    pages.sort();
+    // the alias resolves to post.md, which is listed under its own name
+    assert_eq!(pages, vec![root.join("blog/post.md")]);
+
+    fs::write(root.join("notes.txt"), "notes").unwrap();
+    let all = reader.list_all_files(&root).await.unwrap();
+    assert_eq!(all.len(), 2);
+}
+
+#[tokio::test]
+async fn test_local_reader_streams_markdown_files() {
+    use futures_util::StreamExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::create_dir_all(root.join("blog")).unwrap();
+    fs::write(root.join("index.md"), "# Home").unwrap();
+    fs::write(root.join("blog/LOUD.MD"), "# Loud").unwrap();
+    fs::write(root.join("blog/cover.png"), "png").unwrap();
+    fs::write(root.join("blog/long.markdown"), "# Long").unwrap();
+
+    let reader = LocalContentReader {
+        root_path: root.clone(),
+        max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+    };
+
+    let md = vec!["md".to_string()];
+    let mut stream = reader.stream_markdown_files(&root, &md);
+    let mut streamed = Vec::new();
+    while let Some(path) = stream.next().await {
+        streamed.push(path.unwrap());
+    }
+    streamed.sort();
+    assert_eq!(streamed, vec![root.join("blog/LOUD.MD"), root.join("index.md")]);
+
+    let mut listed = reader.list_markdown_files(&root, &md).await.unwrap();
+    listed.sort();
+    assert_eq!(listed, streamed);
+
+    let mut both = reader
+        .list_markdown_files(&root, &["md".to_string(), "markdown".to_string()])
+        .await
+        .unwrap();
+    both.sort();
+    assert_eq!(
+        both,
+        vec![root.join("blog/LOUD.MD"), root.join("blog/long.markdown"), root.join("index.md")]
+    );
+}
+
+#[tokio::test]
+async fn test_local_reader_stream_can_be_dropped_mid_walk() {
+    use futures_util::StreamExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    // more than the walk may queue up ahead of its consumer
+    for i in 0..1000 {
+        fs::write(root.join(format!("page{}.md", i)), "# Page").unwrap();
+    }
+
+    let reader = LocalContentReader {
+        root_path: root.clone(),
+        max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+    };
+    let md = vec!["md".to_string()];
+
+    let first: Vec<_> = reader.stream_markdown_files(&root, &md).take(5).collect().await;
+    assert_eq!(first.len(), 5);
+
+    let all = reader.list_markdown_files(&root, &md).await.unwrap();
+    assert_eq!(all.len(), 1000);
+}
+
+#[tokio::test]
+async fn test_local_reader_refuses_files_over_max_size() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::write(root.join("small.md"), "# Small").unwrap();
+    fs::write(root.join("big.md"), "x".repeat(100)).unwrap();
+
+    let reader = LocalContentReader {
+        root_path: root.clone(),
+        max_file_bytes: 64,
+    };
+
+    assert_eq!(reader.read_to_string(&root.join("small.md")).await.unwrap(), "# Small");
+
+    let err = reader.read_to_string(&root.join("big.md")).await.unwrap_err();
+    assert!(err.to_string().contains("MAX_FILE_BYTES"), "{}", err);
+    assert!(reader.read_bytes(&root.join("big.md")).await.is_err());
+
+    // only reads are refused; the size is still there to report
+    assert_eq!(reader.get_metadata(&root.join("big.md")).await.unwrap().size, 100);
+}
+
+#[tokio::test]
+async fn test_local_reader_reports_access_time() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    let path = root.join("page.md");
+    fs::write(&path, "# Page").unwrap();
+
+    // set explicitly, so noatime/relatime mounts don't matter
+    let accessed = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+    fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_times(fs::FileTimes::new().set_accessed(accessed))
+        .unwrap();
+
+    let reader = LocalContentReader {
+        root_path: root,
+        max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+    };
+
+    let metadata = reader.get_metadata(&path).await.unwrap();
+    assert_eq!(
+        metadata.accessed,
+        chrono::DateTime::from_timestamp(1_700_000_000, 0).map(|dt| dt.naive_utc())
+    );
+}
@@ -1,5 +1,7 @@
-use chasqui_core::io::verify_relative_path;
-use chasqui_core::io::path_utils::{normalize_logical_path, sanitize_identifier};
+use chasqui_core::io::{verify_absolute_path, verify_relative_path};
+use chasqui_core::io::path_utils::{
+    normalize_logical_path, sanitize_identifier, sidecar_page, sidecar_path, slugify_identifier,
+};
 use std::path::Path;
 
 #[test]
@@ -42,4 +44,57 @@ fn test_io_path_utils_jailbreak_prevention() {
     assert_eq!(sanitize_identifier("blog/My Post!"), "blog/my-post");
     assert_eq!(sanitize_identifier("/absolute/path"), "absolute/path");
     assert_eq!(sanitize_identifier("  space  "), "space");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_io_slugify_identifier() {
+    assert_eq!(slugify_identifier("My Cool Post!"), "my-cool-post");
+    assert_eq!(slugify_identifier("blog/Hello,   World"), "blog/hello-world");
+    assert_eq!(slugify_identifier("snake_case.name"), "snake-case-name");
+    assert_eq!(slugify_identifier("a -- b"), "a-b");
+}
+
+#[test]
+fn test_io_slugify_identifier_unicode() {
+    assert_eq!(slugify_identifier("Café Crème"), "cafe-creme");
+    assert_eq!(slugify_identifier("Ñandú\u{3000}Ångström"), "nandu-angstrom");
+    assert_eq!(slugify_identifier("日本語 post"), "post");
+}
+
+#[test]
+fn test_io_slugify_identifier_trims_hyphens() {
+    assert_eq!(slugify_identifier("--Leading and trailing--"), "leading-and-trailing");
+    assert_eq!(slugify_identifier("  !!wow!!  "), "wow");
+    assert_eq!(slugify_identifier("/blog/-draft-/"), "blog/draft");
+}
+
+#[test]
+fn test_io_slugify_identifier_empty_after_slug() {
+    assert_eq!(slugify_identifier("!!!"), "");
+    assert_eq!(slugify_identifier("日本語"), "");
+    assert_eq!(slugify_identifier("../../secret"), "");
+}
+
+#[test]
+fn test_io_absolute_path_jailbreak_prevention() {
+    let root = Path::new("/content/md");
+
+    assert!(verify_absolute_path(root, Path::new("/content/md/data/items.json")).is_ok());
+    assert!(verify_absolute_path(root, Path::new("/content/md/./items.json")).is_ok());
+
+    assert!(verify_absolute_path(root, Path::new("/content/images/cat.png")).is_err());
+    assert!(verify_absolute_path(root, Path::new("/content/md/../../etc/passwd")).is_err());
+    assert!(verify_absolute_path(root, Path::new("/content/md/data/../../secret.json")).is_err());
+}
+
+#[test]
+fn test_io_sidecar_paths_pair_with_their_page() {
+    let page = Path::new("/content/md/blog/post.md");
+    let sidecar = sidecar_path(page);
+
+    assert_eq!(sidecar, Path::new("/content/md/blog/post.md.meta.yaml"));
+    assert_eq!(sidecar_page(&sidecar).as_deref(), Some(page));
+
+    assert_eq!(sidecar_page(Path::new("/content/md/post.md")), None);
+    assert_eq!(sidecar_page(Path::new("/content/md/.meta.yaml")), None);
+}
@@ -1,5 +1,7 @@
 use chasqui_core::parser::markdown::{
-    apply_nginx_prefix, extract_frontmatter, is_external_url, precompile_markdown,
+    apply_nginx_prefix, excerpt_html, explicit_excerpt, extract_frontmatter, generate_excerpt,
+    heading_slug, include_directives, is_external_url, markdown_to_text, precompile_markdown, section_markdown, text_preview,
+    LinkKind,
 };
 
 #[test]
@@ -12,6 +14,34 @@ fn test_extract_frontmatter_valid() {
     assert_eq!(body.trim(), "# Hello World");
 }
 
+#[test]
+fn test_extract_frontmatter_json_engine() {
+    let input = "---\n{\"identifier\": \"test-id\", \"tags\": [\"rust\", \"tests\"], \"name\": \"Generated\"}\n---\n# Hello World";
+    let (fm, body) = extract_frontmatter(input, "test.md").expect("Should parse JSON frontmatter");
+
+    assert_eq!(fm.identifier, Some("test-id".to_string()));
+    assert_eq!(fm.name, Some("Generated".to_string()));
+    assert_eq!(fm.tags, Some(vec!["rust".to_string(), "tests".to_string()]));
+    assert_eq!(body.trim(), "# Hello World");
+}
+
+#[test]
+fn test_extract_frontmatter_bom_and_crlf() {
+    let inputs = [
+        "\u{FEFF}---\nidentifier: test-id\ntags: [rust, tests]\n---\n# Hello World",
+        "---\r\nidentifier: test-id\r\ntags: [rust, tests]\r\n---\r\n# Hello World",
+        "\u{FEFF}---\r\nidentifier: test-id\ntags:\r\n  - rust\n  - tests\r\n---\n# Hello World",
+    ];
+
+    for input in inputs {
+        let (fm, body) = extract_frontmatter(input, "test.md").expect("Should parse frontmatter");
+
+        assert_eq!(fm.identifier, Some("test-id".to_string()), "{:?}", input);
+        assert_eq!(fm.tags, Some(vec!["rust".to_string(), "tests".to_string()]));
+        assert_eq!(body, "# Hello World");
+    }
+}
+
 #[test]
 fn test_extract_frontmatter_no_fm() {
     let input = "# Just Content";
@@ -26,7 +56,7 @@ fn test_extract_frontmatter_no_fm() {
 fn test_precompile_markdown_link_resolution() {
     let markdown_with_link = "Check out [my post](post.md)";
 
-    let result = precompile_markdown(
+    let (result, _) = precompile_markdown(
         markdown_with_link,
         |link| {
             if link.ends_with(".md") {
@@ -42,10 +72,40 @@ fn test_precompile_markdown_link_resolution() {
     assert!(result.contains("[my post](/post)"));
 }
 
+#[test]
+fn test_precompile_markdown_reports_link_resolutions() {
+    let input = "[post](post.md) [gone](gone.md) [site](https://example.com) [top](#top)\n\n![img](photo.jpg)";
+    let (_, links) = precompile_markdown(
+        input,
+        |link| match link {
+            "post.md" => "/post".to_string(),
+            "photo.jpg" => "/photo".to_string(),
+            other => other.to_string(),
+        },
+        true,
+    )
+    .unwrap();
+
+    let summary: Vec<(&str, &str, LinkKind)> = links
+        .iter()
+        .map(|l| (l.original.as_str(), l.resolved.as_str(), l.kind))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("post.md", "/post", LinkKind::Internal),
+            ("gone.md", "gone.md", LinkKind::Broken),
+            ("https://example.com", "https://example.com", LinkKind::External),
+            ("#top", "#top", LinkKind::Internal),
+            ("photo.jpg", "/photo", LinkKind::Internal),
+        ]
+    );
+}
+
 #[test]
 fn test_precompile_markdown_image_url_resolution() {
     let input = "![An image](photo.jpg)";
-    let result = precompile_markdown(input, |url| format!("/resolved/{}", url), false).unwrap();
+    let (result, _) = precompile_markdown(input, |url| format!("/resolved/{}", url), false).unwrap();
 
     assert!(result.contains("](/resolved/photo.jpg)"));
     assert!(result.contains("[An image]"));
@@ -54,7 +114,7 @@ fn test_precompile_markdown_image_url_resolution() {
 #[test]
 fn test_precompile_markdown_nginx_prefix_images() {
     let input = "![Photo](photo.jpg)";
-    let result = precompile_markdown(input, |url| url.to_string(), true).unwrap();
+    let (result, _) = precompile_markdown(input, |url| url.to_string(), true).unwrap();
 
     assert!(result.contains("/images/photo.jpg"));
 }
@@ -62,7 +122,7 @@ fn test_precompile_markdown_nginx_prefix_images() {
 #[test]
 fn test_precompile_markdown_nginx_prefix_videos() {
     let input = "![Demo](demo.mp4)";
-    let result = precompile_markdown(input, |url| url.to_string(), true).unwrap();
+    let (result, _) = precompile_markdown(input, |url| url.to_string(), true).unwrap();
 
     assert!(result.contains("/videos/demo.mp4"));
 }
@@ -70,7 +130,7 @@ fn test_precompile_markdown_nginx_prefix_videos() {
 #[test]
 fn test_precompile_markdown_nginx_prefix_audio() {
     let input = "![Song](tune.mp3)";
-    let result = precompile_markdown(input, |url| url.to_string(), true).unwrap();
+    let (result, _) = precompile_markdown(input, |url| url.to_string(), true).unwrap();
 
     assert!(result.contains("/audio/tune.mp3"));
 }
@@ -84,7 +144,7 @@ fn test_precompile_markdown_nginx_prefix_external_urls_skipped() {
         "//cdn.example.com/audio.mp3",
     ] {
         let input = format!("[Link]({})", url);
-        let result = precompile_markdown(&input, |u| u.to_string(), true).unwrap();
+        let (result, _) = precompile_markdown(&input, |u| u.to_string(), true).unwrap();
         assert!(
             !result.contains("/images/")
                 && !result.contains("/videos/")
@@ -99,7 +159,7 @@ fn test_precompile_markdown_nginx_prefix_external_urls_skipped() {
 #[test]
 fn test_precompile_markdown_nginx_prefix_disabled() {
     let input = "![Photo](photo.jpg)";
-    let result = precompile_markdown(input, |url| url.to_string(), false).unwrap();
+    let (result, _) = precompile_markdown(input, |url| url.to_string(), false).unwrap();
 
     assert!(result.contains("(photo.jpg)"));
     assert!(!result.contains("/images/"));
@@ -108,7 +168,7 @@ fn test_precompile_markdown_nginx_prefix_disabled() {
 #[test]
 fn test_precompile_markdown_preserves_structure() {
     let input = "# Title\n\nParagraph with **bold** and *italic*.\n\n- Item 1\n- Item 2";
-    let result = precompile_markdown(input, |url| url.to_string(), false).unwrap();
+    let (result, _) = precompile_markdown(input, |url| url.to_string(), false).unwrap();
 
     assert!(result.contains("# Title"));
     assert!(result.contains("bold"));
@@ -122,7 +182,11 @@ fn test_extract_frontmatter_malformed_fields() {
         extract_frontmatter(broken_tags_md, "test.md").expect("Should not crash on malformed YAML");
 
     assert!(fm.tags.is_none());
+    assert!(fm.parse_error.as_deref().is_some_and(|e| e.starts_with("YAML frontmatter: ")));
     assert_eq!(body.trim(), "hello world!");
+
+    let (fm, _) = extract_frontmatter("---\ntags: [ok]\n---\nhello", "test.md").unwrap();
+    assert!(fm.parse_error.is_none());
 }
 
 #[test]
@@ -174,6 +238,12 @@ fn test_apply_nginx_prefix_images() {
     assert_eq!(apply_nginx_prefix("CAPS.JPG", true), "/images/CAPS.JPG");
 }
 
+#[test]
+fn test_apply_nginx_prefix_ignores_fragment() {
+    assert_eq!(apply_nginx_prefix("/post#v1.mp3", true), "/post#v1.mp3");
+    assert_eq!(apply_nginx_prefix("photo.png#crop", true), "/images/photo.png#crop");
+}
+
 #[test]
 fn test_apply_nginx_prefix_videos() {
     assert_eq!(apply_nginx_prefix("demo.mp4", true), "/videos/demo.mp4");
@@ -231,4 +301,132 @@ fn test_is_external_url() {
     assert!(!is_external_url("photo.jpg"));
     assert!(!is_external_url("../assets/video.mp4"));
     assert!(!is_external_url("./relative/path.png"));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_extract_frontmatter_description() {
+    let input = "---\ndescription: A short summary\n---\n# Hello";
+    let (fm, _) = extract_frontmatter(input, "test.md").unwrap();
+
+    assert_eq!(fm.description, Some("A short summary".to_string()));
+}
+
+#[test]
+fn test_generate_excerpt_uses_first_paragraph() {
+    let input = "# Heading\n\nFirst **bold** paragraph with [a link](post.md)\nand a soft break.\n\nSecond paragraph.";
+    let excerpt = generate_excerpt(input, 160);
+
+    assert_eq!(
+        excerpt,
+        Some("First bold paragraph with a link and a soft break.".to_string())
+    );
+}
+
+#[test]
+fn test_generate_excerpt_truncates_on_word_boundary() {
+    let input = "The quick brown fox jumps over the lazy dog, repeatedly.";
+    let excerpt = generate_excerpt(input, 22).unwrap();
+
+    assert_eq!(excerpt, "The quick brown fox…");
+    assert!(generate_excerpt("# Only a heading", 160).is_none());
+}
+
+#[test]
+fn test_text_preview_spans_blocks_and_stops_early() {
+    let input = "# Title\n\nFirst   paragraph.\n\n- one\n- two";
+    assert_eq!(text_preview(input, 200), "Title First paragraph. one two");
+    assert_eq!(text_preview(input, 18), "Title First…");
+
+    let long = "word ".repeat(100_000);
+    assert_eq!(text_preview(&long, 20), "word word word word…");
+}
+
+#[test]
+fn test_frontmatter_missing_fields() {
+    let required = vec!["name".to_string(), "tags".to_string(), "description".to_string()];
+
+    let input = "---\nname: Post\ntags: []\ndescription: \"  \"\n---\n# Hello";
+    let (fm, _) = extract_frontmatter(input, "test.md").unwrap();
+    assert_eq!(fm.missing_fields(&required), vec!["tags", "description"]);
+
+    let input = "---\nname: Post\ntags: [rust]\ndescription: Summary\n---\n# Hello";
+    let (fm, _) = extract_frontmatter(input, "test.md").unwrap();
+    assert!(fm.missing_fields(&required).is_empty());
+}
+
+#[test]
+fn test_markdown_to_text_strips_markup() {
+    let input = "# Title\n\nSome **bold**, [linked](post.md) `code`.\n\n- one\n- two\n\n```rust\nfn main() {}\n```";
+
+    assert_eq!(
+        markdown_to_text(input),
+        "Title Some bold, linked code. one two fn main() {}"
+    );
+}
+
+#[test]
+fn test_extract_frontmatter_keeps_unknown_fields() {
+    let input = "---\nname: Post\ncover_image: cover.png\nweight: 3\nseries:\n  name: Intro\n  part: 1\n---\n# Hello";
+    let (fm, _) = extract_frontmatter(input, "test.md").unwrap();
+
+    assert_eq!(fm.name, Some("Post".to_string()));
+    assert_eq!(fm.extra.len(), 3);
+    assert!(!fm.extra.contains_key("name"));
+    assert_eq!(fm.extra["cover_image"], serde_json::json!("cover.png"));
+    assert_eq!(fm.extra["weight"], serde_json::json!(3));
+    assert_eq!(fm.extra["series"], serde_json::json!({ "name": "Intro", "part": 1 }));
+}
+
+#[test]
+fn test_heading_slug() {
+    assert_eq!(heading_slug("Installation"), "installation");
+    assert_eq!(heading_slug("Getting *Started* with v1.2"), "getting-started-with-v1-2");
+    assert_eq!(heading_slug("Input / Output"), "input-output");
+}
+
+#[test]
+fn test_section_markdown_stops_at_same_or_higher_heading() {
+    let md = "# Title\n\n## Setup\n\nA\n\n### Details\n\nB\n\n## Setup\n\nC\n\n# Next\n\nD\n";
+
+    let first = section_markdown(md, "setup").unwrap();
+    assert_eq!(first, "## Setup\n\nA\n\n### Details\n\nB\n\n");
+
+    // repeated headings are told apart by a numeric suffix
+    let second = section_markdown(md, "setup-1").unwrap();
+    assert_eq!(second, "## Setup\n\nC\n\n");
+
+    assert_eq!(section_markdown(md, "next").unwrap(), "# Next\n\nD\n");
+    assert_eq!(section_markdown(md, "missing"), None);
+}
+
+#[test]
+fn test_include_directives_only_match_whole_paragraphs() {
+    let md = "# Doc\n\n{{include: shared/warning.md}}\n\nSee {{include: inline.md}} here.\n\n```\n{{include: code.md}}\n```\n\n{{ include:  ./spaced.md }}\n\n{{include:}}\n";
+
+    let directives = include_directives(md);
+    let targets: Vec<&str> = directives.iter().map(|(_, target)| target.as_str()).collect();
+    assert_eq!(targets, vec!["shared/warning.md", "./spaced.md"]);
+
+    let (range, _) = &directives[0];
+    assert_eq!(md[range.clone()].trim_end(), "{{include: shared/warning.md}}");
+}
+
+#[test]
+fn test_more_delimiter_ends_the_excerpt() {
+    let md = "Intro with **bold**.\n\nSecond paragraph.\n\n<!-- more -->\n\nRest.\n\n<!--more-->\n\nTail.";
+    assert_eq!(explicit_excerpt(md), Some("Intro with **bold**.\n\nSecond paragraph."));
+    assert_eq!(
+        excerpt_html(md).unwrap(),
+        "<p>Intro with <strong>bold</strong>.</p>\n<p>Second paragraph.</p>\n"
+    );
+
+    // inline and spelled loosely still count
+    assert_eq!(explicit_excerpt("Lead <!--  MORE  --> on"), Some("Lead"));
+
+    // a delimiter in code is content, not a marker
+    let fenced = "Fish & chips\n\n```html\n<!-- more -->\n```\n";
+    assert_eq!(explicit_excerpt(fenced), None);
+    assert_eq!(excerpt_html(fenced).unwrap(), "<p>Fish &amp; chips</p>\n");
+
+    assert_eq!(excerpt_html("# Only a heading"), None);
+}
@@ -0,0 +1 @@
+ALTER TABLE pages ADD COLUMN description TEXT;
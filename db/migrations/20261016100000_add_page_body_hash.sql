@@ -0,0 +1 @@
+ALTER TABLE pages ADD COLUMN body_hash TEXT NOT NULL DEFAULT '';
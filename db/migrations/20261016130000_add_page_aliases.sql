@@ -0,0 +1,2 @@
+ALTER TABLE pages ADD COLUMN aliases TEXT;
+ALTER TABLE pages ADD COLUMN redirect_to TEXT;
@@ -0,0 +1 @@
+ALTER TABLE pages ADD COLUMN frontmatter_error TEXT;
@@ -0,0 +1,9 @@
+-- Page bodies stored once per distinct content, used when DEDUPE_PAGE_BODIES
+-- is on. Such pages keep an empty md_content and point here through body_ref.
+CREATE TABLE IF NOT EXISTS page_bodies (
+    hash        TEXT NOT NULL PRIMARY KEY,
+    md_content  TEXT NOT NULL
+);
+
+ALTER TABLE pages ADD COLUMN body_ref TEXT;
+CREATE INDEX IF NOT EXISTS idx_pages_body_ref ON pages (body_ref);
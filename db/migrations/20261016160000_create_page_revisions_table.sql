@@ -0,0 +1,11 @@
+-- Every version a page file was saved with, appended whenever its content
+-- hash changes. Rows outlive the page, so history survives a deletion.
+CREATE TABLE IF NOT EXISTS page_revisions (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    filename      TEXT NOT NULL,
+    content_hash  TEXT NOT NULL,
+    md_content    TEXT NOT NULL,
+    saved_at      DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_page_revisions_filename ON page_revisions (filename, id);
@@ -2,20 +2,142 @@ pub mod repo;
 pub mod sqlite;
 pub mod testutil;
 
+pub use repo::pages::SortOrder;
 pub use sqlite::SqliteRepository;
 
-use anyhow::Result;
-use sqlx::sqlite::SqlitePoolOptions;
+use anyhow::{anyhow, bail, Context, Result};
+use chasqui_core::config::ChasquiConfig;
+use sqlx::migrate::MigrateError;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
+use std::str::FromStr;
+use std::time::Duration;
 
-pub async fn create_pool(database_url: &str, max_connections: u32) -> Result<sqlx::SqlitePool> {
+/// Pragmas set on every connection the pool opens, so a connection recycled
+/// mid-run behaves the same as the first one.
+#[derive(Debug, Clone)]
+pub struct SqlitePragmas {
+    pub journal_mode: SqliteJournalMode,
+    pub synchronous: SqliteSynchronous,
+    pub busy_timeout: Duration,
+    /// Pages when positive, KiB when negative, as in `PRAGMA cache_size`.
+    pub cache_size: i64,
+    pub foreign_keys: bool,
+}
+
+impl Default for SqlitePragmas {
+    fn default() -> Self {
+        Self {
+            journal_mode: SqliteJournalMode::Wal,
+            synchronous: SqliteSynchronous::Normal,
+            busy_timeout: Duration::from_secs(5),
+            cache_size: -64000,
+            foreign_keys: true,
+        }
+    }
+}
+
+impl SqlitePragmas {
+    pub fn from_config(config: &ChasquiConfig) -> Result<Self> {
+        Ok(Self {
+            journal_mode: SqliteJournalMode::from_str(&config.sqlite_journal_mode)
+                .context("Invalid SQLITE_JOURNAL_MODE")?,
+            synchronous: SqliteSynchronous::from_str(&config.sqlite_synchronous)
+                .context("Invalid SQLITE_SYNCHRONOUS")?,
+            busy_timeout: Duration::from_millis(config.sqlite_busy_timeout_ms),
+            cache_size: config.sqlite_cache_size,
+            foreign_keys: config.sqlite_foreign_keys,
+        })
+    }
+}
+
+pub async fn create_pool(
+    database_url: &str,
+    max_connections: u32,
+    pragmas: &SqlitePragmas,
+) -> Result<sqlx::SqlitePool> {
+    connect(connect_options(database_url, pragmas)?, max_connections).await
+}
+
+/// A pool whose connections refuse writes, for serving reads next to the
+/// pool from `create_pool`. Open it after that one, so the database and its
+/// journal mode are already in place.
+pub async fn create_read_pool(
+    database_url: &str,
+    max_connections: u32,
+    pragmas: &SqlitePragmas,
+) -> Result<sqlx::SqlitePool> {
+    connect(connect_options(database_url, pragmas)?.read_only(true), max_connections).await
+}
+
+fn connect_options(database_url: &str, pragmas: &SqlitePragmas) -> Result<SqliteConnectOptions> {
+    Ok(SqliteConnectOptions::from_str(database_url)?
+        .journal_mode(pragmas.journal_mode)
+        .synchronous(pragmas.synchronous)
+        .busy_timeout(pragmas.busy_timeout)
+        .foreign_keys(pragmas.foreign_keys)
+        .pragma("cache_size", pragmas.cache_size.to_string()))
+}
+
+async fn connect(options: SqliteConnectOptions, max_connections: u32) -> Result<sqlx::SqlitePool> {
     SqlitePoolOptions::new()
         .max_connections(max_connections)
-        .connect(database_url)
+        .connect_with(options)
         .await
         .map_err(Into::into)
 }
 
+// every page is re-read from the content dirs at startup, so a database
+// that can't be migrated can always be rebuilt from scratch
+const REBUILD_HINT: &str = "Pages are re-synced from the content dirs at startup, so moving the \
+     database file aside (or pointing DATABASE_URL at a new one) and restarting rebuilds it; \
+     only page revision history is lost";
+
+/// Brings the schema up to date. Pending migrations only ever add to it, so
+/// they are applied as they are; a database they can't safely apply to is
+/// refused with an error saying what to do instead of failing later on a
+/// missing column.
 pub async fn run_migrations(pool: &sqlx::SqlitePool) -> Result<()> {
-    sqlx::migrate!("./migrations").run(pool).await?;
+    ensure_migration_history(pool).await?;
+    sqlx::migrate!("./migrations")
+        .run(pool)
+        .await
+        .map_err(explain_migrate_error)
+}
+
+// A `pages` table without sqlx's bookkeeping beside it was made before
+// migrations existed, and nothing tells which of them its schema already has.
+async fn ensure_migration_history(pool: &sqlx::SqlitePool) -> Result<()> {
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name IN ('pages', '_sqlx_migrations')",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    if tables.iter().any(|t| t == "pages") && !tables.iter().any(|t| t == "_sqlx_migrations") {
+        bail!(
+            "The database predates schema migrations and can't be upgraded in place. {}.",
+            REBUILD_HINT
+        );
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+fn explain_migrate_error(e: MigrateError) -> anyhow::Error {
+    let problem = match e {
+        MigrateError::VersionMissing(version) => format!(
+            "The database has migration {} applied, which this build doesn't know; it was \
+             last run by a newer chasqui-server. Run that version again, or",
+            version
+        ),
+        MigrateError::VersionMismatch(version) => format!(
+            "Migration {} changed after it was applied to the database. Restore its file, or",
+            version
+        ),
+        MigrateError::Dirty(version) => format!(
+            "Migration {} was interrupted partway through. Restore the database from a backup, or",
+            version
+        ),
+        e => return anyhow::Error::new(e).context("Failed to run database migrations"),
+    };
+    anyhow!("{} rebuild the database. {}.", problem, REBUILD_HINT)
+}
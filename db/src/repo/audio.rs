@@ -1,5 +1,6 @@
 use crate::sqlite::SqliteRepository;
-use anyhow::{Context, Result};
+use crate::repo::DbContext;
+use chasqui_core::error::{ChasquiError, ChasquiResult};
 use chasqui_core::features::assets::audio::model::AudioAsset;
 use chasqui_core::features::assets::model::CommonAssetMetadata;
 use chrono::NaiveDateTime;
@@ -25,9 +26,9 @@ struct DbAudio {
 }
 
 impl TryFrom<DbAudio> for AudioAsset {
-    type Error = anyhow::Error;
+    type Error = ChasquiError;
 
-    fn try_from(db: DbAudio) -> Result<Self> {
+    fn try_from(db: DbAudio) -> ChasquiResult<Self> {
         Ok(AudioAsset {
             metadata: CommonAssetMetadata {
                 id: db.id,
@@ -50,7 +51,7 @@ impl TryFrom<DbAudio> for AudioAsset {
 }
 
 impl SqliteRepository {
-    pub async fn get_audio_by_filename(&self, filename: &str) -> Result<Option<AudioAsset>> {
+    pub async fn get_audio_by_filename(&self, filename: &str) -> ChasquiResult<Option<AudioAsset>> {
         let row = sqlx::query_as!(
             DbAudio,
             r#"
@@ -74,7 +75,7 @@ impl SqliteRepository {
             "#,
             filename
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&self.read_pool)
         .await?;
 
         match row {
@@ -83,7 +84,7 @@ impl SqliteRepository {
         }
     }
 
-    pub async fn get_all_audio(&self) -> Result<Vec<AudioAsset>> {
+    pub async fn get_all_audio(&self) -> ChasquiResult<Vec<AudioAsset>> {
         let rows = sqlx::query_as!(
             DbAudio,
             r#"
@@ -105,7 +106,7 @@ impl SqliteRepository {
             FROM audio_assets
             "#
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&self.read_pool)
         .await?;
 
         let mut audio_list = Vec::new();
@@ -115,7 +116,7 @@ impl SqliteRepository {
         Ok(audio_list)
     }
 
-    pub async fn save_audio(&self, audio: &AudioAsset) -> Result<()> {
+    pub async fn save_audio(&self, audio: &AudioAsset) -> ChasquiResult<()> {
         let meta = &audio.metadata;
         let file_path = meta.file_path.to_string_lossy().to_string();
         let new_path = meta
@@ -164,18 +165,18 @@ impl SqliteRepository {
             channels,
             audio.codec
         )
-        .execute(&self.pool)
+        .execute(&self.write_pool)
         .await
-        .context(format!("Failed to save audio asset {}", meta.filename))?;
+        .db_context(format!("Failed to save audio asset {}", meta.filename))?;
 
         Ok(())
     }
 
-    pub async fn delete_audio(&self, filename: &str) -> Result<()> {
+    pub async fn delete_audio(&self, filename: &str) -> ChasquiResult<()> {
         sqlx::query!("DELETE FROM audio_assets WHERE filename = ?", filename)
-            .execute(&self.pool)
+            .execute(&self.write_pool)
             .await
-            .context(format!("Failed to delete audio asset {}", filename))?;
+            .db_context(format!("Failed to delete audio asset {}", filename))?;
         Ok(())
     }
 }
\ No newline at end of file
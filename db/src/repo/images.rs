@@ -1,5 +1,6 @@
 use crate::sqlite::SqliteRepository;
-use anyhow::{Context, Result};
+use crate::repo::DbContext;
+use chasqui_core::error::{ChasquiError, ChasquiResult};
 use chasqui_core::features::assets::images::model::ImageAsset;
 use chasqui_core::features::assets::model::CommonAssetMetadata;
 use chrono::NaiveDateTime;
@@ -23,9 +24,9 @@ struct DbImage {
 }
 
 impl TryFrom<DbImage> for ImageAsset {
-    type Error = anyhow::Error;
+    type Error = ChasquiError;
 
-    fn try_from(db: DbImage) -> Result<Self> {
+    fn try_from(db: DbImage) -> ChasquiResult<Self> {
         Ok(ImageAsset {
             metadata: CommonAssetMetadata {
                 id: db.id,
@@ -46,7 +47,7 @@ impl TryFrom<DbImage> for ImageAsset {
 }
 
 impl SqliteRepository {
-    pub async fn get_image_by_filename(&self, filename: &str) -> Result<Option<ImageAsset>> {
+    pub async fn get_image_by_filename(&self, filename: &str) -> ChasquiResult<Option<ImageAsset>> {
         let row = sqlx::query_as!(
             DbImage,
             r#"
@@ -68,7 +69,7 @@ impl SqliteRepository {
             "#,
             filename
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&self.read_pool)
         .await?;
 
         match row {
@@ -77,7 +78,7 @@ impl SqliteRepository {
         }
     }
 
-    pub async fn get_all_images(&self) -> Result<Vec<ImageAsset>> {
+    pub async fn get_all_images(&self) -> ChasquiResult<Vec<ImageAsset>> {
         let rows = sqlx::query_as!(
             DbImage,
             r#"
@@ -97,7 +98,7 @@ impl SqliteRepository {
             FROM image_assets
             "#
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&self.read_pool)
         .await?;
 
         let mut images = Vec::new();
@@ -107,7 +108,7 @@ impl SqliteRepository {
         Ok(images)
     }
 
-    pub async fn save_image(&self, image: &ImageAsset) -> Result<()> {
+    pub async fn save_image(&self, image: &ImageAsset) -> ChasquiResult<()> {
         let meta = &image.metadata;
         let file_path = meta.file_path.to_string_lossy().to_string();
         let new_path = meta
@@ -150,18 +151,18 @@ impl SqliteRepository {
         height,
         image.alt_text
         )
-        .execute(&self.pool)
+        .execute(&self.write_pool)
         .await
-        .context(format!("Failed to save image asset {}", meta.filename))?;
+        .db_context(format!("Failed to save image asset {}", meta.filename))?;
 
         Ok(())
     }
 
-    pub async fn delete_image(&self, filename: &str) -> Result<()> {
+    pub async fn delete_image(&self, filename: &str) -> ChasquiResult<()> {
         sqlx::query!("DELETE FROM image_assets WHERE filename = ?", filename)
-            .execute(&self.pool)
+            .execute(&self.write_pool)
             .await
-            .context(format!("Failed to delete image asset {}", filename))?;
+            .db_context(format!("Failed to delete image asset {}", filename))?;
         Ok(())
     }
 }
\ No newline at end of file
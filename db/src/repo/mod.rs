@@ -1,4 +1,18 @@
 pub mod audio;
 pub mod images;
 pub mod pages;
-pub mod videos;
\ No newline at end of file
+pub mod videos;
+
+use chasqui_core::error::{BoxError, ChasquiError, ChasquiResult};
+
+/// Like `anyhow::Context`, but wraps the failure as a
+/// `ChasquiError::Database` carrying `context`.
+pub(crate) trait DbContext<T> {
+    fn db_context(self, context: impl Into<String>) -> ChasquiResult<T>;
+}
+
+impl<T, E: Into<BoxError>> DbContext<T> for Result<T, E> {
+    fn db_context(self, context: impl Into<String>) -> ChasquiResult<T> {
+        self.map_err(|e| ChasquiError::database(context, e))
+    }
+}
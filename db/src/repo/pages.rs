@@ -1,47 +1,113 @@
 use crate::sqlite::SqliteRepository;
-use anyhow::{Context, Result};
-use chasqui_core::features::pages::model::Page;
+use crate::repo::DbContext;
+use chasqui_core::error::{ChasquiError, ChasquiResult};
+use chasqui_core::features::pages::model::{Page, PageRevision};
+use chasqui_core::parser::markdown::excerpt_html;
 use chrono::NaiveDateTime;
 use derive_more::derive::Display;
+use sqlx::{QueryBuilder, Sqlite};
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+const PAGE_COLUMNS: usize = 18;
+// stay below SQLite's historical 999 bound-parameter limit
+const PAGES_PER_INSERT: usize = 999 / PAGE_COLUMNS;
+
+// a deduplicated body lives in `page_bodies`; other rows carry their own
+const SELECT_PAGES: &str = "SELECT pages.identifier, pages.filename, pages.name, pages.description, \
+     COALESCE(page_bodies.md_content, pages.md_content) AS md_content, \
+     pages.content_hash, pages.body_hash, pages.tags, pages.metadata, pages.aliases, \
+     pages.redirect_to, pages.frontmatter_error, pages.modified_datetime, \
+     pages.created_datetime, pages.accessed_datetime, pages.file_path, pages.new_path \
+     FROM pages LEFT JOIN page_bodies ON page_bodies.hash = pages.body_ref";
+
+/// Direction for `get_pages_paginated`, by creation time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl SortOrder {
+    fn sql(self) -> &'static str {
+        match self {
+            SortOrder::Ascending => "ASC",
+            SortOrder::Descending => "DESC",
+        }
+    }
+}
+
 #[derive(sqlx::FromRow, Eq, PartialEq, Clone, Display)]
 #[display("{}", filename)]
 pub struct DbPage {
     pub identifier: String,
     pub filename: String,
     pub name: Option<String>,
+    pub description: Option<String>,
     pub md_content: String,
     pub content_hash: String,
+    pub body_hash: String,
     pub tags: Option<String>,
+    pub metadata: Option<String>,
+    pub aliases: Option<String>,
+    pub redirect_to: Option<String>,
+    pub frontmatter_error: Option<String>,
     pub modified_datetime: Option<NaiveDateTime>,
     pub created_datetime: Option<NaiveDateTime>,
+    pub accessed_datetime: Option<NaiveDateTime>,
     pub file_path: String,
     pub new_path: Option<String>,
 }
 
 impl TryFrom<DbPage> for Page {
-    type Error = anyhow::Error;
+    type Error = ChasquiError;
 
-    fn try_from(db_page: DbPage) -> Result<Self, Self::Error> {
+    fn try_from(db_page: DbPage) -> ChasquiResult<Self> {
         let parsed_tags: Vec<String> = match db_page.tags {
-            Some(tags_str) => serde_json::from_str(&tags_str).context(format!(
+            Some(tags_str) => serde_json::from_str(&tags_str).db_context(format!(
                 "Failed to parse JSON tags for {}",
                 db_page.filename
             ))?,
             None => Vec::new(),
         };
 
+        let metadata = match db_page.metadata {
+            Some(metadata_str) => serde_json::from_str(&metadata_str).db_context(format!(
+                "Failed to parse JSON metadata for {}",
+                db_page.filename
+            ))?,
+            None => BTreeMap::new(),
+        };
+
+        let aliases: Vec<String> = match db_page.aliases {
+            Some(aliases_str) => serde_json::from_str(&aliases_str).db_context(format!(
+                "Failed to parse JSON aliases for {}",
+                db_page.filename
+            ))?,
+            None => Vec::new(),
+        };
+
         Ok(Page {
             identifier: db_page.identifier,
             filename: db_page.filename,
             name: db_page.name,
+            description: db_page.description,
+            // derived from the body, so it isn't stored
+            excerpt_html: excerpt_html(&db_page.md_content),
             md_content: db_page.md_content,
             content_hash: db_page.content_hash,
+            body_hash: db_page.body_hash,
             tags: parsed_tags,
+            metadata,
+            aliases,
+            redirect_to: db_page.redirect_to,
+            frontmatter_error: db_page.frontmatter_error,
+            includes: Vec::new(),
             modified_datetime: db_page.modified_datetime,
             created_datetime: db_page.created_datetime,
+            accessed_datetime: db_page.accessed_datetime,
             file_path: PathBuf::from(db_page.file_path),
             new_path: db_page.new_path.map(PathBuf::from),
         })
@@ -56,15 +122,34 @@ impl From<&Page> for DbPage {
             Some(serde_json::to_string(&page.tags).unwrap_or_default())
         };
 
+        let metadata_str = if page.metadata.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&page.metadata).unwrap_or_default())
+        };
+
+        let aliases_str = if page.aliases.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&page.aliases).unwrap_or_default())
+        };
+
         DbPage {
             identifier: page.identifier.clone(),
             filename: page.filename.clone(),
             name: page.name.clone(),
+            description: page.description.clone(),
             md_content: page.md_content.clone(),
             content_hash: page.content_hash.clone(),
+            body_hash: page.body_hash.clone(),
             tags: tags_str,
+            metadata: metadata_str,
+            aliases: aliases_str,
+            redirect_to: page.redirect_to.clone(),
+            frontmatter_error: page.frontmatter_error.clone(),
             modified_datetime: page.modified_datetime,
             created_datetime: page.created_datetime,
+            accessed_datetime: page.accessed_datetime,
             file_path: page.file_path.to_string_lossy().to_string(),
             new_path: page
                 .new_path
@@ -74,12 +159,78 @@ impl From<&Page> for DbPage {
     }
 }
 
+#[derive(sqlx::FromRow)]
+struct DbPageRevision {
+    content_hash: String,
+    saved_at: NaiveDateTime,
+    md_content: Option<String>,
+}
+
+impl From<DbPageRevision> for PageRevision {
+    fn from(row: DbPageRevision) -> Self {
+        PageRevision {
+            content_hash: row.content_hash,
+            saved_at: row.saved_at,
+            md_content: row.md_content,
+        }
+    }
+}
+
+// upserts key on filename, so a unique violation means another live file
+// already holds one of the identifiers
+fn page_write_error(e: sqlx::Error, pages: &[Page], context: String) -> ChasquiError {
+    match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => ChasquiError::Collision {
+            identifiers: pages.iter().map(|p| p.identifier.clone()).collect(),
+        },
+        _ => ChasquiError::database(context, e),
+    }
+}
+
+fn body_key(md_content: &str) -> String {
+    format!("{:032x}", xxhash_rust::xxh3::xxh3_128(md_content.as_bytes()))
+}
+
+async fn prune_page_bodies(conn: &mut sqlx::SqliteConnection) -> ChasquiResult<()> {
+    // an anti-join over every page, so it runs once per batch of writes
+    // rather than after each one
+    sqlx::query!(
+        "DELETE FROM page_bodies WHERE hash NOT IN (SELECT body_ref FROM pages WHERE body_ref IS NOT NULL)"
+    )
+    .execute(conn)
+    .await
+    .db_context("Failed to prune unreferenced page bodies")?;
+    Ok(())
+}
+
 impl SqliteRepository {
-    pub async fn get_page_by_identifier(&self, id: &str) -> Result<Option<Page>> {
+    // with deduplication on, the body moves out of the row and is returned
+    // keyed by its hash for `page_bodies`
+    fn detach_body(&self, db_page: &mut DbPage) -> Option<(String, String)> {
+        if !self.dedupe_page_bodies {
+            return None;
+        }
+        let body = std::mem::take(&mut db_page.md_content);
+        Some((body_key(&body), body))
+    }
+
+    /// Drops `page_bodies` rows that no page references any more. Page
+    /// writes leave them behind, so a caller runs this once after a batch of
+    /// them. Does nothing unless bodies are deduplicated.
+    pub async fn prune_page_bodies(&self) -> ChasquiResult<()> {
+        if !self.dedupe_page_bodies {
+            return Ok(());
+        }
+        let mut conn = self.write_pool.acquire().await?;
+        prune_page_bodies(&mut conn).await
+    }
+
+    /// Identifiers match regardless of ASCII case, as in the sync manifest.
+    pub async fn get_page_by_identifier(&self, id: &str) -> ChasquiResult<Option<Page>> {
         let db_page_opt =
-            sqlx::query_as::<_, DbPage>("SELECT * FROM pages WHERE identifier LIKE ?")
+            sqlx::query_as::<_, DbPage>(&format!("{} WHERE identifier = ? COLLATE NOCASE AND deleted_at IS NULL", SELECT_PAGES))
                 .bind(id)
-                .fetch_optional(&self.pool)
+                .fetch_optional(&self.read_pool)
                 .await?;
 
         match db_page_opt {
@@ -91,10 +242,10 @@ impl SqliteRepository {
         }
     }
 
-    pub async fn get_page_by_filename(&self, filename: &str) -> Result<Option<Page>> {
-        let db_page_opt = sqlx::query_as::<_, DbPage>("SELECT * FROM pages WHERE filename = ?")
+    pub async fn get_page_by_filename(&self, filename: &str) -> ChasquiResult<Option<Page>> {
+        let db_page_opt = sqlx::query_as::<_, DbPage>(&format!("{} WHERE filename = ? AND deleted_at IS NULL", SELECT_PAGES))
             .bind(filename)
-            .fetch_optional(&self.pool)
+            .fetch_optional(&self.read_pool)
             .await?;
 
         match db_page_opt {
@@ -106,9 +257,9 @@ impl SqliteRepository {
         }
     }
 
-    pub async fn get_all_pages(&self) -> Result<Vec<Page>> {
-        let db_pages = sqlx::query_as::<_, DbPage>("SELECT * FROM pages")
-            .fetch_all(&self.pool)
+    pub async fn get_all_pages(&self) -> ChasquiResult<Vec<Page>> {
+        let db_pages = sqlx::query_as::<_, DbPage>(&format!("{} WHERE deleted_at IS NULL", SELECT_PAGES))
+            .fetch_all(&self.read_pool)
             .await?;
 
         let mut pages = Vec::new();
@@ -120,52 +271,399 @@ impl SqliteRepository {
         Ok(pages)
     }
 
-    pub async fn save_page(&self, page: &Page) -> Result<()> {
-        let db_page: DbPage = page.into();
+    /// One window of live pages ordered by `created_datetime`, for callers
+    /// that can't afford the whole table. Undated pages come last either way,
+    /// and identifier breaks ties so windows never overlap.
+    pub async fn get_pages_paginated(
+        &self,
+        limit: i64,
+        offset: i64,
+        order: SortOrder,
+    ) -> ChasquiResult<Vec<Page>> {
+        let sql = format!(
+            "{1} WHERE deleted_at IS NULL
+             ORDER BY created_datetime IS NULL, created_datetime {0}, identifier {0}
+             LIMIT ? OFFSET ?",
+            order.sql(),
+            SELECT_PAGES
+        );
+
+        let db_pages = sqlx::query_as::<_, DbPage>(&sql)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.read_pool)
+            .await
+            .db_context(format!("failed to load pages {}..{}", offset, offset + limit))?;
+
+        db_pages.into_iter().map(Page::try_from).collect()
+    }
+
+    /// Live pages carrying `tag`, ignoring case, filtered inside SQLite so
+    /// the whole table isn't loaded. Tags are `/`-delimited paths: unless
+    /// `exact` is set, `lang` also matches `lang/rust` and `lang/rust/async`.
+    pub async fn get_pages_by_tag(&self, tag: &str, exact: bool) -> ChasquiResult<Vec<Page>> {
+        let wanted = tag.trim_matches('/').to_lowercase();
+        // `_` and `%` in a tag are literal, not LIKE wildcards
+        let children = format!(
+            "{}/%",
+            wanted.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+
+        let sql = format!(
+            r"{} WHERE deleted_at IS NULL AND json_valid(tags) AND EXISTS (
+                  SELECT 1 FROM json_each(pages.tags)
+                  WHERE lower(trim(json_each.value, '/')) = ?1
+                     OR (NOT ?2 AND lower(trim(json_each.value, '/')) LIKE ?3 ESCAPE '\')
+              )
+              ORDER BY identifier",
+            SELECT_PAGES
+        );
+        let db_pages = sqlx::query_as::<_, DbPage>(&sql)
+        .bind(&wanted)
+        .bind(exact)
+        .bind(&children)
+        .fetch_all(&self.read_pool)
+        .await
+        .db_context(format!("failed to load pages tagged {}", tag))?;
+
+        db_pages.into_iter().map(Page::try_from).collect()
+    }
+
+    pub async fn save_page(&self, page: &Page) -> ChasquiResult<()> {
+        let mut db_page: DbPage = page.into();
+        let body = self.detach_body(&mut db_page);
+        let body_ref = body.as_ref().map(|(hash, _)| hash.clone());
+        let mut tx = self.write_pool.begin().await?;
+
+        if let Some((hash, md_content)) = &body {
+            sqlx::query!(
+                "INSERT INTO page_bodies (hash, md_content) VALUES (?, ?) ON CONFLICT(hash) DO NOTHING",
+                hash,
+                md_content
+            )
+            .execute(&mut *tx)
+            .await
+            .db_context(format!("Failed to store body of page {}", page.filename))?;
+        }
+
+        // a page reclaiming a tombstoned identifier replaces the tombstone
+        sqlx::query!(
+            "DELETE FROM pages WHERE identifier = ? COLLATE NOCASE AND deleted_at IS NOT NULL",
+            db_page.identifier
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // runs before the upsert, while the row still holds the previous hash
+        sqlx::query!(
+            "INSERT INTO page_revisions (filename, content_hash, md_content)
+             SELECT ?1, ?2, ?3 WHERE NOT EXISTS (
+                 SELECT 1 FROM pages WHERE filename = ?1 AND content_hash = ?2 AND deleted_at IS NULL
+             )",
+            page.filename,
+            page.content_hash,
+            page.md_content
+        )
+        .execute(&mut *tx)
+        .await
+        .db_context(format!("Failed to record revision of page {}", page.filename))?;
 
         sqlx::query!(
             r#"
             INSERT INTO pages (
-                identifier, filename, name, md_content, 
-                content_hash, tags, modified_datetime, created_datetime,
-                file_path, new_path
+                identifier, filename, name, description, md_content, 
+                content_hash, body_hash, tags, metadata, aliases, redirect_to,
+                frontmatter_error, modified_datetime, created_datetime, accessed_datetime,
+                file_path, new_path, body_ref
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(filename) DO UPDATE SET
                 identifier = excluded.identifier,
                 name = excluded.name,
+                description = excluded.description,
                 md_content = excluded.md_content,
                 content_hash = excluded.content_hash,
+                body_hash = excluded.body_hash,
                 tags = excluded.tags,
+                metadata = excluded.metadata,
+                aliases = excluded.aliases,
+                redirect_to = excluded.redirect_to,
+                frontmatter_error = excluded.frontmatter_error,
                 modified_datetime = excluded.modified_datetime,
                 created_datetime = excluded.created_datetime,
+                accessed_datetime = excluded.accessed_datetime,
                 file_path = excluded.file_path,
-                new_path = excluded.new_path
+                new_path = excluded.new_path,
+                body_ref = excluded.body_ref,
+                deleted_at = NULL
             "#,
             db_page.identifier,
             db_page.filename,
             db_page.name,
+            db_page.description,
             db_page.md_content,
             db_page.content_hash,
+            db_page.body_hash,
             db_page.tags,
+            db_page.metadata,
+            db_page.aliases,
+            db_page.redirect_to,
+            db_page.frontmatter_error,
             db_page.modified_datetime,
             db_page.created_datetime,
+            db_page.accessed_datetime,
             db_page.file_path,
-            db_page.new_path
+            db_page.new_path,
+            body_ref
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await
-        .context(format!("Failed to save page {}", page.filename))?;
+        .map_err(|e| page_write_error(e, std::slice::from_ref(page), format!("Failed to save page {}", page.filename)))?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Upserts many pages in one transaction using multi-row INSERTs. Either
+    /// every page is written or none are.
+    pub async fn save_pages(&self, pages: &[Page]) -> ChasquiResult<()> {
+        let mut tx = self.write_pool.begin().await?;
+
+        for chunk in pages.chunks(PAGES_PER_INSERT) {
+            let mut tombstones = QueryBuilder::<Sqlite>::new(
+                "DELETE FROM pages WHERE deleted_at IS NOT NULL AND identifier COLLATE NOCASE IN (",
+            );
+            let mut ids = tombstones.separated(", ");
+            for page in chunk {
+                ids.push_bind(page.identifier.clone());
+            }
+            tombstones.push(")");
+            tombstones.build().execute(&mut *tx).await?;
+
+            let mut revisions = QueryBuilder::<Sqlite>::new(
+                "INSERT INTO page_revisions (filename, content_hash, md_content) \
+                 SELECT column1, column2, column3 FROM (",
+            );
+            revisions.push_values(chunk, |mut row, page| {
+                row.push_bind(page.filename.clone())
+                    .push_bind(page.content_hash.clone())
+                    .push_bind(page.md_content.clone());
+            });
+            revisions.push(
+                ") WHERE NOT EXISTS (SELECT 1 FROM pages WHERE pages.filename = column1 \
+                 AND pages.content_hash = column2 AND pages.deleted_at IS NULL)",
+            );
+            revisions
+                .build()
+                .execute(&mut *tx)
+                .await
+                .db_context(format!("Failed to record revisions of {} pages", chunk.len()))?;
+
+            let mut rows = Vec::with_capacity(chunk.len());
+            let mut bodies = BTreeMap::new();
+            for page in chunk {
+                let mut db_page = DbPage::from(page);
+                let body_ref = self.detach_body(&mut db_page).map(|(hash, md_content)| {
+                    bodies.insert(hash.clone(), md_content);
+                    hash
+                });
+                rows.push((db_page, body_ref));
+            }
+
+            if !bodies.is_empty() {
+                let mut body_insert =
+                    QueryBuilder::<Sqlite>::new("INSERT INTO page_bodies (hash, md_content) ");
+                body_insert.push_values(bodies, |mut row, (hash, md_content)| {
+                    row.push_bind(hash).push_bind(md_content);
+                });
+                body_insert.push(" ON CONFLICT(hash) DO NOTHING");
+                body_insert
+                    .build()
+                    .execute(&mut *tx)
+                    .await
+                    .db_context(format!("Failed to store bodies of {} pages", chunk.len()))?;
+            }
+
+            let mut builder = QueryBuilder::<Sqlite>::new(
+                "INSERT INTO pages (identifier, filename, name, description, md_content, \
+                 content_hash, body_hash, tags, metadata, aliases, redirect_to, frontmatter_error, \
+                 modified_datetime, created_datetime, accessed_datetime, file_path, new_path, body_ref) ",
+            );
+
+            builder.push_values(rows, |mut row, (db_page, body_ref)| {
+                row.push_bind(db_page.identifier)
+                    .push_bind(db_page.filename)
+                    .push_bind(db_page.name)
+                    .push_bind(db_page.description)
+                    .push_bind(db_page.md_content)
+                    .push_bind(db_page.content_hash)
+                    .push_bind(db_page.body_hash)
+                    .push_bind(db_page.tags)
+                    .push_bind(db_page.metadata)
+                    .push_bind(db_page.aliases)
+                    .push_bind(db_page.redirect_to)
+                    .push_bind(db_page.frontmatter_error)
+                    .push_bind(db_page.modified_datetime)
+                    .push_bind(db_page.created_datetime)
+                    .push_bind(db_page.accessed_datetime)
+                    .push_bind(db_page.file_path)
+                    .push_bind(db_page.new_path)
+                    .push_bind(body_ref);
+            });
+
+            builder.push(
+                " ON CONFLICT(filename) DO UPDATE SET \
+                 identifier = excluded.identifier, \
+                 name = excluded.name, \
+                 description = excluded.description, \
+                 md_content = excluded.md_content, \
+                 content_hash = excluded.content_hash, \
+                 body_hash = excluded.body_hash, \
+                 tags = excluded.tags, \
+                 metadata = excluded.metadata, \
+                 aliases = excluded.aliases, \
+                 redirect_to = excluded.redirect_to, \
+                 frontmatter_error = excluded.frontmatter_error, \
+                 modified_datetime = excluded.modified_datetime, \
+                 created_datetime = excluded.created_datetime, \
+                 accessed_datetime = excluded.accessed_datetime, \
+                 file_path = excluded.file_path, \
+                 new_path = excluded.new_path, \
+                 body_ref = excluded.body_ref, \
+                 deleted_at = NULL",
+            );
 
+            builder
+                .build()
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| page_write_error(e, chunk, format!("Failed to save batch of {} pages", chunk.len())))?;
+        }
+
+        tx.commit().await?;
         Ok(())
     }
 
-    pub async fn delete_page(&self, filename: &str) -> Result<()> {
+    /// Versions `filename` has been saved with, newest first, without their
+    /// bodies.
+    pub async fn get_page_history(&self, filename: &str) -> ChasquiResult<Vec<PageRevision>> {
+        let rows = sqlx::query_as::<_, DbPageRevision>(
+            "SELECT content_hash, saved_at, NULL AS md_content FROM page_revisions \
+             WHERE filename = ? ORDER BY id DESC",
+        )
+        .bind(filename)
+        .fetch_all(&self.read_pool)
+        .await
+        .db_context(format!("Failed to load history of page {}", filename))?;
+
+        Ok(rows.into_iter().map(PageRevision::from).collect())
+    }
+
+    /// The latest revision of `filename` saved with `content_hash`, body
+    /// included.
+    pub async fn get_page_revision(
+        &self,
+        filename: &str,
+        content_hash: &str,
+    ) -> ChasquiResult<Option<PageRevision>> {
+        let row = sqlx::query_as::<_, DbPageRevision>(
+            "SELECT content_hash, saved_at, md_content FROM page_revisions \
+             WHERE filename = ? AND content_hash = ? ORDER BY id DESC LIMIT 1",
+        )
+        .bind(filename)
+        .bind(content_hash)
+        .fetch_optional(&self.read_pool)
+        .await
+        .db_context(format!("Failed to load revision {} of page {}", content_hash, filename))?;
+
+        Ok(row.map(PageRevision::from))
+    }
+
+    pub async fn delete_page(&self, filename: &str) -> ChasquiResult<()> {
+        let mut tx = self.write_pool.begin().await?;
         sqlx::query!("DELETE FROM pages WHERE filename = ?", filename)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await
-            .context(format!("Failed to delete page {}", filename))?;
+            .db_context(format!("Failed to delete page {}", filename))?;
 
+        tx.commit().await?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Moves each `(filename, identifier)` page to its new identifier in one
+    /// transaction, so pages can trade identifiers among themselves.
+    pub async fn rename_pages(&self, renames: &[(String, String)]) -> ChasquiResult<()> {
+        let mut tx = self.write_pool.begin().await?;
+
+        // parked under their filename first, which no identifier can collide
+        // with since identifiers never start with a NUL
+        for (filename, _) in renames {
+            let parked = format!("\0{}", filename);
+            sqlx::query!("UPDATE pages SET identifier = ? WHERE filename = ?", parked, filename)
+                .execute(&mut *tx)
+                .await
+                .db_context(format!("Failed to rename page {}", filename))?;
+        }
+
+        for (filename, identifier) in renames {
+            sqlx::query!(
+                "DELETE FROM pages WHERE identifier = ? COLLATE NOCASE AND deleted_at IS NOT NULL",
+                identifier
+            )
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query!("UPDATE pages SET identifier = ? WHERE filename = ?", identifier, filename)
+                .execute(&mut *tx)
+                .await
+                .db_context(format!("Failed to rename page {} to {}", filename, identifier))?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Marks a page deleted without removing its row, so its identifier can
+    /// still be told apart from one that never existed.
+    pub async fn soft_delete_page(&self, filename: &str, deleted_at: NaiveDateTime) -> ChasquiResult<()> {
+        sqlx::query!(
+            "UPDATE pages SET deleted_at = ? WHERE filename = ?",
+            deleted_at,
+            filename
+        )
+        .execute(&self.write_pool)
+        .await
+        .db_context(format!("Failed to soft-delete page {}", filename))?;
+
+        Ok(())
+    }
+
+    /// When the page behind `id` was soft-deleted, if it was.
+    pub async fn get_page_tombstone(&self, id: &str) -> ChasquiResult<Option<NaiveDateTime>> {
+        let deleted_at = sqlx::query_scalar::<_, NaiveDateTime>(
+            "SELECT deleted_at FROM pages WHERE identifier = ? COLLATE NOCASE AND deleted_at IS NOT NULL",
+        )
+        .bind(id)
+        .fetch_optional(&self.read_pool)
+        .await?;
+
+        Ok(deleted_at)
+    }
+
+    /// Hard-deletes tombstones older than `cutoff`, returning how many went.
+    pub async fn purge_page_tombstones(&self, cutoff: NaiveDateTime) -> ChasquiResult<u64> {
+        let mut tx = self.write_pool.begin().await?;
+        let result = sqlx::query!(
+            "DELETE FROM pages WHERE deleted_at IS NOT NULL AND deleted_at < ?",
+            cutoff
+        )
+        .execute(&mut *tx)
+        .await
+        .db_context("Failed to purge page tombstones")?;
+
+        if self.dedupe_page_bodies {
+            prune_page_bodies(&mut tx).await?;
+        }
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -1,5 +1,6 @@
 use crate::sqlite::SqliteRepository;
-use anyhow::{Context, Result};
+use crate::repo::DbContext;
+use chasqui_core::error::{ChasquiError, ChasquiResult};
 use chasqui_core::features::assets::model::CommonAssetMetadata;
 use chasqui_core::features::assets::videos::model::VideoAsset;
 use chrono::NaiveDateTime;
@@ -26,9 +27,9 @@ struct DbVideo {
 }
 
 impl TryFrom<DbVideo> for VideoAsset {
-    type Error = anyhow::Error;
+    type Error = ChasquiError;
 
-    fn try_from(db: DbVideo) -> Result<Self> {
+    fn try_from(db: DbVideo) -> ChasquiResult<Self> {
         Ok(VideoAsset {
             metadata: CommonAssetMetadata {
                 id: db.id,
@@ -52,7 +53,7 @@ impl TryFrom<DbVideo> for VideoAsset {
 }
 
 impl SqliteRepository {
-    pub async fn get_video_by_filename(&self, filename: &str) -> Result<Option<VideoAsset>> {
+    pub async fn get_video_by_filename(&self, filename: &str) -> ChasquiResult<Option<VideoAsset>> {
         let row = sqlx::query_as!(
             DbVideo,
             r#"
@@ -77,7 +78,7 @@ impl SqliteRepository {
             "#,
             filename
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&self.read_pool)
         .await?;
 
         match row {
@@ -86,7 +87,7 @@ impl SqliteRepository {
         }
     }
 
-    pub async fn get_all_videos(&self) -> Result<Vec<VideoAsset>> {
+    pub async fn get_all_videos(&self) -> ChasquiResult<Vec<VideoAsset>> {
         let rows = sqlx::query_as!(
             DbVideo,
             r#"
@@ -109,7 +110,7 @@ impl SqliteRepository {
             FROM video_assets
             "#
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&self.read_pool)
         .await?;
 
         let mut video_list = Vec::new();
@@ -119,7 +120,7 @@ impl SqliteRepository {
         Ok(video_list)
     }
 
-    pub async fn save_video(&self, video: &VideoAsset) -> Result<()> {
+    pub async fn save_video(&self, video: &VideoAsset) -> ChasquiResult<()> {
         let meta = &video.metadata;
         let file_path = meta.file_path.to_string_lossy().to_string();
         let new_path = meta
@@ -170,18 +171,18 @@ impl SqliteRepository {
             video.video_codec,
             video.audio_codec
         )
-        .execute(&self.pool)
+        .execute(&self.write_pool)
         .await
-        .context(format!("Failed to save video asset {}", meta.filename))?;
+        .db_context(format!("Failed to save video asset {}", meta.filename))?;
 
         Ok(())
     }
 
-    pub async fn delete_video(&self, filename: &str) -> Result<()> {
+    pub async fn delete_video(&self, filename: &str) -> ChasquiResult<()> {
         sqlx::query!("DELETE FROM video_assets WHERE filename = ?", filename)
-            .execute(&self.pool)
+            .execute(&self.write_pool)
             .await
-            .context(format!("Failed to delete video asset {}", filename))?;
+            .db_context(format!("Failed to delete video asset {}", filename))?;
         Ok(())
     }
 }
\ No newline at end of file
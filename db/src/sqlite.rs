@@ -1,18 +1,42 @@
 use chasqui_core::features::model::{Feature, FeatureType};
-use anyhow::Result;
+use chasqui_core::error::ChasquiResult;
 use sqlx::{Pool, Sqlite};
 
+/// Reads and writes go through separate pools, so API reads never queue
+/// behind the watcher for a connection. SQLite allows one writer at a time
+/// anyway, which makes a small write pool enough.
 #[derive(Clone)]
 pub struct SqliteRepository {
-    pub(crate) pool: Pool<Sqlite>,
+    pub(crate) read_pool: Pool<Sqlite>,
+    pub(crate) write_pool: Pool<Sqlite>,
+    pub(crate) dedupe_page_bodies: bool,
 }
 
 impl SqliteRepository {
+    /// Reads and writes share `pool` until `with_read_pool` splits them.
     pub fn new(pool: Pool<Sqlite>) -> Self {
-        Self { pool }
+        Self {
+            read_pool: pool.clone(),
+            write_pool: pool,
+            dedupe_page_bodies: false,
+        }
+    }
+
+    /// Serves `get_*` lookups from `pool`, leaving writes on the pool given
+    /// to `new`. Both must open the same database.
+    pub fn with_read_pool(mut self, pool: Pool<Sqlite>) -> Self {
+        self.read_pool = pool;
+        self
+    }
+
+    /// Stores each distinct page body once in `page_bodies` instead of on
+    /// every page row. Rows written either way stay readable.
+    pub fn with_deduplicated_bodies(mut self, enabled: bool) -> Self {
+        self.dedupe_page_bodies = enabled;
+        self
     }
 
-    pub async fn save_feature(&self, feature: Feature) -> Result<()> {
+    pub async fn save_feature(&self, feature: Feature) -> ChasquiResult<()> {
         match feature {
             Feature::Page(page) => self.save_page(&page).await,
             Feature::Image(img) => self.save_image(&img).await,
@@ -21,7 +45,7 @@ impl SqliteRepository {
         }
     }
 
-    pub async fn get_feature(&self, filename: &str, feature_type: FeatureType) -> Result<Option<Feature>> {
+    pub async fn get_feature(&self, filename: &str, feature_type: FeatureType) -> ChasquiResult<Option<Feature>> {
         match feature_type {
             FeatureType::Page => Ok(self.get_page_by_filename(filename).await?.map(Feature::Page)),
             FeatureType::Image => Ok(self.get_image_by_filename(filename).await?.map(Feature::Image)),
@@ -30,11 +54,11 @@ impl SqliteRepository {
         }
     }
 
-    pub async fn update_feature(&self, feature: Feature) -> Result<()> {
+    pub async fn update_feature(&self, feature: Feature) -> ChasquiResult<()> {
         self.save_feature(feature).await
     }
 
-    pub async fn delete_feature(&self, filename: &str, feature_type: FeatureType) -> Result<()> {
+    pub async fn delete_feature(&self, filename: &str, feature_type: FeatureType) -> ChasquiResult<()> {
         match feature_type {
             FeatureType::Page => self.delete_page(filename).await,
             FeatureType::Image => self.delete_image(filename).await,
@@ -43,7 +67,7 @@ impl SqliteRepository {
         }
     }
 
-    pub async fn get_all_features(&self, feature_type: FeatureType) -> Result<Vec<Feature>> {
+    pub async fn get_all_features(&self, feature_type: FeatureType) -> ChasquiResult<Vec<Feature>> {
         match feature_type {
             FeatureType::Page => {
                 let pages = self.get_all_pages().await?;
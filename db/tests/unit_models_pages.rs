@@ -8,9 +8,17 @@ fn create_test_page() -> Page {
         identifier: "test-slug".to_string(),
         filename: "test.md".to_string(),
         name: Some("Test Page".to_string()),
+        description: None,
         md_content: "# Hello".to_string(),
+        excerpt_html: None,
         content_hash: "hash123".to_string(),
+        body_hash: "body123".to_string(),
         tags: vec!["rust".to_string(), "api".to_string()],
+        metadata: Default::default(),
+        aliases: Vec::new(),
+        redirect_to: None,
+        frontmatter_error: None,
+        includes: Vec::new(),
         modified_datetime: NaiveDateTime::parse_from_str(
             "2023-01-01 12:00:00",
             "%Y-%m-%d %H:%M:%S",
@@ -20,6 +28,7 @@ fn create_test_page() -> Page {
             .ok(),
         file_path: PathBuf::from("/content/test.md"),
         new_path: None,
+        accessed_datetime: None,
     }
 }
 
@@ -31,6 +40,7 @@ fn test_page_to_db_page_serialization() {
     assert_eq!(db_page.identifier, "test-slug");
     assert_eq!(db_page.tags, Some(r#"["rust","api"]"#.to_string()));
     assert_eq!(db_page.content_hash, "hash123");
+    assert_eq!(db_page.body_hash, "body123");
 }
 
 #[test]
@@ -39,13 +49,20 @@ fn test_db_page_to_page_deserialization() {
         identifier: "db-slug".to_string(),
         filename: "db.md".to_string(),
         name: None,
+        description: None,
         md_content: "".to_string(),
         content_hash: "".to_string(),
+        body_hash: "".to_string(),
         tags: Some(r#"["tag1","tag2"]"#.to_string()),
+        metadata: None,
+        aliases: None,
+        redirect_to: None,
+        frontmatter_error: None,
         modified_datetime: None,
         created_datetime: None,
         file_path: "/content/db.md".to_string(),
         new_path: None,
+        accessed_datetime: None,
     };
 
     let page: Page = db_page.try_into().expect("Should convert from DB model");
@@ -54,6 +71,15 @@ fn test_db_page_to_page_deserialization() {
     assert_eq!(page.tags, vec!["tag1".to_string(), "tag2".to_string()]);
 }
 
+#[test]
+fn test_db_page_excerpt_is_rendered_on_load() {
+    let mut db_page: DbPage = (&create_test_page()).into();
+    db_page.md_content = "# Title\n\nFirst *paragraph*.\n\nSecond.".to_string();
+
+    let page: Page = db_page.try_into().unwrap();
+    assert_eq!(page.excerpt_html.as_deref(), Some("<p>First paragraph.</p>\n"));
+}
+
 #[test]
 fn test_tags_round_trip_empty() {
     let mut page = create_test_page();
@@ -72,13 +98,20 @@ fn test_malformed_db_tags_fails() {
         identifier: "bad".to_string(),
         filename: "bad.md".to_string(),
         name: None,
+        description: None,
         md_content: "".to_string(),
         content_hash: "".to_string(),
+        body_hash: "".to_string(),
         tags: Some("not-json".to_string()),
+        metadata: None,
+        aliases: None,
+        redirect_to: None,
+        frontmatter_error: None,
         modified_datetime: None,
         created_datetime: None,
         file_path: "/content/bad.md".to_string(),
         new_path: None,
+        accessed_datetime: None,
     };
 
     let result: Result<Page, _> = db_page.try_into();
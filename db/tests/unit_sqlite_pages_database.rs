@@ -1,5 +1,7 @@
+use chasqui_core::error::ChasquiError;
 use chasqui_core::features::pages::model::Page;
 use chasqui_db::testutil::create_test_repository;
+use chasqui_db::SortOrder;
 use chrono::NaiveDateTime;
 
 async fn setup_test_db() -> chasqui_db::SqliteRepository {
@@ -11,9 +13,17 @@ fn create_mock_page(identifier: &str, filename: &str) -> Page {
         identifier: identifier.to_string(),
         filename: filename.to_string(),
         name: Some("Test".to_string()),
+        description: None,
         md_content: "# Hello".to_string(),
+        excerpt_html: None,
         content_hash: "hash".to_string(),
+        body_hash: "body".to_string().to_string(),
         tags: vec!["rust".to_string()],
+        metadata: Default::default(),
+        aliases: Vec::new(),
+        redirect_to: None,
+        frontmatter_error: None,
+        includes: Vec::new(),
         modified_datetime: NaiveDateTime::parse_from_str(
             "2023-01-01 12:00:00",
             "%Y-%m-%d %H:%M:%S",
@@ -23,6 +33,7 @@ fn create_mock_page(identifier: &str, filename: &str) -> Page {
             .ok(),
         file_path: std::path::PathBuf::from(format!("/content/{}", filename)),
         new_path: None,
+        accessed_datetime: None,
     }
 }
 
@@ -76,6 +87,10 @@ async fn test_sqlite_unique_identifier_constraint() {
         result.is_err(),
         "Should fail due to unique identifier constraint"
     );
+    match result.unwrap_err() {
+        ChasquiError::Collision { identifiers } => assert_eq!(identifiers, vec!["shared-slug"]),
+        other => panic!("expected a collision, got {}", other.kind()),
+    }
 }
 
 #[tokio::test]
@@ -88,4 +103,337 @@ async fn test_sqlite_delete() {
 
     let retrieved = repo.get_page_by_identifier("slug").await.unwrap();
     assert!(retrieved.is_none());
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_sqlite_save_pages_bulk() {
+    let repo = setup_test_db().await;
+
+    // spans several multi-row INSERT chunks
+    let pages: Vec<Page> = (0..250)
+        .map(|i| create_mock_page(&format!("slug-{}", i), &format!("file{}.md", i)))
+        .collect();
+    repo.save_pages(&pages).await.expect("Should bulk save pages");
+
+    assert_eq!(repo.get_all_pages().await.unwrap().len(), 250);
+    let retrieved = repo.get_page_by_filename("file249.md").await.unwrap().unwrap();
+    assert_eq!(retrieved.identifier, "slug-249");
+    assert_eq!(retrieved.tags, vec!["rust"]);
+
+    let mut updated = pages[0].clone();
+    updated.md_content = "# Updated".to_string();
+    repo.save_pages(&[updated]).await.unwrap();
+    let retrieved = repo.get_page_by_filename("file0.md").await.unwrap().unwrap();
+    assert_eq!(retrieved.md_content, "# Updated");
+}
+
+#[tokio::test]
+async fn test_sqlite_save_pages_is_atomic() {
+    let repo = setup_test_db().await;
+
+    let pages = vec![
+        create_mock_page("fresh", "fresh.md"),
+        create_mock_page("shared-slug", "file1.md"),
+        create_mock_page("shared-slug", "file2.md"),
+    ];
+
+    let err = repo.save_pages(&pages).await.unwrap_err();
+    assert_eq!(err.kind(), "collision");
+    assert!(repo.get_all_pages().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_sqlite_save_pages_spans_several_inserts() {
+    // more pages than one multi-row INSERT binds, so the batch is chunked
+    const PAGE_COUNT: usize = 150;
+
+    let pages: Vec<Page> = (0..PAGE_COUNT)
+        .map(|i| create_mock_page(&format!("slug-{}", i), &format!("file{}.md", i)))
+        .collect();
+
+    let repo = setup_test_db().await;
+    repo.save_pages(&pages).await.unwrap();
+
+    assert_eq!(repo.get_all_pages().await.unwrap().len(), PAGE_COUNT);
+    assert!(repo.get_page_by_filename("file149.md").await.unwrap().is_some());
+}
+
+// run with `cargo test -p chasqui-db -- --ignored --nocapture` to see timings
+#[tokio::test]
+#[ignore]
+async fn test_sqlite_save_pages_benchmark() {
+    const PAGE_COUNT: usize = 2000;
+
+    let pages: Vec<Page> = (0..PAGE_COUNT)
+        .map(|i| create_mock_page(&format!("slug-{}", i), &format!("file{}.md", i)))
+        .collect();
+
+    let per_page_repo = setup_test_db().await;
+    let started = std::time::Instant::now();
+    for page in &pages {
+        per_page_repo.save_page(page).await.unwrap();
+    }
+    let per_page = started.elapsed();
+
+    let bulk_repo = setup_test_db().await;
+    let started = std::time::Instant::now();
+    bulk_repo.save_pages(&pages).await.unwrap();
+    let bulk = started.elapsed();
+
+    println!(
+        "saving {} pages: per-page {:?}, bulk {:?}",
+        PAGE_COUNT, per_page, bulk
+    );
+
+    assert_eq!(per_page_repo.get_all_pages().await.unwrap().len(), PAGE_COUNT);
+    assert_eq!(bulk_repo.get_all_pages().await.unwrap().len(), PAGE_COUNT);
+}
+
+#[tokio::test]
+async fn test_sqlite_metadata_round_trip() {
+    let repo = setup_test_db().await;
+
+    let mut page = create_mock_page("slug-1", "file1.md");
+    page.metadata.insert("cover_image".to_string(), serde_json::json!("cover.png"));
+    page.metadata.insert("weight".to_string(), serde_json::json!(3));
+    page.metadata.insert("series".to_string(), serde_json::json!({ "name": "Intro", "part": 1 }));
+    repo.save_page(&page).await.unwrap();
+
+    let retrieved = repo.get_page_by_filename("file1.md").await.unwrap().unwrap();
+    assert_eq!(retrieved.metadata, page.metadata);
+
+    page.metadata.clear();
+    repo.save_pages(&[page]).await.unwrap();
+    let retrieved = repo.get_page_by_filename("file1.md").await.unwrap().unwrap();
+    assert!(retrieved.metadata.is_empty());
+}
+
+#[tokio::test]
+async fn test_sqlite_aliases_and_redirect_round_trip() {
+    let repo = setup_test_db().await;
+
+    let mut page = create_mock_page("new-home", "moved.md");
+    page.aliases = vec!["old-home".to_string(), "older-home".to_string()];
+    page.redirect_to = Some("https://example.com/elsewhere".to_string());
+    repo.save_pages(std::slice::from_ref(&page)).await.unwrap();
+
+    let retrieved = repo.get_page_by_filename("moved.md").await.unwrap().unwrap();
+    assert_eq!(retrieved.aliases, page.aliases);
+    assert_eq!(retrieved.redirect_to, page.redirect_to);
+
+    page.aliases.clear();
+    page.redirect_to = None;
+    repo.save_page(&page).await.unwrap();
+    let retrieved = repo.get_page_by_filename("moved.md").await.unwrap().unwrap();
+    assert!(retrieved.aliases.is_empty());
+    assert_eq!(retrieved.redirect_to, None);
+}
+
+#[tokio::test]
+async fn test_sqlite_get_pages_paginated() {
+    let repo = setup_test_db().await;
+
+    let dated = |identifier: &str, created: Option<&str>| {
+        let mut page = create_mock_page(identifier, &format!("{}.md", identifier));
+        page.created_datetime =
+            created.map(|c| NaiveDateTime::parse_from_str(c, "%Y-%m-%d %H:%M:%S").unwrap());
+        page
+    };
+    repo.save_pages(&[
+        dated("b", Some("2023-01-01 00:00:00")),
+        dated("a", Some("2023-01-01 00:00:00")),
+        dated("newest", Some("2024-06-01 00:00:00")),
+        dated("oldest", Some("2020-01-01 00:00:00")),
+        dated("undated", None),
+    ])
+    .await
+    .unwrap();
+    repo.save_page(&dated("gone", Some("2021-01-01 00:00:00"))).await.unwrap();
+    repo.soft_delete_page("gone.md", chrono::Utc::now().naive_utc())
+        .await
+        .unwrap();
+
+    let ids = |pages: Vec<Page>| pages.into_iter().map(|p| p.identifier).collect::<Vec<_>>();
+
+    assert_eq!(
+        ids(repo.get_pages_paginated(3, 0, SortOrder::Ascending).await.unwrap()),
+        vec!["oldest", "a", "b"]
+    );
+    assert_eq!(
+        ids(repo.get_pages_paginated(3, 3, SortOrder::Ascending).await.unwrap()),
+        vec!["newest", "undated"]
+    );
+    assert_eq!(
+        ids(repo.get_pages_paginated(2, 0, SortOrder::Descending).await.unwrap()),
+        vec!["newest", "b"]
+    );
+    assert!(repo.get_pages_paginated(10, 5, SortOrder::Descending).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_sqlite_get_pages_by_tag() {
+    let repo = setup_test_db().await;
+
+    let tagged = |identifier: &str, tags: &[&str]| {
+        let mut page = create_mock_page(identifier, &format!("{}.md", identifier));
+        page.tags = tags.iter().map(|t| t.to_string()).collect();
+        page
+    };
+    repo.save_pages(&[
+        tagged("rust", &["Lang/Rust", "lang/rust/async"]),
+        tagged("langs", &["lang"]),
+        tagged("language", &["language"]),
+        tagged("wild", &["la_g/x"]),
+        tagged("untagged", &[]),
+    ])
+    .await
+    .unwrap();
+    repo.soft_delete_page("langs.md", chrono::Utc::now().naive_utc())
+        .await
+        .unwrap();
+
+    let ids = |pages: Vec<Page>| pages.into_iter().map(|p| p.identifier).collect::<Vec<_>>();
+
+    assert_eq!(ids(repo.get_pages_by_tag("lang", false).await.unwrap()), vec!["rust"]);
+    assert_eq!(ids(repo.get_pages_by_tag("lang/rust", true).await.unwrap()), vec!["rust"]);
+    assert!(repo.get_pages_by_tag("lang", true).await.unwrap().is_empty());
+    // `_` must not act as a LIKE wildcard
+    assert_eq!(ids(repo.get_pages_by_tag("la_g", false).await.unwrap()), vec!["wild"]);
+    assert!(repo.get_pages_by_tag("missing", false).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_sqlite_soft_delete_tombstones() {
+    let repo = setup_test_db().await;
+    repo.save_page(&create_mock_page("gone", "gone.md")).await.unwrap();
+    repo.save_page(&create_mock_page("kept", "kept.md")).await.unwrap();
+
+    let deleted_at = chrono::Utc::now().naive_utc();
+    repo.soft_delete_page("gone.md", deleted_at).await.unwrap();
+
+    assert!(repo.get_page_by_identifier("gone").await.unwrap().is_none());
+    assert!(repo.get_page_by_filename("gone.md").await.unwrap().is_none());
+    assert_eq!(repo.get_all_pages().await.unwrap().len(), 1);
+    assert_eq!(repo.get_page_tombstone("gone").await.unwrap(), Some(deleted_at));
+    assert_eq!(repo.get_page_tombstone("kept").await.unwrap(), None);
+
+    // tombstones newer than the cutoff survive a purge
+    let older = deleted_at - chrono::Duration::days(1);
+    assert_eq!(repo.purge_page_tombstones(older).await.unwrap(), 0);
+    let newer = deleted_at + chrono::Duration::seconds(1);
+    assert_eq!(repo.purge_page_tombstones(newer).await.unwrap(), 1);
+    assert_eq!(repo.get_page_tombstone("gone").await.unwrap(), None);
+    assert!(repo.get_page_by_identifier("kept").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_sqlite_save_replaces_tombstone() {
+    let repo = setup_test_db().await;
+    repo.save_page(&create_mock_page("slug", "old.md")).await.unwrap();
+    repo.soft_delete_page("old.md", chrono::Utc::now().naive_utc())
+        .await
+        .unwrap();
+
+    // a different file taking over the identifier, singly and in bulk
+    repo.save_page(&create_mock_page("slug", "new.md")).await.unwrap();
+    assert_eq!(repo.get_page_tombstone("slug").await.unwrap(), None);
+
+    repo.soft_delete_page("new.md", chrono::Utc::now().naive_utc())
+        .await
+        .unwrap();
+    repo.save_pages(&[create_mock_page("slug", "newer.md")]).await.unwrap();
+
+    let page = repo.get_page_by_identifier("slug").await.unwrap().unwrap();
+    assert_eq!(page.filename, "newer.md");
+    assert_eq!(repo.get_page_tombstone("slug").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_sqlite_deduplicated_page_bodies() {
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+    let repo = chasqui_db::SqliteRepository::new(pool.clone()).with_deduplicated_bodies(true);
+
+    let body_rows = || async {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM page_bodies")
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+    };
+
+    let mirror_a = create_mock_page("mirror-a", "a.md");
+    let mirror_b = create_mock_page("mirror-b", "b.md");
+    let mut unique = create_mock_page("unique", "c.md");
+    unique.md_content = "# Different".to_string();
+    repo.save_pages(&[mirror_a, mirror_b, unique.clone()]).await.unwrap();
+    assert_eq!(body_rows().await, 2);
+
+    let stored: String = sqlx::query_scalar("SELECT md_content FROM pages WHERE identifier = 'mirror-a'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, "");
+
+    let read = repo.get_page_by_identifier("mirror-b").await.unwrap().unwrap();
+    assert_eq!(read.md_content, "# Hello");
+    let tagged = repo.get_pages_by_tag("rust", false).await.unwrap();
+    assert!(tagged.iter().all(|p| !p.md_content.is_empty()));
+
+    // a body goes at the next prune once nothing references it
+    unique.md_content = "# Hello".to_string();
+    repo.save_page(&unique).await.unwrap();
+    assert_eq!(body_rows().await, 2);
+    repo.prune_page_bodies().await.unwrap();
+    assert_eq!(body_rows().await, 1);
+    repo.delete_page("a.md").await.unwrap();
+    repo.delete_page("b.md").await.unwrap();
+    repo.prune_page_bodies().await.unwrap();
+    assert_eq!(body_rows().await, 1);
+    repo.delete_page("c.md").await.unwrap();
+    repo.prune_page_bodies().await.unwrap();
+    assert_eq!(body_rows().await, 0);
+
+    // rows written without deduplication keep their own body
+    let plain = chasqui_db::SqliteRepository::new(pool.clone());
+    plain.save_page(&create_mock_page("plain", "plain.md")).await.unwrap();
+    assert_eq!(body_rows().await, 0);
+    let read = repo.get_page_by_identifier("plain").await.unwrap().unwrap();
+    assert_eq!(read.md_content, "# Hello");
+
+    // pruning is skipped entirely without deduplication
+    repo.save_page(&unique).await.unwrap();
+    repo.delete_page("c.md").await.unwrap();
+    plain.prune_page_bodies().await.unwrap();
+    assert_eq!(body_rows().await, 1);
+}
+
+#[tokio::test]
+async fn test_sqlite_page_revisions_follow_hash_changes() {
+    let repo = setup_test_db().await;
+
+    let mut page = create_mock_page("slug-1", "file1.md");
+    repo.save_page(&page).await.unwrap();
+    // an unchanged hash adds nothing
+    repo.save_page(&page).await.unwrap();
+
+    page.md_content = "# Second".to_string();
+    page.content_hash = "hash-2".to_string();
+    repo.save_pages(std::slice::from_ref(&page)).await.unwrap();
+    repo.save_pages(std::slice::from_ref(&page)).await.unwrap();
+
+    let history = repo.get_page_history("file1.md").await.unwrap();
+    let hashes: Vec<&str> = history.iter().map(|r| r.content_hash.as_str()).collect();
+    assert_eq!(hashes, vec!["hash-2", "hash"]);
+    assert!(history.iter().all(|r| r.md_content.is_none()));
+
+    let first = repo.get_page_revision("file1.md", "hash").await.unwrap().unwrap();
+    assert_eq!(first.md_content.as_deref(), Some("# Hello"));
+    assert!(repo.get_page_revision("file1.md", "nope").await.unwrap().is_none());
+
+    // history outlives the page
+    repo.delete_page("file1.md").await.unwrap();
+    assert_eq!(repo.get_page_history("file1.md").await.unwrap().len(), 2);
+}
@@ -0,0 +1,134 @@
+use chasqui_core::features::pages::model::Page;
+use chasqui_db::{create_pool, create_read_pool, run_migrations, SqlitePragmas, SqliteRepository};
+use sqlx::sqlite::SqliteSynchronous;
+
+#[tokio::test]
+async fn test_create_pool_applies_pragmas_to_every_connection() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite:{}?mode=rwc", dir.path().join("pool.db").display());
+    let pragmas = SqlitePragmas {
+        synchronous: SqliteSynchronous::Full,
+        cache_size: -2000,
+        ..SqlitePragmas::default()
+    };
+    let pool = create_pool(&url, 2, &pragmas).await.unwrap();
+
+    // hold both connections at once so neither is a reused one
+    let mut first = pool.acquire().await.unwrap();
+    let mut second = pool.acquire().await.unwrap();
+    for conn in [&mut *first, &mut *second] {
+        let journal: String = sqlx::query_scalar("PRAGMA journal_mode")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(journal, "wal");
+
+        let synchronous: i64 = sqlx::query_scalar("PRAGMA synchronous")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(synchronous, 2);
+
+        let busy_timeout: i64 = sqlx::query_scalar("PRAGMA busy_timeout")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(busy_timeout, 5000);
+
+        let cache_size: i64 = sqlx::query_scalar("PRAGMA cache_size")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(cache_size, -2000);
+
+        let foreign_keys: i64 = sqlx::query_scalar("PRAGMA foreign_keys")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(foreign_keys, 1);
+    }
+}
+
+#[tokio::test]
+async fn test_split_pools_read_what_the_write_pool_saved() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite:{}?mode=rwc", dir.path().join("split.db").display());
+    let pragmas = SqlitePragmas::default();
+
+    let write_pool = create_pool(&url, 1, &pragmas).await.unwrap();
+    run_migrations(&write_pool).await.unwrap();
+    let read_pool = create_read_pool(&url, 4, &pragmas).await.unwrap();
+    let repo = SqliteRepository::new(write_pool).with_read_pool(read_pool.clone());
+
+    let page = Page {
+        identifier: "split".into(),
+        filename: "split.md".into(),
+        name: None,
+        description: None,
+        md_content: "# Split".into(),
+        excerpt_html: None,
+        content_hash: "hash".into(),
+        body_hash: "body".into(),
+        tags: vec!["db".into()],
+        metadata: Default::default(),
+        aliases: Vec::new(),
+        redirect_to: None,
+        frontmatter_error: None,
+        includes: Vec::new(),
+        modified_datetime: None,
+        created_datetime: None,
+        file_path: "/content/split.md".into(),
+        new_path: None,
+        accessed_datetime: None,
+    };
+    repo.save_page(&page).await.unwrap();
+
+    let read = repo.get_page_by_identifier("split").await.unwrap().unwrap();
+    assert_eq!(read.md_content, "# Split");
+    assert_eq!(repo.get_all_pages().await.unwrap().len(), 1);
+
+    repo.delete_page("split.md").await.unwrap();
+    assert!(repo.get_page_by_identifier("split").await.unwrap().is_none());
+
+    // the read pool can't write, so nothing on the read path takes SQLite's write lock
+    let write = sqlx::query("DELETE FROM pages").execute(&read_pool).await;
+    assert!(write.is_err());
+}
+
+#[tokio::test]
+async fn test_migrations_refuse_a_database_they_cannot_upgrade() {
+    let dir = tempfile::tempdir().unwrap();
+    let pragmas = SqlitePragmas::default();
+
+    // made before migrations existed: a pages table and no history
+    let legacy_url = format!("sqlite:{}?mode=rwc", dir.path().join("legacy.db").display());
+    let legacy = create_pool(&legacy_url, 1, &pragmas).await.unwrap();
+    sqlx::query("CREATE TABLE pages (filename TEXT PRIMARY KEY, md_content TEXT)")
+        .execute(&legacy)
+        .await
+        .unwrap();
+    let error = run_migrations(&legacy).await.unwrap_err().to_string();
+    assert!(error.contains("predates schema migrations"), "{}", error);
+    assert!(error.contains("DATABASE_URL"), "{}", error);
+
+    // last migrated by a newer build
+    let newer_url = format!("sqlite:{}?mode=rwc", dir.path().join("newer.db").display());
+    let newer = create_pool(&newer_url, 1, &pragmas).await.unwrap();
+    run_migrations(&newer).await.unwrap();
+    sqlx::query(
+        "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) \
+         VALUES (99990101000000, 'from the future', TRUE, X'00', 0)",
+    )
+    .execute(&newer)
+    .await
+    .unwrap();
+    let error = run_migrations(&newer).await.unwrap_err().to_string();
+    assert!(error.contains("migration 99990101000000"), "{}", error);
+    assert!(error.contains("newer chasqui-server"), "{}", error);
+
+    // an up-to-date database migrates again without complaint
+    let current_url = format!("sqlite:{}?mode=rwc", dir.path().join("current.db").display());
+    let current = create_pool(&current_url, 1, &pragmas).await.unwrap();
+    run_migrations(&current).await.unwrap();
+    run_migrations(&current).await.unwrap();
+}
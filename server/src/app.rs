@@ -1,9 +1,86 @@
 use chasqui_core::config::ChasquiConfig;
+use crate::features::pages::search::SearchIndexCache;
+use crate::services::link_health::LinkHealth;
 use crate::services::sync::SyncService;
+use axum::extract::Request;
+use axum::http::{header, HeaderValue, Method};
+use axum::middleware::Next;
+use axum::response::Response;
 use std::sync::Arc;
+use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tower_http::limit::RequestBodyLimitLayer;
 
 #[derive(Clone)]
 pub struct AppState {
     pub sync_service: Arc<SyncService>,
     pub config: Arc<ChasquiConfig>,
-}
\ No newline at end of file
+    pub search_index: Arc<SearchIndexCache>,
+    pub link_health: Arc<LinkHealth>,
+}
+
+/// Builds the CORS layer from `CORS_ALLOWED_ORIGINS`. Returns `None` when no
+/// origins are configured, leaving the API same-origin only.
+pub fn cors_layer(config: &ChasquiConfig) -> Option<CorsLayer> {
+    if config.cors_allowed_origins.is_empty() {
+        return None;
+    }
+
+    let allow_origin = if config.cors_allowed_origins.iter().any(|o| o == "*") {
+        AllowOrigin::any()
+    } else {
+        let origins: Vec<HeaderValue> = config
+            .cors_allowed_origins
+            .iter()
+            .filter_map(|origin| match HeaderValue::from_str(origin) {
+                Ok(value) => Some(value),
+                Err(_) => {
+                    tracing::warn!(origin = %origin, "ignoring invalid CORS origin");
+                    None
+                }
+            })
+            .collect();
+        AllowOrigin::list(origins)
+    };
+
+    Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods([Method::GET, Method::HEAD, Method::POST])
+            .allow_headers([
+                header::ACCEPT,
+                header::ACCEPT_LANGUAGE,
+                header::CONTENT_LANGUAGE,
+                header::CONTENT_TYPE,
+            ]),
+    )
+}
+
+/// Bodies below this are sent as-is; compressing them costs more than it saves.
+pub const MIN_COMPRESSED_BYTES: u16 = 1024;
+
+/// Gzip/brotli-compresses responses when the client's `Accept-Encoding`
+/// allows it. Small bodies, images and event streams are left alone.
+pub fn compression_layer() -> CompressionLayer<impl Predicate> {
+    let predicate = SizeAbove::new(MIN_COMPRESSED_BYTES)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE);
+    CompressionLayer::new().compress_when(predicate)
+}
+
+/// Marks responses `Cache-Control: no-store`, for admin routes whose answers
+/// reflect or change server state and must never come from a cache.
+pub async fn no_store(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    response
+}
+
+/// Rejects request bodies over `MAX_REQUEST_BODY_BYTES` with 413.
+pub fn body_limit_layer(config: &ChasquiConfig) -> RequestBodyLimitLayer {
+    RequestBodyLimitLayer::new(config.max_request_body_bytes)
+}
@@ -0,0 +1,238 @@
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{Html, IntoResponse};
+use axum::Json;
+use crate::app::AppState;
+use crate::features::handlers::{error_status, sync_error_status};
+use crate::features::pages::negotiate::JsonDates;
+use crate::features::pages::service::render_preview;
+use crate::services::link_health::LinkStatus;
+use crate::services::sync::DryRunReport;
+use chasqui_core::features::pages::model::Page;
+use futures_util::stream;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize)]
+pub struct JsonResyncResult {
+    pub page_count: usize,
+}
+
+pub async fn resync_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<JsonResyncResult>, StatusCode> {
+    if !is_authorized(&headers, &state.config.admin_token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match state.sync_service.resync().await {
+        Ok(page_count) => Ok(Json(JsonResyncResult { page_count })),
+        Err(e) => {
+            tracing::error!(error = %e, "admin resync failed");
+            Err(sync_error_status(&e))
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct JsonRecompileResult {
+    pub recompiled: usize,
+    pub invalid: usize,
+    pub failed: usize,
+}
+
+/// Rebuilds every page from source, skipping the unchanged-file checks.
+pub async fn recompile_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<JsonRecompileResult>, StatusCode> {
+    if !is_authorized(&headers, &state.config.admin_token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match state.sync_service.recompile_all().await {
+        Ok(report) => Ok(Json(JsonRecompileResult {
+            recompiled: report
+                .changes
+                .saturating_sub(report.skipped + report.collisions + report.failed + report.invalid.len()),
+            invalid: report.invalid.len(),
+            failed: report.failed,
+        })),
+        Err(e) => {
+            tracing::error!(error = %e, "admin recompile failed");
+            Err(sync_error_status(&e))
+        }
+    }
+}
+
+/// Reports what a full sync would change without applying any of it.
+pub async fn dry_run_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<DryRunReport>, StatusCode> {
+    if !is_authorized(&headers, &state.config.admin_token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match state.sync_service.dry_run_sync().await {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => {
+            tracing::error!(error = %e, "dry-run sync failed");
+            Err(sync_error_status(&e))
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct JsonPurgeResult {
+    pub purged: u64,
+}
+
+pub async fn purge_tombstones_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<JsonPurgeResult>, StatusCode> {
+    if !is_authorized(&headers, &state.config.admin_token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match state.sync_service.purge_tombstones().await {
+        Ok(purged) => Ok(Json(JsonPurgeResult { purged })),
+        Err(e) => {
+            tracing::error!(error = %e, "tombstone purge failed");
+            Err(sync_error_status(&e))
+        }
+    }
+}
+
+pub async fn manifest_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<BTreeMap<String, String>>, StatusCode> {
+    if !is_authorized(&headers, &state.config.admin_token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    Ok(Json(state.sync_service.dump_manifest().await))
+}
+
+/// The latest external link check, by URL. Empty while
+/// `LINK_CHECK_INTERVAL_SECS` is 0 or before the first round finishes.
+pub async fn link_health_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<BTreeMap<String, LinkStatus>>, StatusCode> {
+    if !is_authorized(&headers, &state.config.admin_token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    Ok(Json(state.link_health.snapshot().await))
+}
+
+/// Every page as one JSON array of full `JsonPage` objects, for backups and
+/// static-site generators.
+pub async fn export_json_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    dates: JsonDates,
+) -> Result<impl IntoResponse, StatusCode> {
+    if !is_authorized(&headers, &state.config.admin_token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    // each page is serialized only as the body is polled, so a large site
+    // is never held in memory as one string
+    let pages = export_pages(&state).await;
+    let items = pages.into_iter().enumerate().map(move |(i, page)| {
+        let separator = if i == 0 { "" } else { "," };
+        serde_json::to_string(&dates.page(&page)).map(|json| format!("{}{}", separator, json))
+    });
+    let chunks = std::iter::once(Ok("[".to_string()))
+        .chain(items)
+        .chain(std::iter::once(Ok("]".to_string())));
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/json")],
+        Body::from_stream(stream::iter(chunks)),
+    ))
+}
+
+/// The same pages as `/export.json`, one JSON object per line.
+pub async fn export_ndjson_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    dates: JsonDates,
+) -> Result<impl IntoResponse, StatusCode> {
+    if !is_authorized(&headers, &state.config.admin_token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let pages = export_pages(&state).await;
+    let lines = pages
+        .into_iter()
+        .map(move |page| serde_json::to_string(&dates.page(&page)).map(|json| json + "\n"));
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(stream::iter(lines)),
+    ))
+}
+
+// identifier order keeps exports diffable between runs; scheduled pages are
+// part of the content, so they're exported too
+async fn export_pages(state: &AppState) -> Vec<Page> {
+    let mut pages = state.sync_service.get_all_pages_with_unpublished().await;
+    pages.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+    pages
+}
+
+#[derive(Deserialize)]
+pub struct RenderRequest {
+    pub filename: String,
+    pub markdown: String,
+}
+
+/// Live preview: compiles posted markdown to HTML as if it were saved at
+/// `filename`, so relative links resolve from there.
+pub async fn render_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<RenderRequest>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    if !is_authorized(&headers, &state.config.admin_token) {
+        return Err((StatusCode::UNAUTHORIZED, String::new()));
+    }
+
+    let manifest = state.sync_service.manifest.read().await;
+    render_preview(
+        &request.markdown,
+        &request.filename,
+        &state.config,
+        state.sync_service.reader.as_ref(),
+        &manifest,
+    )
+    .await
+    .map(Html)
+    .map_err(|e| (error_status(&e), e.to_string()))
+}
+
+fn is_authorized(headers: &HeaderMap, admin_token: &str) -> bool {
+    if admin_token.is_empty() {
+        return false;
+    }
+
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.as_bytes(), admin_token.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
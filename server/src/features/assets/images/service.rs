@@ -1,9 +1,7 @@
-use chasqui_core::config::ChasquiConfig;
 use chasqui_core::features::assets::images::model::ImageAsset;
 use chasqui_core::features::assets::model::CommonAssetMetadata;
 use chasqui_core::features::assets::metadata::extract_image_metadata;
 use chasqui_core::io::ContentReader;
-use chasqui_core::io::path_utils::normalize_path;
 use crate::services::sync::manifest::Manifest;
 use anyhow::Result;
 use std::path::Path;
@@ -11,19 +9,15 @@ use uuid::Uuid;
 
 pub async fn create_image_asset(
     path: &Path,
-    config: &ChasquiConfig,
+    filename: &str,
     reader: &dyn ContentReader,
     manifest: &Manifest,
 ) -> Result<ImageAsset> {
-    let filename = normalize_path(path
-        .strip_prefix(&config.images_dir)
-        .unwrap_or(path));
-
     let metadata = reader.get_metadata(path).await?;
     let content_hash = reader.get_hash(path).await?;
     let bytes_size = metadata.size;
 
-    let identifier = manifest.file_to_id.get(&filename).cloned();
+    let identifier = manifest.file_to_id.get(filename).cloned();
 
     let tech_meta = {
         let stream = reader.open_file(path).await?;
@@ -39,7 +33,7 @@ pub async fn create_image_asset(
     Ok(ImageAsset {
         metadata: CommonAssetMetadata {
             id: Uuid::new_v4(),
-            filename,
+            filename: filename.to_string(),
             identifier,
             file_path: path.to_path_buf(),
             content_hash,
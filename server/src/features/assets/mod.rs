@@ -1,3 +1,4 @@
 pub mod audio;
 pub mod images;
+pub mod passthrough;
 pub mod videos;
\ No newline at end of file
@@ -0,0 +1,106 @@
+use crate::app::AppState;
+use crate::features::pages::etag::BodyDigest;
+use axum::extract::{Path as UrlPath, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chasqui_core::features::model::FeatureType;
+use chasqui_core::io::verify_absolute_path;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Serves a file with one of the `PASSTHROUGH_EXTENSIONS` from the page dirs
+/// exactly as it is on disk, e.g. a JSON data file kept beside the pages that
+/// use it. Nothing is compiled or stored, so an edit shows up on the next
+/// request. Roots are tried in mount order and the first one holding `path`
+/// wins, as it would for a page.
+#[utoipa::path(
+    get,
+    path = "/assets/{path}",
+    tag = "assets",
+    params(("path" = String, Path, description = "File path relative to a page dir")),
+    responses(
+        (status = 200, description = "The file, typed by its extension"),
+        (status = 304, description = "`If-None-Match` already names the file"),
+        (status = 404, description = "No such file, or its extension isn't passed through"),
+    )
+)]
+pub async fn passthrough_handler(
+    State(state): State<AppState>,
+    UrlPath(path): UrlPath<String>,
+    request_headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    if !state.config.is_passthrough_file(relative) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let bytes = read_passthrough(&state, relative).await.ok_or(StatusCode::NOT_FOUND)?;
+    let digest = BodyDigest::of_bytes(&bytes);
+    let cache_control = format!("public, max-age={}", state.config.page_cache_max_age);
+
+    if digest.matches(&request_headers) {
+        return Ok((
+            StatusCode::NOT_MODIFIED,
+            [
+                (header::ETAG, digest.etag),
+                (header::CACHE_CONTROL, cache_control),
+            ],
+        )
+            .into_response());
+    }
+
+    let content_type = mime_guess::from_path(relative).first_or_octet_stream();
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::ETAG, digest.etag),
+            (header::CACHE_CONTROL, cache_control),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+async fn read_passthrough(state: &AppState, relative: &Path) -> Option<Vec<u8>> {
+    for (mount, f_type) in state.config.mounts() {
+        if f_type != FeatureType::Page {
+            continue;
+        }
+
+        let verified = match verify_absolute_path(&mount, &mount.join(relative)) {
+            Ok(verified) => verified,
+            Err(e) => {
+                debug!(path = %relative.display(), error = %e, "refusing passthrough path");
+                return None;
+            }
+        };
+
+        let path = match resolve_on_disk(&mount, verified.as_path()).await {
+            Ok(path) => path,
+            Err(e) => {
+                debug!(path = %relative.display(), error = %e, "refusing passthrough path");
+                return None;
+            }
+        };
+
+        match state.sync_service.reader.read_bytes(&path).await {
+            Ok(bytes) => return Some(bytes),
+            Err(e) => debug!(path = %path.display(), error = %e, "passthrough file not read"),
+        }
+    }
+    None
+}
+
+// The lexical check above can't see symlinks, so a file on disk must also
+// resolve inside the canonical mount, as it must for `FileWalk` to sync it.
+// A path missing on disk is left to the reader, e.g. one reading from git.
+async fn resolve_on_disk(mount: &Path, path: &Path) -> anyhow::Result<PathBuf> {
+    let real = match tokio::fs::canonicalize(path).await {
+        Ok(real) => real,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(path.to_path_buf()),
+        Err(e) => return Err(e.into()),
+    };
+    let real_mount = tokio::fs::canonicalize(mount).await?;
+    verify_absolute_path(&real_mount, &real)?;
+    Ok(real)
+}
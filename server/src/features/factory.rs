@@ -1,4 +1,5 @@
 use chasqui_core::config::ChasquiConfig;
+use chasqui_core::error::ChasquiResult;
 use chasqui_core::features::assets::audio::model::AudioAsset;
 use chasqui_core::features::assets::images::model::ImageAsset;
 use chasqui_core::features::assets::videos::model::VideoAsset;
@@ -7,6 +8,7 @@ use chasqui_core::features::pages::model::Page;
 use chasqui_core::io::ContentReader;
 use crate::services::sync::manifest::Manifest;
 use crate::services::sync::manifest::claim::ManifestClaim;
+use crate::features::pages::compiled::CompiledPageCache;
 use crate::features::pages::service::create_page;
 use crate::features::assets::images::service::create_image_asset;
 use crate::features::assets::audio::service::create_audio_asset;
@@ -19,6 +21,7 @@ pub struct FeatureFactory {
     pub manifest: Arc<RwLock<Manifest>>,
     pub reader: Arc<dyn ContentReader>,
     pub config: Arc<ChasquiConfig>,
+    pub compiled_pages: CompiledPageCache,
 }
 
 impl FeatureFactory {
@@ -30,63 +33,91 @@ impl FeatureFactory {
         Self {
             manifest,
             reader,
+            compiled_pages: CompiledPageCache::new(config.compiled_cache_entries),
             config,
         }
     }
 
     pub async fn get_feature_from_file(&self, claim: ManifestClaim) -> Result<Feature> {
         let manifest_snapshot = { self.manifest.read().await.snapshot() };
-        self.get_feature_from_file_with_manifest(claim, &manifest_snapshot).await
+        self.get_feature_from_file_with_manifest(claim, &manifest_snapshot, &*self.reader, None)
+            .await
     }
 
-    pub async fn get_feature_from_file_with_manifest(&self, claim: ManifestClaim, manifest: &Manifest) -> Result<Feature> {
+    /// `previous` is the currently cached version of the feature, if any, and
+    /// lets builders reuse work that the change didn't invalidate.
+    pub async fn get_feature_from_file_with_manifest(
+        &self,
+        claim: ManifestClaim,
+        manifest: &Manifest,
+        reader: &dyn ContentReader,
+        previous: Option<&Feature>,
+    ) -> Result<Feature> {
         match claim.feature_type {
-            FeatureType::Page => Ok(Feature::Page(self.build_page_with_manifest(claim, manifest).await?)),
-            FeatureType::Video => Ok(Feature::Video(self.build_video_with_manifest(claim, manifest).await?)),
-            FeatureType::Audio => Ok(Feature::Audio(self.build_audio_with_manifest(claim, manifest).await?)),
-            FeatureType::Image => Ok(Feature::Image(self.build_image_with_manifest(claim, manifest).await?)),
+            FeatureType::Page => {
+                let previous = match previous {
+                    Some(Feature::Page(page)) => Some(page),
+                    _ => None,
+                };
+                Ok(Feature::Page(self.build_page_with_manifest(claim, manifest, reader, previous).await?))
+            }
+            FeatureType::Video => Ok(Feature::Video(self.build_video_with_manifest(claim, manifest, reader).await?)),
+            FeatureType::Audio => Ok(Feature::Audio(self.build_audio_with_manifest(claim, manifest, reader).await?)),
+            FeatureType::Image => Ok(Feature::Image(self.build_image_with_manifest(claim, manifest, reader).await?)),
         }
     }
 
-    async fn build_page_with_manifest(&self, claim: ManifestClaim, manifest: &Manifest) -> Result<Page> {
-        let full_path = claim.mount_path.join(&claim.filename);
-        create_page(
-            &full_path,
+    async fn build_page_with_manifest(
+        &self,
+        claim: ManifestClaim,
+        manifest: &Manifest,
+        reader: &dyn ContentReader,
+        previous: Option<&Page>,
+    ) -> ChasquiResult<Page> {
+        let mut page = create_page(
+            &claim.path,
+            &claim.mount_path,
             &self.config,
-            &*self.reader,
+            reader,
             manifest,
+            previous,
+            &self.compiled_pages,
         )
-        .await
+        .await?;
+
+        // the claim's identifier is what the manifest routes, and differs
+        // from the file's own when `COLLISION_POLICY` appended a suffix
+        if let Some(identifier) = claim.identifier {
+            page.identifier = identifier;
+        }
+        Ok(page)
     }
 
-    async fn build_video_with_manifest(&self, claim: ManifestClaim, manifest: &Manifest) -> Result<VideoAsset> {
-        let full_path = claim.mount_path.join(&claim.filename);
+    async fn build_video_with_manifest(&self, claim: ManifestClaim, manifest: &Manifest, reader: &dyn ContentReader) -> Result<VideoAsset> {
         create_video_asset(
-            &full_path,
-            &self.config,
-            &*self.reader,
+            &claim.path,
+            &claim.filename,
+            reader,
             manifest,
         )
         .await
     }
 
-    async fn build_audio_with_manifest(&self, claim: ManifestClaim, manifest: &Manifest) -> Result<AudioAsset> {
-        let full_path = claim.mount_path.join(&claim.filename);
+    async fn build_audio_with_manifest(&self, claim: ManifestClaim, manifest: &Manifest, reader: &dyn ContentReader) -> Result<AudioAsset> {
         create_audio_asset(
-            &full_path,
-            &self.config,
-            &*self.reader,
+            &claim.path,
+            &claim.filename,
+            reader,
             manifest,
         )
         .await
     }
 
-    async fn build_image_with_manifest(&self, claim: ManifestClaim, manifest: &Manifest) -> Result<ImageAsset> {
-        let full_path = claim.mount_path.join(&claim.filename);
+    async fn build_image_with_manifest(&self, claim: ManifestClaim, manifest: &Manifest, reader: &dyn ContentReader) -> Result<ImageAsset> {
         create_image_asset(
-            &full_path,
-            &self.config,
-            &*self.reader,
+            &claim.path,
+            &claim.filename,
+            reader,
             manifest,
         )
         .await
@@ -1,10 +1,43 @@
 use axum::extract::{State, Path};
 use axum::http::StatusCode;
 use axum::Json;
+use chasqui_core::error::ChasquiError;
 use chasqui_core::features::model::JsonFeature;
 use crate::app::AppState;
 use crate::features::routing::{path_to_identifier, get_identifier_variants};
+use crate::telemetry;
+use metrics::counter;
+use serde::Serialize;
+use utoipa::ToSchema;
 
+/// Status for a typed failure: unusable content is 422, an identifier clash
+/// 409, and anything the server failed to do itself 500.
+pub fn error_status(e: &ChasquiError) -> StatusCode {
+    match e {
+        ChasquiError::FrontmatterParse { .. }
+        | ChasquiError::LinkResolution { .. }
+        | ChasquiError::Validation { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        ChasquiError::Collision { .. } => StatusCode::CONFLICT,
+        ChasquiError::Io { .. } | ChasquiError::Database { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// `error_status` for errors that may or may not carry a `ChasquiError`.
+pub fn sync_error_status(e: &anyhow::Error) -> StatusCode {
+    e.downcast_ref::<ChasquiError>()
+        .map_or(StatusCode::INTERNAL_SERVER_ERROR, error_status)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/metadata/{identifier}",
+    tag = "pages",
+    params(("identifier" = String, Path, description = "Feature identifier, may contain `/` or a file extension")),
+    responses(
+        (status = 200, body = JsonFeature),
+        (status = 404, description = "No feature with that identifier"),
+    )
+)]
 pub async fn metadata_handler(
     State(state): State<AppState>,
     Path(path): Path<String>,
@@ -18,5 +51,31 @@ pub async fn metadata_handler(
         }
     }
 
+    counter!(telemetry::NOT_FOUND).increment(1);
     Err(StatusCode::NOT_FOUND)
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct JsonSyncStatus {
+    pub page_count: usize,
+    pub last_sync: Option<String>,
+    pub sync_in_progress: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/status",
+    tag = "status",
+    responses((status = 200, body = JsonSyncStatus))
+)]
+pub async fn status_handler(State(state): State<AppState>) -> Json<JsonSyncStatus> {
+    let status = state.sync_service.status().await;
+
+    Json(JsonSyncStatus {
+        page_count: status.page_count,
+        last_sync: status
+            .last_sync
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string()),
+        sync_in_progress: status.sync_in_progress,
+    })
+}
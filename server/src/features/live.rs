@@ -0,0 +1,50 @@
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use chasqui_core::notifier::BuildChanges;
+use crate::app::AppState;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tracing::{debug, warn};
+
+/// Upgrades to a WebSocket that receives a `{"changed": [...], "deleted": [...]}`
+/// message after every sync batch that touched a feature. Nothing is read
+/// from the client beyond noticing that it went away.
+#[utoipa::path(
+    get,
+    path = "/ws",
+    tag = "status",
+    responses((status = 101, description = "Upgraded to a WebSocket of change notifications"))
+)]
+pub async fn ws_handler(State(state): State<AppState>, ws: WebSocketUpgrade) -> Response {
+    let changes = state.sync_service.subscribe_changes();
+    ws.on_upgrade(move |socket| forward_changes(socket, changes))
+}
+
+async fn forward_changes(mut socket: WebSocket, mut changes: Receiver<BuildChanges>) {
+    loop {
+        tokio::select! {
+            event = changes.recv() => {
+                let changes = match event {
+                    Ok(changes) => changes,
+                    // a slow client skips what it missed; the next batch still arrives
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!(missed, "websocket client fell behind, dropping change events");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let Ok(text) = serde_json::to_string(&changes) else {
+                    continue;
+                };
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    debug!("websocket client disconnected");
+}
@@ -1,5 +1,8 @@
+pub mod admin;
 pub mod assets;
 pub mod factory;
 pub mod handlers;
+pub mod live;
+pub mod openapi;
 pub mod pages;
 pub mod routing;
\ No newline at end of file
@@ -0,0 +1,36 @@
+use crate::features::assets::passthrough;
+use crate::features::{handlers, live, pages};
+use utoipa::OpenApi;
+
+/// The read API as an OpenAPI 3.1 document, served at `/openapi.json`.
+/// Handlers are registered here by hand, so a new endpoint needs both a
+/// `#[utoipa::path]` and an entry below.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Chasqui", description = "Pages, tags and feeds served by a Chasqui backend."),
+    paths(
+        pages::list_pages_handler,
+        pages::get_page_handler,
+        pages::get_page_by_filename_handler,
+        pages::batch_pages_handler,
+        pages::html_page_handler,
+        pages::sitemap_handler,
+        handlers::metadata_handler,
+        pages::tags_handler,
+        pages::stats_handler,
+        pages::search_index_handler,
+        pages::feed_handler,
+        pages::json_feed_handler,
+        pages::tag_feed_handler,
+        passthrough::passthrough_handler,
+        handlers::status_handler,
+        live::ws_handler,
+    ),
+    tags(
+        (name = "pages", description = "Page lookups and listings"),
+        (name = "feeds", description = "RSS and JSON Feed documents"),
+        (name = "assets", description = "Data files served as they are on disk"),
+        (name = "status", description = "Sync state and live change notifications"),
+    )
+)]
+pub struct ApiDoc;
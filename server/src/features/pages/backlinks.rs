@@ -0,0 +1,73 @@
+use chasqui_core::config::ChasquiConfig;
+use chasqui_core::features::pages::model::Page;
+use chasqui_core::parser::markdown::{is_external_url, link_destinations};
+use std::collections::{HashMap, HashSet};
+
+/// Who links to whom. Sources are kept by filename, since a page's own
+/// identifier can change without its links changing; targets by identifier,
+/// which is what a compiled link points at.
+#[derive(Default)]
+pub struct BacklinkIndex {
+    outgoing: HashMap<String, HashSet<String>>,
+    incoming: HashMap<String, HashSet<String>>,
+}
+
+impl BacklinkIndex {
+    /// Replaces the outgoing edges of `filename` with `targets`.
+    pub fn set_links(&mut self, filename: &str, targets: HashSet<String>) {
+        self.remove(filename);
+        for target in &targets {
+            self.incoming
+                .entry(target.clone())
+                .or_default()
+                .insert(filename.to_string());
+        }
+        if !targets.is_empty() {
+            self.outgoing.insert(filename.to_string(), targets);
+        }
+    }
+
+    /// Drops every edge leaving `filename`.
+    pub fn remove(&mut self, filename: &str) {
+        let Some(targets) = self.outgoing.remove(filename) else {
+            return;
+        };
+        for target in targets {
+            if let Some(sources) = self.incoming.get_mut(&target) {
+                sources.remove(filename);
+                if sources.is_empty() {
+                    self.incoming.remove(&target);
+                }
+            }
+        }
+    }
+
+    /// Filenames of the pages linking to `identifier`.
+    pub fn sources(&self, identifier: &str) -> Vec<String> {
+        self.incoming
+            .get(identifier)
+            .map(|sources| sources.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Identifiers `page` links to, read back from its compiled links. Those
+/// point at `{BASE_PATH}/{identifier}`, or at `{BASE_PATH}/` for the home page.
+pub fn link_targets(page: &Page, config: &ChasquiConfig) -> HashSet<String> {
+    link_destinations(&page.md_content)
+        .iter()
+        .filter(|dest| !is_external_url(dest))
+        .filter_map(|dest| {
+            let path = dest.split(['#', '?']).next().unwrap_or(dest);
+            let rest = path.strip_prefix(config.base_path.as_str())?.strip_prefix('/')?;
+            let identifier = rest.trim_end_matches('/');
+
+            if identifier.is_empty() {
+                config.serve_home.then(|| config.home_identifier.clone())
+            } else {
+                Some(identifier.to_string())
+            }
+        })
+        .filter(|identifier| identifier != &page.identifier)
+        .collect()
+}
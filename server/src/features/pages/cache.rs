@@ -0,0 +1,25 @@
+use chasqui_core::config::ChasquiConfig;
+use chasqui_core::features::pages::model::Page;
+use chrono::NaiveDateTime;
+
+/// `Cache-Control` for a single page. A page untouched for
+/// `STABLE_PAGE_AFTER_DAYS` is unlikely to change again, so it gets the
+/// longer `STABLE_PAGE_MAX_AGE`; undated pages never count as stable.
+pub fn page_cache_control(page: &Page, config: &ChasquiConfig, now: NaiveDateTime) -> String {
+    let stable = config.stable_page_after_days > 0
+        && page.modified_datetime.is_some_and(|modified| {
+            now.signed_duration_since(modified).num_days() >= config.stable_page_after_days as i64
+        });
+
+    let max_age = if stable {
+        config.stable_page_max_age
+    } else {
+        config.page_cache_max_age
+    };
+    format!("public, max-age={}", max_age)
+}
+
+/// `Cache-Control` for lists and feeds, which change whenever any page does.
+pub fn list_cache_control(config: &ChasquiConfig) -> String {
+    format!("public, max-age={}", config.list_cache_max_age)
+}
@@ -0,0 +1,69 @@
+use chasqui_core::parser::markdown::LinkResolution;
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+
+/// A page body after link resolution, with how each of its links resolved
+/// at the time.
+#[derive(Clone, Debug)]
+pub struct CompiledBody {
+    pub md_content: String,
+    pub links: Vec<LinkResolution>,
+}
+
+/// Least-recently-used compiled bodies keyed by filename and body hash, so
+/// rebuilding a page whose body was seen before skips `precompile_markdown`.
+/// Mostly pays off with remote readers, where every full sync rebuilds pages
+/// the feature cache no longer holds.
+///
+/// The output also depends on the manifest, so a hit only counts while every
+/// recorded link still resolves the same way. A capacity of 0 disables it.
+pub struct CompiledPageCache {
+    entries: Option<Mutex<LruCache<(String, String), CompiledBody>>>,
+}
+
+impl CompiledPageCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: NonZeroUsize::new(capacity).map(|cap| Mutex::new(LruCache::new(cap))),
+        }
+    }
+
+    pub fn get(
+        &self,
+        filename: &str,
+        body_hash: &str,
+        is_current: impl Fn(&CompiledBody) -> bool,
+    ) -> Option<CompiledBody> {
+        let mut entries = self.entries.as_ref()?.lock().unwrap();
+        let key = (filename.to_string(), body_hash.to_string());
+
+        match entries.get(&key) {
+            Some(body) if is_current(body) => Some(body.clone()),
+            Some(_) => {
+                entries.pop(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, filename: &str, body_hash: &str, body: CompiledBody) {
+        if let Some(entries) = &self.entries {
+            entries
+                .lock()
+                .unwrap()
+                .put((filename.to_string(), body_hash.to_string()), body);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries
+            .as_ref()
+            .map_or(0, |entries| entries.lock().unwrap().len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,68 @@
+use axum::http::{header, HeaderMap};
+use serde::Serialize;
+use std::io::Write;
+use xxhash_rust::xxh3::Xxh3;
+
+/// Strong validator and byte length of a response body.
+pub struct BodyDigest {
+    pub etag: String,
+    pub len: u64,
+}
+
+impl BodyDigest {
+    pub fn of_bytes(bytes: &[u8]) -> Self {
+        let mut writer = DigestWriter::default();
+        writer.update(bytes);
+        writer.finish()
+    }
+
+    /// Same digest as `of_bytes` on the serialized JSON, but streamed so the
+    /// body is never held in memory. Lets HEAD answer without building it.
+    pub fn of_json<T: Serialize>(value: &T) -> serde_json::Result<Self> {
+        let mut writer = DigestWriter::default();
+        serde_json::to_writer(&mut writer, value)?;
+        Ok(writer.finish())
+    }
+
+    /// Whether the request's `If-None-Match` already names this body.
+    pub fn matches(&self, request_headers: &HeaderMap) -> bool {
+        request_headers
+            .get_all(header::IF_NONE_MATCH)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == self.etag)
+    }
+}
+
+#[derive(Default)]
+struct DigestWriter {
+    hasher: Xxh3,
+    len: u64,
+}
+
+impl DigestWriter {
+    fn update(&mut self, bytes: &[u8]) {
+        self.hasher.update(bytes);
+        self.len += bytes.len() as u64;
+    }
+
+    fn finish(self) -> BodyDigest {
+        BodyDigest {
+            etag: format!("\"{:016x}\"", self.hasher.digest()),
+            len: self.len,
+        }
+    }
+}
+
+impl Write for DigestWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
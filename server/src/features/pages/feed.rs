@@ -0,0 +1,117 @@
+use chasqui_core::config::ChasquiConfig;
+use chasqui_core::features::pages::model::Page;
+use crate::features::pages::sitemap::escape_xml;
+use crate::features::routing::{absolute_url, home_path, identifier_to_path};
+use chasqui_core::parser::markdown::render_html;
+use serde::Serialize;
+use utoipa::ToSchema;
+use std::cmp::Reverse;
+
+pub const JSON_FEED_VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+// newest first; undated pages sink to the end
+fn feed_items(pages: &[Page]) -> Vec<&Page> {
+    let mut items: Vec<&Page> = pages.iter().collect();
+    items.sort_by_key(|p| (Reverse(p.created_datetime), p.identifier.clone()));
+    items
+}
+
+/// Builds an RSS 2.0 document from `pages`, newest first.
+pub fn build_feed(pages: &[Page], config: &ChasquiConfig, title: &str) -> String {
+    let items = feed_items(pages);
+
+    let site = absolute_url(config, &home_path(config));
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\">\n<channel>\n");
+    xml.push_str(&format!("  <title>{}</title>\n", escape_xml(title)));
+    xml.push_str(&format!("  <link>{}</link>\n", escape_xml(&site)));
+    xml.push_str(&format!("  <description>{}</description>\n", escape_xml(title)));
+
+    for page in items {
+        let link = absolute_url(config, &identifier_to_path(config, &page.identifier));
+        xml.push_str("  <item>\n");
+        xml.push_str(&format!(
+            "    <title>{}</title>\n",
+            escape_xml(page.name.as_deref().unwrap_or(&page.identifier))
+        ));
+        xml.push_str(&format!("    <link>{}</link>\n", escape_xml(&link)));
+        xml.push_str(&format!("    <guid>{}</guid>\n", escape_xml(&link)));
+        if let Some(description) = &page.description {
+            xml.push_str(&format!("    <description>{}</description>\n", escape_xml(description)));
+        }
+        if let Some(created) = page.created_datetime {
+            // RFC 822 dates; stored datetimes are naive UTC
+            xml.push_str(&format!(
+                "    <pubDate>{}</pubDate>\n",
+                created.and_utc().to_rfc2822()
+            ));
+        }
+        for tag in &page.tags {
+            xml.push_str(&format!("    <category>{}</category>\n", escape_xml(tag)));
+        }
+        xml.push_str("  </item>\n");
+    }
+
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+#[derive(Serialize, ToSchema, Debug)]
+pub struct JsonFeed {
+    pub version: &'static str,
+    pub title: String,
+    pub home_page_url: String,
+    pub feed_url: String,
+    pub items: Vec<JsonFeedItem>,
+}
+
+#[derive(Serialize, ToSchema, Debug)]
+pub struct JsonFeedItem {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub content_html: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_published: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// The same items as `build_feed`, as a JSON Feed 1.1 document.
+pub fn build_json_feed(pages: &[Page], config: &ChasquiConfig, title: &str) -> JsonFeed {
+    let items = feed_items(pages)
+        .into_iter()
+        .map(|page| {
+            let url = absolute_url(config, &identifier_to_path(config, &page.identifier));
+            JsonFeedItem {
+                id: url.clone(),
+                url,
+                title: page.name.clone().unwrap_or_else(|| page.identifier.clone()),
+                content_html: render_html(&page.md_content),
+                summary: page.description.clone(),
+                // RFC 3339; stored datetimes are naive UTC
+                date_published: page.created_datetime.map(|created| created.and_utc().to_rfc3339()),
+                tags: page.tags.clone(),
+            }
+        })
+        .collect();
+
+    JsonFeed {
+        version: JSON_FEED_VERSION,
+        title: title.to_string(),
+        home_page_url: absolute_url(config, &home_path(config)),
+        feed_url: absolute_url(config, "/feed.json"),
+        items,
+    }
+}
+
+/// Channel title for the site-wide feed.
+pub fn feed_title(config: &ChasquiConfig) -> String {
+    config.site_url.trim_end_matches('/').to_string()
+}
+
+/// Channel title for a single tag's feed.
+pub fn tag_feed_title(config: &ChasquiConfig, tag: &str) -> String {
+    format!("{} - tagged \"{}\"", feed_title(config), tag)
+}
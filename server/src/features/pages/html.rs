@@ -0,0 +1,111 @@
+use chasqui_core::features::pages::model::{DatetimeFormat, Page};
+use chasqui_core::parser::markdown::render_html;
+use crate::features::pages::sitemap::escape_xml;
+use serde_json::Value;
+
+/// Built-in skeleton used when no `HTML_TEMPLATE_PATH` is configured.
+/// Templates fill `{{title}}`, `{{meta}}` and `{{content}}`, and for pages
+/// also `{{identifier}}`, `{{description}}`, `{{tags}}`,
+/// `{{created_datetime}}`, `{{modified_datetime}}` and `{{metadata.<field>}}`
+/// for any text, number or boolean frontmatter field.
+pub const DEFAULT_TEMPLATE: &str = "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<title>{{title}}</title>
+{{meta}}</head>
+<body>
+<main>
+{{content}}</main>
+</body>
+</html>
+";
+
+/// The `layout` frontmatter field, naming the template a page is served in.
+pub fn page_layout(page: &Page) -> Option<&str> {
+    page.metadata
+        .get("layout")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|layout| !layout.is_empty())
+}
+
+pub fn render_page_document(page: &Page, template: &str) -> String {
+    let title = page.name.as_deref().unwrap_or(&page.identifier);
+    let meta = page
+        .description
+        .as_deref()
+        .map(|d| format!("<meta name=\"description\" content=\"{}\">\n", escape_xml(d)))
+        .unwrap_or_default();
+    let datetime = |dt: Option<chrono::NaiveDateTime>| {
+        dt.map(|dt| DatetimeFormat::Rfc3339.format(dt)).unwrap_or_default()
+    };
+
+    let mut values = vec![
+        ("title".to_string(), escape_xml(title)),
+        ("meta".to_string(), meta),
+        ("content".to_string(), render_html(&page.md_content)),
+        ("identifier".to_string(), escape_xml(&page.identifier)),
+        ("description".to_string(), escape_xml(page.description.as_deref().unwrap_or_default())),
+        ("tags".to_string(), escape_xml(&page.tags.join(", "))),
+        ("created_datetime".to_string(), datetime(page.created_datetime)),
+        ("modified_datetime".to_string(), datetime(page.modified_datetime)),
+    ];
+    // lists and tables have no obvious text form, so they're left out
+    for (field, value) in &page.metadata {
+        let text = match value {
+            Value::String(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            _ => continue,
+        };
+        values.push((format!("metadata.{}", field), escape_xml(&text)));
+    }
+
+    let values: Vec<(&str, &str)> = values.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    fill_template(template, &values)
+}
+
+pub fn render_not_found_document(template: &str) -> String {
+    fill_template(
+        template,
+        &[
+            ("title", "Not Found"),
+            ("meta", ""),
+            ("content", "<h1>Not Found</h1>\n<p>No page exists at this address.</p>\n"),
+        ],
+    )
+}
+
+// Single pass so placeholder-looking text inside page content is left alone.
+fn fill_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let value = after.find("}}").and_then(|end| {
+            let key = after[..end].trim();
+            values
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| (*v, end + 2))
+        });
+
+        match value {
+            Some((value, consumed)) => {
+                output.push_str(value);
+                rest = &after[consumed..];
+            }
+            None => {
+                output.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
@@ -0,0 +1,30 @@
+use axum::http::{header, HeaderMap};
+use chrono::{DateTime, NaiveDateTime, SubsecRound};
+
+/// `modified` as an IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+/// Page datetimes are UTC.
+pub fn format_http_date(modified: NaiveDateTime) -> String {
+    modified.and_utc().format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Parses any of the three HTTP-date forms a client may send: IMF-fixdate
+/// (RFC 1123), the obsolete RFC 850 form, and asctime.
+pub fn parse_http_date(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    DateTime::parse_from_rfc2822(value)
+        .map(|dt| dt.naive_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%A, %d-%b-%y %H:%M:%S GMT"))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%a %b %e %H:%M:%S %Y"))
+        .ok()
+}
+
+/// Whether `If-Modified-Since` is at or after `modified`. HTTP dates carry
+/// whole seconds, so a page modified within the same second still counts.
+/// An unparseable date is ignored, as RFC 9110 requires.
+pub fn not_modified_since(request_headers: &HeaderMap, modified: NaiveDateTime) -> bool {
+    request_headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_http_date)
+        .is_some_and(|since| since >= modified.trunc_subsecs(0))
+}
@@ -0,0 +1,129 @@
+use crate::services::sync::manifest::Manifest;
+use chasqui_core::features::model::FeatureType;
+use chasqui_core::io::path_utils::normalize_logical_path;
+use chasqui_core::io::ContentReader;
+use chasqui_core::parser::markdown::{extract_frontmatter, include_directives};
+use std::path::Path;
+use tracing::warn;
+
+/// How many includes may nest inside one another before the innermost is
+/// treated as broken.
+pub const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// A page body with its `{{include: …}}` directives spliced in.
+pub struct ExpandedBody {
+    pub markdown: String,
+    /// Directives that could not be spliced, each left as a placeholder.
+    pub broken: Vec<String>,
+    /// Filenames of the pages spliced in, nested ones too.
+    pub included: Vec<String>,
+}
+
+/// Replaces every `{{include: target}}` paragraph in `body` with the body of
+/// the page it names, recursively. A target is a filename relative to its
+/// content root, one relative to `filename` when it starts with `./` or
+/// `../`, or an identifier. Only pages in the manifest can be included, so a
+/// target never reaches outside the content roots.
+///
+/// The snippet's own frontmatter is dropped, and its links resolve from the
+/// including page once spliced. The pages spliced in are listed under
+/// `included`, which is how a sync knows to rebuild a page when one changes.
+pub async fn expand_includes(
+    body: &str,
+    filename: &str,
+    reader: &dyn ContentReader,
+    manifest: &Manifest,
+) -> ExpandedBody {
+    let mut broken = Vec::new();
+    let mut included = Vec::new();
+    let mut stack = vec![filename.to_string()];
+    let markdown = expand(body, &mut stack, reader, manifest, &mut broken, &mut included).await;
+    ExpandedBody {
+        markdown,
+        broken,
+        included,
+    }
+}
+
+// `stack` holds the chain of files being expanded, the current one last
+async fn expand(
+    body: &str,
+    stack: &mut Vec<String>,
+    reader: &dyn ContentReader,
+    manifest: &Manifest,
+    broken: &mut Vec<String>,
+    included: &mut Vec<String>,
+) -> String {
+    let directives = include_directives(body);
+    if directives.is_empty() {
+        return body.to_string();
+    }
+
+    let current = stack.last().cloned().unwrap_or_default();
+    let mut output = String::with_capacity(body.len());
+    let mut last = 0;
+
+    for (range, target) in directives {
+        output.push_str(&body[last..range.start]);
+        last = range.end;
+
+        let file = included_file(&target, &current, manifest);
+        // a failed one counts too, since an edit to it may fix the include
+        if let Some(file) = file.as_ref().filter(|f| !included.contains(f)) {
+            included.push(file.clone());
+        }
+
+        let failure = match file {
+            None => Some("not found"),
+            Some(file) if stack.contains(&file) => Some("include cycle"),
+            Some(_) if stack.len() > MAX_INCLUDE_DEPTH => Some("nested too deeply"),
+            Some(file) => match read_snippet(&file, reader, manifest).await {
+                Some(snippet) => {
+                    stack.push(file);
+                    let expanded = Box::pin(expand(&snippet, stack, reader, manifest, broken, included)).await;
+                    stack.pop();
+                    output.push_str(expanded.trim_end());
+                    None
+                }
+                None => Some("unreadable"),
+            },
+        };
+
+        if let Some(reason) = failure {
+            warn!(filename = %current, target = %target, reason, "include failed");
+            broken.push(format!("{} ({})", target, reason));
+            output.push_str(&format!("*[include failed: {}]*", target));
+        }
+        if body[range].ends_with('\n') {
+            output.push('\n');
+        }
+    }
+
+    output.push_str(&body[last..]);
+    output
+}
+
+// the filename of the page `target` names, seen from the file `current`
+fn included_file(target: &str, current: &str, manifest: &Manifest) -> Option<String> {
+    let key = if target.starts_with("./") || target.starts_with("../") {
+        let base = Path::new(current).parent().unwrap_or(Path::new(""));
+        normalize_logical_path(base.join(target))
+    } else {
+        target.trim_start_matches('/').to_string()
+    };
+
+    let filename = if manifest.filenames.contains(&key) {
+        key
+    } else {
+        manifest.file_for_identifier(&key)?.clone()
+    };
+
+    (manifest.feature_types.get(&filename) == Some(&FeatureType::Page)).then_some(filename)
+}
+
+async fn read_snippet(filename: &str, reader: &dyn ContentReader, manifest: &Manifest) -> Option<String> {
+    let path = manifest.file_paths.get(filename)?;
+    let raw = reader.read_to_string(path).await.ok()?;
+    let (_, body) = extract_frontmatter(&raw, filename).ok()?;
+    Some(body)
+}
@@ -0,0 +1,54 @@
+use chasqui_core::config::ChasquiConfig;
+use chasqui_core::features::pages::model::Page;
+use crate::features::routing::{absolute_url, identifier_to_path};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+// frontmatter field holding the social preview image
+const COVER_IMAGE_FIELD: &str = "cover_image";
+
+/// Open Graph style sharing metadata for one page.
+#[derive(Serialize)]
+pub struct PageMeta {
+    pub title: String,
+    pub description: Option<String>,
+    pub og_image: Option<String>,
+    pub url: String,
+    pub published_time: Option<String>,
+    pub modified_time: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl PageMeta {
+    pub fn from_page(page: &Page, config: &ChasquiConfig) -> Self {
+        let og_image = page
+            .metadata
+            .get(COVER_IMAGE_FIELD)
+            .and_then(|value| value.as_str())
+            .filter(|image| !image.is_empty())
+            .map(|image| absolute_image_url(config, image));
+
+        Self {
+            title: page.name.clone().unwrap_or_else(|| page.identifier.clone()),
+            description: page.description.clone(),
+            og_image,
+            url: absolute_url(config, &identifier_to_path(config, &page.identifier)),
+            published_time: page.created_datetime.map(iso_8601),
+            modified_time: page.modified_datetime.map(iso_8601),
+            tags: page.tags.clone(),
+        }
+    }
+}
+
+// scrapers need absolute image URLs; site-relative ones are anchored to SITE_URL
+fn absolute_image_url(config: &ChasquiConfig, image: &str) -> String {
+    if image.starts_with("http://") || image.starts_with("https://") {
+        return image.to_string();
+    }
+    absolute_url(config, &format!("/{}", image.trim_start_matches('/')))
+}
+
+// stored datetimes are naive UTC
+fn iso_8601(datetime: NaiveDateTime) -> String {
+    datetime.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
@@ -1,40 +1,768 @@
+pub mod backlinks;
+pub mod cache;
+pub mod compiled;
+pub mod etag;
+pub mod feed;
+pub mod html;
+pub mod http_date;
+pub mod include;
+pub mod meta;
+pub mod negotiate;
+pub mod related;
+pub mod search;
 pub mod service;
+pub mod sitemap;
+pub mod sort;
+pub mod stats;
+pub mod tags;
 
-use chasqui_core::features::model::FeatureType;
-use axum::{Json, Router, extract::State, routing::get, http::StatusCode};
+use axum::{Json, Router, body::Body, extract::{rejection::JsonRejection, OriginalUri, Query, State}, routing::get, http::{header, HeaderMap, StatusCode, Uri}, response::{Html, IntoResponse, Response}};
 use crate::app::AppState;
-use chasqui_core::features::pages::model::JsonPage;
+use crate::features::routing::path_to_identifier;
+use crate::telemetry;
+use etag::BodyDigest;
+use negotiate::{JsonDates, PageFormat};
+use chasqui_core::config::ChasquiConfig;
+use chasqui_core::features::model::Feature;
+use chasqui_core::features::pages::model::{JsonPage, Page};
+use chasqui_core::parser::markdown::{is_external_url, render_html, section_markdown};
+use cache::{list_cache_control, page_cache_control};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 
 pub fn pages_router() -> Router<AppState> {
     Router::new()
         .route("/", get(list_pages_handler))
-        .route("/{*identifier}", get(get_page_handler))
-}
-
-async fn list_pages_handler(State(state): State<AppState>) -> Json<Vec<JsonPage>> {
-    let features = state.sync_service.get_all_features_by_type(FeatureType::Page).await;
-    let pages: Vec<JsonPage> = features
-        .into_iter()
-        .filter_map(|f| {
-            if let chasqui_core::features::model::Feature::Page(p) = f {
-                Some((&p).into())
-            } else {
-                None
-            }
+        .route(
+            "/{*identifier}",
+            get(get_page_handler).head(head_page_handler).post(batch_pages_handler),
+        )
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListQuery {
+    /// Earliest creation date, `YYYY-MM-DD`, inclusive.
+    pub from: Option<String>,
+    /// Latest creation date, `YYYY-MM-DD`, inclusive.
+    pub to: Option<String>,
+    /// Only pages modified strictly after this `YYYY-MM-DDTHH:MM:SS` (UTC)
+    /// or RFC 3339 timestamp.
+    pub since: Option<String>,
+    pub tag: Option<String>,
+    /// Match `tag` literally instead of including its `/`-delimited children.
+    pub exact: Option<bool>,
+    /// Defaults to `created`.
+    #[param(inline)]
+    pub sort: Option<sort::SortField>,
+    /// Defaults to `desc` for dates and `asc` for `name` and `identifier`.
+    #[param(inline)]
+    pub order: Option<sort::SortOrder>,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+    /// Add each page's `html_length` and a plain-text `preview`.
+    pub preview: Option<bool>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/pages",
+    tag = "pages",
+    params(
+        ListQuery,
+        ("datetime_format" = Option<String>, Query, description = "`legacy` or `rfc3339`; defaults to `JSON_DATETIME_FORMAT`"),
+    ),
+    responses(
+        (status = 200, description = "Pages in the requested order", body = Vec<JsonPage>,
+            headers(("x-total-count" = usize, description = "Matching pages before offset/limit"))),
+        (status = 400, description = "Malformed date bound, sort or datetime format parameter", body = String),
+    )
+)]
+async fn list_pages_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ListQuery>,
+    dates: JsonDates,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let from = parse_date_bound("from", query.from.as_deref())?;
+    let to = parse_date_bound("to", query.to.as_deref())?;
+    let since = parse_since(query.since.as_deref())?;
+
+    let mut pages = state.sync_service.get_all_pages().await;
+
+    // both bounds are inclusive; undated pages can't be placed in a range
+    if from.is_some() || to.is_some() {
+        pages.retain(|p| {
+            p.created_datetime.is_some_and(|created| {
+                let date = created.date();
+                from.is_none_or(|from| date >= from) && to.is_none_or(|to| date <= to)
+            })
+        });
+    }
+
+    // strictly newer, so a build started at `since` doesn't refetch its own input
+    if let Some(since) = since {
+        pages.retain(|p| p.modified_datetime.is_some_and(|modified| modified > since));
+    }
+
+    if let Some(tag) = &query.tag {
+        let exact = query.exact.unwrap_or(false);
+        pages.retain(|p| tags::page_has_tag(p, tag, exact));
+    }
+
+    // a total order keeps offset/limit pages stable between requests
+    let field = query.sort.unwrap_or_default();
+    let order = query.order.unwrap_or_else(|| field.default_order());
+    sort::sort_pages(&mut pages, field, order);
+    let total = pages.len();
+
+    let limit = query
+        .limit
+        .unwrap_or(state.config.max_list_pages)
+        .min(state.config.max_list_pages);
+    let page_slice: Vec<JsonPage> = pages
+        .iter()
+        .skip(query.offset.unwrap_or(0))
+        .take(limit)
+        .map(|p| match query.preview {
+            Some(true) => dates.page(p).with_preview(p),
+            _ => dates.page(p),
         })
         .collect();
-    Json(pages)
+
+    Ok((
+        [
+            (header::HeaderName::from_static("x-total-count"), total.to_string()),
+            (header::CACHE_CONTROL, list_cache_control(&state.config)),
+        ],
+        Json(page_slice),
+    ))
 }
 
+fn parse_date_bound(name: &str, raw: Option<&str>) -> Result<Option<NaiveDate>, (StatusCode, String)> {
+    raw.map(|value| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid `{}` date '{}': expected YYYY-MM-DD", name, value),
+            )
+        })
+    })
+    .transpose()
+}
+
+// `YYYY-MM-DDTHH:MM:SS` in UTC; an RFC 3339 offset is converted to UTC
+fn parse_since(raw: Option<&str>) -> Result<Option<NaiveDateTime>, (StatusCode, String)> {
+    raw.map(|value| {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+            .or_else(|_| DateTime::parse_from_rfc3339(value).map(|dt| dt.naive_utc()))
+            .map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid `since` timestamp '{}': expected YYYY-MM-DDTHH:MM:SS", value),
+                )
+            })
+    })
+    .transpose()
+}
+
+// `/{*identifier}` swallows everything below it, so sub-resources such as
+// `{identifier}/related` are dispatched here. An exact page match wins so a
+// page whose identifier happens to end in `/related` is never shadowed.
+#[utoipa::path(
+    get,
+    path = "/api/pages/{identifier}",
+    tag = "pages",
+    params(
+        ("identifier" = String, Path, description = "Page identifier, may contain `/`"),
+        ("section" = Option<String>, Query, description = "Heading anchor; only that section is returned, as HTML"),
+        ("datetime_format" = Option<String>, Query, description = "`legacy` or `rfc3339`; defaults to `JSON_DATETIME_FORMAT`"),
+    ),
+    responses(
+        (status = 200, description = "The page, as JSON or HTML depending on `Accept`", content(
+            (JsonPage = "application/json"),
+            (String = "text/html"),
+        )),
+        (status = 301, description = "Alias or `redirect_to` page"),
+        (status = 304, description = "`If-None-Match` matched the current ETag, or without one, `If-Modified-Since` is not before `Last-Modified`"),
+        (status = 400, description = "Unknown `datetime_format`", body = String),
+        (status = 404, description = "No such page, or no heading with the `section` anchor"),
+        (status = 410, description = "Page was deleted"),
+    )
+)]
 async fn get_page_handler(
     State(state): State<AppState>,
     axum::extract::Path(identifier): axum::extract::Path<String>,
+    Query(query): Query<PageQuery>,
+    OriginalUri(uri): OriginalUri,
+    format: PageFormat,
+    dates: JsonDates,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let result = resolve_page_route(&state, &identifier, query, &uri, format, dates, &headers).await;
+    telemetry::record_page_request(&result);
+
+    // browsers following a shared link get a readable miss, not an empty body
+    match (format, result) {
+        (PageFormat::Html, Err(status)) => {
+            Ok((status, Html(error_document(&state, status).await)).into_response())
+        }
+        (PageFormat::Json, Err(StatusCode::NOT_FOUND)) => match not_found_page(&state).await {
+            Some(page) => Ok((StatusCode::NOT_FOUND, Json(dates.page(&page))).into_response()),
+            None => Err(StatusCode::NOT_FOUND),
+        },
+        (_, result) => result,
+    }
+}
+
+// Mirrors the GET headers for a page without building its body. Sub-resources
+// fall through to the GET path; axum drops the body for HEAD either way.
+async fn head_page_handler(
+    State(state): State<AppState>,
+    axum::extract::Path(identifier): axum::extract::Path<String>,
+    Query(query): Query<PageQuery>,
+    OriginalUri(uri): OriginalUri,
+    format: PageFormat,
+    dates: JsonDates,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let result = match (format, find_page(&state, &identifier).await) {
+        (PageFormat::Json, Some(p)) if p.redirect_to.is_none() && query.section.is_none() => BodyDigest::of_json(&dates.page(&p))
+            .map(|digest| {
+                let cache_control = page_cache_control(&p, &state.config, Utc::now().naive_utc());
+                page_response(&digest, p.modified_datetime, JSON_CONTENT_TYPE, &cache_control, &headers, Body::empty())
+            })
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR),
+        _ => resolve_page_route(&state, &identifier, query, &uri, format, dates, &headers).await,
+    };
+    telemetry::record_page_request(&result);
+    result
+}
+
+async fn resolve_page_route(
+    state: &AppState,
+    identifier: &str,
+    query: PageQuery,
+    uri: &Uri,
+    format: PageFormat,
+    dates: JsonDates,
+    headers: &HeaderMap,
+) -> Result<Response, StatusCode> {
+    if let Some(p) = find_page(state, identifier).await {
+        if let Some(target) = &p.redirect_to {
+            return Ok(page_redirect(uri, identifier, target));
+        }
+        let cache_control = page_cache_control(&p, &state.config, Utc::now().naive_utc());
+
+        // a section is a fragment for embedding, so it is HTML whatever `Accept` says
+        if let Some(anchor) = &query.section {
+            let section = section_markdown(&p.md_content, anchor).ok_or(StatusCode::NOT_FOUND)?;
+            let body = render_html(section).into_bytes();
+            let digest = BodyDigest::of_bytes(&body);
+            return Ok(page_response(&digest, p.modified_datetime, HTML_CONTENT_TYPE, &cache_control, headers, Body::from(body)));
+        }
+
+        let (body, content_type) = match format {
+            PageFormat::Json => (
+                serde_json::to_vec(&dates.page(&p))
+                    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+                JSON_CONTENT_TYPE,
+            ),
+            PageFormat::Html => {
+                let template = load_page_template(&state.config, &p).await;
+                (html::render_page_document(&p, &template).into_bytes(), HTML_CONTENT_TYPE)
+            }
+        };
+        let digest = BodyDigest::of_bytes(&body);
+        return Ok(page_response(&digest, p.modified_datetime, content_type, &cache_control, headers, Body::from(body)));
+    }
+
+    let lookup = path_to_identifier(&state.config, identifier);
+    if let Some(canonical) = state.sync_service.resolve_alias(&lookup).await {
+        return Ok(page_redirect(uri, identifier, &canonical));
+    }
+
+    if let Some(base) = identifier.strip_suffix("/related") {
+        return related_pages_handler(state, base, query, dates)
+            .await
+            .map(IntoResponse::into_response);
+    }
+
+    if let Some(base) = identifier.strip_suffix("/backlinks") {
+        let page = find_page(state, base).await.ok_or(StatusCode::NOT_FOUND)?;
+        let backlinks = state.sync_service.get_backlinks(&page.identifier).await;
+        return Ok(Json(backlinks.iter().map(|p| dates.page(p)).collect::<Vec<_>>()).into_response());
+    }
+
+    if let Some(base) = identifier.strip_suffix("/meta") {
+        let page = find_page(state, base).await.ok_or(StatusCode::NOT_FOUND)?;
+        return Ok(Json(meta::PageMeta::from_page(&page, &state.config)).into_response());
+    }
+
+    if let Some(base) = identifier.strip_suffix("/history") {
+        let page = find_page(state, base).await.ok_or(StatusCode::NOT_FOUND)?;
+        let history = state.sync_service.page_history(&page.filename).await;
+        return Ok(Json(history.iter().map(|r| dates.revision(r)).collect::<Vec<_>>()).into_response());
+    }
+
+    if let Some((base, hash)) = identifier.rsplit_once("/revisions/") {
+        let page = find_page(state, base).await.ok_or(StatusCode::NOT_FOUND)?;
+        let revision = state
+            .sync_service
+            .page_revision(&page.filename, hash)
+            .await
+            .ok_or(StatusCode::NOT_FOUND)?;
+        return Ok(Json(dates.revision(&revision)).into_response());
+    }
+
+    if let Some(base) = identifier.strip_suffix("/raw") {
+        return raw_page_handler(state, base)
+            .await
+            .map(IntoResponse::into_response);
+    }
+
+    Err(missing_page_status(state, identifier).await)
+}
+
+// URLs are used as-is. An identifier replaces the requested one in the
+// request path, so the redirect stays on whichever route was asked.
+fn page_redirect(uri: &Uri, requested: &str, target: &str) -> Response {
+    let location = if is_external_url(target) {
+        target.to_string()
+    } else {
+        let mut prefix = uri.path();
+        for _ in requested.split('/') {
+            prefix = prefix.rsplit_once('/').map_or("", |(head, _)| head);
+        }
+        format!("{}/{}", prefix, target.trim_start_matches('/'))
+    };
+
+    (StatusCode::MOVED_PERMANENTLY, [(header::LOCATION, location)]).into_response()
+}
+
+// 410 for pages that were soft-deleted, 404 for ones that never existed
+async fn missing_page_status(state: &AppState, identifier: &str) -> StatusCode {
+    let identifier = path_to_identifier(&state.config, identifier);
+    match state.sync_service.page_tombstone(&identifier).await {
+        Some(_) => StatusCode::GONE,
+        None => StatusCode::NOT_FOUND,
+    }
+}
+
+const JSON_CONTENT_TYPE: &str = "application/json";
+const HTML_CONTENT_TYPE: &str = "text/html; charset=utf-8";
+
+// the same URL serves JSON or HTML, so caches must key on `Accept`; a 304
+// repeats `Cache-Control` so the revalidated copy gets a fresh lifetime.
+// `If-None-Match` wins over `If-Modified-Since` when a request sends both.
+fn page_response(
+    digest: &BodyDigest,
+    modified: Option<NaiveDateTime>,
+    content_type: &str,
+    cache_control: &str,
+    request_headers: &HeaderMap,
+    body: Body,
+) -> Response {
+    let not_modified = if request_headers.contains_key(header::IF_NONE_MATCH) {
+        digest.matches(request_headers)
+    } else {
+        modified.is_some_and(|modified| http_date::not_modified_since(request_headers, modified))
+    };
+
+    let mut response = if not_modified {
+        (
+            StatusCode::NOT_MODIFIED,
+            [
+                (header::ETAG, digest.etag.clone()),
+                (header::VARY, "accept".to_string()),
+                (header::CACHE_CONTROL, cache_control.to_string()),
+            ],
+        )
+            .into_response()
+    } else {
+        (
+            [
+                (header::CONTENT_TYPE, content_type.to_string()),
+                (header::VARY, "accept".to_string()),
+                (header::ETAG, digest.etag.clone()),
+                (header::CONTENT_LENGTH, digest.len.to_string()),
+                (header::CACHE_CONTROL, cache_control.to_string()),
+            ],
+            body,
+        )
+            .into_response()
+    };
+
+    if let Some(value) = modified.and_then(|modified| http_date::format_http_date(modified).parse().ok()) {
+        response.headers_mut().insert(header::LAST_MODIFIED, value);
+    }
+    response
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/by-filename/{path}",
+    tag = "pages",
+    params(
+        ("path" = String, Path, description = "Source filename relative to its mount"),
+        ("datetime_format" = Option<String>, Query, description = "`legacy` or `rfc3339`; defaults to `JSON_DATETIME_FORMAT`"),
+    ),
+    responses(
+        (status = 200, body = JsonPage),
+        (status = 404, description = "No page from that file"),
+    )
+)]
+// mounted beside `/api/pages` rather than inside it, where it would shadow
+// every page whose identifier starts with `by-filename/`
+pub async fn get_page_by_filename_handler(
+    State(state): State<AppState>,
+    axum::extract::Path(path): axum::extract::Path<String>,
+    dates: JsonDates,
 ) -> Result<Json<JsonPage>, StatusCode> {
-    if let Some(chasqui_core::features::model::Feature::Page(p)) =
-        state.sync_service.get_feature_by_identifier(&identifier).await
-    {
-        Ok(Json((&p).into()))
+    let result = state
+        .sync_service
+        .get_page_by_filename(&path)
+        .await
+        .map(|p| Json(dates.page(&p)))
+        .ok_or(StatusCode::NOT_FOUND);
+    telemetry::record_page_request(&result);
+    result
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct BatchRequest {
+    pub identifiers: Vec<String>,
+}
+
+// POST goes through the wildcard route rather than its own `/batch`, which
+// would answer GET with 405 and hide a page whose identifier is `batch`
+#[utoipa::path(
+    post,
+    path = "/api/pages/batch",
+    tag = "pages",
+    request_body = BatchRequest,
+    params(("datetime_format" = Option<String>, Query, description = "`legacy` or `rfc3339`; defaults to `JSON_DATETIME_FORMAT`")),
+    responses(
+        (status = 200, description = "Found pages keyed by the identifier asked for; misses are left out",
+            body = std::collections::BTreeMap<String, JsonPage>),
+        (status = 400, description = "More identifiers than `MAX_BATCH_PAGES`", body = String),
+    )
+)]
+async fn batch_pages_handler(
+    State(state): State<AppState>,
+    axum::extract::Path(identifier): axum::extract::Path<String>,
+    dates: JsonDates,
+    request: Result<Json<BatchRequest>, JsonRejection>,
+) -> Result<Json<std::collections::BTreeMap<String, JsonPage>>, Response> {
+    if identifier != "batch" {
+        return Err(StatusCode::METHOD_NOT_ALLOWED.into_response());
+    }
+    let Json(request) = request.map_err(IntoResponse::into_response)?;
+
+    if request.identifiers.len() > state.config.max_batch_pages {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("at most {} identifiers per batch", state.config.max_batch_pages),
+        )
+            .into_response());
+    }
+
+    let mut pages = std::collections::BTreeMap::new();
+    for requested in request.identifiers {
+        if let Some(page) = find_page(&state, &requested).await {
+            pages.insert(requested, dates.page(&page));
+        }
+    }
+    Ok(Json(pages))
+}
+
+async fn find_page(state: &AppState, identifier: &str) -> Option<Page> {
+    let identifier = path_to_identifier(&state.config, identifier);
+    match state.sync_service.get_feature_by_identifier(&identifier).await {
+        Some(Feature::Page(p)) => Some(p),
+        _ => None,
+    }
+}
+
+async fn raw_page_handler(state: &AppState, identifier: &str) -> Result<impl IntoResponse, StatusCode> {
+    let page = find_page(state, identifier).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+        page.md_content,
+    ))
+}
+
+/// Serves a page as a standalone HTML document. Misses get an HTML 404 too,
+/// since this route is meant for browsers rather than API clients.
+#[utoipa::path(
+    get,
+    path = "/p/{identifier}",
+    tag = "pages",
+    params(("identifier" = String, Path, description = "Page identifier, may contain `/`")),
+    responses(
+        (status = 200, description = "The page as a full HTML document", body = String, content_type = "text/html"),
+        (status = 404, description = "No such page", body = String, content_type = "text/html"),
+        (status = 410, description = "The page was deleted", body = String, content_type = "text/html"),
+    )
+)]
+pub async fn html_page_handler(
+    State(state): State<AppState>,
+    axum::extract::Path(identifier): axum::extract::Path<String>,
+) -> Response {
+    let result = match find_page(&state, &identifier).await {
+        Some(page) => Ok(page),
+        None => Err(missing_page_status(&state, &identifier).await),
+    };
+    telemetry::record_page_request(&result);
+
+    match result {
+        Ok(page) => {
+            let template = load_page_template(&state.config, &page).await;
+            Html(html::render_page_document(&page, &template)).into_response()
+        }
+        Err(status) => (status, Html(error_document(&state, status).await)).into_response(),
+    }
+}
+
+// only a 404 uses the content's page; a 410 keeps the built-in document
+async fn error_document(state: &AppState, status: StatusCode) -> String {
+    let not_found = match status {
+        StatusCode::NOT_FOUND => not_found_page(state).await,
+        _ => None,
+    };
+    match not_found {
+        Some(page) => html::render_page_document(&page, &load_page_template(&state.config, &page).await),
+        None => html::render_not_found_document(&load_template(&state.config).await),
+    }
+}
+
+// the content's own not-found page, e.g. `404.md`, when there is one
+async fn not_found_page(state: &AppState) -> Option<Page> {
+    if state.config.not_found_identifier.is_empty() {
+        return None;
+    }
+    find_page(state, &state.config.not_found_identifier).await
+}
+
+// read per request so template edits show up without a restart
+async fn load_template(config: &ChasquiConfig) -> String {
+    let Some(path) = &config.html_template_path else {
+        return html::DEFAULT_TEMPLATE.to_string();
+    };
+
+    match tokio::fs::read_to_string(path).await {
+        Ok(template) => template,
+        Err(e) => {
+            tracing::warn!(path = %path.display(), error = %e, "failed to read HTML template, using default");
+            html::DEFAULT_TEMPLATE.to_string()
+        }
+    }
+}
+
+// A page's `layout` picks `{HTML_LAYOUTS_DIR}/{layout}.html`. Without one,
+// or when it names no readable template, the default template is used, so a
+// typo in a page's frontmatter never turns into a 500.
+async fn load_page_template(config: &ChasquiConfig, page: &Page) -> String {
+    let Some(layout) = html::page_layout(page) else {
+        return load_template(config).await;
+    };
+
+    // names are plain words, so a layout can't reach outside the dir
+    let is_name = layout.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    let path = match &config.html_layouts_dir {
+        Some(dir) if is_name => dir.join(format!("{}.html", layout)),
+        _ => {
+            tracing::warn!(layout, identifier = %page.identifier, "unknown layout, using default template");
+            return load_template(config).await;
+        }
+    };
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(template) => template,
+        Err(e) => {
+            tracing::warn!(layout, path = %path.display(), error = %e, "failed to read layout, using default template");
+            load_template(config).await
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PageQuery {
+    /// Only for `{identifier}/related`.
+    pub limit: Option<usize>,
+    /// Only for `{identifier}` itself: serves just the section under the
+    /// heading with this anchor, as HTML, or 404 when no heading has it.
+    /// Absent serves the whole page.
+    pub section: Option<String>,
+}
+
+async fn related_pages_handler(
+    state: &AppState,
+    identifier: &str,
+    query: PageQuery,
+    dates: JsonDates,
+) -> Result<Json<Vec<JsonPage>>, StatusCode> {
+    let target = find_page(state, identifier).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    let pages = state.sync_service.get_all_pages().await;
+    let limit = query.limit.unwrap_or(related::DEFAULT_RELATED_LIMIT);
+    let related = related::related_pages(&target, &pages, limit);
+
+    Ok(Json(related.into_iter().map(|p| dates.page(p)).collect()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/sitemap.xml",
+    tag = "pages",
+    responses((status = 200, description = "Sitemap of every published page", body = String, content_type = "application/xml"))
+)]
+pub async fn sitemap_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let mut pages = state.sync_service.get_all_pages().await;
+    pages.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+
+    (
+        [(header::CONTENT_TYPE, "application/xml")],
+        sitemap::build_sitemap(&pages, &state.config),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/feed.xml",
+    tag = "feeds",
+    responses((status = 200, description = "RSS 2.0 feed", body = String, content_type = "application/rss+xml"))
+)]
+pub async fn feed_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let pages = state.sync_service.get_all_pages().await;
+
+    (
+        [
+            (header::CONTENT_TYPE, "application/rss+xml".to_string()),
+            (header::CACHE_CONTROL, list_cache_control(&state.config)),
+        ],
+        feed::build_feed(&pages, &state.config, &feed::feed_title(&state.config)),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/feed.json",
+    tag = "feeds",
+    responses((status = 200, body = feed::JsonFeed, content_type = "application/feed+json"))
+)]
+pub async fn json_feed_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let pages = state.sync_service.get_all_pages().await;
+    let feed = feed::build_json_feed(&pages, &state.config, &feed::feed_title(&state.config));
+
+    (
+        [
+            (header::CONTENT_TYPE, "application/feed+json".to_string()),
+            (header::CACHE_CONTROL, list_cache_control(&state.config)),
+        ],
+        Json(feed),
+    )
+}
+
+// Unknown tags still get a valid empty feed so readers keep the subscription.
+#[utoipa::path(
+    get,
+    path = "/feed/{file}",
+    tag = "feeds",
+    params(("file" = String, Path, description = "`{tag}.xml`")),
+    responses(
+        (status = 200, description = "RSS 2.0 feed of one tag", body = String, content_type = "application/rss+xml"),
+        (status = 404, description = "Not an `.xml` path"),
+    )
+)]
+pub async fn tag_feed_handler(
+    State(state): State<AppState>,
+    axum::extract::Path(file): axum::extract::Path<String>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let tag = file.strip_suffix(".xml").ok_or(StatusCode::NOT_FOUND)?;
+
+    // the same pages `/api/pages?tag=` returns, child tags included
+    let mut pages = state.sync_service.get_all_pages().await;
+    pages.retain(|p| tags::page_has_tag(p, tag, false));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/rss+xml".to_string()),
+            (header::CACHE_CONTROL, list_cache_control(&state.config)),
+        ],
+        feed::build_feed(&pages, &state.config, &feed::tag_feed_title(&state.config, tag)),
+    ))
+}
+
+// derived from the in-memory cache like the other listings; nothing here
+// touches the database or the cache's write side
+#[utoipa::path(
+    get,
+    path = "/api/stats",
+    tag = "pages",
+    responses((status = 200, body = stats::PageStats))
+)]
+pub async fn stats_handler(State(state): State<AppState>) -> Json<stats::PageStats> {
+    let pages = state.sync_service.get_all_pages().await;
+    Json(stats::page_stats(&pages))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TagsQuery {
+    /// Nest `/`-delimited tags instead of listing flat counts.
+    pub tree: Option<bool>,
+}
+
+#[derive(serde::Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum TagListing {
+    Flat(std::collections::BTreeMap<String, usize>),
+    Tree(Vec<tags::TagNode>),
+}
+
+// flat counts are per tag, case folded like `?tag=`; `?tree=true` nests `/`-delimited tags
+// and counts each node the way `?tag=` would filter
+#[utoipa::path(
+    get,
+    path = "/api/tags",
+    tag = "pages",
+    params(TagsQuery),
+    responses((status = 200, body = TagListing))
+)]
+pub async fn tags_handler(
+    State(state): State<AppState>,
+    Query(query): Query<TagsQuery>,
+) -> Json<TagListing> {
+    let pages = state.sync_service.get_all_pages().await;
+
+    if query.tree.unwrap_or(false) {
+        Json(TagListing::Tree(tags::tag_tree(&pages)))
     } else {
-        Err(StatusCode::NOT_FOUND)
+        Json(TagListing::Flat(tags::tag_counts(&pages)))
     }
-}
\ No newline at end of file
+}
+
+#[utoipa::path(
+    get,
+    path = "/search-index.json",
+    tag = "pages",
+    responses((status = 200, body = Vec<search::SearchEntry>))
+)]
+pub async fn search_index_handler(State(state): State<AppState>) -> impl IntoResponse {
+    // read before collecting pages so a concurrent sync only makes us rebuild again
+    let generation = state.sync_service.cache_generation();
+
+    let index = match state.search_index.get(generation).await {
+        Some(index) => index,
+        None => {
+            let pages = state.sync_service.get_all_pages().await;
+            let expires = state.sync_service.next_scheduled_publication().await;
+            let json = serde_json::to_string(&search::build_search_index(&pages)).unwrap_or_else(|_| "[]".to_string());
+            state.search_index.store(generation, json, expires).await
+        }
+    };
+
+    ([(header::CONTENT_TYPE, "application/json")], index.to_string())
+}
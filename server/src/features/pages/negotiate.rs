@@ -0,0 +1,95 @@
+use axum::extract::{FromRequestParts, Query};
+use axum::http::{header, request::Parts, HeaderMap, StatusCode};
+use chasqui_core::features::pages::model::{DatetimeFormat, JsonPage, JsonPageRevision, Page, PageRevision};
+use crate::app::AppState;
+use serde::Deserialize;
+use std::convert::Infallible;
+
+/// Representation a client asked for through its `Accept` header.
+///
+/// HTML is only chosen when `text/html` is strictly preferred over JSON, so a
+/// missing header, `*/*` or a tie keeps API clients on JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageFormat {
+    Json,
+    Html,
+}
+
+impl PageFormat {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let mut html_q = 0.0;
+        let mut json_q = 0.0;
+
+        for range in headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+        {
+            let mut params = range.split(';');
+            let media_type = params.next().unwrap_or("").trim().to_ascii_lowercase();
+            let q = params
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            match media_type.as_str() {
+                "text/html" => html_q = f32::max(html_q, q),
+                "application/json" | "application/*" | "*/*" => json_q = f32::max(json_q, q),
+                _ => {}
+            }
+        }
+
+        if html_q > json_q {
+            PageFormat::Html
+        } else {
+            PageFormat::Json
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for PageFormat {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(PageFormat::from_headers(&parts.headers))
+    }
+}
+
+/// Timestamp format a client asked for with `?datetime_format=`, falling back
+/// to `JSON_DATETIME_FORMAT`.
+#[derive(Debug, Clone, Copy)]
+pub struct JsonDates(pub DatetimeFormat);
+
+impl JsonDates {
+    pub fn page(self, page: &Page) -> JsonPage {
+        JsonPage::with_datetime_format(page, self.0)
+    }
+
+    pub fn revision(self, revision: &PageRevision) -> JsonPageRevision {
+        JsonPageRevision::with_datetime_format(revision, self.0)
+    }
+}
+
+#[derive(Deserialize)]
+struct DatetimeQuery {
+    datetime_format: Option<String>,
+}
+
+impl FromRequestParts<AppState> for JsonDates {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let requested = Query::<DatetimeQuery>::try_from_uri(&parts.uri)
+            .ok()
+            .and_then(|Query(query)| query.datetime_format);
+
+        match requested {
+            None => Ok(JsonDates(state.config.json_datetime_format)),
+            Some(name) => DatetimeFormat::parse(&name).map(JsonDates).ok_or((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid `datetime_format` '{}': expected legacy or rfc3339", name),
+            )),
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use chasqui_core::features::pages::model::Page;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+pub const DEFAULT_RELATED_LIMIT: usize = 5;
+
+pub fn related_pages<'a>(target: &Page, pages: &'a [Page], limit: usize) -> Vec<&'a Page> {
+    if target.tags.is_empty() {
+        return Vec::new();
+    }
+
+    let target_tags: HashSet<&str> = target.tags.iter().map(String::as_str).collect();
+
+    let mut scored: Vec<(usize, &Page)> = pages
+        .iter()
+        .filter(|p| p.identifier != target.identifier)
+        .filter_map(|p| {
+            let shared = p
+                .tags
+                .iter()
+                .map(String::as_str)
+                .collect::<HashSet<_>>()
+                .intersection(&target_tags)
+                .count();
+            (shared > 0).then_some((shared, p))
+        })
+        .collect();
+
+    // most shared tags first, newest first among equals (undated pages sink)
+    scored.sort_by_key(|(shared, p)| (Reverse(*shared), Reverse(p.created_datetime)));
+
+    scored.into_iter().take(limit).map(|(_, p)| p).collect()
+}
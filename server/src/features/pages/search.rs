@@ -0,0 +1,68 @@
+use chasqui_core::features::pages::model::Page;
+use chasqui_core::parser::markdown::markdown_to_text;
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+#[derive(Serialize, ToSchema)]
+pub struct SearchEntry {
+    pub id: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub body: String,
+}
+
+impl From<&Page> for SearchEntry {
+    fn from(page: &Page) -> Self {
+        SearchEntry {
+            id: page.identifier.clone(),
+            title: page.name.clone().unwrap_or_else(|| page.identifier.clone()),
+            tags: page.tags.clone(),
+            body: markdown_to_text(&page.md_content),
+        }
+    }
+}
+
+pub fn build_search_index(pages: &[Page]) -> Vec<SearchEntry> {
+    let mut entries: Vec<SearchEntry> = pages.iter().map(SearchEntry::from).collect();
+    entries.sort_by(|a, b| a.id.cmp(&b.id));
+    entries
+}
+
+/// The serialized index, tagged with the cache generation it was built from
+/// so it is only rebuilt after a sync has actually changed something, or
+/// once a scheduled page it left out goes live.
+#[derive(Default)]
+pub struct SearchIndexCache {
+    cached: RwLock<Option<CachedIndex>>,
+}
+
+struct CachedIndex {
+    generation: u64,
+    expires: Option<NaiveDateTime>,
+    index: Arc<String>,
+}
+
+impl SearchIndexCache {
+    pub async fn get(&self, generation: u64) -> Option<Arc<String>> {
+        let now = Utc::now().naive_utc();
+        match &*self.cached.read().await {
+            Some(cached) if cached.generation == generation && cached.expires.is_none_or(|at| now < at) => {
+                Some(cached.index.clone())
+            }
+            _ => None,
+        }
+    }
+
+    pub async fn store(&self, generation: u64, index: String, expires: Option<NaiveDateTime>) -> Arc<String> {
+        let index = Arc::new(index);
+        *self.cached.write().await = Some(CachedIndex {
+            generation,
+            expires,
+            index: index.clone(),
+        });
+        index
+    }
+}
@@ -1,84 +1,399 @@
-use chasqui_core::config::ChasquiConfig;
+use chasqui_core::config::{ChasquiConfig, IdentifierStrategy};
+use chasqui_core::error::{ChasquiError, ChasquiResult};
 use chasqui_core::features::pages::model::Page;
-use chasqui_core::io::path_utils::{normalize_path, sanitize_identifier};
+use chasqui_core::io::path_utils::{normalize_path, sanitize_identifier, sidecar_path, slugify_identifier};
 use chasqui_core::io::ContentReader;
-use chasqui_core::parser::markdown::{extract_frontmatter, precompile_markdown};
+use chasqui_core::parser::markdown::{
+    apply_nginx_prefix, excerpt_html, extract_frontmatter, generate_excerpt, has_frontmatter, is_external_url,
+    link_and_image_destinations, parse_sidecar, precompile_markdown, render_html, LinkKind, LinkResolution,
+    EXCERPT_MAX_CHARS,
+};
+use chasqui_core::parser::model::PageFrontMatter;
+use crate::features::pages::compiled::{CompiledBody, CompiledPageCache};
+use crate::features::pages::include::expand_includes;
 use crate::services::sync::manifest::Manifest;
-use anyhow::{Context, Result};
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use std::path::Path;
 
 pub async fn create_page(
     path: &Path,
+    mount: &Path,
     config: &ChasquiConfig,
     reader: &dyn ContentReader,
     manifest: &Manifest,
-) -> Result<Page> {
+    previous: Option<&Page>,
+    compiled: &CompiledPageCache,
+) -> ChasquiResult<Page> {
     let relative_path = path
-        .strip_prefix(&config.pages_dir)
-        .or_else(|_| path.strip_prefix(&config.pages_dir.parent().unwrap_or(&config.pages_dir)))
-        .map_err(|_| anyhow::anyhow!("File {} is outside of pages dir", path.display()))?;
+        .strip_prefix(mount)
+        .or_else(|_| path.strip_prefix(mount.parent().unwrap_or(mount)))
+        .map_err(|_| ChasquiError::Validation {
+            filename: path.display().to_string(),
+            reason: "outside of pages dir".into(),
+        })?;
 
-    let filename = normalize_path(path.strip_prefix(&config.pages_dir).unwrap_or(path));
+    let filename = config
+        .content_key(path, mount)
+        .unwrap_or_else(|| normalize_path(path));
 
-    let raw_markdown = reader.read_to_string(path).await?;
-    let metadata = reader.get_metadata(path).await?;
+    // stat before reading, so the atime is the one the file came in with
+    let metadata = reader
+        .get_metadata(path)
+        .await
+        .map_err(|e| ChasquiError::io(path, e))?;
+    let raw_markdown = reader
+        .read_to_string(path)
+        .await
+        .map_err(|e| ChasquiError::io(path, e))?;
 
-    let (frontmatter, content_body) = extract_frontmatter(&raw_markdown, &filename)?;
+    let sidecar = read_sidecar(path, &raw_markdown, reader).await;
+    let (mut frontmatter, content_body) = page_frontmatter(&raw_markdown, sidecar.as_deref(), &filename)?;
+    let expanded = expand_includes(&content_body, &filename, reader, manifest).await;
+    if config.strict_links && !expanded.broken.is_empty() {
+        return Err(ChasquiError::Validation {
+            filename,
+            reason: format!("broken includes: {}", expanded.broken.join(", ")),
+        });
+    }
+    // the spliced body is what gets hashed, compiled and excerpted
+    let content_body = expanded.markdown;
+    let includes = expanded.included;
+    frontmatter.apply_datetime_aliases(
+        &config.created_datetime_aliases,
+        &config.modified_datetime_aliases,
+    );
 
-    let identifier = frontmatter
-        .identifier
-        .map(|id| sanitize_identifier(&id))
-        .unwrap_or_else(|| {
-            sanitize_identifier(&generate_default_identifier(
-                relative_path,
-                config.page_strip_extension,
-            ))
+    // only means the page is left out of the sync, not that the sync failed
+    let missing = frontmatter.missing_fields(&config.required_frontmatter);
+    if !missing.is_empty() {
+        return Err(ChasquiError::Validation {
+            filename,
+            reason: format!("missing required frontmatter: {}", missing.join(", ")),
         });
+    }
 
-    let content_hash = format!(
-        "{:016x}",
-        xxhash_rust::xxh3::xxh3_64(raw_markdown.as_bytes())
-    );
+    let identifier = resolve_identifier(frontmatter.identifier, relative_path, config);
+    let aliases = resolve_aliases(frontmatter.aliases, &identifier, config);
+    let redirect_to = frontmatter
+        .redirect_to
+        .map(|target| target.trim().to_string())
+        .filter(|target| !target.is_empty());
 
-    let md_content = precompile_markdown(
-        &content_body,
-        |link| manifest.resolve_link(link, Path::new(&filename), config),
-        config.nginx_media_prefixes,
-    )?;
+    let content_hash = hash_text(&raw_markdown);
+    let body_hash = hash_text(&content_body);
 
-    let modified_datetime = resolve_datetime(frontmatter.modified_datetime, metadata.modified);
-    let created_datetime = resolve_datetime(frontmatter.created_datetime, metadata.created);
+    let description = frontmatter
+        .description
+        .or_else(|| generate_excerpt(&content_body, EXCERPT_MAX_CHARS));
+
+    // a renamed target or a changed alias moves links even when the body
+    // itself didn't change, so the previous build only stands if none did
+    let reused = previous.and_then(|p| reuse_previous_body(p, &content_body, &body_hash, &filename, config, manifest));
+    let compiled = match reused {
+        Some(body) => body,
+        None => compile_body_cached(&content_body, &filename, &body_hash, config, manifest, compiled)?,
+    };
+    let broken = broken_page_links(&compiled.links, config);
+    if config.strict_links && !broken.is_empty() {
+        return Err(ChasquiError::Validation {
+            filename,
+            reason: format!("broken links: {}", broken.join(", ")),
+        });
+    }
+    let md_content = compiled.md_content;
+
+    // an mtime bump or a frontmatter-only edit isn't an update to the page,
+    // so unless the frontmatter dates it, the previous date carries forward
+    let file_modified = match previous {
+        Some(p) if p.body_hash == body_hash && p.modified_datetime.is_some() => p.modified_datetime,
+        _ => metadata.modified,
+    };
+    let modified_datetime = resolve_datetime(frontmatter.modified_datetime, file_modified);
+    // a Jekyll-style filename dates the page ahead of the file's own ctime
+    let file_created = match config.identifier_strategy {
+        IdentifierStrategy::JekyllDate => jekyll_filename(relative_path)
+            .and_then(|(date, _)| date.and_hms_opt(0, 0, 0))
+            .or(metadata.created),
+        _ => metadata.created,
+    };
+    let created_datetime = resolve_datetime(frontmatter.created_datetime, file_created);
 
     Ok(Page {
         identifier,
         filename,
         name: frontmatter.name,
+        description,
+        excerpt_html: excerpt_html(&md_content),
         md_content,
         content_hash,
+        body_hash,
         tags: frontmatter.tags.unwrap_or_default(),
+        metadata: frontmatter.extra,
+        aliases,
+        redirect_to,
+        frontmatter_error: frontmatter.parse_error,
+        includes,
         modified_datetime,
         created_datetime,
+        accessed_datetime: metadata.accessed.filter(|_| config.track_accessed_datetime),
         file_path: path.to_path_buf(),
         new_path: None,
     })
 }
 
+/// Renders `markdown` the way a page at `filename` would be served, without
+/// saving anything. Links and includes resolve against the current manifest.
+pub async fn render_preview(
+    markdown: &str,
+    filename: &str,
+    config: &ChasquiConfig,
+    reader: &dyn ContentReader,
+    manifest: &Manifest,
+) -> ChasquiResult<String> {
+    let filename = normalize_path(Path::new(filename));
+    let (_, content_body) = parse_frontmatter(markdown, &filename)?;
+    let expanded = expand_includes(&content_body, &filename, reader, manifest).await;
+    let compiled = compile_body(&expanded.markdown, &filename, config, manifest)?;
+
+    Ok(render_html(&compiled.md_content))
+}
+
+fn parse_frontmatter(markdown: &str, filename: &str) -> ChasquiResult<(PageFrontMatter, String)> {
+    extract_frontmatter(markdown, filename).map_err(|e| ChasquiError::FrontmatterParse {
+        filename: filename.to_string(),
+        source: e.into(),
+    })
+}
+
+// Generated pages can't carry frontmatter, so a sidecar file beside one
+// (`post.md.meta.yaml`) stands in for it. Frontmatter in the page wins.
+fn page_frontmatter(
+    markdown: &str,
+    sidecar: Option<&str>,
+    filename: &str,
+) -> ChasquiResult<(PageFrontMatter, String)> {
+    let (frontmatter, content_body) = parse_frontmatter(markdown, filename)?;
+    match sidecar {
+        Some(yaml) if !has_frontmatter(markdown) => Ok((parse_sidecar(yaml, filename), content_body)),
+        _ => Ok((frontmatter, content_body)),
+    }
+}
+
+/// The sidecar metadata of the page at `path`, unless `markdown` has
+/// frontmatter of its own. Most pages have no sidecar, which is fine.
+pub async fn read_sidecar(path: &Path, markdown: &str, reader: &dyn ContentReader) -> Option<String> {
+    if has_frontmatter(markdown) {
+        return None;
+    }
+    reader.read_to_string(&sidecar_path(path)).await.ok()
+}
+
+fn compile_body(
+    content_body: &str,
+    filename: &str,
+    config: &ChasquiConfig,
+    manifest: &Manifest,
+) -> ChasquiResult<CompiledBody> {
+    let (md_content, links) = precompile_markdown(
+        content_body,
+        |link| manifest.resolve_link(link, Path::new(filename), config),
+        config.nginx_media_prefixes,
+    )
+    .map_err(|e| ChasquiError::LinkResolution {
+        filename: filename.to_string(),
+        source: e.into(),
+    })?;
+
+    Ok(CompiledBody { md_content, links })
+}
+
+// re-resolving the recorded links is far cheaper than recompiling, and
+// catches a target that was renamed since the entry was stored
+fn compile_body_cached(
+    content_body: &str,
+    filename: &str,
+    body_hash: &str,
+    config: &ChasquiConfig,
+    manifest: &Manifest,
+    compiled: &CompiledPageCache,
+) -> ChasquiResult<CompiledBody> {
+    let is_current = |body: &CompiledBody| {
+        body.links
+            .iter()
+            .all(|link| manifest.resolve_link(&link.original, Path::new(filename), config) == link.resolved)
+    };
+    if let Some(body) = compiled.get(filename, body_hash, is_current) {
+        return Ok(body);
+    }
+
+    let body = compile_body(content_body, filename, config, manifest)?;
+    compiled.insert(filename, body_hash, body.clone());
+    Ok(body)
+}
+
+// the previous build of the same body, as long as each of its links still
+// resolves to the destination it was compiled to
+fn reuse_previous_body(
+    previous: &Page,
+    content_body: &str,
+    body_hash: &str,
+    filename: &str,
+    config: &ChasquiConfig,
+    manifest: &Manifest,
+) -> Option<CompiledBody> {
+    if previous.body_hash != body_hash {
+        return None;
+    }
+
+    let compiled_to = link_and_image_destinations(&previous.md_content);
+    let links: Vec<LinkResolution> = link_and_image_destinations(content_body)
+        .iter()
+        .map(|link| LinkResolution::new(link, &manifest.resolve_link(link, Path::new(filename), config)))
+        .collect();
+    let is_current = links.len() == compiled_to.len()
+        && links
+            .iter()
+            .zip(&compiled_to)
+            .all(|(link, dest)| apply_nginx_prefix(&link.resolved, config.nginx_media_prefixes) == *dest);
+
+    is_current.then(|| CompiledBody {
+        md_content: previous.md_content.clone(),
+        links,
+    })
+}
+
+/// Links in `markdown` that look like page links but match nothing in the
+/// manifest. Media links are left to the nginx prefixes, so they never count.
+pub fn find_broken_links(
+    markdown: &str,
+    filename: &str,
+    config: &ChasquiConfig,
+    manifest: &Manifest,
+) -> ChasquiResult<Vec<String>> {
+    let (_, content_body) = parse_frontmatter(markdown, filename)?;
+    let compiled = compile_body(&content_body, filename, config, manifest)?;
+
+    Ok(broken_page_links(&compiled.links, config))
+}
+
+// external links and bare fragments never count
+fn broken_page_links(links: &[LinkResolution], config: &ChasquiConfig) -> Vec<String> {
+    links
+        .iter()
+        .filter(|link| link.kind == LinkKind::Broken && is_page_link(&link.original, config))
+        .map(|link| link.original.clone())
+        .collect()
+}
+
+fn is_page_link(link: &str, config: &ChasquiConfig) -> bool {
+    if link.is_empty() || link.starts_with('#') || is_external_url(link) {
+        return false;
+    }
+
+    let path = Path::new(link.split(['#', '?']).next().unwrap_or(link));
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => ext.eq_ignore_ascii_case("html") || config.is_content_file(path),
+        None => true,
+    }
+}
+
+/// Every identifier a page answers to: its own plus any `aliases`.
+pub struct PageIdentity {
+    pub identifier: String,
+    pub aliases: Vec<String>,
+}
+
 pub fn resolve_page_identity(
     relative_path: &Path,
-    bytes: &[u8],
+    raw_content: &str,
+    sidecar: Option<&str>,
     config: &ChasquiConfig,
-) -> Result<String> {
-    let raw_content = String::from_utf8(bytes.to_vec()).context("Invalid UTF-8 in Page")?;
+) -> ChasquiResult<PageIdentity> {
     let filename = normalize_path(relative_path);
-    let (fm, _) = extract_frontmatter(&raw_content, &filename)?;
-    let id = fm.identifier.unwrap_or_else(|| {
-        generate_default_identifier(relative_path, config.page_strip_extension)
-    });
-    Ok(sanitize_identifier(&id))
+    let (fm, _) = page_frontmatter(raw_content, sidecar, &filename)?;
+    let identifier = resolve_identifier(fm.identifier, relative_path, config);
+    let aliases = resolve_aliases(fm.aliases, &identifier, config);
+    Ok(PageIdentity {
+        identifier,
+        aliases,
+    })
 }
 
-fn generate_default_identifier(relative_path: &Path, strip_extension: bool) -> String {
+// aliases go through the same cleanup as identifiers; blanks, repeats and
+// the page's own identifier are dropped
+fn resolve_aliases(aliases: Option<Vec<String>>, identifier: &str, config: &ChasquiConfig) -> Vec<String> {
+    let mut resolved: Vec<String> = Vec::new();
+    for alias in aliases.unwrap_or_default() {
+        let alias = if config.slugify_identifiers {
+            slugify_identifier(&alias)
+        } else {
+            sanitize_identifier(&alias)
+        };
+        if !alias.is_empty() && alias != identifier && !resolved.contains(&alias) {
+            resolved.push(alias);
+        }
+    }
+    resolved
+}
+
+fn resolve_identifier(
+    frontmatter_identifier: Option<String>,
+    relative_path: &Path,
+    config: &ChasquiConfig,
+) -> String {
+    let default_identifier = || {
+        sanitize_identifier(&generate_default_identifier(
+            relative_path,
+            config.page_strip_extension,
+            config.folder_index_pages,
+            config.identifier_strategy,
+        ))
+    };
+
+    match frontmatter_identifier {
+        Some(id) if config.slugify_identifiers => {
+            let slug = slugify_identifier(&id);
+            if slug.is_empty() {
+                default_identifier()
+            } else {
+                slug
+            }
+        }
+        Some(id) => sanitize_identifier(&id),
+        None => default_identifier(),
+    }
+}
+
+fn generate_default_identifier(
+    relative_path: &Path,
+    strip_extension: bool,
+    folder_index_pages: bool,
+    strategy: IdentifierStrategy,
+) -> String {
+    if folder_index_pages && relative_path.file_stem().is_some_and(|stem| stem == "index") {
+        if let Some(folder) = relative_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            return match (strategy, folder.file_name()) {
+                (IdentifierStrategy::Stem, Some(name)) => name.to_string_lossy().to_string(),
+                _ => normalize_path(folder),
+            };
+        }
+    }
+
+    // both drop the extension whatever DEFAULT_PAGE_IDENTIFIER_STRIP_EXTENSION says
+    match strategy {
+        IdentifierStrategy::Stem => {
+            if let Some(stem) = relative_path.file_stem() {
+                return stem.to_string_lossy().to_string();
+            }
+        }
+        IdentifierStrategy::JekyllDate => {
+            if let Some((_, title)) = jekyll_filename(relative_path) {
+                return normalize_path(relative_path.with_file_name(title));
+            }
+        }
+        IdentifierStrategy::FullPath => {}
+    }
+
     let path_str = if strip_extension {
         relative_path
             .with_extension("")
@@ -91,6 +406,18 @@ fn generate_default_identifier(relative_path: &Path, strip_extension: bool) -> S
     path_str.replace("\\", "/")
 }
 
+// `2024-01-31-title.md` splits into its date and `title`
+fn jekyll_filename(relative_path: &Path) -> Option<(NaiveDate, &str)> {
+    let stem = relative_path.file_stem()?.to_str()?;
+    let date = NaiveDate::parse_from_str(stem.get(..10)?, "%Y-%m-%d").ok()?;
+    let title = stem.get(10..)?.strip_prefix('-')?;
+    (!title.is_empty()).then_some((date, title))
+}
+
+fn hash_text(text: &str) -> String {
+    format!("{:016x}", xxhash_rust::xxh3::xxh3_64(text.as_bytes()))
+}
+
 fn resolve_datetime(
     frontmatter_date: Option<String>,
     os_date: Option<NaiveDateTime>,
@@ -99,6 +426,16 @@ fn resolve_datetime(
         if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(&date_str) {
             return Some(dt.naive_utc());
         }
+        if let Ok(dt) = chrono::DateTime::parse_from_rfc2822(&date_str) {
+            return Some(dt.naive_utc());
+        }
+        // Jekyll writes `2024-01-31 09:30:00 +0100`, often without the offset
+        if let Ok(dt) = chrono::DateTime::parse_from_str(&date_str, "%Y-%m-%d %H:%M:%S %z") {
+            return Some(dt.naive_utc());
+        }
+        if let Ok(dt) = NaiveDateTime::parse_from_str(&date_str, "%Y-%m-%d %H:%M:%S") {
+            return Some(dt);
+        }
         if let Ok(dt) = chrono::NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
             return Some(dt.and_hms_opt(0, 0, 0).unwrap_or_default());
         }
@@ -0,0 +1,41 @@
+use chasqui_core::config::ChasquiConfig;
+use chasqui_core::features::pages::model::Page;
+use crate::features::routing::{absolute_url, identifier_to_path};
+
+pub fn build_sitemap(pages: &[Page], config: &ChasquiConfig) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+
+    for page in pages {
+        let loc = absolute_url(config, &identifier_to_path(config, &page.identifier));
+        xml.push_str("  <url>\n");
+        xml.push_str(&format!("    <loc>{}</loc>\n", escape_xml(&loc)));
+        if let Some(modified) = page.modified_datetime {
+            // W3C datetime; stored datetimes are naive UTC
+            xml.push_str(&format!(
+                "    <lastmod>{}</lastmod>\n",
+                modified.format("%Y-%m-%dT%H:%M:%SZ")
+            ));
+        }
+        xml.push_str("  </url>\n");
+    }
+
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+pub fn escape_xml(raw: &str) -> String {
+    let mut escaped = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
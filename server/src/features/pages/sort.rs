@@ -0,0 +1,71 @@
+use chasqui_core::features::pages::model::Page;
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use std::cmp::Ordering;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortField {
+    #[default]
+    Created,
+    Modified,
+    Name,
+    Identifier,
+}
+
+#[derive(Deserialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortField {
+    /// Newest first for dates, alphabetical for text.
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            SortField::Created | SortField::Modified => SortOrder::Desc,
+            SortField::Name | SortField::Identifier => SortOrder::Asc,
+        }
+    }
+}
+
+/// Sorts `pages` by `field`. Pages without the datetime being sorted on go
+/// last in either direction, and ties fall back to identifier order so the
+/// result never depends on cache iteration order.
+pub fn sort_pages(pages: &mut [Page], field: SortField, order: SortOrder) {
+    pages.sort_by(|a, b| {
+        let primary = match field {
+            SortField::Created => compare_dates(a.created_datetime, b.created_datetime, order),
+            SortField::Modified => compare_dates(a.modified_datetime, b.modified_datetime, order),
+            SortField::Name => directed(display_name(a).cmp(&display_name(b)), order),
+            SortField::Identifier => directed(a.identifier.cmp(&b.identifier), order),
+        };
+        primary.then_with(|| a.identifier.cmp(&b.identifier))
+    });
+}
+
+fn compare_dates(a: Option<NaiveDateTime>, b: Option<NaiveDateTime>, order: SortOrder) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => directed(a.cmp(&b), order),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+fn directed(ordering: Ordering, order: SortOrder) -> Ordering {
+    match order {
+        SortOrder::Asc => ordering,
+        SortOrder::Desc => ordering.reverse(),
+    }
+}
+
+// untitled pages sort under their identifier, like everywhere a title is shown
+fn display_name(page: &Page) -> String {
+    page.name
+        .as_deref()
+        .unwrap_or(&page.identifier)
+        .to_lowercase()
+}
@@ -0,0 +1,41 @@
+use chasqui_core::features::pages::model::Page;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use utoipa::ToSchema;
+use std::collections::BTreeMap;
+
+/// Site-wide aggregates for dashboards, so they need one request instead of
+/// walking the page list themselves.
+#[derive(Serialize, ToSchema, Debug, Default, PartialEq)]
+pub struct PageStats {
+    pub total_pages: usize,
+    pub total_tags: usize,
+    pub tag_counts: BTreeMap<String, usize>,
+    pub oldest_created: Option<NaiveDateTime>,
+    pub newest_created: Option<NaiveDateTime>,
+}
+
+// single pass over the pages; tags are counted as written, once per page
+pub fn page_stats(pages: &[Page]) -> PageStats {
+    let mut stats = PageStats {
+        total_pages: pages.len(),
+        ..PageStats::default()
+    };
+
+    for page in pages {
+        let mut tags: Vec<&String> = page.tags.iter().collect();
+        tags.sort();
+        tags.dedup();
+        for tag in tags {
+            *stats.tag_counts.entry(tag.clone()).or_default() += 1;
+        }
+
+        if let Some(created) = page.created_datetime {
+            stats.oldest_created = Some(stats.oldest_created.map_or(created, |o| o.min(created)));
+            stats.newest_created = Some(stats.newest_created.map_or(created, |n| n.max(created)));
+        }
+    }
+
+    stats.total_tags = stats.tag_counts.len();
+    stats
+}
@@ -0,0 +1,101 @@
+use chasqui_core::features::pages::model::Page;
+use serde::Serialize;
+use utoipa::ToSchema;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Whether `tag` satisfies a filter for `wanted`, ignoring case. Tags are
+/// `/`-delimited paths, so unless `exact` is set a filter for `lang` also
+/// matches `lang/rust` and `lang/rust/async`, but not `language`.
+pub fn tag_matches(tag: &str, wanted: &str, exact: bool) -> bool {
+    let tag = tag_key(tag);
+    let wanted = tag_key(wanted);
+
+    tag == wanted
+        || (!exact && tag.strip_prefix(&wanted).is_some_and(|rest| rest.starts_with('/')))
+}
+
+/// The form tags are compared and listed in: lowercase, without outer
+/// slashes, so `Rust` and `rust/` are the same tag everywhere.
+pub fn tag_key(tag: &str) -> String {
+    tag.trim_matches('/').to_lowercase()
+}
+
+pub fn page_has_tag(page: &Page, wanted: &str, exact: bool) -> bool {
+    page.tags.iter().any(|t| tag_matches(t, wanted, exact))
+}
+
+/// One level of the tag hierarchy. `count` is the number of pages a
+/// `?tag={path}` filter would return, so a parent counts every page tagged
+/// anywhere below it, each page once.
+#[derive(Serialize, ToSchema, Debug, PartialEq)]
+pub struct TagNode {
+    pub name: String,
+    pub path: String,
+    pub count: usize,
+    #[schema(no_recursion)]
+    pub children: Vec<TagNode>,
+}
+
+#[derive(Default)]
+struct Branch {
+    count: usize,
+    children: BTreeMap<String, Branch>,
+}
+
+/// Pages per tag, keyed by `tag_key`, each page counted once per tag.
+pub fn tag_counts(pages: &[Page]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for page in pages {
+        let tags: BTreeSet<String> = page.tags.iter().map(|tag| tag_key(tag)).collect();
+        for tag in tags {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+    counts
+}
+
+pub fn tag_tree(pages: &[Page]) -> Vec<TagNode> {
+    let mut root = Branch::default();
+
+    for page in pages {
+        // every ancestor of every tag, so a page tagged both `lang/rust` and
+        // `lang/python` still counts once towards `lang`
+        let mut prefixes: BTreeSet<Vec<String>> = BTreeSet::new();
+        for tag in &page.tags {
+            let key = tag_key(tag);
+            let segments: Vec<String> = key.split('/').filter(|s| !s.is_empty()).map(str::to_string).collect();
+            for depth in 1..=segments.len() {
+                prefixes.insert(segments[..depth].to_vec());
+            }
+        }
+
+        for prefix in prefixes {
+            let mut branch = &mut root;
+            for segment in prefix {
+                branch = branch.children.entry(segment).or_default();
+            }
+            branch.count += 1;
+        }
+    }
+
+    into_nodes(root.children, "")
+}
+
+fn into_nodes(children: BTreeMap<String, Branch>, parent: &str) -> Vec<TagNode> {
+    children
+        .into_iter()
+        .map(|(name, branch)| {
+            let path = if parent.is_empty() {
+                name.clone()
+            } else {
+                format!("{}/{}", parent, name)
+            };
+            TagNode {
+                children: into_nodes(branch.children, &path),
+                count: branch.count,
+                name,
+                path,
+            }
+        })
+        .collect()
+}
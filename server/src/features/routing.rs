@@ -1,12 +1,32 @@
 use chasqui_core::config::ChasquiConfig;
 
 pub fn path_to_identifier(config: &ChasquiConfig, path: &str) -> String {
+    let normalized;
+    let path = if config.normalize_slashes {
+        normalized = normalize_slashes(path);
+        // a path of nothing but slashes is the site root
+        if normalized.is_empty() && config.serve_home {
+            return config.home_identifier.clone();
+        }
+        normalized.as_str()
+    } else {
+        path
+    };
+
     if config.serve_home && path == config.home_identifier {
         return config.home_identifier.clone();
     }
     path.to_string()
 }
 
+/// `/about/`, `//about` and `about` all become `about`.
+pub fn normalize_slashes(path: &str) -> String {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
 pub fn get_identifier_variants(raw_id: &str) -> Vec<String> {
     let mut variants = vec![raw_id.to_string()];
 
@@ -17,4 +37,23 @@ pub fn get_identifier_variants(raw_id: &str) -> Vec<String> {
     }
 
     variants
-}
\ No newline at end of file
+}
+
+/// Site-relative URL of a page, below `BASE_PATH` when one is set. The home
+/// page maps to the base itself (`/docs/`), never `/docs/index`.
+pub fn identifier_to_path(config: &ChasquiConfig, identifier: &str) -> String {
+    // identifiers are looked up regardless of ASCII case, so `Index` is
+    // still the page served at the root when HOME_IDENTIFIER is `index`
+    if config.serve_home && identifier.eq_ignore_ascii_case(&config.home_identifier) {
+        return home_path(config);
+    }
+    format!("{}/{}", config.base_path, identifier)
+}
+
+pub fn home_path(config: &ChasquiConfig) -> String {
+    format!("{}/", config.base_path)
+}
+
+pub fn absolute_url(config: &ChasquiConfig, path: &str) -> String {
+    format!("{}{}", config.site_url.trim_end_matches('/'), path)
+}
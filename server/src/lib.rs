@@ -1,6 +1,7 @@
 pub mod app;
 pub mod features;
 pub mod services;
+pub mod telemetry;
 pub mod watcher;
 
 pub mod testutil;
@@ -1,27 +1,60 @@
 use chasqui_core::config::ChasquiConfig;
+use chasqui_core::io::git::GitContentReader;
 use chasqui_core::io::local::LocalContentReader;
-use chasqui_db::{create_pool, run_migrations, SqliteRepository};
+use chasqui_core::io::ContentReader;
+use chasqui_core::notifier::ContentBuildNotifier;
+use chasqui_db::{create_pool, create_read_pool, run_migrations, SqlitePragmas, SqliteRepository};
 use crate::app::AppState;
+use crate::features::openapi::ApiDoc;
+use crate::services::link_health::start_link_checker;
 use crate::services::sync::SyncService;
-use crate::services::WebhookBuildNotifier;
+use crate::services::{CoalescingBuildNotifier, NoopBuildNotifier, WebhookBuildNotifier};
+use crate::watcher::start_git_poller;
 use crate::watcher::watcher::start_directory_watcher;
+use anyhow::Context;
 use axum::Router;
+use clap::{Parser, Subcommand};
 use dotenv;
 use sqlx::migrate::MigrateDatabase;
 use sqlx::Sqlite;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use tracing_subscriber::EnvFilter;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 pub mod app;
 pub mod features;
 pub mod services;
+pub mod telemetry;
 pub mod watcher;
 
 #[cfg(test)]
 mod testutil;
 
+#[derive(Parser)]
+#[command(version, about = "Serves a directory of markdown pages and media over HTTP")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Default)]
+enum Command {
+    /// Sync the content, serve it and reload it as it changes (the default)
+    #[default]
+    Serve,
+    /// Validate the content without serving it: report identifier
+    /// collisions, files that fail to build and broken links, and exit
+    /// non-zero if there are any
+    Check,
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
     dotenv::dotenv().ok();
 
     let docker_runtime = std::env::var("DOCKER_RUNTIME").unwrap_or_default() == "true";
@@ -30,21 +63,38 @@ async fn main() -> anyhow::Result<()> {
         dotenv::from_filename(".env.containers.default").ok();
     }
 
-    dotenv::from_filename(".env.default").ok();
+    // a config file takes over from .env.default, whose values would
+    // otherwise shadow everything in it
+    let config_file = std::env::var("CHASQUI_CONFIG")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| Some(PathBuf::from("chasqui.toml")).filter(|path| path.exists()));
+
+    if config_file.is_none() {
+        dotenv::from_filename(".env.default").ok();
+    }
+
+    init_tracing();
 
-    let config = ChasquiConfig::from_env();
+    match cli.command.unwrap_or_default() {
+        Command::Serve => serve(config_file.as_deref()).await,
+        Command::Check => check(config_file.as_deref()).await,
+    }
+}
+
+async fn serve(config_file: Option<&Path>) -> anyhow::Result<()> {
+    telemetry::install_recorder();
+
+    let config = load_config_with_content(config_file).await?;
     let shared_config = Arc::new(config.clone());
 
     if !Sqlite::database_exists(&config.database_url)
         .await
         .unwrap_or(false)
     {
-        println!(
-            "Unable to connect to database at {}, creating...",
-            config.database_url
-        );
+        info!(database_url = %config.database_url, "database not found, creating");
         match Sqlite::create_database(&config.database_url).await {
-            Ok(_) => println!("Successfully created database at {}.", &config.database_url),
+            Ok(_) => info!(database_url = %config.database_url, "created database"),
             Err(e) => panic!(
                 "Unable to create database at {}. Error details: {}",
                 &config.database_url, e
@@ -52,29 +102,38 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
-    let pool = create_pool(&config.database_url, config.max_connections)
+    let pragmas = SqlitePragmas::from_config(&config).expect("Invalid SQLite pragma settings");
+    let write_pool = create_pool(&config.database_url, config.max_write_connections, &pragmas)
         .await
-        .expect("Failed to create database pool");
+        .expect("Failed to create database write pool");
+
+    run_migrations(&write_pool).await?;
 
-    run_migrations(&pool)
+    let read_pool = create_read_pool(&config.database_url, config.max_connections, &pragmas)
         .await
-        .expect("Failed to run database migrations.");
+        .expect("Failed to create database read pool");
 
-    let repository = SqliteRepository::new(pool);
+    let repository = SqliteRepository::new(write_pool)
+        .with_read_pool(read_pool)
+        .with_deduplicated_bodies(config.dedupe_page_bodies);
 
-    let reader = Arc::new(LocalContentReader {
-        root_path: PathBuf::from("/"),
-    });
+    let (reader, git_reader) = content_reader(&config)?;
 
-    let notifier = WebhookBuildNotifier::new(
+    let webhook = WebhookBuildNotifier::new(
         config.webhook_url.clone(),
         config.webhook_secret.clone(),
+        config.webhook_payload.clone(),
     );
+    let notifier: Box<dyn ContentBuildNotifier> = if config.webhook_coalesce {
+        Box::new(CoalescingBuildNotifier::new(webhook))
+    } else {
+        Box::new(webhook)
+    };
 
     let sync_service = SyncService::new(
         repository,
         reader,
-        Box::new(notifier),
+        notifier,
         shared_config.clone(),
     )
     .await
@@ -84,34 +143,296 @@ async fn main() -> anyhow::Result<()> {
     let app_state = AppState {
         sync_service: shared_sync_service.clone(),
         config: shared_config.clone(),
+        search_index: Default::default(),
+        link_health: Default::default(),
     };
 
-    start_directory_watcher(shared_sync_service.clone(), shared_config.clone());
+    if config.link_check_interval_secs > 0 {
+        start_link_checker(
+            shared_sync_service.clone(),
+            app_state.link_health.clone(),
+            &config,
+        );
+    }
+
+    match git_reader {
+        Some(git_reader) => start_git_poller(
+            shared_sync_service.clone(),
+            git_reader,
+            Duration::from_secs(config.git_poll_secs),
+        ),
+        None => {
+            if let Err(e) = start_directory_watcher(shared_sync_service.clone(), shared_config.clone()) {
+                error!(error = %e, "live reload disabled; content changes need a restart or POST /admin/resync");
+            }
+        }
+    }
 
     match shared_sync_service.notify_build().await {
-        Ok(_) => println!("Initial build notification sent successfully."),
-        Err(e) => eprintln!(
-            "Initial build notification failed (this is expected if frontend is not running): {}",
-            e
+        Ok(_) => info!("initial build notification sent"),
+        Err(e) => warn!(
+            error = %e,
+            "initial build notification failed (expected if the frontend is not running)"
         ),
     }
 
-    println!("Starting server...");
+    info!("starting server");
 
     let api_router = Router::new()
         .nest("/pages", features::pages::pages_router())
+        .route(
+            "/by-filename/{*path}",
+            axum::routing::get(features::pages::get_page_by_filename_handler),
+        )
         .route(
             "/metadata/{*identifier}",
             axum::routing::get(features::handlers::metadata_handler),
+        )
+        .route(
+            "/status",
+            axum::routing::get(features::handlers::status_handler),
+        )
+        .route(
+            "/stats",
+            axum::routing::get(features::pages::stats_handler),
+        )
+        .route(
+            "/tags",
+            axum::routing::get(features::pages::tags_handler),
         );
 
-    let app = Router::new().nest("/api", api_router).with_state(app_state);
+    let api_router = match app::cors_layer(&config) {
+        Some(cors) => api_router.layer(cors),
+        None => api_router,
+    };
+
+    // admin answers reflect or change live state, so nothing may cache them
+    let admin_router = Router::new()
+        .route(
+            "/admin/resync",
+            axum::routing::post(features::admin::resync_handler),
+        )
+        .route(
+            "/render",
+            axum::routing::post(features::admin::render_handler),
+        )
+        .route(
+            "/admin/recompile",
+            axum::routing::post(features::admin::recompile_handler),
+        )
+        .route(
+            "/admin/dry-run",
+            axum::routing::get(features::admin::dry_run_handler),
+        )
+        .route(
+            "/admin/purge-tombstones",
+            axum::routing::post(features::admin::purge_tombstones_handler),
+        )
+        .route(
+            "/admin/manifest",
+            axum::routing::get(features::admin::manifest_handler),
+        )
+        .route(
+            "/admin/link-health",
+            axum::routing::get(features::admin::link_health_handler),
+        )
+        .route(
+            "/export.json",
+            axum::routing::get(features::admin::export_json_handler),
+        )
+        .route(
+            "/export.ndjson",
+            axum::routing::get(features::admin::export_ndjson_handler),
+        )
+        .layer(axum::middleware::from_fn(app::no_store));
+
+    let app = Router::new()
+        .nest("/api", api_router)
+        .route(
+            "/p/{*identifier}",
+            axum::routing::get(features::pages::html_page_handler),
+        )
+        .route(
+            "/feed.xml",
+            axum::routing::get(features::pages::feed_handler),
+        )
+        .route(
+            "/feed.json",
+            axum::routing::get(features::pages::json_feed_handler),
+        )
+        .route(
+            "/feed/{file}",
+            axum::routing::get(features::pages::tag_feed_handler),
+        )
+        .route(
+            "/search-index.json",
+            axum::routing::get(features::pages::search_index_handler),
+        )
+        .route(
+            "/assets/{*path}",
+            axum::routing::get(features::assets::passthrough::passthrough_handler),
+        )
+        .route(
+            "/sitemap.xml",
+            axum::routing::get(features::pages::sitemap_handler),
+        )
+        .route(
+            "/ws",
+            axum::routing::get(features::live::ws_handler),
+        )
+        .route(
+            "/metrics",
+            axum::routing::get(telemetry::metrics_handler),
+        )
+        .merge(admin_router)
+        .merge(SwaggerUi::new("/docs").url("/openapi.json", ApiDoc::openapi()))
+        .layer(app::body_limit_layer(&config))
+        .layer(app::compression_layer())
+        .layer(axum::middleware::from_fn(telemetry::access_log))
+        .with_state(app_state);
 
     let addr = format!("0.0.0.0:{}", config.port);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    println!("Server listening on http://{}", addr);
+    info!(%addr, "server listening");
 
     axum::serve(listener, app).await?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+// Runs the steps of a full sync against an in-memory database, so CI can
+// gate on content health without a server or the real database.
+async fn check(config_file: Option<&Path>) -> anyhow::Result<()> {
+    let config = load_config(config_file)?;
+    let missing = config.missing_page_dirs();
+    if config.git_repo.is_none() && !missing.is_empty() {
+        let missing: Vec<String> = missing.iter().map(|dir| dir.display().to_string()).collect();
+        anyhow::bail!("content dir not found: {}", missing.join(", "));
+    }
+
+    let pragmas = SqlitePragmas::from_config(&config)?;
+    // one connection, since every connection to `:memory:` is its own database
+    let pool = create_pool("sqlite::memory:", 1, &pragmas).await?;
+    run_migrations(&pool).await?;
+
+    let (reader, _) = content_reader(&config)?;
+    let sync_service = SyncService::unsynced(
+        SqliteRepository::new(pool),
+        reader,
+        Box::new(NoopBuildNotifier),
+        Arc::new(config),
+    );
+    let report = sync_service.dry_run_sync().await?;
+
+    for filename in &report.collisions {
+        println!("collision: {}", filename);
+    }
+    for failed in &report.failed {
+        println!("failed: {} ({}): {}", failed.filename, failed.kind, failed.error);
+    }
+    for broken in &report.broken_links {
+        println!("broken link: {} -> {}", broken.filename, broken.link);
+    }
+    println!(
+        "checked {} files: {} collisions, {} failed, {} broken links",
+        report.inserts.len() + report.collisions.len() + report.failed.len(),
+        report.collisions.len(),
+        report.failed.len(),
+        report.broken_links.len(),
+    );
+
+    if report.has_problems() {
+        anyhow::bail!("content check failed");
+    }
+    Ok(())
+}
+
+// the reader content comes through, plus the git reader behind it, if any,
+// whose ref gets polled
+type ContentReaders = (Arc<dyn ContentReader>, Option<Arc<GitContentReader>>);
+
+// Reads from CONTENT_GIT_REPO when set, else from the filesystem.
+fn content_reader(config: &ChasquiConfig) -> anyhow::Result<ContentReaders> {
+    // the repo's tree stands in for CONTENT_DIR, which holds md/, images/, ...
+    let git_reader = match &config.git_repo {
+        Some(repo_path) => {
+            let mount = config
+                .pages_dir
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            let reader = GitContentReader::open(repo_path, &config.git_ref, mount)
+                .context("Failed to open content git repository")?
+                .with_max_file_bytes(config.max_file_bytes);
+            Some(Arc::new(reader))
+        }
+        None => None,
+    };
+
+    let reader: Arc<dyn ContentReader> = match &git_reader {
+        Some(git_reader) => git_reader.clone(),
+        None => Arc::new(LocalContentReader {
+            root_path: PathBuf::from("/"),
+            max_file_bytes: config.max_file_bytes,
+        }),
+    };
+    Ok((reader, git_reader))
+}
+
+fn load_config(config_file: Option<&Path>) -> anyhow::Result<ChasquiConfig> {
+    Ok(match config_file {
+        Some(path) => ChasquiConfig::from_env_and_file(path)?,
+        None => ChasquiConfig::from_env()?,
+    })
+}
+
+// Content volumes may be mounted just after the server starts, so a missing
+// page dir is re-checked CONTENT_DIR_RETRIES times, doubling the wait each
+// time, before giving up. The config is reloaded on every check so a dir
+// that turns up is resolved like any other.
+async fn load_config_with_content(config_file: Option<&Path>) -> anyhow::Result<ChasquiConfig> {
+    let mut delay = Duration::from_secs(1);
+    let mut attempt = 0;
+
+    loop {
+        let config = load_config(config_file)?;
+        // a git repo stands in for the content dirs
+        if config.git_repo.is_some() {
+            return Ok(config);
+        }
+        if config.create_content_dirs {
+            config.create_missing_dirs()?;
+            return load_config(config_file);
+        }
+
+        let missing = config.missing_page_dirs();
+        if missing.is_empty() {
+            return Ok(config);
+        }
+        let missing: Vec<String> = missing.iter().map(|dir| dir.display().to_string()).collect();
+        if attempt >= config.content_dir_retries {
+            anyhow::bail!(
+                "content dir not found: {} (set CREATE_CONTENT_DIRS=true to create it)",
+                missing.join(", ")
+            );
+        }
+
+        attempt += 1;
+        warn!(missing = %missing.join(", "), retry_in = ?delay, attempt, "content dir not found, waiting for it");
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+    }
+}
+
+// RUST_LOG controls verbosity (default `info`); LOG_FORMAT=json switches to
+// one JSON object per line for log shippers.
+fn init_tracing() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    if std::env::var("LOG_FORMAT").is_ok_and(|format| format == "json") {
+        builder.json().init();
+    } else {
+        builder.init();
+    }
+}
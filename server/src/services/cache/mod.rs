@@ -10,5 +10,6 @@ pub trait SyncableCache: Send + Sync {
     async fn remove(&self, filename: &str) -> Result<()>;
     async fn get_all(&self) -> Vec<Feature>;
     async fn get_by_key(&self, key: &str) -> Option<Feature>;
+    async fn count(&self) -> usize;
     fn can_handle(&self, feature_type: FeatureType) -> bool;
 }
\ No newline at end of file
@@ -123,6 +123,10 @@ impl<F: AsFeature + Send + Sync + Clone> SyncableCache for InMemoryCache<F> {
         storage.get(key).map(|v| v.into_feature())
     }
 
+    async fn count(&self) -> usize {
+        self.storage.read().await.len()
+    }
+
     fn can_handle(&self, feature_type: FeatureType) -> bool {
         self.feature_type == feature_type
     }
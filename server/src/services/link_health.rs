@@ -0,0 +1,150 @@
+use crate::services::sync::SyncService;
+use chasqui_core::config::ChasquiConfig;
+use chasqui_core::features::pages::model::Page;
+use chasqui_core::parser::markdown::link_destinations;
+use chrono::{NaiveDateTime, Utc};
+use futures_util::StreamExt;
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::MissedTickBehavior;
+use tracing::info;
+
+/// The last check of one external URL.
+#[derive(Serialize, Clone, Debug)]
+pub struct LinkStatus {
+    pub ok: bool,
+    /// Status of the final response, after redirects. Absent when none came.
+    pub status: Option<u16>,
+    /// Why no response came, e.g. a timeout or a refused connection.
+    pub error: Option<String>,
+    pub checked_at: NaiveDateTime,
+    /// Identifiers of the pages linking to the URL.
+    pub pages: BTreeSet<String>,
+}
+
+/// Results of the latest external link check, keyed by URL. Empty until
+/// the first round finishes.
+#[derive(Default)]
+pub struct LinkHealth {
+    results: RwLock<BTreeMap<String, LinkStatus>>,
+}
+
+impl LinkHealth {
+    pub async fn snapshot(&self) -> BTreeMap<String, LinkStatus> {
+        self.results.read().await.clone()
+    }
+}
+
+/// Every `http(s)` link in `pages` with the pages linking to it. Fragments
+/// never reach the server, so URLs differing only in one are checked once.
+pub fn external_links(pages: &[Page]) -> BTreeMap<String, BTreeSet<String>> {
+    let mut links: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for page in pages {
+        for destination in link_destinations(&page.md_content) {
+            let url = match destination.strip_prefix("//") {
+                Some(rest) => format!("https://{}", rest),
+                None => destination,
+            };
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                continue;
+            }
+            let url = url.split('#').next().unwrap_or_default().to_string();
+            links.entry(url).or_default().insert(page.identifier.clone());
+        }
+    }
+    links
+}
+
+/// Issues a request per distinct external URL, a few at a time, each
+/// bounded by `LINK_CHECK_TIMEOUT_SECS`.
+pub struct LinkChecker {
+    client: Client,
+    concurrency: usize,
+}
+
+impl LinkChecker {
+    pub fn new(config: &ChasquiConfig) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.link_check_timeout_secs))
+            .user_agent(concat!("chasqui-link-check/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Self {
+            client,
+            concurrency: config.link_check_concurrency.max(1),
+        }
+    }
+
+    /// Checks the external links of the current pages and replaces
+    /// `health` with the results, so links no page uses any more drop out.
+    pub async fn check_all(&self, sync_service: &SyncService, health: &LinkHealth) {
+        // scheduled pages too, so their links are sound by the time they're out
+        let links = external_links(&sync_service.get_all_pages_with_unpublished().await);
+
+        let results: BTreeMap<String, LinkStatus> = futures_util::stream::iter(links)
+            .map(|(url, pages)| async move {
+                let status = self.check(&url, pages).await;
+                (url, status)
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+
+        let broken = results.values().filter(|status| !status.ok).count();
+        info!(checked = results.len(), broken, "external link check finished");
+        *health.results.write().await = results;
+    }
+
+    async fn check(&self, url: &str, pages: BTreeSet<String>) -> LinkStatus {
+        // some servers refuse HEAD outright, so those get a GET instead
+        let response = match self.client.head(url).send().await {
+            Ok(response)
+                if matches!(response.status(), StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED) =>
+            {
+                self.client.get(url).send().await
+            }
+            other => other,
+        };
+
+        let (status, error) = match response {
+            Ok(response) => (Some(response.status()), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+
+        LinkStatus {
+            ok: status.is_some_and(|status| status.is_success()),
+            status: status.map(|status| status.as_u16()),
+            error,
+            checked_at: Utc::now().naive_utc(),
+            pages,
+        }
+    }
+}
+
+/// Checks external links every `LINK_CHECK_INTERVAL_SECS` on a task of its
+/// own. It only reads pages, so neither syncs nor requests wait on it, and
+/// a URL is requested once per round however many pages link to it.
+pub fn start_link_checker(
+    sync_service: Arc<SyncService>,
+    health: Arc<LinkHealth>,
+    config: &ChasquiConfig,
+) {
+    let checker = LinkChecker::new(config);
+    let interval = Duration::from_secs(config.link_check_interval_secs);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // a slow round pushes the next one back rather than queueing more
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            checker.check_all(&sync_service, &health).await;
+        }
+    });
+}
@@ -1,49 +1,214 @@
 pub mod cache;
+pub mod link_health;
 pub mod sync;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use chasqui_core::notifier::ContentBuildNotifier;
+use chasqui_core::notifier::{BuildChanges, ContentBuildNotifier};
+use crate::telemetry;
+use metrics::counter;
+use reqwest::header::CONTENT_TYPE;
 use reqwest::Client;
+use serde_json::Value;
+use std::sync::{Mutex, PoisonError};
+use tracing::{info, warn};
+
+// string value in WEBHOOK_PAYLOAD replaced by the change lists
+const CHANGES_PLACEHOLDER: &str = "{{changes}}";
 
 pub struct WebhookBuildNotifier {
     client: Client,
     url: String,
     secret: String,
+    payload: Option<Value>,
 }
 
 impl WebhookBuildNotifier {
-    pub fn new(url: String, secret: String) -> Self {
+    pub fn new(url: String, secret: String, payload: Option<Value>) -> Self {
         Self {
             client: Client::new(),
             url,
             secret,
+            payload,
+        }
+    }
+}
+
+/// The JSON sent with a build notification. Without a configured payload
+/// this is just the change lists; otherwise it is the payload with every
+/// `"{{changes}}"` string swapped for them.
+pub fn webhook_body(payload: Option<&Value>, changes: &BuildChanges) -> Value {
+    let changes = serde_json::to_value(changes).unwrap_or_default();
+    match payload {
+        Some(payload) => fill_changes(payload.clone(), &changes),
+        None => changes,
+    }
+}
+
+fn fill_changes(value: Value, changes: &Value) -> Value {
+    match value {
+        Value::String(s) if s == CHANGES_PLACEHOLDER => changes.clone(),
+        Value::Array(items) => {
+            Value::Array(items.into_iter().map(|v| fill_changes(v, changes)).collect())
         }
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(k, v)| (k, fill_changes(v, changes)))
+                .collect(),
+        ),
+        other => other,
     }
 }
 
 #[async_trait]
 impl ContentBuildNotifier for WebhookBuildNotifier {
-    async fn notify(&self) -> Result<()> {
+    #[tracing::instrument(skip_all, fields(url = %self.url))]
+    async fn notify(&self, changes: &BuildChanges) -> Result<()> {
         if self.url.is_empty() {
             return Ok(());
         }
 
-        let mut request = self.client.post(&self.url);
+        let body = webhook_body(self.payload.as_ref(), changes);
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(&body)?);
 
         if !self.secret.is_empty() {
             request = request.header("X-Webhook-Secret", &self.secret);
         }
 
-        let response = request.send().await?;
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                counter!(telemetry::WEBHOOK_NOTIFICATIONS, "outcome" => "failure").increment(1);
+                return Err(e.into());
+            }
+        };
 
         if !response.status().is_success() {
-            eprintln!(
-                "Webhook notification failed with status: {}",
-                response.status()
-            );
+            warn!(status = %response.status(), "webhook notification failed");
+            counter!(telemetry::WEBHOOK_NOTIFICATIONS, "outcome" => "failure").increment(1);
+        } else {
+            info!(status = %response.status(), "webhook notification sent");
+            counter!(telemetry::WEBHOOK_NOTIFICATIONS, "outcome" => "success").increment(1);
+        }
+
+        Ok(())
+    }
+}
+/// Keeps at most one notification to `inner` in flight. Changes sent while
+/// one is out are merged and go out as a single follow-up once it finishes,
+/// so a burst of sync batches starts at most two frontend builds rather than
+/// one per batch. A follow-up is sent by the caller whose notification was
+/// in flight; the others return straight away.
+///
+/// A failed notification hands the caller's own changes back with the error,
+/// for the caller to retry as `SyncService::notify_build` does. Only changes
+/// of callers that already returned are kept here for the next notification.
+pub struct CoalescingBuildNotifier<N> {
+    inner: N,
+    state: Mutex<CoalesceState>,
+}
+
+#[derive(Default)]
+struct CoalesceState {
+    in_flight: bool,
+    // changes of callers that already returned, waiting for the
+    // notification in flight to finish or left over from a failed follow-up
+    queued: Option<BuildChanges>,
+}
+
+// Clears `in_flight` however `notify` ends, a dropped future included, and
+// queues again whatever it was sending on behalf of callers that returned.
+struct InFlight<'a> {
+    state: &'a Mutex<CoalesceState>,
+    others: Option<BuildChanges>,
+    landed: bool,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if self.landed {
+            return;
         }
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.in_flight = false;
+        if let Some(mut others) = self.others.take() {
+            if let Some(newer) = state.queued.take() {
+                others.merge(newer);
+            }
+            state.queued = Some(others);
+        }
+    }
+}
+
+impl<N: ContentBuildNotifier> CoalescingBuildNotifier<N> {
+    pub fn new(inner: N) -> Self {
+        Self {
+            inner,
+            state: Mutex::new(CoalesceState::default()),
+        }
+    }
+}
+
+#[async_trait]
+impl<N: ContentBuildNotifier> ContentBuildNotifier for CoalescingBuildNotifier<N> {
+    async fn notify(&self, changes: &BuildChanges) -> Result<()> {
+        let mut flight = {
+            let mut state = self.state.lock().unwrap();
+            if state.in_flight {
+                state.queued.get_or_insert_default().merge(changes.clone());
+                return Ok(());
+            }
+            state.in_flight = true;
+            InFlight {
+                state: &self.state,
+                others: state.queued.take(),
+                landed: false,
+            }
+        };
 
+        let mut current = flight.others.clone().unwrap_or_default();
+        current.merge(changes.clone());
+        self.inner.notify(&current).await?;
+        flight.others = None;
+
+        loop {
+            let next = {
+                let mut state = self.state.lock().unwrap();
+                match state.queued.take() {
+                    Some(next) => next,
+                    None => {
+                        // cleared under the lock, so a caller arriving now
+                        // sends its own notification rather than queueing
+                        state.in_flight = false;
+                        flight.landed = true;
+                        return Ok(());
+                    }
+                }
+            };
+
+            // the caller's changes already went out, so a failed follow-up
+            // isn't its error; the changes wait for the next notification
+            flight.others = Some(next.clone());
+            if let Err(e) = self.inner.notify(&next).await {
+                warn!(error = %e, "coalesced build notification failed, keeping its changes for the next one");
+                return Ok(());
+            }
+            flight.others = None;
+        }
+    }
+}
+
+/// Sends nothing, for runs like `chasqui check` that never build a frontend.
+pub struct NoopBuildNotifier;
+
+#[async_trait]
+impl ContentBuildNotifier for NoopBuildNotifier {
+    async fn notify(&self, _changes: &BuildChanges) -> Result<()> {
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -1,17 +1,22 @@
 use chasqui_core::features::model::FeatureType;
-use chasqui_core::io::path_utils::path_to_identifier;
+use chasqui_core::io::path_utils::{path_to_identifier, sidecar_path};
 use chasqui_core::config::ChasquiConfig;
+use chasqui_core::error::ChasquiError;
 use chasqui_core::io::ContentReader;
-use crate::features::pages::service::resolve_page_identity;
+use crate::features::pages::service::{read_sidecar, resolve_page_identity};
 use anyhow::Result;
 use std::path::{Path, PathBuf};
 
 #[derive(Clone)]
 pub struct ManifestClaim {
     pub feature_type: FeatureType,
+    /// The key the file is tracked under; see `ChasquiConfig::content_key`.
     pub filename: String,
+    pub path: PathBuf,
     pub mount_path: PathBuf,
     pub identifier: Option<String>,
+    /// Extra identifiers that resolve to this file; only pages have any.
+    pub aliases: Vec<String>,
     pub content_hash: String,
 }
 
@@ -28,26 +33,39 @@ impl ManifestClaim {
             .strip_prefix(mount_path)
             .map_err(|_| anyhow::anyhow!("File {} is outside of mount path {}", path.display(), mount_path.display()))?;
 
-        let filename = relative_path.to_string_lossy().replace("\\", "/");
+        let filename = config
+            .content_key(path, mount_path)
+            .unwrap_or_else(|| relative_path.to_string_lossy().replace("\\", "/"));
 
-        let hash = reader.get_hash(path).await?;
+        let mut hash = reader.get_hash(path).await?;
+        // an edited sidecar changes the page as much as an edited file would
+        if feature_type == FeatureType::Page {
+            if let Ok(sidecar_hash) = reader.get_hash(&sidecar_path(path)).await {
+                hash = format!("{}:{}", hash, sidecar_hash);
+            }
+        }
 
         if manifest.hashes.get(&filename) == Some(&hash) {
             return Ok(None);
         }
 
-        let identifier = if feature_type == FeatureType::Page {
+        let (identifier, aliases) = if feature_type == FeatureType::Page {
             let bytes = reader.read_bytes(path).await?;
-            Some(resolve_page_identity(relative_path, &bytes, config)?)
+            let raw_content = String::from_utf8(bytes).map_err(|e| ChasquiError::io(relative_path, e))?;
+            let sidecar = read_sidecar(path, &raw_content, reader).await;
+            let identity = resolve_page_identity(relative_path, &raw_content, sidecar.as_deref(), config)?;
+            (identity.identifier, identity.aliases)
         } else {
-            Some(path_to_identifier(relative_path, config.asset_strip_extension))
+            (path_to_identifier(relative_path, config.asset_strip_extension), Vec::new())
         };
 
         Ok(Some(Self {
             feature_type,
             filename,
+            path: path.to_path_buf(),
             mount_path: mount_path.to_path_buf(),
-            identifier,
+            identifier: Some(identifier),
+            aliases,
             content_hash: hash,
         }))
     }
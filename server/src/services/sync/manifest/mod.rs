@@ -1,17 +1,66 @@
 pub mod claim;
 
+use chasqui_core::config::CollisionPolicy;
 use chasqui_core::features::model::FeatureType;
 pub use self::claim::ManifestClaim;
+use crate::features::routing::identifier_to_path;
 use chasqui_core::io::path_utils::normalize_logical_path;
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tracing::warn;
 
+#[derive(Default)]
+pub struct ClaimReport {
+    pub claims: Vec<ManifestClaim>,
+    pub skipped: usize,
+    pub collisions: usize,
+    // filenames behind `collisions`
+    pub collided: Vec<String>,
+    pub failed: usize,
+    pub renamed: Vec<Renamed>,
+}
+
+impl ClaimReport {
+    /// Folds in the report of another claim pass over the same batch.
+    pub fn absorb(&mut self, other: ClaimReport) {
+        self.claims.extend(other.claims);
+        self.skipped += other.skipped;
+        self.collisions += other.collisions;
+        self.collided.extend(other.collided);
+        self.failed += other.failed;
+        self.renamed.extend(other.renamed);
+    }
+}
+
+/// A registered file whose identifier moved, e.g. because a claimant that
+/// sorts before it took its suffix.
+pub struct Renamed {
+    pub filename: String,
+    pub feature_type: FeatureType,
+    pub from: String,
+    pub to: String,
+}
+
+/// Identifiers and aliases match regardless of ASCII case, the same as the
+/// repository's `COLLATE NOCASE` lookups, so `/About` finds `about`. The keys
+/// of `id_to_file` and `alias_to_file` are folded by [`identifier_key`] on
+/// insert and on lookup; `file_to_id` keeps the identifier as claimed.
 pub struct Manifest {
     pub filenames: HashSet<String>,
     pub file_to_id: HashMap<String, String>,
     pub id_to_file: HashMap<String, String>,
+    pub alias_to_file: HashMap<String, String>,
+    pub file_to_aliases: HashMap<String, Vec<String>>,
     pub hashes: HashMap<String, String>,
     pub feature_types: HashMap<String, FeatureType>,
+    pub mount_paths: HashMap<String, PathBuf>,
+    /// Where each file lives, since a key under an extra root isn't a path
+    /// below its mount.
+    pub file_paths: HashMap<String, PathBuf>,
+    /// The identifier each file asked for, before any `-N` suffix.
+    pub requested_ids: HashMap<String, String>,
+    // folded identifiers whose claimants lost a file since the last batch
+    vacated: HashSet<String>,
 }
 
 impl Manifest {
@@ -20,8 +69,14 @@ impl Manifest {
             filenames: HashSet::new(),
             file_to_id: HashMap::new(),
             id_to_file: HashMap::new(),
+            alias_to_file: HashMap::new(),
+            file_to_aliases: HashMap::new(),
             hashes: HashMap::new(),
             feature_types: HashMap::new(),
+            mount_paths: HashMap::new(),
+            file_paths: HashMap::new(),
+            requested_ids: HashMap::new(),
+            vacated: HashSet::new(),
         }
     }
 
@@ -30,8 +85,14 @@ impl Manifest {
             filenames: self.filenames.clone(),
             file_to_id: self.file_to_id.clone(),
             id_to_file: self.id_to_file.clone(),
+            alias_to_file: self.alias_to_file.clone(),
+            file_to_aliases: self.file_to_aliases.clone(),
             hashes: self.hashes.clone(),
             feature_types: self.feature_types.clone(),
+            mount_paths: self.mount_paths.clone(),
+            file_paths: self.file_paths.clone(),
+            requested_ids: self.requested_ids.clone(),
+            vacated: self.vacated.clone(),
         }
     }
 
@@ -40,10 +101,26 @@ impl Manifest {
         self.hashes
             .insert(claim.filename.clone(), claim.content_hash);
         self.feature_types.insert(claim.filename.clone(), claim.feature_type);
+        self.mount_paths.insert(claim.filename.clone(), claim.mount_path);
+        self.file_paths.insert(claim.filename.clone(), claim.path);
+
+        self.remove_aliases(&claim.filename);
+        for alias in &claim.aliases {
+            self.alias_to_file.insert(identifier_key(alias), claim.filename.clone());
+        }
+        if !claim.aliases.is_empty() {
+            self.file_to_aliases.insert(claim.filename.clone(), claim.aliases);
+        }
 
         if let Some(id) = claim.identifier {
-            self.file_to_id.insert(claim.filename.clone(), id.clone());
-            self.id_to_file.insert(id, claim.filename);
+            // a file that changed identifier gives up the old one
+            if let Some(old) = self.file_to_id.insert(claim.filename.clone(), id.clone()) {
+                let old_key = identifier_key(&old);
+                if self.id_to_file.get(&old_key) == Some(&claim.filename) {
+                    self.id_to_file.remove(&old_key);
+                }
+            }
+            self.id_to_file.insert(identifier_key(&id), claim.filename);
         }
     }
 
@@ -51,11 +128,73 @@ impl Manifest {
         self.filenames.remove(filename);
         self.hashes.remove(filename);
         self.feature_types.remove(filename);
+        self.mount_paths.remove(filename);
+        self.file_paths.remove(filename);
         if let Some(id) = self.file_to_id.remove(filename) {
-            self.id_to_file.remove(&id);
+            self.id_to_file.remove(&identifier_key(&id));
+        }
+        if let Some(requested) = self.requested_ids.remove(filename) {
+            self.vacated.insert(identifier_key(&requested));
+        }
+        self.remove_aliases(filename);
+    }
+
+    // a claim re-registering `filename` as it stands, asking for `requested`
+    fn registered_claim(&self, filename: &str, requested: &str) -> Option<ManifestClaim> {
+        Some(ManifestClaim {
+            feature_type: *self.feature_types.get(filename)?,
+            filename: filename.to_string(),
+            path: self.file_paths.get(filename)?.clone(),
+            mount_path: self.mount_paths.get(filename)?.clone(),
+            identifier: Some(requested.to_string()),
+            aliases: self.file_to_aliases.get(filename).cloned().unwrap_or_default(),
+            content_hash: self.hashes.get(filename)?.clone(),
+        })
+    }
+
+    fn remove_aliases(&mut self, filename: &str) {
+        for alias in self.file_to_aliases.remove(filename).unwrap_or_default() {
+            self.alias_to_file.remove(&identifier_key(&alias));
         }
     }
 
+    /// The identifier of the page that lists `alias` among its aliases.
+    pub fn resolve_alias(&self, alias: &str) -> Option<&String> {
+        self.file_to_id.get(self.alias_to_file.get(&identifier_key(alias))?)
+    }
+
+    /// The file whose identifier is `identifier`, in any case.
+    pub fn file_for_identifier(&self, identifier: &str) -> Option<&String> {
+        self.id_to_file.get(&identifier_key(identifier))
+    }
+
+    // the file holding `key` as its identifier or as an alias
+    fn key_owner(&self, key: &str) -> Option<&String> {
+        let key = identifier_key(key);
+        self.id_to_file.get(&key).or_else(|| self.alias_to_file.get(&key))
+    }
+
+    // `identifier` if no other file holds it, else its first free `-N` form
+    fn free_identifier(&self, identifier: &str, filename: &str) -> String {
+        let is_free = |key: &str| self.key_owner(key).is_none_or(|owner| owner == filename);
+        if is_free(identifier) {
+            return identifier.to_string();
+        }
+        (2..)
+            .map(|n| format!("{}-{}", identifier, n))
+            .find(|candidate| is_free(candidate))
+            .unwrap_or_default()
+    }
+
+    pub fn filenames_under(&self, dir: &str, f_type: FeatureType) -> Vec<String> {
+        let prefix = format!("{}/", dir.trim_end_matches('/'));
+        self.feature_types
+            .iter()
+            .filter(|(filename, t)| **t == f_type && filename.starts_with(&prefix))
+            .map(|(filename, _)| filename.clone())
+            .collect()
+    }
+
     pub fn resolve_link(&self, link: &str, current_filename: &Path, config: &chasqui_core::config::ChasquiConfig) -> String {
         if link.starts_with("http://")
             || link.starts_with("https://")
@@ -65,9 +204,11 @@ impl Manifest {
             return link.to_string();
         }
 
-        let parts: Vec<&str> = link.split('#').collect();
-        let raw_lookup = parts[0];
-        let fragment = parts.get(1).map(|f| format!("#{}", f)).unwrap_or_default();
+        // everything after the first `#` is the fragment, kept verbatim
+        let (raw_lookup, fragment) = match link.split_once('#') {
+            Some((lookup, fragment)) => (lookup, format!("#{}", fragment)),
+            None => (link, String::new()),
+        };
 
         let lookup_key = if raw_lookup.starts_with("./") || raw_lookup.starts_with("../") {
             let mut base = std::path::PathBuf::from(current_filename);
@@ -77,74 +218,215 @@ impl Manifest {
         } else {
             raw_lookup.trim_start_matches('/').to_string()
         };
+        let lookup_key = lookup_key.trim_end_matches('/');
 
-        let resolved_identifier = if let Some(identifier) = self.file_to_id.get(&lookup_key)
-        {
-            Some(identifier.clone())
-        } else if self.id_to_file.contains_key(&lookup_key) {
-            Some(lookup_key.to_string())
-        } else {
-            None
-        };
+        // `about.html` may stand in for `about.md` or the `about` identifier,
+        // but only when enabled; otherwise it stays a literal link
+        let html_stem = lookup_key
+            .strip_suffix(".html")
+            .filter(|_| config.resolve_html_links);
+
+        let resolved_identifier = self.lookup_identifier(lookup_key, config).or_else(|| {
+            html_stem.and_then(|stem| {
+                config
+                    .content_extensions
+                    .iter()
+                    .find_map(|ext| self.lookup_identifier(&format!("{}.{}", stem, ext), config))
+                    .or_else(|| self.lookup_identifier(stem, config))
+            })
+        });
 
         match resolved_identifier {
-            Some(id) => {
-                if config.serve_home && id == config.home_identifier {
-                    format!("/{}", fragment)
-                } else {
-                    format!("/{}{}", id, fragment)
-                }
-            }
+            Some(id) => format!("{}{}", identifier_to_path(config, &id), fragment),
             None => link.to_string(),
         }
     }
 
+    fn lookup_identifier(&self, key: &str, config: &chasqui_core::config::ChasquiConfig) -> Option<String> {
+        if let Some(identifier) = self.file_to_id.get(key) {
+            return Some(identifier.clone());
+        }
+        if let Some(file) = self.file_for_identifier(key) {
+            return self.file_to_id.get(file).cloned();
+        }
+        if let Some(identifier) = self.resolve_alias(key) {
+            return Some(identifier.clone());
+        }
+        // a folder link falls back to the folder's index page, but only when
+        // index files stand in for their folder
+        if !config.folder_index_pages {
+            return None;
+        }
+        config
+            .content_extensions
+            .iter()
+            .find_map(|ext| self.file_to_id.get(&format!("{}/index.{}", key, ext)))
+            .cloned()
+    }
+
     pub async fn register_claims(
         &mut self,
         path_mount_type_triples: Vec<(std::path::PathBuf, std::path::PathBuf, FeatureType)>,
         reader: &dyn chasqui_core::io::ContentReader,
         config: &chasqui_core::config::ChasquiConfig,
-    ) -> Vec<ManifestClaim> {
+    ) -> ClaimReport {
+        let mut report = ClaimReport::default();
+        let vacated = std::mem::take(&mut self.vacated);
         let mut potentials = Vec::new();
         let mut id_counts: HashMap<String, usize> = HashMap::new();
+        // keys of extra roots are namespaced by the root's dir name, so they only
+        // meet when two extra roots share one
+        let mut filename_mounts: HashMap<String, HashSet<PathBuf>> = HashMap::new();
 
         for (path, mount, f_type) in path_mount_type_triples {
             match ManifestClaim::new(&path, &mount, reader, config, self, f_type).await {
                 Ok(Some(claim)) => {
-                    if let Some(ref id) = claim.identifier {
-                        *id_counts.entry(id.clone()).or_insert(0) += 1;
+                    // aliases share the identifier namespace
+                    for key in claim.identifier.iter().chain(&claim.aliases) {
+                        *id_counts.entry(identifier_key(key)).or_insert(0) += 1;
                     }
+                    filename_mounts
+                        .entry(claim.filename.clone())
+                        .or_default()
+                        .insert(claim.mount_path.clone());
                     potentials.push(claim);
                 }
-                Ok(None) => {}
-                Err(e) => eprintln!("Manifest: Failed to generate claim for {:?}: {}", path, e),
+                Ok(None) => report.skipped += 1,
+                Err(e) => {
+                    warn!(path = %path.display(), error = %e, "failed to generate manifest claim");
+                    report.failed += 1;
+                }
+            }
+        }
+
+        // suffixes go to claimants in filename order. Every claimant of an
+        // identifier this batch touches is numbered again, the registered ones
+        // included, so a suffix never depends on the order files arrived in
+        let append_suffix = config.collision_policy == CollisionPolicy::AppendSuffix;
+        let mut residents = HashSet::new();
+        if append_suffix {
+            let mut bases = vacated;
+            for claim in &potentials {
+                bases.extend(claim.identifier.iter().map(|id| identifier_key(id)));
+                bases.extend(self.requested_ids.get(&claim.filename).map(|id| identifier_key(id)));
+            }
+            let incoming: HashSet<&String> = potentials.iter().map(|c| &c.filename).collect();
+            let registered: Vec<ManifestClaim> = self
+                .requested_ids
+                .iter()
+                .filter(|(filename, requested)| {
+                    !incoming.contains(filename) && bases.contains(&identifier_key(requested))
+                })
+                .filter_map(|(filename, requested)| self.registered_claim(filename, requested))
+                .collect();
+
+            residents.extend(registered.iter().map(|c| c.filename.clone()));
+            potentials.extend(registered);
+
+            // identifiers held now are free until their files are numbered below
+            for claim in &potentials {
+                if let Some(id) = self.file_to_id.get(&claim.filename) {
+                    self.id_to_file.remove(&identifier_key(id));
+                }
             }
+            potentials.sort_by(|a, b| a.filename.cmp(&b.filename));
         }
 
-        let mut valid_claims = Vec::new();
-        for claim in potentials {
+        for mut claim in potentials {
+            let requested = claim.identifier.clone();
+
+            // already registered, so only its identifier can move
+            if residents.contains(&claim.filename) {
+                claim.identifier = requested
+                    .as_ref()
+                    .map(|id| self.free_identifier(id, &claim.filename));
+                self.register_tracking_renames(claim, requested, &mut report);
+                continue;
+            }
+
             let mut has_collision = false;
 
-            if let Some(ref id) = claim.identifier {
-                if *id_counts.get(id).unwrap_or(&0) > 1 {
-                    eprintln!("Collision (Internal): Identifier '{}' claimed by multiple files in batch. Rejecting all.", id);
+            let owner_mount = self.mount_paths.get(&claim.filename);
+            if filename_mounts.get(&claim.filename).is_some_and(|m| m.len() > 1)
+                || owner_mount.is_some_and(|m| *m != claim.mount_path)
+            {
+                warn!(filename = %claim.filename, "filename exists under multiple content roots of the same name, rejecting claim");
+                has_collision = true;
+            }
+
+            // claims register as they go, so an earlier claimant in this batch
+            // already owns the identifier by the time a later one gets here
+            if append_suffix {
+                if let Some(id) = claim.identifier.take() {
+                    let free = self.free_identifier(&id, &claim.filename);
+                    if free != id {
+                        warn!(identifier = %id, renamed = %free, filename = %claim.filename, "identifier already claimed, appending suffix");
+                    }
+                    claim.identifier = Some(free);
+                }
+            }
+
+            for id in claim.identifier.iter().chain(&claim.aliases) {
+                let suffixed = append_suffix && Some(id) == claim.identifier.as_ref();
+                if !suffixed && *id_counts.get(&identifier_key(id)).unwrap_or(&0) > 1 {
+                    warn!(identifier = %id, filename = %claim.filename, "identifier claimed by multiple files in batch, rejecting all");
                     has_collision = true;
                 }
 
-                if let Some(existing_file) = self.id_to_file.get(id) {
+                if let Some(existing_file) = self.key_owner(id) {
                     if existing_file != &claim.filename {
-                        eprintln!("Collision (External): Identifier '{}' already owned by {}. Rejecting {}.", id, existing_file, claim.filename);
+                        warn!(identifier = %id, owner = %existing_file, filename = %claim.filename, "identifier already owned, rejecting claim");
                         has_collision = true;
                     }
                 }
             }
 
-            if !has_collision {
-                self.register_claim(claim.clone());
-                valid_claims.push(claim);
+            if has_collision {
+                report.collisions += 1;
+                report.collided.push(claim.filename.clone());
+                // a registered file keeps what it held, if nobody took it meanwhile
+                if let Some(id) = self.file_to_id.get(&claim.filename) {
+                    if self.key_owner(id).is_none() {
+                        self.id_to_file.insert(identifier_key(id), claim.filename.clone());
+                    }
+                }
+            } else {
+                self.register_tracking_renames(claim.clone(), requested, &mut report);
+                report.claims.push(claim);
             }
         }
 
-        valid_claims
+        report
     }
-}
\ No newline at end of file
+
+    // registers `claim`, noting in `report` when that moves the file off the
+    // identifier it held before
+    fn register_tracking_renames(&mut self, claim: ManifestClaim, requested: Option<String>, report: &mut ClaimReport) {
+        let filename = claim.filename.clone();
+        let feature_type = claim.feature_type;
+        let previous = self.file_to_id.get(&filename).cloned();
+        let current = claim.identifier.clone();
+
+        self.register_claim(claim);
+        match requested {
+            Some(requested) => self.requested_ids.insert(filename.clone(), requested),
+            None => self.requested_ids.remove(&filename),
+        };
+
+        if let (Some(from), Some(to)) = (previous, current) {
+            if from != to {
+                report.renamed.push(Renamed {
+                    filename,
+                    feature_type,
+                    from,
+                    to,
+                });
+            }
+        }
+    }
+}
+
+/// The key an identifier or alias is indexed under.
+pub fn identifier_key(identifier: &str) -> String {
+    identifier.to_ascii_lowercase()
+}
@@ -1,4 +1,4 @@
 pub mod manifest;
 pub mod sync;
 
-pub use sync::SyncService;
\ No newline at end of file
+pub use sync::{BrokenLink, DryRunReport, FailedFeature, InvalidPage, SyncReport, SyncService, SyncStatus};
\ No newline at end of file
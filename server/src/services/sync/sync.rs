@@ -1,18 +1,39 @@
 use chasqui_core::config::ChasquiConfig;
+use chasqui_core::error::ChasquiError;
 use chasqui_core::features::model::{match_feature_to_type, Feature, FeatureType};
+use chasqui_core::features::pages::model::{Page, PageRevision};
+use chasqui_core::io::caching::CachingContentReader;
 use chasqui_core::io::ContentReader;
 use chasqui_db::SqliteRepository;
 use crate::features::factory::FeatureFactory;
+use crate::features::pages::backlinks::{link_targets, BacklinkIndex};
+use crate::features::pages::service::find_broken_links;
 use crate::services::cache::models::InMemoryCache;
 use crate::services::cache::SyncableCache;
-use crate::services::sync::manifest::Manifest;
-use chasqui_core::notifier::ContentBuildNotifier;
+use crate::services::sync::manifest::{Manifest, Renamed};
+use chasqui_core::notifier::{BuildChanges, ContentBuildNotifier};
 use anyhow::Result;
-use std::collections::HashMap;
-use std::path::Path;
+use chrono::NaiveDateTime;
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use chasqui_core::io::path_utils::normalize_path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, Mutex, RwLock};
+use tracing::{error, info, instrument, warn};
+use crate::telemetry;
+use metrics::{counter, gauge};
 
+/// Keeps the features of every mount in step with the content, in the
+/// database and in memory.
+///
+/// The reader and notifier stay trait objects rather than type parameters:
+/// `main` picks the git or local reader at runtime, the API tests build one
+/// `AppState` over both local and mock readers, and every call through them
+/// waits on file or network I/O that dwarfs the dispatch.
 pub struct SyncService {
     repo: SqliteRepository,
     pub reader: Arc<dyn ContentReader>,
@@ -21,6 +42,106 @@ pub struct SyncService {
     pub manifest: Arc<RwLock<Manifest>>,
     pub factory: FeatureFactory,
     pub caches: HashMap<FeatureType, Box<dyn SyncableCache>>,
+    last_sync: RwLock<Option<NaiveDateTime>>,
+    active_batches: AtomicUsize,
+    cache_generation: AtomicU64,
+    // held by whole-tree operations and each watcher batch, so neither
+    // interleaves with the other
+    resync_lock: Mutex<()>,
+    // identifiers touched since the last build notification
+    pending_changes: Mutex<BuildChanges>,
+    // identifiers touched by the batch in progress, published when it ends
+    batch_changes: Mutex<BuildChanges>,
+    change_events: broadcast::Sender<BuildChanges>,
+    backlinks: RwLock<BacklinkIndex>,
+    // who includes whom, shaped like the backlinks but keyed by filename
+    includes: RwLock<BacklinkIndex>,
+}
+
+// pages are written to the repository in bulk, this many per transaction
+const PAGE_SAVE_BATCH: usize = 500;
+
+// batches a subscriber may fall behind by before it misses some
+const CHANGE_EVENT_BACKLOG: usize = 64;
+
+pub struct SyncStatus {
+    pub page_count: usize,
+    pub last_sync: Option<NaiveDateTime>,
+    pub sync_in_progress: bool,
+}
+
+/// Outcome of one `process_batch` run.
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub changes: usize,
+    pub deletions: usize,
+    pub skipped: usize,
+    pub collisions: usize,
+    pub failed: usize,
+    pub invalid: Vec<InvalidPage>,
+    /// Pages synced with default frontmatter because theirs was malformed.
+    pub frontmatter_errors: Vec<InvalidPage>,
+    /// Identifiers the batch actually inserted, updated or deleted. Empty
+    /// when every file turned out unchanged, e.g. after an mtime-only touch.
+    pub written: BuildChanges,
+}
+
+/// A page left out of a sync because it failed frontmatter validation, or
+/// kept with defaults when listed under `frontmatter_errors`.
+#[derive(Debug)]
+pub struct InvalidPage {
+    pub filename: String,
+    pub reason: String,
+}
+
+/// What a full sync would do right now, computed without writing anything.
+/// Filenames are relative to their mount.
+#[derive(Debug, Default, Serialize)]
+pub struct DryRunReport {
+    pub inserts: Vec<String>,
+    pub updates: Vec<String>,
+    pub deletions: Vec<String>,
+    pub no_change: Vec<String>,
+    pub collisions: Vec<String>,
+    pub failed: Vec<FailedFeature>,
+    pub broken_links: Vec<BrokenLink>,
+}
+
+impl DryRunReport {
+    /// Whether a sync would leave content out or with broken links.
+    pub fn has_problems(&self) -> bool {
+        !(self.collisions.is_empty() && self.failed.is_empty() && self.broken_links.is_empty())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FailedFeature {
+    pub filename: String,
+    /// `ChasquiError::kind` of the failure, or `other` for untyped errors.
+    pub kind: &'static str,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BrokenLink {
+    pub filename: String,
+    pub link: String,
+}
+
+// Decrements the in-flight batch count however `process_batch` exits.
+struct BatchGuard<'a>(&'a AtomicUsize);
+
+impl<'a> BatchGuard<'a> {
+    fn enter(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self(counter)
+    }
+}
+
+impl Drop for BatchGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 impl SyncService {
@@ -30,15 +151,36 @@ impl SyncService {
         notifier: Box<dyn ContentBuildNotifier>,
         config: Arc<ChasquiConfig>,
     ) -> Result<Self> {
-        print!(
-            "Sync Service: Booting up universal sync engine and performing full multi-mount sync... "
-        );
+        info!("booting sync engine and performing full multi-mount sync");
+
+        let service = Self::unsynced(repo, reader, notifier, config);
+
+        match service.full_sync().await {
+            Ok(_) => {
+                info!("initial sync complete");
+                return Ok(service);
+            }
+
+            Err(e) => {
+                error!(error = %e, "initial sync failed");
+                return Err(e);
+            }
+        }
+    }
 
+    /// A service that hasn't synced anything yet, so its manifest is empty
+    /// and `dry_run_sync` reports on every file, as `chasqui check` does.
+    pub fn unsynced(
+        repo: SqliteRepository,
+        reader: Arc<dyn ContentReader>,
+        notifier: Box<dyn ContentBuildNotifier>,
+        config: Arc<ChasquiConfig>,
+    ) -> Self {
         let manifest = Arc::new(RwLock::new(Manifest::new()));
         let factory = FeatureFactory::new(manifest.clone(), reader.clone(), config.clone());
         let caches = Self::initialize_caches();
 
-        let service = Self {
+        Self {
             repo,
             reader,
             notifier,
@@ -46,18 +188,15 @@ impl SyncService {
             manifest,
             factory,
             caches,
-        };
-
-        match service.full_sync().await {
-            Ok(_) => {
-                println!("Success.");
-                return Ok(service);
-            }
-
-            Err(e) => {
-                println!("FAILURE.");
-                return Err(e);
-            }
+            last_sync: RwLock::new(None),
+            active_batches: AtomicUsize::new(0),
+            cache_generation: AtomicU64::new(0),
+            resync_lock: Mutex::new(()),
+            pending_changes: Mutex::new(BuildChanges::default()),
+            batch_changes: Mutex::new(BuildChanges::default()),
+            change_events: broadcast::channel(CHANGE_EVENT_BACKLOG).0,
+            backlinks: RwLock::new(BacklinkIndex::default()),
+            includes: RwLock::new(BacklinkIndex::default()),
         }
     }
 
@@ -90,20 +229,10 @@ impl SyncService {
         caches
     }
 
-    pub fn identify_mount(&self, path: &Path) -> Option<(&Path, FeatureType)> {
-        let mounts = [
-            (&self.config.pages_dir, FeatureType::Page),
-            (&self.config.images_dir, FeatureType::Image),
-            (&self.config.audio_dir, FeatureType::Audio),
-            (&self.config.videos_dir, FeatureType::Video),
-        ];
-
-        mounts
-            .into_iter()
-            .find(|(root, f_type)| {
-                path.starts_with(root) && self.is_file_matching_type(path, *f_type)
-            })
-            .map(|(root, f_type)| (root.as_path(), f_type))
+    pub fn identify_mount(&self, path: &Path) -> Option<(PathBuf, FeatureType)> {
+        self.config.mounts().into_iter().find(|(root, f_type)| {
+            path.starts_with(root) && self.is_file_matching_type(path, *f_type)
+        })
     }
 
     pub fn is_file_matching_type(&self, path: &Path, f_type: FeatureType) -> bool {
@@ -113,7 +242,7 @@ impl SyncService {
             .map(|s| s.to_lowercase())
             .unwrap_or_default();
         match f_type {
-            FeatureType::Page => ext == "md",
+            FeatureType::Page => self.config.is_content_file(path),
             FeatureType::Video => {
                 matches!(ext.as_str(), "mp4" | "mov" | "webm" | "mkv" | "ogv" | "avi")
             }
@@ -128,108 +257,625 @@ impl SyncService {
         }
     }
 
+    /// Sends a build notification carrying every identifier changed or
+    /// deleted since the last one that went through.
     pub async fn notify_build(&self) -> Result<()> {
-        self.notifier.notify().await
+        let changes = std::mem::take(&mut *self.pending_changes.lock().await);
+        if let Err(e) = self.notifier.notify(&changes).await {
+            // keep them for the next attempt, under anything recorded since
+            let mut pending = self.pending_changes.lock().await;
+            let newer = std::mem::replace(&mut *pending, changes);
+            pending.merge(newer);
+            return Err(e);
+        }
+        Ok(())
     }
 
-    pub async fn full_sync(&self) -> Result<()> {
+    /// Syncs every file of every mount. Discovery streams, but every file it
+    /// finds is claimed before any is built, so links between them resolve
+    /// and identifier clashes are settled over the whole set rather than in
+    /// the order the walk happened to find the files. Anything synced before
+    /// that discovery no longer finds is deleted, as `dry_run_sync` reports.
+    pub async fn full_sync(&self) -> Result<SyncReport> {
+        let entries = self.discover_all().await;
+        let discovered = self.discovered_keys(&entries);
+        let deletions = {
+            let manifest = self.manifest.read().await;
+            manifest
+                .filenames
+                .iter()
+                .filter(|f| !discovered.contains(*f))
+                .filter_map(|f| manifest.file_paths.get(f).cloned())
+                .collect()
+        };
+        self.process_batch(entries, deletions).await
+    }
+
+    // discovered files as the manifest names them
+    fn discovered_keys(&self, entries: &[(PathBuf, PathBuf, FeatureType)]) -> HashSet<String> {
+        entries
+            .iter()
+            .filter_map(|(path, mount, _)| self.config.content_key(path, mount))
+            .collect()
+    }
+
+    // every mount's files, for the callers that need the whole set at once
+    async fn discover_all(&self) -> Vec<(PathBuf, PathBuf, FeatureType)> {
         let mut all_entries = Vec::new();
+        for (mount, f_type) in self.config.mounts() {
+            all_entries.extend(self.discover_mount(&mount, f_type).collect::<Vec<_>>().await);
+        }
+        all_entries
+    }
 
-        let mounts = [
-            (&self.config.pages_dir, FeatureType::Page),
-            (&self.config.images_dir, FeatureType::Image),
-            (&self.config.audio_dir, FeatureType::Audio),
-            (&self.config.videos_dir, FeatureType::Video),
-        ];
-
-        for (mount, f_type) in mounts {
-            if let Ok(entries) = self.reader.list_all_files(mount).await {
-                for e in entries {
-                    if self.is_file_matching_type(&e, f_type) {
-                        all_entries.push((e, (*mount).clone(), f_type));
+    // pages are taken as the reader's listing yields them; media mounts are
+    // listed in one go and filtered by type
+    fn discover_mount<'a>(
+        &'a self,
+        mount: &'a Path,
+        f_type: FeatureType,
+    ) -> BoxStream<'a, (PathBuf, PathBuf, FeatureType)> {
+        if f_type == FeatureType::Page {
+            return self
+                .reader
+                .stream_markdown_files(mount, &self.config.content_extensions)
+                .filter_map(move |path| async move {
+                    match path {
+                        Ok(path) => Some((path, mount.to_path_buf(), f_type)),
+                        Err(e) => {
+                            warn!(mount = %mount.display(), error = %e, "failed to list page");
+                            None
+                        }
                     }
+                })
+                .boxed();
+        }
+
+        stream::once(self.reader.list_all_files(mount))
+            .flat_map(move |entries| {
+                let entries: Vec<_> = entries
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|e| self.is_file_matching_type(e, f_type))
+                    .map(|e| (e, mount.to_path_buf(), f_type))
+                    .collect();
+                stream::iter(entries)
+            })
+            .boxed()
+    }
+
+    /// Runs the discovery, claim and build steps of a full sync against a
+    /// copy of the manifest, reporting the outcome instead of saving it.
+    pub async fn dry_run_sync(&self) -> Result<DryRunReport> {
+        let entries = self.discover_all().await;
+        let reader = CachingContentReader::new(self.reader.clone());
+        let mut manifest = self.manifest.read().await.snapshot();
+        let original = manifest.snapshot();
+
+        let discovered = self.discovered_keys(&entries);
+        let page_paths: Vec<PathBuf> = entries
+            .iter()
+            .filter(|(_, _, f_type)| *f_type == FeatureType::Page)
+            .map(|(path, _, _)| path.clone())
+            .collect();
+
+        let claim_report = manifest
+            .register_claims(entries, &reader, &self.config)
+            .await;
+
+        let mut report = DryRunReport {
+            collisions: claim_report.collided,
+            deletions: original
+                .filenames
+                .iter()
+                .filter(|f| !discovered.contains(*f))
+                .cloned()
+                .collect(),
+            ..Default::default()
+        };
+
+        let claimed: HashSet<&str> = claim_report.claims.iter().map(|c| c.filename.as_str()).collect();
+        report.no_change = discovered
+            .iter()
+            .filter(|f| original.hashes.contains_key(*f) && !claimed.contains(f.as_str()))
+            .filter(|f| !report.collisions.contains(f))
+            .cloned()
+            .collect();
+
+        for claim in &claim_report.claims {
+            let previous = self.cached_feature(claim.feature_type, &claim.filename).await;
+            let built = self
+                .factory
+                .get_feature_from_file_with_manifest(claim.clone(), &manifest, &reader, previous.as_ref())
+                .await;
+
+            if let Err(e) = built {
+                let kind = error_kind(&e);
+                warn!(filename = %claim.filename, kind, error = %e, "dry run: feature would fail to build");
+                report.failed.push(FailedFeature {
+                    filename: claim.filename.clone(),
+                    kind,
+                    error: e.to_string(),
+                });
+            } else if original.filenames.contains(&claim.filename) {
+                report.updates.push(claim.filename.clone());
+            } else {
+                report.inserts.push(claim.filename.clone());
+            }
+        }
+
+        // links are checked on every page, since a moved target breaks links
+        // in files that did not change themselves
+        let failed = report.failed.iter().map(|f| &f.filename);
+        for filename in failed.chain(&report.deletions) {
+            manifest.remove_by_filename(filename);
+        }
+        for path in page_paths {
+            let Some((mount, _)) = self.identify_mount(&path) else {
+                continue;
+            };
+            let filename = self
+                .config
+                .content_key(&path, &mount)
+                .unwrap_or_else(|| normalize_path(&path));
+            if !manifest.filenames.contains(&filename) {
+                continue;
+            }
+
+            // unreadable pages already show up under `failed`
+            let Ok(markdown) = reader.read_to_string(&path).await else {
+                continue;
+            };
+            let Ok(links) = find_broken_links(&markdown, &filename, &self.config, &manifest) else {
+                continue;
+            };
+            for link in links {
+                report.broken_links.push(BrokenLink {
+                    filename: filename.clone(),
+                    link,
+                });
+            }
+        }
+        report
+            .broken_links
+            .sort_by(|a, b| (&a.filename, &a.link).cmp(&(&b.filename, &b.link)));
+
+        report.inserts.sort();
+        report.updates.sort();
+        report.deletions.sort();
+        report.no_change.sort();
+        report.collisions.sort();
+        report.failed.sort_by(|a, b| a.filename.cmp(&b.filename));
+        Ok(report)
+    }
+
+    /// Forces a full sync on demand, returning the resulting page count.
+    /// Concurrent callers queue behind each other rather than overlapping.
+    pub async fn resync(&self) -> Result<usize> {
+        let _guard = self.lock_sync().await;
+        self.full_sync().await?;
+        Ok(self.status().await.page_count)
+    }
+
+    /// Waits out any resync, recompile or watcher batch in progress and
+    /// keeps others from starting until the guard is dropped.
+    pub async fn lock_sync(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.resync_lock.lock().await
+    }
+
+    /// Rebuilds every page from its source file even when the file is
+    /// unchanged, re-resolving links against the current manifest. Stored
+    /// bodies only change on edits otherwise, so this is how a change to
+    /// link or rendering settings reaches them.
+    pub async fn recompile_all(&self) -> Result<SyncReport> {
+        let _guard = self.lock_sync().await;
+
+        let pages: Vec<_> = self
+            .discover_all()
+            .await
+            .into_iter()
+            .filter(|(_, _, f_type)| *f_type == FeatureType::Page)
+            .collect();
+
+        // without a known hash every page is claimed as if it had changed
+        {
+            let mut manifest = self.manifest.write().await;
+            for (path, mount, _) in &pages {
+                if let Some(filename) = self.config.content_key(path, mount) {
+                    manifest.hashes.remove(&filename);
                 }
             }
         }
 
-        self.process_batch(all_entries, Vec::new()).await
+        let report = self.process_batch(pages, Vec::new()).await?;
+        if let Err(e) = self.notify_build().await {
+            warn!(error = %e, "build notification after recompile failed");
+        }
+        Ok(report)
     }
 
+    #[instrument(skip_all, fields(changes = changes.len(), deletions = deletions.len()))]
     pub async fn process_batch(
         &self,
         changes: Vec<(std::path::PathBuf, std::path::PathBuf, FeatureType)>,
         deletions: Vec<std::path::PathBuf>,
-    ) -> Result<()> {
+    ) -> Result<SyncReport> {
+        let _guard = BatchGuard::enter(&self.active_batches);
+
+        let mut sync_report = SyncReport {
+            changes: changes.len(),
+            deletions: deletions.len(),
+            ..Default::default()
+        };
+
+        let mut deleted = Vec::new();
         for path in deletions {
-            self.handle_deletion(&path).await?;
+            deleted.extend(self.handle_deletion(&path).await?);
         }
 
-        let (valid_claims, manifest_snapshot) = {
+        // reads are memoized for this batch only, so the next one sees fresh content
+        let reader = CachingContentReader::new(self.reader.clone());
+
+        let (report, manifest_snapshot) = {
             let mut manifest_guard = self.manifest.write().await;
-            let claims = manifest_guard
-                .register_claims(changes, &*self.reader, &self.config)
+            let mut report = manifest_guard
+                .register_claims(changes, &reader, &self.config)
                 .await;
 
-            (claims, manifest_guard.snapshot())
+            // pages splicing in a snippet that changed or went are rebuilt with it
+            let touched = report.claims.iter().map(|c| c.filename.clone()).chain(deleted);
+            let includers = self.includers_of(touched, &mut manifest_guard).await;
+            if !includers.is_empty() {
+                let reclaimed = manifest_guard
+                    .register_claims(includers, &reader, &self.config)
+                    .await;
+                report.absorb(reclaimed);
+            }
+
+            (report, manifest_guard.snapshot())
         };
 
-        for claim in valid_claims {
+        sync_report.skipped = report.skipped;
+        sync_report.collisions = report.collisions;
+        sync_report.failed = report.failed;
+        self.apply_renames(&report.renamed).await?;
+
+        let mut pending_pages = Vec::new();
+        for claim in report.claims {
+            let previous = self.cached_feature(claim.feature_type, &claim.filename).await;
             match self
                 .factory
-                .get_feature_from_file_with_manifest(claim.clone(), &manifest_snapshot)
+                .get_feature_from_file_with_manifest(
+                    claim.clone(),
+                    &manifest_snapshot,
+                    &reader,
+                    previous.as_ref(),
+                )
                 .await
             {
+                Ok(Feature::Page(page)) => {
+                    if let Some(reason) = &page.frontmatter_error {
+                        sync_report.frontmatter_errors.push(InvalidPage {
+                            filename: claim.filename.clone(),
+                            reason: reason.clone(),
+                        });
+                    }
+                    pending_pages.push(page);
+                    if pending_pages.len() >= PAGE_SAVE_BATCH {
+                        self.save_pages(std::mem::take(&mut pending_pages)).await?;
+                    }
+                }
                 Ok(feature) => {
                     if let Err(e) = self.repo.save_feature(feature.clone()).await {
-                        eprintln!("Sync Service: Failed to save feature to repository: {}. Rolling back manifest claim.", e);
+                        error!(filename = %claim.filename, error = %e, "failed to save feature, rolling back manifest claim");
                         let mut manifest_guard = self.manifest.write().await;
                         manifest_guard.remove_by_filename(&claim.filename);
-                        return Err(e);
+                        return Err(e.into());
                     }
                     self.update_cache(feature).await?;
+                    let identifier = claim
+                        .identifier
+                        .clone()
+                        .unwrap_or_else(|| claim.filename.clone());
+                    self.record_changed(identifier).await;
                 }
                 Err(e) => {
-                    eprintln!("Sync Service: Failed to produce feature: {}", e);
+                    if let Some(ChasquiError::Validation { reason, .. }) = e.downcast_ref() {
+                        warn!(filename = %claim.filename, reason = %reason, "page failed validation, skipping");
+                        sync_report.invalid.push(InvalidPage {
+                            filename: claim.filename.clone(),
+                            reason: reason.clone(),
+                        });
+                    } else {
+                        warn!(filename = %claim.filename, kind = error_kind(&e), error = %e, "failed to produce feature");
+                        sync_report.failed += 1;
+                    }
                     let mut manifest_guard = self.manifest.write().await;
                     manifest_guard.remove_by_filename(&claim.filename);
                 }
             }
         }
 
-        Ok(())
+        self.save_pages(pending_pages).await?;
+        self.repo.prune_page_bodies().await?;
+
+        info!(
+            changes = sync_report.changes,
+            deletions = sync_report.deletions,
+            skipped = sync_report.skipped,
+            collisions = sync_report.collisions,
+            failed = sync_report.failed,
+            invalid = sync_report.invalid.len(),
+            frontmatter_errors = sync_report.frontmatter_errors.len(),
+            "sync batch complete"
+        );
+
+        counter!(telemetry::SYNC_BATCHES).increment(1);
+        gauge!(telemetry::PAGE_COUNT).set(self.status().await.page_count as f64);
+
+        *self.last_sync.write().await = Some(chrono::Utc::now().naive_utc());
+
+        sync_report.written = std::mem::take(&mut *self.batch_changes.lock().await);
+        if !sync_report.written.is_empty() {
+            // an error only means nobody is listening
+            let _ = self.change_events.send(sync_report.written.clone());
+        }
+        Ok(sync_report)
+    }
+
+    /// Identifiers changed or deleted by each batch from now on, one message
+    /// per batch that touched anything.
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<BuildChanges> {
+        self.change_events.subscribe()
+    }
+
+    async fn record_changed(&self, identifier: String) {
+        self.batch_changes.lock().await.record_changed(identifier.clone());
+        self.pending_changes.lock().await.record_changed(identifier);
+    }
+
+    async fn record_deleted(&self, identifier: String) {
+        self.batch_changes.lock().await.record_deleted(identifier.clone());
+        self.pending_changes.lock().await.record_deleted(identifier);
     }
 
-    async fn handle_deletion(&self, path: &Path) -> Result<()> {
-        let filename = if let Some((mount_root, _)) = self.identify_mount(path) {
-            path.strip_prefix(mount_root)
-                .unwrap_or(path)
-                .to_string_lossy()
-                .replace("\\", "/")
-        } else {
-            path.to_string_lossy().replace("\\", "/")
+    pub async fn status(&self) -> SyncStatus {
+        let page_count = match self.caches.get(&FeatureType::Page) {
+            Some(cache) => cache.count().await,
+            None => 0,
         };
 
+        SyncStatus {
+            page_count,
+            last_sync: *self.last_sync.read().await,
+            sync_in_progress: self.active_batches.load(Ordering::SeqCst) > 0,
+        }
+    }
+
+    async fn save_pages(&self, pages: Vec<Page>) -> Result<()> {
+        if pages.is_empty() {
+            return Ok(());
+        }
+
+        if let Err(e) = self.repo.save_pages(&pages).await {
+            error!(count = pages.len(), error = %e, "failed to save pages, rolling back manifest claims");
+            let mut manifest_guard = self.manifest.write().await;
+            for page in &pages {
+                manifest_guard.remove_by_filename(&page.filename);
+            }
+            return Err(e.into());
+        }
+
+        counter!(telemetry::PAGES_INGESTED).increment(pages.len() as u64);
+        for page in &pages {
+            self.record_changed(page.identifier.clone()).await;
+        }
+        for page in pages {
+            self.update_cache(Feature::Page(page)).await?;
+        }
+        Ok(())
+    }
+
+    // pages whose identifier moved without their file changing aren't rebuilt,
+    // so the stored and cached copies are moved here instead
+    async fn apply_renames(&self, renamed: &[Renamed]) -> Result<()> {
+        let pages: Vec<(String, String)> = renamed
+            .iter()
+            .filter(|r| r.feature_type == FeatureType::Page)
+            .map(|r| (r.filename.clone(), r.to.clone()))
+            .collect();
+        if pages.is_empty() {
+            return Ok(());
+        }
+
+        if let Err(e) = self.repo.rename_pages(&pages).await {
+            error!(count = pages.len(), error = %e, "failed to rename pages, rolling back manifest claims");
+            let mut manifest_guard = self.manifest.write().await;
+            for (filename, _) in &pages {
+                manifest_guard.remove_by_filename(filename);
+            }
+            return Err(e.into());
+        }
+
+        // one page's old identifier may be another's new one
+        for rename in renamed.iter().filter(|r| r.feature_type == FeatureType::Page) {
+            self.record_deleted(rename.from.clone()).await;
+        }
+        for rename in renamed.iter().filter(|r| r.feature_type == FeatureType::Page) {
+            if let Some(Feature::Page(mut page)) = self.cached_feature(FeatureType::Page, &rename.filename).await {
+                page.identifier = rename.to.clone();
+                self.update_cache(Feature::Page(page)).await?;
+            }
+            self.record_changed(rename.to.clone()).await;
+        }
+        Ok(())
+    }
+
+    // returns the filenames it deleted
+    async fn handle_deletion(&self, path: &Path) -> Result<Vec<String>> {
+        let filename = self
+            .identify_mount(path)
+            .and_then(|(mount_root, _)| self.config.content_key(path, &mount_root))
+            .unwrap_or_else(|| normalize_path(path));
+
         let mut manifest_guard = self.manifest.write().await;
-        if let Some(f_type) = manifest_guard.feature_types.get(&filename).cloned() {
-            self.repo.delete_feature(&filename, f_type).await?;
+
+        // an untracked path is most likely a removed or renamed-away directory,
+        // so everything registered beneath it goes too
+        let targets = match manifest_guard.feature_types.get(&filename) {
+            Some(f_type) => vec![(filename.clone(), *f_type)],
+            None => self
+                .mounts_containing(path)
+                .into_iter()
+                .flat_map(|(root, f_type)| {
+                    let dir = self
+                        .config
+                        .content_key(path, &root)
+                        .unwrap_or_else(|| normalize_path(path));
+                    manifest_guard
+                        .filenames_under(&dir, f_type)
+                        .into_iter()
+                        .map(move |f| (f, f_type))
+                })
+                .collect(),
+        };
+
+        let mut deleted = Vec::new();
+        for (target, f_type) in targets {
+            if self.config.soft_delete && f_type == FeatureType::Page {
+                self.repo
+                    .soft_delete_page(&target, chrono::Utc::now().naive_utc())
+                    .await?;
+            } else {
+                self.repo.delete_feature(&target, f_type).await?;
+            }
             if let Some(cache) = self.caches.get(&f_type) {
-                cache.remove(&filename).await?;
+                cache.remove(&target).await?;
+                self.cache_generation.fetch_add(1, Ordering::SeqCst);
+            }
+            if f_type == FeatureType::Page {
+                self.backlinks.write().await.remove(&target);
+                self.includes.write().await.remove(&target);
             }
+            let identifier = manifest_guard.file_to_id.get(&target).cloned();
+            self.record_deleted(identifier.unwrap_or_else(|| target.clone()))
+                .await;
+            manifest_guard.remove_by_filename(&target);
+            info!(filename = %target, "deleted feature");
+            deleted.push(target);
         }
 
         manifest_guard.remove_by_filename(&filename);
-        println!("Successfully deleted {}", filename);
-        Ok(())
+        Ok(deleted)
+    }
+
+    // the pages including any of `filenames`, other than those, as entries to
+    // claim again. Their hashes are dropped so the claims count as changes.
+    async fn includers_of(
+        &self,
+        filenames: impl Iterator<Item = String>,
+        manifest: &mut Manifest,
+    ) -> Vec<(PathBuf, PathBuf, FeatureType)> {
+        let filenames: HashSet<String> = filenames.collect();
+        let includers: BTreeSet<String> = {
+            let includes = self.includes.read().await;
+            filenames
+                .iter()
+                .flat_map(|filename| includes.sources(filename))
+                .filter(|includer| !filenames.contains(includer))
+                .collect()
+        };
+
+        includers
+            .into_iter()
+            .filter_map(|includer| {
+                let path = manifest.file_paths.get(&includer)?.clone();
+                let mount = manifest.mount_paths.get(&includer)?.clone();
+                manifest.hashes.remove(&includer);
+                Some((path, mount, FeatureType::Page))
+            })
+            .collect()
+    }
+
+    fn mounts_containing(&self, path: &Path) -> Vec<(PathBuf, FeatureType)> {
+        self.config
+            .mounts()
+            .into_iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .collect()
+    }
+
+    /// Lists every syncable file below `dir`, e.g. after a directory was
+    /// created or renamed into place.
+    pub async fn scan_directory(&self, dir: &Path) -> Vec<(PathBuf, PathBuf, FeatureType)> {
+        let mut entries = Vec::new();
+        if let Ok(files) = self.reader.list_all_files(dir).await {
+            for file in files {
+                if let Some((mount, f_type)) = self.identify_mount(&file) {
+                    entries.push((file.clone(), mount, f_type));
+                }
+            }
+        }
+        entries
     }
 
     async fn update_cache(&self, feature: Feature) -> Result<()> {
+        if let Feature::Page(page) = &feature {
+            self.update_backlinks(page).await;
+            let included = page.includes.iter().cloned().collect();
+            self.includes.write().await.set_links(&page.filename, included);
+        }
+
         let f_type = match_feature_to_type(&feature);
         if let Some(cache) = self.caches.get(&f_type) {
             cache.add(feature).await?;
+            self.cache_generation.fetch_add(1, Ordering::SeqCst);
         }
         Ok(())
     }
 
+    // only this page's outgoing edges are replaced; links into it are kept
+    // by the pages they come from
+    async fn update_backlinks(&self, page: &Page) {
+        let targets = {
+            let manifest_guard = self.manifest.read().await;
+            link_targets(page, &self.config)
+                .into_iter()
+                .filter(|id| {
+                    manifest_guard
+                        .file_for_identifier(id)
+                        .and_then(|file| manifest_guard.feature_types.get(file))
+                        == Some(&FeatureType::Page)
+                })
+                .collect()
+        };
+        self.backlinks.write().await.set_links(&page.filename, targets);
+    }
+
+    /// Pages linking to `identifier`, ordered by identifier.
+    pub async fn get_backlinks(&self, identifier: &str) -> Vec<Page> {
+        let sources = self.backlinks.read().await.sources(identifier);
+
+        let mut pages = Vec::new();
+        for filename in sources {
+            if let Some(page) = self.get_page_by_filename(&filename).await {
+                pages.push(page);
+            }
+        }
+        pages.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        pages
+    }
+
+    /// Increases whenever a cache entry is added, replaced or removed, so
+    /// derived data can tell whether it is stale.
+    pub fn cache_generation(&self) -> u64 {
+        self.cache_generation.load(Ordering::SeqCst)
+    }
+
+    async fn cached_feature(&self, f_type: FeatureType, filename: &str) -> Option<Feature> {
+        self.caches.get(&f_type)?.get_by_key(filename).await
+    }
+
     pub async fn get_all_features_by_type(&self, f_type: FeatureType) -> Vec<Feature> {
         if let Some(cache) = self.caches.get(&f_type) {
             return cache.get_all().await;
@@ -237,14 +883,142 @@ impl SyncService {
         Vec::new()
     }
 
+    /// Pages being served. With `HIDE_UNPUBLISHED_PAGES` on, that leaves out
+    /// unpublished and scheduled pages as of this call.
+    pub async fn get_all_pages(&self) -> Vec<Page> {
+        let mut pages = self.get_all_pages_with_unpublished().await;
+        if self.config.hide_unpublished_pages {
+            let now = chrono::Utc::now().naive_utc();
+            pages.retain(|page| page.is_published_at(now));
+        }
+        pages
+    }
+
+    /// Every synced page, whether it's out yet or not.
+    pub async fn get_all_pages_with_unpublished(&self) -> Vec<Page> {
+        self.get_all_features_by_type(FeatureType::Page)
+            .await
+            .into_iter()
+            .filter_map(|f| match f {
+                Feature::Page(p) => Some(p),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// When the next scheduled page goes live, so anything derived from the
+    /// served pages knows when it goes stale without a sync.
+    pub async fn next_scheduled_publication(&self) -> Option<NaiveDateTime> {
+        if !self.config.hide_unpublished_pages {
+            return None;
+        }
+
+        let now = chrono::Utc::now().naive_utc();
+        self.get_all_pages_with_unpublished()
+            .await
+            .into_iter()
+            // pages marked `published: false` never go live, whatever their date
+            .filter(|page| !page.is_published_at(now) && page.is_published_at(NaiveDateTime::MAX))
+            .filter_map(|page| page.created_datetime)
+            .min()
+    }
+
+    // the cache holds every page; unpublished ones are only hidden on the way out
+    fn published(&self, feature: Feature) -> Option<Feature> {
+        match &feature {
+            Feature::Page(page)
+                if self.config.hide_unpublished_pages
+                    && !page.is_published_at(chrono::Utc::now().naive_utc()) =>
+            {
+                None
+            }
+            _ => Some(feature),
+        }
+    }
+
+    /// When the page behind `identifier` was soft-deleted. Always `None`
+    /// unless `SOFT_DELETE` is on.
+    pub async fn page_tombstone(&self, identifier: &str) -> Option<NaiveDateTime> {
+        if !self.config.soft_delete {
+            return None;
+        }
+
+        match self.repo.get_page_tombstone(identifier).await {
+            Ok(deleted_at) => deleted_at,
+            Err(e) => {
+                warn!(identifier, error = %e, "failed to look up page tombstone");
+                None
+            }
+        }
+    }
+
+    /// Versions the page file `filename` was saved with, newest first.
+    pub async fn page_history(&self, filename: &str) -> Vec<PageRevision> {
+        match self.repo.get_page_history(filename).await {
+            Ok(history) => history,
+            Err(e) => {
+                warn!(filename, error = %e, "failed to load page history");
+                Vec::new()
+            }
+        }
+    }
+
+    /// The version of `filename` saved with `content_hash`, body included.
+    pub async fn page_revision(&self, filename: &str, content_hash: &str) -> Option<PageRevision> {
+        match self.repo.get_page_revision(filename, content_hash).await {
+            Ok(revision) => revision,
+            Err(e) => {
+                warn!(filename, content_hash, error = %e, "failed to load page revision");
+                None
+            }
+        }
+    }
+
+    /// Hard-deletes page tombstones older than `TOMBSTONE_RETENTION_DAYS`.
+    pub async fn purge_tombstones(&self) -> Result<u64> {
+        let retention = chrono::Duration::days(self.config.tombstone_retention_days as i64);
+        let cutoff = chrono::Utc::now().naive_utc() - retention;
+        let purged = self.repo.purge_page_tombstones(cutoff).await?;
+        info!(purged, "purged page tombstones");
+        Ok(purged)
+    }
+
+    /// Snapshot of the manifest's filename to identifier map, sorted by filename.
+    pub async fn dump_manifest(&self) -> BTreeMap<String, String> {
+        let manifest_guard = self.manifest.read().await;
+        manifest_guard
+            .file_to_id
+            .iter()
+            .map(|(filename, id)| (filename.clone(), id.clone()))
+            .collect()
+    }
+
+    pub async fn get_page_by_filename(&self, filename: &str) -> Option<Page> {
+        let feature = self.caches.get(&FeatureType::Page)?.get_by_key(filename).await;
+        match feature.and_then(|f| self.published(f)) {
+            Some(Feature::Page(p)) => Some(p),
+            _ => None,
+        }
+    }
+
+    /// The identifier of the page that lists `alias` in its `aliases`.
+    pub async fn resolve_alias(&self, alias: &str) -> Option<String> {
+        self.manifest.read().await.resolve_alias(alias).cloned()
+    }
+
     pub async fn get_feature_by_identifier(&self, identifier: &str) -> Option<Feature> {
         let manifest_guard = self.manifest.read().await;
-        let filename = manifest_guard.id_to_file.get(identifier)?;
+        let filename = manifest_guard.file_for_identifier(identifier)?;
         let f_type = manifest_guard.feature_types.get(filename)?;
 
         if let Some(cache) = self.caches.get(f_type) {
-            return cache.get_by_key(filename).await;
+            return cache.get_by_key(filename).await.and_then(|f| self.published(f));
         }
         None
     }
-}
\ No newline at end of file
+}
+
+fn error_kind(e: &anyhow::Error) -> &'static str {
+    e.downcast_ref::<ChasquiError>()
+        .map_or("other", ChasquiError::kind)
+}
@@ -0,0 +1,71 @@
+use axum::extract::Request;
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use metrics::{counter, histogram};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+pub const PAGE_REQUESTS: &str = "chasqui_page_requests_total";
+pub const NOT_FOUND: &str = "chasqui_not_found_total";
+pub const SYNC_BATCHES: &str = "chasqui_sync_batches_total";
+pub const PAGES_INGESTED: &str = "chasqui_pages_ingested_total";
+pub const WEBHOOK_NOTIFICATIONS: &str = "chasqui_webhook_notifications_total";
+pub const PAGE_COUNT: &str = "chasqui_pages";
+pub const WATCHER_EVENTS: &str = "chasqui_watcher_events_total";
+pub const WATCHER_COALESCED: &str = "chasqui_watcher_coalesced_events_total";
+pub const WATCHER_DROPPED: &str = "chasqui_watcher_dropped_events_total";
+pub const WATCHER_FULL_SYNCS: &str = "chasqui_watcher_full_sync_fallbacks_total";
+pub const WATCHER_BATCH_SIZE: &str = "chasqui_watcher_batch_size";
+pub const REQUEST_DURATION: &str = "chasqui_http_request_duration_seconds";
+
+static PROMETHEUS: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Installs the global Prometheus recorder. Safe to call more than once; only
+/// the first call registers it.
+pub fn install_recorder() -> &'static PrometheusHandle {
+    PROMETHEUS.get_or_init(|| {
+        PrometheusBuilder::new()
+            .install_recorder()
+            .expect("Failed to install Prometheus recorder")
+    })
+}
+
+pub fn record_page_request<T>(result: &Result<T, StatusCode>) {
+    counter!(PAGE_REQUESTS).increment(1);
+    if matches!(result, Err(StatusCode::NOT_FOUND)) {
+        counter!(NOT_FOUND).increment(1);
+    }
+}
+
+/// One `access` log line per request with method, path, status and latency.
+/// Latencies also feed `REQUEST_DURATION`, which `/metrics` renders with
+/// p50/p90/p99 quantiles.
+pub async fn access_log(request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let start = Instant::now();
+
+    let response = next.run(request).await;
+
+    let latency = start.elapsed();
+    histogram!(REQUEST_DURATION).record(latency.as_secs_f64());
+    tracing::info!(
+        target: "access",
+        method = %method,
+        path = %path,
+        status = response.status().as_u16(),
+        latency_ms = latency.as_secs_f64() * 1000.0,
+        "request"
+    );
+
+    response
+}
+
+pub async fn metrics_handler() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        install_recorder().render(),
+    )
+}
@@ -1,5 +1,5 @@
-use chasqui_core::io::{ContentMetadata, ContentReader, SyncFile};
-use chasqui_core::notifier::ContentBuildNotifier;
+use chasqui_core::io::{ContentMetadata, ContentReader, PathStream, SyncFile};
+use chasqui_core::notifier::{BuildChanges, ContentBuildNotifier};
 use anyhow::Result;
 use async_trait::async_trait;
 use std::collections::HashSet;
@@ -41,7 +41,7 @@ impl ContentReader for BlockingReader {
             let block = self.block_on.lock().unwrap();
             let mut blocked = self.blocked_files.lock().unwrap();
 
-            let match_found = block.iter().any(|b| path_str.contains(b));
+            let match_found = block.iter().any(|b| path_str.ends_with(b));
             if match_found && !blocked.contains(&path_str.to_string()) {
                 blocked.insert(path_str.to_string());
                 true
@@ -68,7 +68,7 @@ impl ContentReader for BlockingReader {
     async fn get_metadata(&self, path: &Path) -> Result<ContentMetadata> { self.inner.get_metadata(path).await }
     async fn list_all_files(&self, root: &Path) -> Result<Vec<PathBuf>> { self.inner.list_all_files(root).await }
     async fn list_files_by_extension(&self, root: &Path, ext: String) { self.inner.list_files_by_extension(root, ext).await }
-    async fn list_markdown_files(&self, root: &Path) -> Result<Vec<PathBuf>> { self.inner.list_markdown_files(root).await }
+    fn stream_markdown_files<'a>(&'a self, root: &'a Path, extensions: &'a [String]) -> PathStream<'a> { self.inner.stream_markdown_files(root, extensions) }
 }
 
 #[derive(Clone)]
@@ -76,6 +76,7 @@ pub struct MockBuildNotifier {
     pub call_count: Arc<Mutex<usize>>,
     pub simulate_latency: Arc<Mutex<Option<Duration>>>,
     pub should_fail: Arc<Mutex<bool>>,
+    pub last_changes: Arc<Mutex<Option<BuildChanges>>>,
 }
 
 impl MockBuildNotifier {
@@ -84,6 +85,7 @@ impl MockBuildNotifier {
             call_count: Arc::new(Mutex::new(0)),
             simulate_latency: Arc::new(Mutex::new(None)),
             should_fail: Arc::new(Mutex::new(false)),
+            last_changes: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -100,7 +102,7 @@ impl MockBuildNotifier {
 
 #[async_trait]
 impl ContentBuildNotifier for MockBuildNotifier {
-    async fn notify(&self) -> Result<()> {
+    async fn notify(&self, changes: &BuildChanges) -> Result<()> {
         let latency = { *self.simulate_latency.lock().unwrap() };
         if let Some(d) = latency {
             tokio::time::sleep(d).await;
@@ -111,6 +113,7 @@ impl ContentBuildNotifier for MockBuildNotifier {
             return Err(anyhow::anyhow!("Webhook Failed"));
         }
 
+        *self.last_changes.lock().unwrap() = Some(changes.clone());
         let mut count = self.call_count.lock().unwrap();
         *count += 1;
         Ok(())
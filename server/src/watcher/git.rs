@@ -0,0 +1,80 @@
+use crate::services::sync::SyncService;
+use chasqui_core::io::git::{GitContentReader, Oid};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Polls the configured git ref and resyncs whenever it moves. Blobs removed
+/// between the two commits are deleted first, since a full sync only sees
+/// what is still there.
+pub fn start_git_poller(
+    sync_service: Arc<SyncService>,
+    reader: Arc<GitContentReader>,
+    interval: Duration,
+) {
+    tokio::spawn(async move {
+        let mut last = match reader.head_commit() {
+            Ok(oid) => oid,
+            Err(e) => {
+                error!(error = %e, "git poller could not resolve ref, not polling");
+                return;
+            }
+        };
+
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+
+            let head = match reader.head_commit() {
+                Ok(oid) => oid,
+                Err(e) => {
+                    warn!(error = %e, "failed to resolve git ref");
+                    continue;
+                }
+            };
+            if head == last {
+                continue;
+            }
+
+            info!(from = %last, to = %head, "git ref moved, resyncing");
+            let content_changed = match sync_ref_change(&sync_service, &reader, last, head).await {
+                Ok(content_changed) => content_changed,
+                Err(e) => {
+                    error!(error = %e, "git resync failed");
+                    continue;
+                }
+            };
+            last = head;
+
+            // a commit touching only files outside the content tree writes nothing
+            if !content_changed {
+                continue;
+            }
+            if let Err(e) = sync_service.notify_build().await {
+                error!(error = %e, "build notification failed");
+            }
+        }
+    });
+}
+
+// whether the move wrote any page or asset
+async fn sync_ref_change(
+    sync_service: &SyncService,
+    reader: &GitContentReader,
+    from: Oid,
+    to: Oid,
+) -> anyhow::Result<bool> {
+    let mut content_changed = false;
+    let deletions = reader.deleted_paths(from, to)?;
+    if !deletions.is_empty() {
+        let report = sync_service.process_batch(Vec::new(), deletions).await?;
+        content_changed |= !report.written.is_empty();
+    }
+
+    let _guard = sync_service.lock_sync().await;
+    let report = sync_service.full_sync().await?;
+    content_changed |= !report.written.is_empty();
+    Ok(content_changed)
+}
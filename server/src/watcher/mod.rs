@@ -1,3 +1,5 @@
+pub mod git;
 pub mod watcher;
 
+pub use git::start_git_poller;
 pub use watcher::{SyncCommand, start_directory_watcher, run_watcher_worker};
\ No newline at end of file
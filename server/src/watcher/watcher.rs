@@ -1,35 +1,57 @@
 use chasqui_core::config::ChasquiConfig;
 use chasqui_core::features::model::FeatureType;
+use chasqui_core::io::path_utils::sidecar_page;
 use crate::services::sync::SyncService;
+use crate::telemetry;
+use anyhow::Context;
+use metrics::{counter, histogram};
+use notify::event::{ModifyKind, RenameMode};
 use notify::{EventKind, RecursiveMode, Watcher};
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use tokio::sync::mpsc;
-
-const DEBOUNCE_MS: u64 = 1500;
+use tracing::{debug, error, info, warn};
 
 #[derive(Debug, Clone)]
 pub enum SyncCommand {
     SingleFile(PathBuf, PathBuf, FeatureType),
     DeleteFile(PathBuf),
+    RescanDirectory(PathBuf),
 }
 
+/// Watches every mount and feeds changes to `run_watcher_worker`.
+///
+/// Editor save strategies this copes with:
+/// - in-place writes (nano, Vim with `backupcopy=yes`): coalesced `Modify`
+///   events within the debounce window
+/// - write-to-temp-then-rename (Vim's `.foo.md.swp`, JetBrains and VS Code
+///   atomic saves, Emacs `foo.md~` backups): the dotfile and `~` temp names
+///   are ignored and the rename lands as a change to `foo.md`
+/// - truncate-then-write, or a rename that outruns the flush: a file found
+///   empty or unreadable after the debounce window is retried once, one
+///   window later, before being synced as it is
+///
+/// Fails only when the platform watcher can't be created. A mount that
+/// can't be watched is logged and skipped, leaving the others live.
 pub fn start_directory_watcher(
     sync_service: Arc<SyncService>,
     config: Arc<ChasquiConfig>,
-) -> mpsc::Sender<SyncCommand> {
+) -> anyhow::Result<mpsc::Sender<SyncCommand>> {
     let (tx, rx) = mpsc::channel::<SyncCommand>(100);
     let tx_clone = tx.clone();
     let service_ref = sync_service.clone();
     let needs_full_sync = Arc::new(AtomicBool::new(false));
     let needs_full_sync_worker = needs_full_sync.clone();
-
-    tokio::spawn(run_watcher_worker(sync_service, rx, needs_full_sync_worker));
+    let mount_paths = MountPaths::new(config.mounts().into_iter().map(|(root, _)| root));
 
     let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
-        if let Ok(event) = res {
+        if let Ok(mut event) = res {
+            for path in event.paths.iter_mut() {
+                *path = mount_paths.to_mount_path(path);
+            }
             if let Some(path) = event.paths.first() {
                 let filename = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
 
@@ -37,41 +59,176 @@ pub fn start_directory_watcher(
                     return;
                 }
 
-                let command = match event.kind {
-                    EventKind::Create(_) | EventKind::Modify(_) => {
-                        if let Some((mount, f_type)) = service_ref.identify_mount(path) {
-                            Some(SyncCommand::SingleFile(path.clone(), mount.to_path_buf(), f_type))
-                        } else {
-                            None
+                debug!(kind = ?event.kind, path = %path.display(), "file event");
+
+                let commands = if let Some(page) = sidecar_page(path).filter(|page| page.is_file()) {
+                    // whatever happens to a sidecar, it's its page that changed
+                    change_command(&service_ref, &page).into_iter().collect()
+                } else {
+                    match event.kind {
+                        // renames arrive either as a From/To pair or as a single
+                        // event carrying both paths; the old side is a deletion
+                        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                            vec![SyncCommand::DeleteFile(path.clone())]
+                        }
+                        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                            let mut cmds = vec![SyncCommand::DeleteFile(path.clone())];
+                            if let Some(to) = event.paths.get(1) {
+                                cmds.extend(change_command(&service_ref, to));
+                            }
+                            cmds
                         }
+                        EventKind::Create(_) | EventKind::Modify(_) => {
+                            change_command(&service_ref, path).into_iter().collect()
+                        }
+                        EventKind::Remove(_) => vec![SyncCommand::DeleteFile(path.clone())],
+                        _ => Vec::new(),
                     }
-                    EventKind::Remove(_) => Some(SyncCommand::DeleteFile(path.clone())),
-                    _ => None,
                 };
 
-                if let Some(cmd) = command {
+                for cmd in commands {
                     if let Err(mpsc::error::TrySendError::Full(_)) = tx_clone.try_send(cmd) {
+                        counter!(telemetry::WATCHER_DROPPED).increment(1);
                         needs_full_sync.store(true, Ordering::SeqCst);
                     }
                 }
             }
         }
     })
-    .expect("Failed to initialize file watcher");
+    .context("Failed to initialize file watcher")?;
 
-    let mut unique_roots = std::collections::HashSet::new();
-    unique_roots.insert(&config.pages_dir);
-    unique_roots.insert(&config.images_dir);
-    unique_roots.insert(&config.audio_dir);
-    unique_roots.insert(&config.videos_dir);
+    let unique_roots: HashSet<PathBuf> = config.mounts().into_iter().map(|(root, _)| root).collect();
 
     for root in unique_roots {
-        let _ = watcher.watch(root, RecursiveMode::Recursive);
+        if let Err(e) = watcher.watch(&root, RecursiveMode::Recursive) {
+            warn!(root = %root.display(), error = %e, "not watching content dir");
+        }
     }
 
     Box::leak(Box::new(watcher));
+    tokio::spawn(run_watcher_worker(sync_service, rx, needs_full_sync_worker));
+
+    Ok(tx)
+}
+
+/// Maps watcher event paths back onto the configured mount roots. A root
+/// reached through a symlink (macOS's `/tmp` is `/private/tmp`) can report
+/// events under its resolved path, or the other way round, and either would
+/// fail the `strip_prefix` against the mount.
+pub struct MountPaths {
+    // each configured root with the path it resolves to
+    roots: Vec<(PathBuf, PathBuf)>,
+}
+
+impl MountPaths {
+    pub fn new(roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        let roots = roots
+            .into_iter()
+            .map(|root| {
+                let resolved = std::fs::canonicalize(&root).unwrap_or_else(|_| root.clone());
+                (root, resolved)
+            })
+            .collect();
+        Self { roots }
+    }
+
+    pub fn to_mount_path(&self, path: &Path) -> PathBuf {
+        if self.roots.iter().any(|(root, _)| path.starts_with(root)) {
+            return path.to_path_buf();
+        }
+        if let Some(mapped) = self.under_resolved(path) {
+            return mapped;
+        }
+
+        // the event path itself runs through a symlink; a deleted file can't
+        // be resolved, but its directory usually still can
+        let resolved = std::fs::canonicalize(path).ok().or_else(|| {
+            let parent = std::fs::canonicalize(path.parent()?).ok()?;
+            Some(parent.join(path.file_name()?))
+        });
+        resolved
+            .and_then(|resolved| self.under_resolved(&resolved))
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
+    fn under_resolved(&self, path: &Path) -> Option<PathBuf> {
+        self.roots.iter().find_map(|(root, resolved)| {
+            path.strip_prefix(resolved).ok().map(|rest| root.join(rest))
+        })
+    }
+}
+
+fn change_command(service: &SyncService, path: &Path) -> Option<SyncCommand> {
+    if path.is_dir() {
+        return Some(SyncCommand::RescanDirectory(path.to_path_buf()));
+    }
+
+    service
+        .identify_mount(path)
+        .map(|(mount, f_type)| SyncCommand::SingleFile(path.to_path_buf(), mount, f_type))
+}
+
+#[derive(Default)]
+struct PendingBatch {
+    changes: HashMap<PathBuf, (PathBuf, FeatureType)>,
+    deletions: HashSet<PathBuf>,
+    rescans: HashSet<PathBuf>,
+    // changes that looked half-written and wait one more debounce window
+    retries: HashMap<PathBuf, (PathBuf, FeatureType)>,
+    // changes already retried once, synced as they are next time
+    retried: HashSet<PathBuf>,
+    // commands queued since the last batch, before coalescing
+    received: usize,
+}
+
+impl PendingBatch {
+    fn queue(&mut self, cmd: SyncCommand) {
+        self.received += 1;
+        match cmd {
+            SyncCommand::SingleFile(p, m, t) => {
+                // a fresh event means the file moved on, so it earns a retry again
+                self.retried.remove(&p);
+                self.deletions.remove(&p);
+                self.changes.insert(p, (m, t));
+            }
+            SyncCommand::DeleteFile(p) => {
+                self.retries.remove(&p);
+                self.retried.remove(&p);
+                self.changes.remove(&p);
+                self.rescans.remove(&p);
+                self.deletions.insert(p);
+            }
+            SyncCommand::RescanDirectory(p) => {
+                self.rescans.insert(p);
+            }
+        }
+    }
 
-    tx
+    fn len(&self) -> usize {
+        self.changes.len() + self.deletions.len() + self.rescans.len()
+    }
+
+    fn clear(&mut self) {
+        self.changes.clear();
+        self.deletions.clear();
+        self.rescans.clear();
+        self.retries.clear();
+        self.retried.clear();
+    }
+
+    // brings back the changes deferred by the previous batch
+    fn requeue_retries(&mut self) {
+        for (p, target) in self.retries.drain() {
+            self.retried.insert(p.clone());
+            self.changes.entry(p).or_insert(target);
+        }
+    }
+}
+
+// An atomic save can fire `Create` before the content is flushed, and a
+// truncate-then-write save passes through an empty file.
+async fn is_settled(sync_service: &SyncService, path: &Path) -> bool {
+    matches!(sync_service.reader.get_metadata(path).await, Ok(meta) if meta.size > 0)
 }
 
 pub async fn run_watcher_worker(
@@ -79,73 +236,96 @@ pub async fn run_watcher_worker(
     mut receiver: mpsc::Receiver<SyncCommand>,
     needs_full_sync: Arc<AtomicBool>,
 ) {
-    let mut pending_changes = std::collections::HashMap::new();
-    let mut pending_deletions = std::collections::HashSet::new();
+    let mut pending = PendingBatch::default();
+    let debounce = Duration::from_millis(sync_service.config.watch_debounce_ms);
 
     loop {
-        let first_cmd = match receiver.recv().await {
-            Some(cmd) => cmd,
-            None => break,
-        };
-
-        match first_cmd {
-            SyncCommand::SingleFile(p, m, t) => {
-                pending_changes.insert(p.clone(), (m, t));
-                pending_deletions.remove(&p);
+        // with retries waiting, a quiet debounce window is enough to go again
+        let first_cmd = if pending.retries.is_empty() {
+            match receiver.recv().await {
+                Some(cmd) => Some(cmd),
+                None => break,
             }
-            SyncCommand::DeleteFile(p) => {
-                pending_deletions.insert(p.clone());
-                pending_changes.remove(&p);
+        } else {
+            match tokio::time::timeout(debounce, receiver.recv()).await {
+                Ok(Some(cmd)) => Some(cmd),
+                Ok(None) => break,
+                Err(_) => None,
             }
-        }
+        };
+        pending.requeue_retries();
 
-        loop {
-            let timeout =
-                tokio::time::timeout(Duration::from_millis(DEBOUNCE_MS), receiver.recv()).await;
-            match timeout {
-                Ok(Some(cmd)) => match cmd {
-                    SyncCommand::SingleFile(p, m, t) => {
-                        pending_changes.insert(p.clone(), (m, t));
-                        pending_deletions.remove(&p);
-                    }
-                    SyncCommand::DeleteFile(p) => {
-                        pending_deletions.insert(p.clone());
-                        pending_changes.remove(&p);
-                    }
-                },
-                Ok(None) => break,
-                Err(_) => break,
+        if let Some(first_cmd) = first_cmd {
+            pending.queue(first_cmd);
+
+            loop {
+                let timeout = tokio::time::timeout(debounce, receiver.recv()).await;
+                match timeout {
+                    Ok(Some(cmd)) => pending.queue(cmd),
+                    Ok(None) => break,
+                    Err(_) => break,
+                }
             }
         }
 
-        let mut sync_occurred = false;
+        let received = std::mem::take(&mut pending.received);
+        let coalesced = received.saturating_sub(pending.len());
+        counter!(telemetry::WATCHER_EVENTS).increment(received as u64);
+        counter!(telemetry::WATCHER_COALESCED).increment(coalesced as u64);
+
+        // only batches that wrote something are worth a frontend rebuild
+        let mut content_changed = false;
         if needs_full_sync.swap(false, Ordering::SeqCst) {
-            if let Err(e) = sync_service.full_sync().await {
-                eprintln!("Error: {}", e);
-            } else {
-                sync_occurred = true;
+            warn!(received, "watcher queue overflowed, falling back to full sync");
+            counter!(telemetry::WATCHER_FULL_SYNCS).increment(1);
+            let _guard = sync_service.lock_sync().await;
+            match sync_service.full_sync().await {
+                Ok(report) => content_changed = !report.written.is_empty(),
+                Err(e) => error!(error = %e, "full sync failed"),
             }
-            pending_changes.clear();
-            pending_deletions.clear();
+            pending.clear();
         } else {
-            let changes: Vec<(PathBuf, PathBuf, FeatureType)> =
-                pending_changes.drain().map(|(p, (m, t))| (p, m, t)).collect();
-            let deletions: Vec<PathBuf> = pending_deletions.drain().collect();
+            for dir in pending.rescans.drain() {
+                for (p, m, t) in sync_service.scan_directory(&dir).await {
+                    pending.changes.entry(p).or_insert((m, t));
+                }
+            }
 
-            if !changes.is_empty() || !deletions.is_empty() {
-                if let Err(e) = sync_service.process_batch(changes, deletions).await {
-                    eprintln!("Error: {}", e);
+            let mut changes: Vec<(PathBuf, PathBuf, FeatureType)> = Vec::new();
+            for (p, (m, t)) in pending.changes.drain() {
+                if pending.retried.remove(&p) || is_settled(&sync_service, &p).await {
+                    changes.push((p, m, t));
                 } else {
-                    sync_occurred = true;
+                    debug!(path = %p.display(), "file empty or unreadable, retrying after the debounce window");
+                    pending.retries.insert(p, (m, t));
+                }
+            }
+            let deletions: Vec<PathBuf> = pending.deletions.drain().collect();
+
+            let batch_size = changes.len() + deletions.len();
+            histogram!(telemetry::WATCHER_BATCH_SIZE).record(batch_size as f64);
+            info!(
+                received,
+                coalesced,
+                changes = changes.len(),
+                deletions = deletions.len(),
+                "watcher batch ready"
+            );
+
+            if !changes.is_empty() || !deletions.is_empty() {
+                let _guard = sync_service.lock_sync().await;
+                match sync_service.process_batch(changes, deletions).await {
+                    Ok(report) => content_changed = !report.written.is_empty(),
+                    Err(e) => error!(error = %e, "sync batch failed"),
                 }
             }
         }
 
-        if sync_occurred {
+        if content_changed {
             let service_clone = sync_service.clone();
             tokio::spawn(async move {
                 if let Err(e) = service_clone.notify_build().await {
-                    eprintln!("Sync Service: Build notification failed: {}", e);
+                    error!(error = %e, "build notification failed");
                 }
             });
         }
@@ -6,13 +6,25 @@ use axum::{
     Router,
 };
 use tower::ServiceExt;
-use chasqui_server::app::AppState;
-use chasqui_server::features::pages::pages_router;
-use chasqui_server::features::handlers::metadata_handler;
+use chasqui_server::app::{AppState, body_limit_layer, compression_layer, cors_layer, no_store};
+use chasqui_server::features::admin::{
+    dry_run_handler, export_json_handler, export_ndjson_handler, link_health_handler, manifest_handler,
+    purge_tombstones_handler,
+    recompile_handler, render_handler, resync_handler,
+};
+use chasqui_server::features::pages::{
+    feed_handler, get_page_by_filename_handler, html_page_handler, json_feed_handler, pages_router,
+    search_index_handler, sitemap_handler, stats_handler, tag_feed_handler, tags_handler,
+};
+use chasqui_server::features::assets::passthrough::passthrough_handler;
+use chasqui_server::features::handlers::{metadata_handler, status_handler};
+use chasqui_server::services::link_health::LinkChecker;
 use chasqui_server::services::sync::SyncService;
+use chasqui_server::telemetry;
 use chasqui_server::testutil::{MockBuildNotifier, MockContentReader};
 use chasqui_core::config::ChasquiConfig;
 use chasqui_core::io::local::LocalContentReader;
+use chasqui_core::io::DEFAULT_MAX_FILE_BYTES;
 use std::sync::Arc;
 use std::fs;
 use tempfile::tempdir;
@@ -33,14 +45,66 @@ async fn setup_api_test_state() -> (AppState, tempfile::TempDir) {
         images_dir: content_dir.clone(),
         audio_dir: content_dir.clone(),
         videos_dir: content_dir.clone(),
+        extra_content_dirs: Vec::new(),
         page_strip_extension: true,
         asset_strip_extension: false,
         serve_home: true,
         home_identifier: "index".into(),
+        folder_index_pages: false,
+        resolve_html_links: false,
         webhook_url: "".into(),
         webhook_secret: "".into(),
+        webhook_payload: None,
         port: 3000,
         nginx_media_prefixes: false,
+        site_url: "http://localhost:3000".into(),
+        watch_debounce_ms: 1500,
+        cors_allowed_origins: Vec::new(),
+        slugify_identifiers: false,
+        admin_token: "".into(),
+        required_frontmatter: Vec::new(),
+        html_template_path: None,
+        created_datetime_aliases: Vec::new(),
+        modified_datetime_aliases: Vec::new(),
+        max_request_body_bytes: 1024 * 1024,
+        max_list_pages: 1000,
+        soft_delete: false,
+        tombstone_retention_days: 30,
+        git_repo: None,
+        git_ref: "HEAD".into(),
+        git_poll_secs: 30,
+        sqlite_journal_mode: "WAL".to_string(),
+        sqlite_synchronous: "NORMAL".to_string(),
+        sqlite_busy_timeout_ms: 5000,
+        sqlite_cache_size: -64000,
+        sqlite_foreign_keys: true,
+        base_path: String::new(),
+        compiled_cache_entries: 0,
+        normalize_slashes: false,
+        strict_links: false,
+        max_batch_pages: 50,
+        content_extensions: vec!["md".to_string()],
+        json_datetime_format: Default::default(),
+        not_found_identifier: "404".into(),
+        dedupe_page_bodies: false,
+        page_cache_max_age: 300,
+        stable_page_after_days: 0,
+        stable_page_max_age: 86400,
+        list_cache_max_age: 60,
+        max_file_bytes: 10_485_760,
+        max_write_connections: 1,
+        passthrough_extensions: Vec::new(),
+        create_content_dirs: false,
+        content_dir_retries: 0,
+        collision_policy: Default::default(),
+        link_check_interval_secs: 0,
+        link_check_concurrency: 4,
+        link_check_timeout_secs: 10,
+        webhook_coalesce: true,
+        html_layouts_dir: None,
+        track_accessed_datetime: false,
+        identifier_strategy: Default::default(),
+        hide_unpublished_pages: false,
     });
 
     let file_path = content_dir.join("api-test.md");
@@ -48,6 +112,7 @@ async fn setup_api_test_state() -> (AppState, tempfile::TempDir) {
 
     let reader = Arc::new(LocalContentReader {
         root_path: content_dir.clone(),
+        max_file_bytes: DEFAULT_MAX_FILE_BYTES,
     });
 
     let service = SyncService::new(
@@ -63,6 +128,8 @@ async fn setup_api_test_state() -> (AppState, tempfile::TempDir) {
     (AppState {
         sync_service: Arc::new(service),
         config: config.clone(),
+        search_index: Default::default(),
+        link_health: Default::default(),
     }, dir)
 }
 
@@ -160,4 +227,2396 @@ async fn test_security_metadata_leak_prevention() {
     assert!(data["new_path"].is_null(), "new_path should not be serialized");
 
     assert!(!body_str.contains(&content_dir_str), "Absolute path leaked in JSON body: {}", body_str);
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_sitemap_lists_pages_with_home_at_root() {
+    let (state, _dir) = setup_api_test_state().await;
+
+    fs::write(
+        state.config.pages_dir.join("index.md"),
+        "---\nmodified_datetime: 2024-03-01\n---\n# Home",
+    )
+    .unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .route("/sitemap.xml", axum::routing::get(sitemap_handler))
+        .with_state(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/sitemap.xml")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/xml"
+    );
+
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let xml = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(xml.contains("<loc>http://localhost:3000/api-test</loc>"));
+    assert!(xml.contains("<loc>http://localhost:3000/</loc>"));
+    assert!(!xml.contains("/index</loc>"));
+    assert!(xml.contains("<lastmod>2024-03-01T00:00:00Z</lastmod>"));
+}
+
+#[tokio::test]
+async fn test_stats_aggregate_pages_and_tags() {
+    let (state, _dir) = setup_api_test_state().await;
+    let pages_dir = state.config.pages_dir.clone();
+
+    fs::write(
+        pages_dir.join("old.md"),
+        "---\ntags: [api, api, notes]\ncreated_datetime: 2020-05-01\n---\n# Old",
+    )
+    .unwrap();
+    fs::write(pages_dir.join("plain.md"), "# Plain").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .route("/stats", axum::routing::get(stats_handler))
+        .with_state(state);
+
+    let response = app
+        .oneshot(Request::builder().uri("/stats").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["total_pages"], 3);
+    assert_eq!(json["total_tags"], 3);
+    // a tag repeated within one page still counts that page once
+    assert_eq!(json["tag_counts"], serde_json::json!({"api": 2, "notes": 1, "test": 1}));
+    assert_eq!(json["oldest_created"], "2020-05-01T00:00:00");
+    assert!(json["newest_created"].is_string());
+}
+
+#[tokio::test]
+async fn test_related_pages_ranked_by_shared_tags() {
+    let (state, _dir) = setup_api_test_state().await;
+    let pages_dir = state.config.pages_dir.clone();
+
+    fs::write(
+        pages_dir.join("close.md"),
+        "---\ntags: [api, test]\ncreated_datetime: 2024-01-01\n---\n# Close",
+    )
+    .unwrap();
+    fs::write(
+        pages_dir.join("near-new.md"),
+        "---\ntags: [api]\ncreated_datetime: 2025-01-01\n---\n# Near New",
+    )
+    .unwrap();
+    fs::write(
+        pages_dir.join("near-old.md"),
+        "---\ntags: [api]\ncreated_datetime: 2023-01-01\n---\n# Near Old",
+    )
+    .unwrap();
+    fs::write(pages_dir.join("unrelated.md"), "---\ntags: [other]\n---\n# Unrelated").unwrap();
+    fs::write(pages_dir.join("untagged.md"), "# Untagged").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .with_state(state);
+
+    let fetch = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            json.as_array()
+                .unwrap()
+                .iter()
+                .map(|p| p["identifier"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(
+        fetch("/pages/api-test/related").await,
+        vec!["close", "near-new", "near-old"]
+    );
+    assert_eq!(fetch("/pages/api-test/related?limit=1").await, vec!["close"]);
+    assert!(fetch("/pages/untagged/related").await.is_empty());
+}
+
+#[tokio::test]
+async fn test_cors_preflight_allows_configured_origin() {
+    let (state, _dir) = setup_api_test_state().await;
+
+    let mut config = (*state.config).clone();
+    config.cors_allowed_origins = vec!["https://frontend.example".into()];
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .layer(cors_layer(&config).expect("origins are configured"))
+        .with_state(state);
+
+    let preflight = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("OPTIONS")
+                .uri("/pages")
+                .header("origin", "https://frontend.example")
+                .header("access-control-request-method", "GET")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(preflight.status(), StatusCode::OK);
+    assert_eq!(
+        preflight.headers().get("access-control-allow-origin").unwrap(),
+        "https://frontend.example"
+    );
+    let methods = preflight.headers().get("access-control-allow-methods").unwrap();
+    assert!(methods.to_str().unwrap().contains("GET"));
+
+    let foreign = app
+        .oneshot(
+            Request::builder()
+                .uri("/pages")
+                .header("origin", "https://elsewhere.example")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(foreign.status(), StatusCode::OK);
+    assert!(foreign.headers().get("access-control-allow-origin").is_none());
+}
+
+#[tokio::test]
+async fn test_raw_markdown_endpoint() {
+    let (state, _dir) = setup_api_test_state().await;
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .with_state(state);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/pages/api-test/raw")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "text/markdown; charset=utf-8"
+    );
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    assert_eq!(String::from_utf8(body.to_vec()).unwrap().trim(), "# API Test Content");
+
+    let missing = app
+        .oneshot(
+            Request::builder()
+                .uri("/pages/missing/raw")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_status_endpoint() {
+    let (state, _dir) = setup_api_test_state().await;
+    let app = Router::new()
+        .route("/status", axum::routing::get(status_handler))
+        .with_state(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/status")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["page_count"], 1);
+    assert!(json["last_sync"].is_string());
+    assert_eq!(json["sync_in_progress"], false);
+}
+
+#[tokio::test]
+async fn test_admin_resync_requires_token() {
+    let (state, _dir) = setup_api_test_state().await;
+
+    let mut config = (*state.config).clone();
+    config.admin_token = "s3cret".into();
+    let state = AppState {
+        sync_service: state.sync_service.clone(),
+        config: Arc::new(config),
+        search_index: Default::default(),
+        link_health: Default::default(),
+    };
+
+    fs::write(state.config.pages_dir.join("fresh.md"), "# Fresh").unwrap();
+
+    let app = Router::new()
+        .route("/admin/resync", axum::routing::post(resync_handler))
+        .with_state(state);
+
+    let resync = |auth: Option<&'static str>| {
+        let app = app.clone();
+        async move {
+            let mut request = Request::builder().method("POST").uri("/admin/resync");
+            if let Some(auth) = auth {
+                request = request.header("authorization", auth);
+            }
+            app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+        }
+    };
+
+    assert_eq!(resync(None).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(resync(Some("Bearer wrong")).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(resync(Some("s3cret")).await.status(), StatusCode::UNAUTHORIZED);
+
+    let (first, second) = tokio::join!(resync(Some("Bearer s3cret")), resync(Some("Bearer s3cret")));
+    for response in [first, second] {
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["page_count"], 2);
+    }
+}
+
+#[tokio::test]
+async fn test_get_page_by_filename() {
+    let (state, _dir) = setup_api_test_state().await;
+
+    let nested = state.config.pages_dir.join("blog/2024");
+    fs::create_dir_all(&nested).unwrap();
+    fs::write(nested.join("post.md"), "---\nidentifier: nested-post\n---\n# Nested").unwrap();
+    let shadowed = state.config.pages_dir.join("by-filename");
+    fs::create_dir_all(&shadowed).unwrap();
+    fs::write(shadowed.join("guide.md"), "# Guide").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .route("/by-filename/{*path}", axum::routing::get(get_page_by_filename_handler))
+        .with_state(state);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/by-filename/blog/2024/post.md")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["identifier"], "nested-post");
+    assert_eq!(json["filename"], "blog/2024/post.md");
+
+    let missing = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/by-filename/blog/missing.md")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+
+    // a page whose identifier starts with `by-filename/` stays reachable
+    let page = app
+        .oneshot(
+            Request::builder()
+                .uri("/pages/by-filename/guide")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint_exposes_counters() {
+    telemetry::install_recorder();
+    let (state, _dir) = setup_api_test_state().await;
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .route("/metrics", axum::routing::get(telemetry::metrics_handler))
+        .with_state(state);
+
+    for uri in ["/pages/api-test", "/pages/does-not-exist"] {
+        app.clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+    }
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/metrics")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(text.contains("chasqui_page_requests_total"), "{}", text);
+    assert!(text.contains("chasqui_not_found_total"), "{}", text);
+    assert!(text.contains("chasqui_sync_batches_total"), "{}", text);
+    assert!(text.contains("chasqui_pages_ingested_total"), "{}", text);
+    assert!(text.contains("chasqui_pages "), "{}", text);
+}
+
+#[tokio::test]
+async fn test_access_log_records_request_latency() {
+    telemetry::install_recorder();
+    let (state, _dir) = setup_api_test_state().await;
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .route("/metrics", axum::routing::get(telemetry::metrics_handler))
+        .layer(axum::middleware::from_fn(telemetry::access_log))
+        .with_state(state);
+
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/pages/api-test").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().contains_key("etag"));
+
+    let response = app
+        .oneshot(Request::builder().uri("/metrics").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+
+    for quantile in ["0.5", "0.99"] {
+        let line = format!("chasqui_http_request_duration_seconds{{quantile=\"{}\"}}", quantile);
+        assert!(text.contains(&line), "{}", text);
+    }
+}
+
+#[tokio::test]
+async fn test_list_pages_date_range_filter() {
+    let (state, _dir) = setup_api_test_state().await;
+    let pages_dir = state.config.pages_dir.clone();
+
+    fs::write(pages_dir.join("jan.md"), "---\ncreated_datetime: 2024-01-15\n---\n# Jan").unwrap();
+    fs::write(pages_dir.join("feb-first.md"), "---\ncreated_datetime: 2024-02-01\n---\n# Feb").unwrap();
+    fs::write(pages_dir.join("feb-last.md"), "---\ncreated_datetime: 2024-02-29T23:30:00Z\n---\n# Feb").unwrap();
+    fs::write(pages_dir.join("mar.md"), "---\ncreated_datetime: 2024-03-01\n---\n# Mar").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .with_state(state);
+
+    let fetch = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            app.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap()
+        }
+    };
+
+    let response = fetch("/pages?from=2024-02-01&to=2024-02-29").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let mut ids: Vec<&str> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["identifier"].as_str().unwrap())
+        .collect();
+    ids.sort();
+    assert_eq!(ids, vec!["feb-first", "feb-last"]);
+
+    let response = fetch("/pages?from=2024-03-01&to=2024-12-31").await;
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+    assert_eq!(json[0]["identifier"], "mar");
+
+    let response = fetch("/pages?to=2024-13-01").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("YYYY-MM-DD"));
+}
+
+#[tokio::test]
+async fn test_list_pages_since_filter() {
+    let (state, _dir) = setup_api_test_state().await;
+    let pages_dir = state.config.pages_dir.clone();
+
+    fs::write(pages_dir.join("old.md"), "---\nmodified_datetime: 2024-01-15T08:00:00Z\n---\n# Old").unwrap();
+    fs::write(pages_dir.join("edge.md"), "---\nmodified_datetime: 2024-02-01T12:00:00Z\n---\n# Edge").unwrap();
+    fs::write(pages_dir.join("new.md"), "---\nmodified_datetime: 2024-02-01T12:00:01Z\n---\n# New").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .with_state(state);
+
+    let fetch = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            app.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap()
+        }
+    };
+
+    // api-test.md has no frontmatter date, so its file mtime is used
+    let response = fetch("/pages?since=2024-02-01T12:00:00&sort=identifier").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let ids: Vec<&str> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["identifier"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["api-test", "new"]);
+
+    let response = fetch("/pages?since=2024-02-01T13:00:00%2B01:00").await;
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 2);
+
+    let response = fetch("/pages?since=yesterday").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("YYYY-MM-DDTHH:MM:SS"));
+}
+
+#[tokio::test]
+async fn test_admin_manifest_dump() {
+    let (state, _dir) = setup_api_test_state().await;
+
+    let mut config = (*state.config).clone();
+    config.admin_token = "s3cret".into();
+    let state = AppState {
+        sync_service: state.sync_service.clone(),
+        config: Arc::new(config),
+        search_index: Default::default(),
+        link_health: Default::default(),
+    };
+
+    let app = Router::new()
+        .route("/admin/manifest", axum::routing::get(manifest_handler))
+        .with_state(state);
+
+    let unauthorized = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/admin/manifest")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(unauthorized.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/admin/manifest")
+                .header("authorization", "Bearer s3cret")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["api-test.md"], "api-test");
+}
+
+#[tokio::test]
+async fn test_export_bundles_every_page() {
+    let (state, _dir) = setup_api_test_state().await;
+    fs::write(state.config.pages_dir.join("zebra.md"), "---\ntags: [z]\n---\n# Zebra").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let mut config = (*state.config).clone();
+    config.admin_token = "s3cret".into();
+    let state = AppState {
+        sync_service: state.sync_service.clone(),
+        config: Arc::new(config),
+        search_index: Default::default(),
+        link_health: Default::default(),
+    };
+
+    let app = Router::new()
+        .route("/export.json", axum::routing::get(export_json_handler))
+        .route("/export.ndjson", axum::routing::get(export_ndjson_handler))
+        .with_state(state);
+
+    let get = |uri: &'static str, token: Option<&'static str>| {
+        let app = app.clone();
+        async move {
+            let mut request = Request::builder().uri(uri);
+            if let Some(token) = token {
+                request = request.header("authorization", format!("Bearer {}", token));
+            }
+            app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+        }
+    };
+
+    assert_eq!(get("/export.json", None).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(get("/export.ndjson", Some("wrong")).await.status(), StatusCode::UNAUTHORIZED);
+
+    let response = get("/export.json", Some("s3cret")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/json");
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let pages: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+    let identifiers: Vec<&str> = pages.iter().map(|p| p["identifier"].as_str().unwrap()).collect();
+    assert_eq!(identifiers, vec!["api-test", "zebra"]);
+    // full pages, body included
+    assert!(pages[1]["md_content"].as_str().unwrap().contains("# Zebra"));
+    assert_eq!(pages[1]["tags"], serde_json::json!(["z"]));
+
+    let response = get("/export.ndjson", Some("s3cret")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/x-ndjson");
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(body.ends_with('\n'));
+    let lines: Vec<serde_json::Value> = body.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines, pages);
+}
+
+#[tokio::test]
+async fn test_html_page_endpoint() {
+    let (state, dir) = setup_api_test_state().await;
+    let app = Router::new()
+        .route("/p/{*identifier}", axum::routing::get(html_page_handler))
+        .with_state(state.clone());
+
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/p/api-test").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "text/html; charset=utf-8"
+    );
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>api-test</title>"));
+    assert!(html.contains("<h1>API Test Content</h1>"));
+
+    let missing = app
+        .oneshot(Request::builder().uri("/p/missing").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        missing.headers().get("content-type").unwrap(),
+        "text/html; charset=utf-8"
+    );
+
+    let template_path = dir.path().join("template.html");
+    fs::write(&template_path, "<html><head>{{meta}}<title>{{ title }} | Site</title></head>{{content}}</html>").unwrap();
+    let mut config = (*state.config).clone();
+    config.html_template_path = Some(template_path);
+    let app = Router::new()
+        .route("/p/{*identifier}", axum::routing::get(html_page_handler))
+        .with_state(AppState {
+            config: Arc::new(config),
+            ..state
+        });
+
+    let response = app
+        .oneshot(Request::builder().uri("/p/api-test").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    // the page has no description, so no meta tag is emitted
+    assert!(html.starts_with("<html><head><title>api-test | Site</title></head><h1>"), "{}", html);
+}
+
+#[tokio::test]
+async fn test_html_page_layouts() {
+    let (state, dir) = setup_api_test_state().await;
+
+    fs::write(
+        state.config.pages_dir.join("post.md"),
+        "---\nname: A Post\nlayout: post\ntags: [rust, web]\nauthor: Ana <a>\n---\n# Post Body",
+    )
+    .unwrap();
+    fs::write(
+        state.config.pages_dir.join("typo.md"),
+        "---\nname: Typo\nlayout: psot\n---\n# Typo Body",
+    )
+    .unwrap();
+    fs::write(
+        state.config.pages_dir.join("escape.md"),
+        "---\nname: Escape\nlayout: ../template\n---\n# Escape Body",
+    )
+    .unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let layouts_dir = dir.path().join("layouts");
+    fs::create_dir_all(&layouts_dir).unwrap();
+    fs::write(
+        layouts_dir.join("post.html"),
+        "<article data-id=\"{{identifier}}\" data-tags=\"{{tags}}\">{{metadata.author}}{{content}}</article>",
+    )
+    .unwrap();
+    fs::write(dir.path().join("template.html"), "<main>{{content}}</main>").unwrap();
+
+    let mut config = (*state.config).clone();
+    config.html_template_path = Some(dir.path().join("template.html"));
+    config.html_layouts_dir = Some(layouts_dir);
+    let app = Router::new()
+        .route("/p/{*identifier}", axum::routing::get(html_page_handler))
+        .with_state(AppState {
+            config: Arc::new(config),
+            ..state
+        });
+
+    let get = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        }
+    };
+
+    let (status, html) = get("/p/post").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        html.starts_with("<article data-id=\"post\" data-tags=\"rust, web\">Ana &lt;a&gt;<h1>Post Body</h1>"),
+        "{}",
+        html
+    );
+
+    // a layout that doesn't exist, or isn't a plain name, falls back to the default
+    let (status, html) = get("/p/typo").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(html.starts_with("<main><h1>Typo Body</h1>"), "{}", html);
+
+    let (status, html) = get("/p/escape").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(html.starts_with("<main><h1>Escape Body</h1>"), "{}", html);
+
+    // pages without a layout keep the default
+    let (_, html) = get("/p/api-test").await;
+    assert!(html.starts_with("<main><h1>API Test Content</h1>"), "{}", html);
+}
+
+#[tokio::test]
+async fn test_tag_feeds() {
+    let (state, _dir) = setup_api_test_state().await;
+
+    fs::write(
+        state.config.pages_dir.join("rusty.md"),
+        "---\nname: Rust & Friends\ntags: [Rust]\ncreated_datetime: 2024-03-01\n---\n# Rusty",
+    )
+    .unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .route("/feed.xml", axum::routing::get(feed_handler))
+        .route("/feed/{file}", axum::routing::get(tag_feed_handler))
+        .with_state(state);
+
+    let fetch = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        }
+    };
+
+    let (status, all) = fetch("/feed.xml").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(all.matches("<item>").count(), 2);
+
+    let (status, rust) = fetch("/feed/rust.xml").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(rust.contains("<title>http://localhost:3000 - tagged &quot;rust&quot;</title>"), "{}", rust);
+    assert_eq!(rust.matches("<item>").count(), 1);
+    assert!(rust.contains("<title>Rust &amp; Friends</title>"));
+    assert!(rust.contains("<link>http://localhost:3000/rusty</link>"));
+    assert!(rust.contains("<pubDate>Fri, 1 Mar 2024 00:00:00 +0000</pubDate>"));
+
+    let (status, empty) = fetch("/feed/unknown.xml").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(empty.contains("<channel>"));
+    assert_eq!(empty.matches("<item>").count(), 0);
+
+    let (status, _) = fetch("/feed/rust").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_search_index_rebuilds_after_sync() {
+    let (state, _dir) = setup_api_test_state().await;
+    let app = Router::new()
+        .route("/search-index.json", axum::routing::get(search_index_handler))
+        .with_state(state.clone());
+
+    let fetch = || {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri("/search-index.json").body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        }
+    };
+
+    let index = fetch().await;
+    assert_eq!(
+        index,
+        serde_json::json!([
+            { "id": "api-test", "title": "api-test", "tags": ["api", "test"], "body": "API Test Content" }
+        ])
+    );
+    let generation = state.sync_service.cache_generation();
+    assert!(state.search_index.get(generation).await.is_some());
+
+    fs::write(
+        state.config.pages_dir.join("second.md"),
+        "---\nname: Second Page\n---\nSome *emphasis* here.",
+    )
+    .unwrap();
+    state.sync_service.full_sync().await.unwrap();
+    assert!(state.search_index.get(state.sync_service.cache_generation()).await.is_none());
+
+    let index = fetch().await;
+    assert_eq!(index.as_array().unwrap().len(), 2);
+    assert_eq!(index[1]["title"], "Second Page");
+    assert_eq!(index[1]["body"], "Some emphasis here.");
+}
+
+#[tokio::test]
+async fn test_head_page_matches_get_headers() {
+    let (state, _dir) = setup_api_test_state().await;
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .with_state(state);
+
+    let request = |method: &str, uri: &str| {
+        Request::builder().method(method).uri(uri).body(Body::empty()).unwrap()
+    };
+
+    let get = app.clone().oneshot(request("GET", "/pages/api-test")).await.unwrap();
+    assert_eq!(get.status(), StatusCode::OK);
+    let etag = get.headers().get("etag").unwrap().clone();
+    let content_length = get.headers().get("content-length").unwrap().clone();
+    let body = axum::body::to_bytes(get.into_body(), 1024 * 1024).await.unwrap();
+    assert_eq!(content_length.to_str().unwrap(), body.len().to_string());
+
+    let head = app.clone().oneshot(request("HEAD", "/pages/api-test")).await.unwrap();
+    assert_eq!(head.status(), StatusCode::OK);
+    assert_eq!(head.headers().get("etag").unwrap(), &etag);
+    assert_eq!(head.headers().get("content-length").unwrap(), &content_length);
+    assert_eq!(head.headers().get("content-type").unwrap(), "application/json");
+    let head_body = axum::body::to_bytes(head.into_body(), 1024).await.unwrap();
+    assert!(head_body.is_empty());
+
+    let conditional = Request::builder()
+        .method("HEAD")
+        .uri("/pages/api-test")
+        .header("if-none-match", etag.clone())
+        .body(Body::empty())
+        .unwrap();
+    let not_modified = app.clone().oneshot(conditional).await.unwrap();
+    assert_eq!(not_modified.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(not_modified.headers().get("etag").unwrap(), &etag);
+
+    let missing = app.oneshot(request("HEAD", "/pages/missing")).await.unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_last_modified_and_if_modified_since() {
+    let (state, _dir) = setup_api_test_state().await;
+    fs::write(
+        state.config.pages_dir.join("dated.md"),
+        "---\nmodified_datetime: 2024-03-05 08:09:10\n---\n# Dated",
+    )
+    .unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .with_state(state);
+    let get = |since: Option<&'static str>| {
+        let app = app.clone();
+        async move {
+            let mut request = Request::builder().uri("/pages/dated");
+            if let Some(since) = since {
+                request = request.header("if-modified-since", since);
+            }
+            app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+        }
+    };
+
+    let response = get(None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["last-modified"], "Tue, 05 Mar 2024 08:09:10 GMT");
+    let etag = response.headers()["etag"].clone();
+
+    for since in [
+        "Tue, 05 Mar 2024 08:09:10 GMT",
+        "Wed, 06 Mar 2024 00:00:00 GMT",
+        "Tuesday, 05-Mar-24 08:09:10 GMT",
+        "Tue Mar  5 08:09:10 2024",
+    ] {
+        let response = get(Some(since)).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED, "{}", since);
+        assert_eq!(response.headers()["last-modified"], "Tue, 05 Mar 2024 08:09:10 GMT");
+        assert_eq!(response.headers()["etag"], etag);
+    }
+
+    assert_eq!(get(Some("Tue, 05 Mar 2024 08:09:09 GMT")).await.status(), StatusCode::OK);
+    assert_eq!(get(Some("yesterday")).await.status(), StatusCode::OK);
+
+    // If-None-Match takes precedence over If-Modified-Since
+    let stale_etag = Request::builder()
+        .uri("/pages/dated")
+        .header("if-none-match", "\"stale\"")
+        .header("if-modified-since", "Wed, 06 Mar 2024 00:00:00 GMT")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(app.clone().oneshot(stale_etag).await.unwrap().status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_passthrough_serves_data_files_verbatim() {
+    let (state, dir) = setup_api_test_state().await;
+    fs::create_dir_all(state.config.pages_dir.join("data")).unwrap();
+    fs::write(state.config.pages_dir.join("data/items.json"), "{\"items\": [1, 2]}").unwrap();
+    fs::write(state.config.pages_dir.join("notes.txt"), "not passed through").unwrap();
+    fs::write(dir.path().join("secret.json"), "{\"secret\": true}").unwrap();
+
+    let mut config = (*state.config).clone();
+    config.passthrough_extensions = vec!["json".to_string()];
+    let app = Router::new()
+        .route("/assets/{*path}", axum::routing::get(passthrough_handler))
+        .with_state(AppState {
+            config: Arc::new(config),
+            ..state
+        });
+    let get = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            app.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap()
+        }
+    };
+
+    let response = get("/assets/data/items.json").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/json");
+    let etag = response.headers()["etag"].clone();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(&body[..], b"{\"items\": [1, 2]}");
+
+    let revalidate = Request::builder()
+        .uri("/assets/data/items.json")
+        .header("if-none-match", etag)
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(app.clone().oneshot(revalidate).await.unwrap().status(), StatusCode::NOT_MODIFIED);
+
+    // pages and unlisted extensions stay behind the API
+    assert_eq!(get("/assets/api-test.md").await.status(), StatusCode::NOT_FOUND);
+    assert_eq!(get("/assets/notes.txt").await.status(), StatusCode::NOT_FOUND);
+    assert_eq!(get("/assets/data/missing.json").await.status(), StatusCode::NOT_FOUND);
+
+    // nothing outside the page dirs is reachable
+    assert_eq!(get("/assets/../secret.json").await.status(), StatusCode::NOT_FOUND);
+    assert_eq!(get("/assets/data/../../secret.json").await.status(), StatusCode::NOT_FOUND);
+    assert_eq!(get("/assets/%2e%2e/secret.json").await.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_passthrough_refuses_symlinks_out_of_the_content_root() {
+    use std::os::unix::fs::symlink;
+
+    let (state, dir) = setup_api_test_state().await;
+    let outside = dir.path().join("outside");
+    fs::create_dir_all(&outside).unwrap();
+    fs::write(outside.join("secret.json"), "{\"secret\": true}").unwrap();
+    fs::write(state.config.pages_dir.join("items.json"), "{\"items\": []}").unwrap();
+    symlink(outside.join("secret.json"), state.config.pages_dir.join("linked.json")).unwrap();
+    symlink(&outside, state.config.pages_dir.join("linked-dir")).unwrap();
+    symlink(state.config.pages_dir.join("items.json"), state.config.pages_dir.join("alias.json")).unwrap();
+
+    let mut config = (*state.config).clone();
+    config.passthrough_extensions = vec!["json".to_string()];
+    let app = Router::new()
+        .route("/assets/{*path}", axum::routing::get(passthrough_handler))
+        .with_state(AppState {
+            config: Arc::new(config),
+            ..state
+        });
+    let get = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            app.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap()
+                .status()
+        }
+    };
+
+    assert_eq!(get("/assets/linked.json").await, StatusCode::NOT_FOUND);
+    assert_eq!(get("/assets/linked-dir/secret.json").await, StatusCode::NOT_FOUND);
+    // a link that stays inside the content root is still served
+    assert_eq!(get("/assets/alias.json").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_page_history_and_revisions() {
+    let (state, _dir) = setup_api_test_state().await;
+    fs::write(state.config.pages_dir.join("api-test.md"), "# Rewritten").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .with_state(state);
+    let get_json = |uri: String| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+        }
+    };
+
+    let (status, history) = get_json("/pages/api-test/history".to_string()).await;
+    assert_eq!(status, StatusCode::OK);
+    let history = history.as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert!(history.iter().all(|r| r.get("md_content").is_none()));
+
+    let original = history[1]["content_hash"].as_str().unwrap();
+    let (status, revision) = get_json(format!("/pages/api-test/revisions/{}", original)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(revision["md_content"].as_str().unwrap().contains("API Test Content"));
+    assert_eq!(revision["saved_at"], history[1]["saved_at"]);
+
+    let (status, _) = get_json("/pages/api-test/revisions/unknown".to_string()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = get_json("/pages/missing/history".to_string()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_link_health_reports_external_link_status() {
+    let (state, _dir) = setup_api_test_state().await;
+
+    // a stand-in for the outside world; /get-only refuses HEAD like some servers do
+    let remote = Router::new()
+        .route("/ok", axum::routing::get(|| async { "fine" }))
+        .route("/gone", axum::routing::get(|| async { StatusCode::NOT_FOUND }))
+        .route("/get-only", axum::routing::on(axum::routing::MethodFilter::GET, || async { "fine" }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let remote_addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, remote).await.unwrap() });
+
+    fs::write(
+        state.config.pages_dir.join("links.md"),
+        format!(
+            "[ok](http://{0}/ok) [again](http://{0}/ok#top) [gone](http://{0}/gone) \
+             [get](http://{0}/get-only) [local](api-test)",
+            remote_addr
+        ),
+    )
+    .unwrap();
+    fs::write(state.config.pages_dir.join("more-links.md"), format!("[ok](http://{}/ok)", remote_addr)).unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let mut config = (*state.config).clone();
+    config.admin_token = "s3cret".into();
+    let state = AppState {
+        sync_service: state.sync_service.clone(),
+        config: Arc::new(config),
+        search_index: Default::default(),
+        link_health: Default::default(),
+    };
+    LinkChecker::new(&state.config).check_all(&state.sync_service, &state.link_health).await;
+
+    let app = Router::new()
+        .route("/admin/link-health", axum::routing::get(link_health_handler))
+        .with_state(state);
+
+    let unauthorized = app
+        .clone()
+        .oneshot(Request::builder().uri("/admin/link-health").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(unauthorized.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/admin/link-health")
+                .header("authorization", "Bearer s3cret")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let report = json.as_object().unwrap();
+    assert_eq!(report.len(), 3, "only distinct external URLs are checked: {}", json);
+
+    let ok = &json[format!("http://{}/ok", remote_addr)];
+    assert_eq!(ok["status"], 200);
+    assert_eq!(ok["ok"], true);
+    assert_eq!(ok["pages"], serde_json::json!(["links", "more-links"]));
+
+    let gone = &json[format!("http://{}/gone", remote_addr)];
+    assert_eq!(gone["status"], 404);
+    assert_eq!(gone["ok"], false);
+
+    assert_eq!(json[format!("http://{}/get-only", remote_addr)]["status"], 200);
+}
+
+#[tokio::test]
+async fn test_list_pages_preview_adds_size_and_snippet() {
+    let (state, _dir) = setup_api_test_state().await;
+    let app = Router::new().nest("/pages", pages_router()).with_state(state);
+
+    let fetch = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        }
+    };
+
+    let plain = fetch("/pages").await;
+    assert!(plain[0].get("html_length").is_none());
+    assert!(plain[0].get("preview").is_none());
+
+    let previewed = fetch("/pages?preview=true").await;
+    assert_eq!(previewed[0]["preview"], "API Test Content");
+    assert_eq!(previewed[0]["html_length"], "<h1>API Test Content</h1>\n".len());
+}
+
+#[tokio::test]
+async fn test_unpublished_pages_are_hidden_until_due() {
+    let (state, _dir) = setup_api_test_state().await;
+    let pages_dir = state.config.pages_dir.clone();
+
+    let soon = chrono::Utc::now().naive_utc() + chrono::Duration::seconds(2);
+    fs::write(
+        pages_dir.join("scheduled.md"),
+        format!("---\ncreated_datetime: {}Z\n---\n# Scheduled", soon.format("%Y-%m-%dT%H:%M:%S")),
+    )
+    .unwrap();
+    fs::write(pages_dir.join("past.md"), "---\ncreated_datetime: 2020-01-01\n---\n# Past").unwrap();
+    fs::write(pages_dir.join("hidden.md"), "---\npublished: false\n---\n# Hidden").unwrap();
+
+    let mut config = (*state.config).clone();
+    config.hide_unpublished_pages = true;
+    let config = Arc::new(config);
+    let service = SyncService::new(
+        chasqui_db::testutil::create_test_repository().await,
+        Arc::new(LocalContentReader {
+            root_path: pages_dir.clone(),
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+        }),
+        Box::new(MockBuildNotifier::new()),
+        config.clone(),
+    )
+    .await
+    .unwrap();
+    service.full_sync().await.unwrap();
+
+    let state = AppState {
+        sync_service: Arc::new(service),
+        config,
+        ..state
+    };
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .route("/search-index.json", axum::routing::get(search_index_handler))
+        .with_state(state.clone());
+
+    let fetch = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+        }
+    };
+    let ids = |listing: serde_json::Value, key: &str| {
+        let mut ids: Vec<String> = listing
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|page| page[key].as_str().unwrap().to_string())
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    // undated and past-dated pages show; `published: false` never does
+    let (_, listing) = fetch("/pages").await;
+    assert_eq!(ids(listing, "identifier"), vec!["api-test", "past"]);
+    assert_eq!(fetch("/pages/scheduled").await.0, StatusCode::NOT_FOUND);
+    assert_eq!(fetch("/pages/hidden").await.0, StatusCode::NOT_FOUND);
+    let (_, index) = fetch("/search-index.json").await;
+    assert_eq!(ids(index, "id"), vec!["api-test", "past"]);
+
+    // once its date passes the page is served, with no sync in between
+    let generation = state.sync_service.cache_generation();
+    tokio::time::sleep(std::time::Duration::from_millis(2500)).await;
+
+    assert_eq!(fetch("/pages/scheduled").await.0, StatusCode::OK);
+    let (_, listing) = fetch("/pages").await;
+    assert_eq!(ids(listing, "identifier"), vec!["api-test", "past", "scheduled"]);
+    let (_, index) = fetch("/search-index.json").await;
+    assert_eq!(ids(index, "id"), vec!["api-test", "past", "scheduled"]);
+    assert_eq!(state.sync_service.cache_generation(), generation);
+}
+
+#[tokio::test]
+async fn test_list_pages_is_capped_and_paginated() {
+    let (state, _dir) = setup_api_test_state().await;
+    for name in ["a", "b", "c", "d"] {
+        fs::write(
+            state.config.pages_dir.join(format!("{}.md", name)),
+            "# Page",
+        )
+        .unwrap();
+    }
+    state.sync_service.full_sync().await.unwrap();
+
+    let mut config = (*state.config).clone();
+    config.max_list_pages = 2;
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .with_state(AppState {
+            config: Arc::new(config),
+            ..state
+        });
+
+    let fetch = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let total = response
+                .headers()
+                .get("x-total-count")
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .to_string();
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024)
+                .await
+                .unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let ids: Vec<String> = json
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|p| p["identifier"].as_str().unwrap().to_string())
+                .collect();
+            (total, ids)
+        }
+    };
+
+    assert_eq!(
+        fetch("/pages?sort=identifier").await,
+        (
+            "5".to_string(),
+            vec!["a".to_string(), "api-test".to_string()]
+        )
+    );
+    assert_eq!(fetch("/pages?sort=identifier&offset=2&limit=50").await.1, vec!["b", "c"]);
+    assert_eq!(fetch("/pages?sort=identifier&offset=4").await.1, vec!["d"]);
+}
+
+#[tokio::test]
+async fn test_request_body_limit() {
+    let (state, _dir) = setup_api_test_state().await;
+    let mut config = (*state.config).clone();
+    config.max_request_body_bytes = 16;
+
+    let app = Router::new()
+        .route(
+            "/echo",
+            axum::routing::post(|body: String| async move { body }),
+        )
+        .layer(body_limit_layer(&config))
+        .with_state(state);
+
+    let post = |body: &'static str| {
+        Request::builder()
+            .method("POST")
+            .uri("/echo")
+            .body(Body::from(body))
+            .unwrap()
+    };
+
+    let small = app.clone().oneshot(post("tiny")).await.unwrap();
+    assert_eq!(small.status(), StatusCode::OK);
+
+    let large = app
+        .oneshot(post("this body is well over sixteen bytes"))
+        .await
+        .unwrap();
+    assert_eq!(large.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_soft_deleted_pages_answer_gone() {
+    let (state, _dir) = setup_api_test_state().await;
+
+    let mut config = (*state.config).clone();
+    config.soft_delete = true;
+    config.tombstone_retention_days = 0;
+    config.admin_token = "s3cret".into();
+    let config = Arc::new(config);
+
+    let doomed = config.pages_dir.join("doomed.md");
+    fs::write(&doomed, "# Doomed").unwrap();
+
+    let reader = Arc::new(LocalContentReader {
+        root_path: config.pages_dir.clone(),
+        max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+    });
+    let service = SyncService::new(
+        chasqui_db::testutil::create_test_repository().await,
+        reader,
+        Box::new(MockBuildNotifier::new()),
+        config.clone(),
+    )
+    .await
+    .unwrap();
+
+    fs::remove_file(&doomed).unwrap();
+    service.process_batch(Vec::new(), vec![doomed]).await.unwrap();
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .route("/p/{*identifier}", axum::routing::get(html_page_handler))
+        .route("/admin/purge-tombstones", axum::routing::post(purge_tombstones_handler))
+        .with_state(AppState {
+            sync_service: Arc::new(service),
+            config,
+            search_index: Default::default(),
+            link_health: Default::default(),
+        });
+
+    let status = |method: &'static str, uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("authorization", "Bearer s3cret")
+                .body(Body::empty())
+                .unwrap();
+            app.oneshot(request).await.unwrap().status()
+        }
+    };
+
+    assert_eq!(status("GET", "/pages/doomed").await, StatusCode::GONE);
+    assert_eq!(status("GET", "/p/doomed").await, StatusCode::GONE);
+    assert_eq!(status("GET", "/pages/never-existed").await, StatusCode::NOT_FOUND);
+    assert_eq!(status("GET", "/pages/api-test").await, StatusCode::OK);
+
+    // zero retention days makes every tombstone eligible
+    assert_eq!(status("POST", "/admin/purge-tombstones").await, StatusCode::OK);
+    assert_eq!(status("GET", "/pages/doomed").await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_page_meta_endpoint() {
+    let (state, _dir) = setup_api_test_state().await;
+
+    fs::write(
+        state.config.pages_dir.join("shared.md"),
+        "---\nname: Shared Post\ndescription: Worth sharing\ntags: [social]\ncover_image: /images/cover.png\ncreated_datetime: 2024-03-01T09:30:00Z\n---\n# Shared",
+    )
+    .unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .with_state(state);
+
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/pages/shared/meta").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["title"], "Shared Post");
+    assert_eq!(json["description"], "Worth sharing");
+    assert_eq!(json["og_image"], "http://localhost:3000/images/cover.png");
+    assert_eq!(json["url"], "http://localhost:3000/shared");
+    assert_eq!(json["published_time"], "2024-03-01T09:30:00Z");
+    assert_eq!(json["tags"], serde_json::json!(["social"]));
+
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/pages/api-test/meta").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["title"], "api-test");
+    assert!(json["og_image"].is_null());
+
+    let missing = app
+        .oneshot(Request::builder().uri("/pages/missing/meta").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_render_preview_endpoint() {
+    let (state, _dir) = setup_api_test_state().await;
+
+    let mut config = (*state.config).clone();
+    config.admin_token = "s3cret".into();
+    let app = Router::new()
+        .route("/render", axum::routing::post(render_handler))
+        .with_state(AppState {
+            config: Arc::new(config),
+            ..state
+        });
+
+    let render = |auth: &'static str, body: serde_json::Value| {
+        let app = app.clone();
+        async move {
+            let request = Request::builder()
+                .method("POST")
+                .uri("/render")
+                .header("content-type", "application/json")
+                .header("authorization", auth)
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            app.oneshot(request).await.unwrap()
+        }
+    };
+
+    let draft = serde_json::json!({
+        "filename": "drafts/new.md",
+        "markdown": "---\nname: Draft\n---\n# Draft\n\nSee [the test](../api-test.md).",
+    });
+
+    assert_eq!(render("Bearer wrong", draft.clone()).await.status(), StatusCode::UNAUTHORIZED);
+
+    let response = render("Bearer s3cret", draft).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("<h1>Draft</h1>"), "{}", html);
+    assert!(html.contains("<a href=\"/api-test\">the test</a>"), "{}", html);
+    assert!(!html.contains("name: Draft"), "{}", html);
+}
+
+#[tokio::test]
+async fn test_dry_run_reports_without_applying() {
+    let (state, dir) = setup_api_test_state().await;
+    let content_dir = dir.path().join("content");
+    fs::write(content_dir.join("gone.md"), "# Gone").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    fs::write(content_dir.join("api-test.md"), "# API Test Content, revised").unwrap();
+    fs::write(content_dir.join("fresh.md"), "# Fresh\n\nSee [x](missing-page).").unwrap();
+    fs::remove_file(content_dir.join("gone.md")).unwrap();
+
+    let mut config = (*state.config).clone();
+    config.admin_token = "s3cret".into();
+    let sync_service = state.sync_service.clone();
+    let app = Router::new()
+        .route("/admin/dry-run", axum::routing::get(dry_run_handler))
+        .with_state(AppState {
+            config: Arc::new(config),
+            ..state
+        });
+
+    let dry_run = |auth: &'static str| {
+        let app = app.clone();
+        async move {
+            let request = Request::builder()
+                .uri("/admin/dry-run")
+                .header("authorization", auth)
+                .body(Body::empty())
+                .unwrap();
+            app.oneshot(request).await.unwrap()
+        }
+    };
+
+    assert_eq!(dry_run("Bearer wrong").await.status(), StatusCode::UNAUTHORIZED);
+
+    let response = dry_run("Bearer s3cret").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(report["inserts"], serde_json::json!(["fresh.md"]));
+    assert_eq!(report["updates"], serde_json::json!(["api-test.md"]));
+    assert_eq!(report["deletions"], serde_json::json!(["gone.md"]));
+    assert_eq!(
+        report["broken_links"],
+        serde_json::json!([{ "filename": "fresh.md", "link": "missing-page" }])
+    );
+
+    // nothing was applied
+    assert!(sync_service.get_page_by_filename("fresh.md").await.is_none());
+    assert!(sync_service.get_page_by_filename("gone.md").await.is_some());
+}
+
+#[tokio::test]
+async fn test_page_route_negotiates_html_or_json() {
+    let (state, _dir) = setup_api_test_state().await;
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .with_state(state);
+
+    let get = |uri: &'static str, accept: Option<&'static str>| {
+        let app = app.clone();
+        async move {
+            let mut request = Request::builder().uri(uri);
+            if let Some(accept) = accept {
+                request = request.header("accept", accept);
+            }
+            app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+        }
+    };
+
+    for accept in [None, Some("application/json"), Some("*/*"), Some("text/html;q=0.5, application/json")] {
+        let response = get("/pages/api-test", accept).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("content-type").unwrap(), "application/json", "{:?}", accept);
+        assert_eq!(response.headers().get("vary").unwrap(), "accept");
+    }
+
+    let browser = "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8";
+    let response = get("/pages/api-test", Some(browser)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("content-type").unwrap(), "text/html; charset=utf-8");
+    let json_etag = get("/pages/api-test", None).await.headers().get("etag").unwrap().clone();
+    assert_ne!(response.headers().get("etag").unwrap(), &json_etag);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("<h1>API Test Content</h1>"), "{}", html);
+
+    let missing = get("/pages/nope", Some(browser)).await;
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    let body = axum::body::to_bytes(missing.into_body(), 1024 * 1024).await.unwrap();
+    assert!(String::from_utf8(body.to_vec()).unwrap().contains("<html>"));
+
+    // sub-resources stay JSON whatever the client prefers
+    let meta = get("/pages/api-test/meta", Some(browser)).await;
+    assert_eq!(meta.status(), StatusCode::OK);
+    assert_eq!(meta.headers().get("content-type").unwrap(), "application/json");
+}
+
+#[tokio::test]
+async fn test_large_responses_are_compressed() {
+    let (state, dir) = setup_api_test_state().await;
+    let paragraph = "Lorem ipsum dolor sit amet, consectetur adipiscing elit.\n\n";
+    fs::write(dir.path().join("content/long.md"), paragraph.repeat(200)).unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .layer(compression_layer())
+        .with_state(state);
+
+    let get = |uri: &'static str, encoding: &'static str| {
+        let app = app.clone();
+        async move {
+            let request = Request::builder()
+                .uri(uri)
+                .header("accept-encoding", encoding)
+                .body(Body::empty())
+                .unwrap();
+            app.oneshot(request).await.unwrap()
+        }
+    };
+
+    let response = get("/pages/long", "br, gzip").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("content-encoding").unwrap(), "br");
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    assert!(body.len() < paragraph.len() * 200);
+
+    let response = get("/pages/long", "gzip").await;
+    assert_eq!(response.headers().get("content-encoding").unwrap(), "gzip");
+
+    let response = get("/pages/long", "identity").await;
+    assert!(response.headers().get("content-encoding").is_none());
+
+    // small bodies aren't worth compressing
+    let response = get("/pages/api-test", "gzip").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("content-encoding").is_none());
+    let response = get("/pages/nope", "gzip").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(response.headers().get("content-encoding").is_none());
+}
+
+#[tokio::test]
+async fn test_page_aliases_and_redirects_answer_301() {
+    let (state, _dir) = setup_api_test_state().await;
+    let pages_dir = state.config.pages_dir.clone();
+
+    fs::create_dir_all(pages_dir.join("blog")).unwrap();
+    fs::write(pages_dir.join("blog/new.md"), "---\naliases: [blog/old, legacy]\n---\n# New").unwrap();
+    fs::write(pages_dir.join("stub.md"), "---\nredirect_to: api-test\n---\n").unwrap();
+    fs::write(pages_dir.join("away.md"), "---\nredirect_to: https://example.com/away\n---\n").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .nest("/api/pages", pages_router())
+        .with_state(state);
+
+    let location = |method: &'static str, uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let request = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+            let response = app.oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY, "{}", uri);
+            response.headers().get("location").unwrap().to_str().unwrap().to_string()
+        }
+    };
+
+    assert_eq!(location("GET", "/api/pages/blog/old").await, "/api/pages/blog/new");
+    assert_eq!(location("GET", "/api/pages/legacy").await, "/api/pages/blog/new");
+    assert_eq!(location("GET", "/api/pages/stub").await, "/api/pages/api-test");
+    assert_eq!(location("HEAD", "/api/pages/stub").await, "/api/pages/api-test");
+    assert_eq!(location("GET", "/api/pages/away").await, "https://example.com/away");
+
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/api/pages/blog/new").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["aliases"], serde_json::json!(["blog/old", "legacy"]));
+}
+
+#[tokio::test]
+async fn test_hierarchical_tag_filter_and_tree() {
+    let (state, _dir) = setup_api_test_state().await;
+    let pages_dir = state.config.pages_dir.clone();
+
+    fs::write(pages_dir.join("rust.md"), "---\ntags: [lang/rust, lang/rust/async]\n---\n# Rust").unwrap();
+    fs::write(pages_dir.join("python.md"), "---\ntags: [lang/python]\n---\n# Python").unwrap();
+    fs::write(pages_dir.join("langs.md"), "---\ntags: [lang]\n---\n# Languages").unwrap();
+    fs::write(pages_dir.join("language.md"), "---\ntags: [language]\n---\n# Language").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .route("/tags", axum::routing::get(tags_handler))
+        .with_state(state);
+
+    let fetch = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        }
+    };
+    let ids = |json: serde_json::Value| {
+        json.as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["identifier"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(ids(fetch("/pages?tag=lang&sort=identifier").await), vec!["langs", "python", "rust"]);
+    assert_eq!(ids(fetch("/pages?tag=Lang/Rust").await), vec!["rust"]);
+    assert_eq!(ids(fetch("/pages?tag=lang&exact=true&sort=identifier").await), vec!["langs"]);
+
+    let flat = fetch("/tags").await;
+    assert_eq!(flat["lang/rust"], 1);
+    assert_eq!(flat["lang"], 1);
+
+    let tree = fetch("/tags?tree=true").await;
+    let lang = tree
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|n| n["name"] == "lang")
+        .unwrap();
+    // rust.md carries two tags under lang but is counted once
+    assert_eq!(lang["count"], 3);
+    assert_eq!(lang["children"][0]["path"], "lang/python");
+    assert_eq!(lang["children"][1]["path"], "lang/rust");
+    assert_eq!(lang["children"][1]["count"], 1);
+    assert_eq!(lang["children"][1]["children"][0]["path"], "lang/rust/async");
+}
+
+#[tokio::test]
+async fn test_tag_listings_and_feeds_share_one_case_rule() {
+    let (state, _dir) = setup_api_test_state().await;
+    let pages_dir = state.config.pages_dir.clone();
+
+    fs::write(pages_dir.join("upper.md"), "---\ntags: [Rust]\n---\n# Upper").unwrap();
+    fs::write(pages_dir.join("lower.md"), "---\ntags: [rust]\n---\n# Lower").unwrap();
+    fs::write(pages_dir.join("child.md"), "---\ntags: [Rust/Async]\n---\n# Child").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .route("/tags", axum::routing::get(tags_handler))
+        .route("/feed/{file}", axum::routing::get(tag_feed_handler))
+        .with_state(state);
+
+    let fetch = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            String::from_utf8(body.to_vec()).unwrap()
+        }
+    };
+    let json = |body: String| serde_json::from_str::<serde_json::Value>(&body).unwrap();
+
+    assert_eq!(json(fetch("/pages?tag=RUST").await).as_array().unwrap().len(), 3);
+    // the feed matches the filter, child tags included
+    assert_eq!(fetch("/feed/rust.xml").await.matches("<item>").count(), 3);
+    assert_eq!(fetch("/feed/Rust%2FASYNC.xml").await.matches("<item>").count(), 1);
+
+    let flat = json(fetch("/tags").await);
+    assert_eq!(flat["rust"], 2);
+    assert_eq!(flat["rust/async"], 1);
+    assert!(flat.get("Rust").is_none());
+
+    let tree = json(fetch("/tags?tree=true").await);
+    let rust = tree.as_array().unwrap().iter().find(|n| n["name"] == "rust").unwrap();
+    assert_eq!(rust["count"], 3);
+    assert_eq!(rust["children"][0]["path"], "rust/async");
+}
+
+#[tokio::test]
+async fn test_backlinks_follow_edits_and_deletions() {
+    let (state, _dir) = setup_api_test_state().await;
+    let pages_dir = state.config.pages_dir.clone();
+
+    fs::write(pages_dir.join("target.md"), "# Target").unwrap();
+    fs::write(pages_dir.join("first.md"), "[to target](target.md#top)").unwrap();
+    fs::write(pages_dir.join("second.md"), "[to target](./target.md) [self](second.md)").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .with_state(state.clone());
+
+    let backlinks = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            json.as_array()
+                .unwrap()
+                .iter()
+                .map(|p| p["identifier"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(backlinks("/pages/target/backlinks").await, vec!["first", "second"]);
+    // a page linking to itself is not its own backlink
+    assert!(backlinks("/pages/second/backlinks").await.is_empty());
+
+    // only the edited page's outgoing edges change
+    fs::write(pages_dir.join("second.md"), "no links any more").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+    assert_eq!(backlinks("/pages/target/backlinks").await, vec!["first"]);
+
+    fs::remove_file(pages_dir.join("first.md")).unwrap();
+    state
+        .sync_service
+        .process_batch(Vec::new(), vec![pages_dir.join("first.md")])
+        .await
+        .unwrap();
+    assert!(backlinks("/pages/target/backlinks").await.is_empty());
+}
+
+#[tokio::test]
+async fn test_normalize_slashes_in_page_lookup() {
+    let (state, _dir) = setup_api_test_state().await;
+    fs::write(state.config.pages_dir.join("index.md"), "# Home").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let status = |state: AppState, uri: &'static str| async move {
+        let app = Router::new().nest("/pages", pages_router()).with_state(state);
+        let response = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let identifier = serde_json::from_slice::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|json| json["identifier"].as_str().map(str::to_string));
+        (status, identifier)
+    };
+
+    assert_eq!(status(state.clone(), "/pages/api-test/").await.0, StatusCode::NOT_FOUND);
+
+    let mut config = (*state.config).clone();
+    config.normalize_slashes = true;
+    let state = AppState {
+        config: Arc::new(config),
+        ..state
+    };
+
+    for uri in ["/pages/api-test", "/pages/api-test/", "/pages//api-test", "/pages/api-test//"] {
+        assert_eq!(
+            status(state.clone(), uri).await,
+            (StatusCode::OK, Some("api-test".to_string())),
+            "{}",
+            uri
+        );
+    }
+    // nothing but slashes is still the home page
+    assert_eq!(
+        status(state.clone(), "/pages//").await,
+        (StatusCode::OK, Some("index".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn test_json_feed() {
+    let (state, _dir) = setup_api_test_state().await;
+
+    fs::write(
+        state.config.pages_dir.join("rusty.md"),
+        "---\nname: Rust & Friends\ntags: [Rust]\ncreated_datetime: 2099-03-01\n---\n# Rusty <b>",
+    )
+    .unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .route("/feed.json", axum::routing::get(json_feed_handler))
+        .with_state(state);
+
+    let response = app
+        .oneshot(Request::builder().uri("/feed.json").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/feed+json");
+
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let feed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(feed["version"], "https://jsonfeed.org/version/1.1");
+    assert_eq!(feed["home_page_url"], "http://localhost:3000/");
+    assert_eq!(feed["feed_url"], "http://localhost:3000/feed.json");
+
+    // ordered like the RSS feed, so the future-dated page comes first
+    let items = feed["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    let rusty = &items[0];
+    assert_eq!(rusty["id"], "http://localhost:3000/rusty");
+    assert_eq!(rusty["url"], "http://localhost:3000/rusty");
+    assert_eq!(rusty["title"], "Rust & Friends");
+    assert!(rusty["content_html"].as_str().unwrap().starts_with("<h1>Rusty"));
+    assert_eq!(rusty["date_published"], "2099-03-01T00:00:00+00:00");
+    assert_eq!(rusty["tags"], serde_json::json!(["Rust"]));
+    assert_eq!(items[1]["id"], "http://localhost:3000/api-test");
+}
+
+#[tokio::test]
+async fn test_openapi_document_and_swagger_ui() {
+    use chasqui_server::features::openapi::ApiDoc;
+    use utoipa::OpenApi;
+    use utoipa_swagger_ui::SwaggerUi;
+
+    let (state, _dir) = setup_api_test_state().await;
+    let app = Router::new()
+        .merge(SwaggerUi::new("/docs").url("/openapi.json", ApiDoc::openapi()))
+        .with_state(state);
+
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/openapi.json").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let spec: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    for path in [
+        "/api/pages",
+        "/api/pages/{identifier}",
+        "/api/by-filename/{path}",
+        "/api/tags",
+        "/api/stats",
+        "/search-index.json",
+        "/feed.xml",
+        "/feed.json",
+        "/feed/{file}",
+        "/sitemap.xml",
+        "/p/{identifier}",
+        "/api/metadata/{identifier}",
+        "/api/status",
+        "/ws",
+    ] {
+        assert!(spec["paths"][path]["get"].is_object(), "missing {}", path);
+    }
+
+    let page = &spec["components"]["schemas"]["JsonPage"]["properties"];
+    assert!(page["identifier"].is_object());
+    assert!(page["has_frontmatter_error"].is_object());
+    assert!(spec["components"]["schemas"]["TagNode"].is_object());
+
+    let list_params: Vec<&str> = spec["paths"]["/api/pages"]["get"]["parameters"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["name"].as_str().unwrap())
+        .collect();
+    assert!(list_params.contains(&"tag"));
+    assert!(list_params.contains(&"since"));
+
+    let response = app
+        .oneshot(Request::builder().uri("/docs/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_list_pages_sort_and_order() {
+    let (service, reader, _notifier, config, _repo) = common::setup_service().await;
+    reader.add_file("/content/old.md", "---\nname: Zebra\ncreated_datetime: 2020-01-01\nmodified_datetime: 2024-06-01\n---\n# Old");
+    reader.add_file("/content/new.md", "---\nname: apple\ncreated_datetime: 2023-01-01\nmodified_datetime: 2023-06-01\n---\n# New");
+    reader.add_file("/content/undated.md", "---\nname: Mango\n---\n# Undated");
+    service.full_sync().await.unwrap();
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .with_state(AppState {
+            sync_service: service,
+            config,
+            search_index: Default::default(),
+            link_health: Default::default(),
+        });
+
+    let fetch = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            if response.status() != StatusCode::OK {
+                return Err(response.status());
+            }
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            Ok(json
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|p| p["identifier"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>())
+        }
+    };
+
+    // newest first by default, undated pages last either way
+    assert_eq!(fetch("/pages").await.unwrap(), vec!["new", "old", "undated"]);
+    assert_eq!(fetch("/pages?order=asc").await.unwrap(), vec!["old", "new", "undated"]);
+    assert_eq!(fetch("/pages?sort=modified").await.unwrap(), vec!["old", "new", "undated"]);
+
+    // names compare without case; text fields default to ascending
+    assert_eq!(fetch("/pages?sort=name").await.unwrap(), vec!["new", "undated", "old"]);
+    assert_eq!(fetch("/pages?sort=identifier&order=desc").await.unwrap(), vec!["undated", "old", "new"]);
+
+    assert_eq!(fetch("/pages?sort=size").await, Err(StatusCode::BAD_REQUEST));
+    assert_eq!(fetch("/pages?order=up").await, Err(StatusCode::BAD_REQUEST));
+}
+
+#[tokio::test]
+async fn test_admin_recompile_rebuilds_unchanged_pages() {
+    let (service, reader, _notifier, config, repo) = common::setup_service().await;
+    reader.add_file("/content/target.md", "# Target");
+    reader.add_file("/content/post.md", "[target](target.md)");
+    service.full_sync().await.unwrap();
+
+    // post.md itself is untouched, so the sync keeps its old link
+    reader.add_file("/content/target.md", "---\nidentifier: renamed\n---\n# Target");
+    service.full_sync().await.unwrap();
+    let stale = service.get_page_by_filename("post.md").await.unwrap();
+    assert!(stale.md_content.contains("(/target)"), "{}", stale.md_content);
+
+    let mut config = (*config).clone();
+    config.admin_token = "s3cret".into();
+    let app = Router::new()
+        .route("/admin/recompile", axum::routing::post(recompile_handler))
+        .with_state(AppState {
+            sync_service: service.clone(),
+            config: Arc::new(config),
+            search_index: Default::default(),
+            link_health: Default::default(),
+        });
+    let recompile = |auth: &'static str| {
+        let app = app.clone();
+        async move {
+            let request = Request::builder()
+                .method("POST")
+                .uri("/admin/recompile")
+                .header("authorization", auth)
+                .body(Body::empty())
+                .unwrap();
+            app.oneshot(request).await.unwrap()
+        }
+    };
+
+    assert_eq!(recompile("Bearer wrong").await.status(), StatusCode::UNAUTHORIZED);
+
+    let response = recompile("Bearer s3cret").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["recompiled"], 2);
+
+    let fresh = service.get_page_by_filename("post.md").await.unwrap();
+    assert!(fresh.md_content.contains("(/renamed)"), "{}", fresh.md_content);
+    let stored = repo.get_page_by_filename("post.md").await.unwrap().unwrap();
+    assert_eq!(stored.md_content, fresh.md_content);
+}
+
+#[tokio::test]
+async fn test_batch_fetch_pages() {
+    let (state, _dir) = setup_api_test_state().await;
+    fs::write(state.config.pages_dir.join("featured.md"), "---\nname: Featured\n---\n# Featured").unwrap();
+    fs::write(state.config.pages_dir.join("batch.md"), "# A page called batch").unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let mut config = (*state.config).clone();
+    config.max_batch_pages = 3;
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .with_state(AppState {
+            config: Arc::new(config),
+            ..state
+        });
+
+    let post = |uri: &'static str, body: serde_json::Value| {
+        let app = app.clone();
+        async move {
+            let request = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        }
+    };
+
+    let (status, body) = post(
+        "/pages/batch",
+        serde_json::json!({ "identifiers": ["featured", "missing", "api-test"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+    assert_eq!(keys, vec!["api-test", "featured"]);
+    assert_eq!(json["featured"]["name"], "Featured");
+
+    let (status, body) = post(
+        "/pages/batch",
+        serde_json::json!({ "identifiers": ["a", "b", "c", "d"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("at most 3"), "{}", body);
+
+    let (status, _) = post("/pages/featured", serde_json::json!({ "identifiers": [] })).await;
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+
+    // the batch route doesn't shadow a page of the same name
+    let response = app
+        .oneshot(Request::builder().uri("/pages/batch").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_get_page_section_by_heading_anchor() {
+    let (state, _dir) = setup_api_test_state().await;
+    fs::write(
+        state.config.pages_dir.join("guide.md"),
+        "# Guide\n\nIntro.\n\n## Installation\n\nRun it.\n\n### From source\n\nBuild it.\n\n## Usage\n\nUse it.\n",
+    )
+    .unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let app = Router::new().nest("/pages", pages_router()).with_state(state);
+    let get = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let content_type = response.headers().get("content-type").cloned();
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            (status, content_type, String::from_utf8(body.to_vec()).unwrap())
+        }
+    };
+
+    let (status, content_type, body) = get("/pages/guide?section=installation").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.unwrap(), "text/html; charset=utf-8");
+    assert!(body.starts_with("<h2>Installation</h2>"), "{}", body);
+    assert!(body.contains("<h3>From source</h3>"));
+    assert!(!body.contains("Usage"));
+    assert!(!body.contains("Intro"));
+
+    let (status, _, body) = get("/pages/guide?section=usage").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("Use it."));
+
+    let (status, _, _) = get("/pages/guide?section=uninstall").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_websocket_pushes_batch_changes() {
+    use futures_util::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let (state, _dir) = setup_api_test_state().await;
+    let service = state.sync_service.clone();
+    let pages_dir = state.config.pages_dir.clone();
+
+    let app = Router::new()
+        .route("/ws", axum::routing::get(chasqui_server::features::live::ws_handler))
+        .with_state(state);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}/ws", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let (mut client, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    let (leaver, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    // a client going away must not stop delivery to the others
+    drop(leaver);
+
+    let fresh = pages_dir.join("fresh.md");
+    fs::write(&fresh, "# Fresh").unwrap();
+    service
+        .process_batch(vec![(fresh, pages_dir.clone(), chasqui_core::features::model::FeatureType::Page)], Vec::new())
+        .await
+        .unwrap();
+    service
+        .process_batch(Vec::new(), vec![pages_dir.join("api-test.md")])
+        .await
+        .unwrap();
+
+    let mut next_event = async || {
+        let message = tokio::time::timeout(std::time::Duration::from_secs(5), client.next())
+            .await
+            .expect("no change event within 5s")
+            .unwrap()
+            .unwrap();
+        let Message::Text(text) = message else {
+            panic!("expected a text message, got {:?}", message);
+        };
+        serde_json::from_str::<serde_json::Value>(&text).unwrap()
+    };
+
+    let first = next_event().await;
+    assert_eq!(first["changed"], serde_json::json!(["fresh"]));
+    assert_eq!(first["deleted"], serde_json::json!([]));
+
+    let second = next_event().await;
+    assert_eq!(second["changed"], serde_json::json!([]));
+    assert_eq!(second["deleted"], serde_json::json!(["api-test"]));
+}
+
+#[tokio::test]
+async fn test_json_page_datetime_format() {
+    let (state, _dir) = setup_api_test_state().await;
+    fs::write(
+        state.config.pages_dir.join("dated.md"),
+        "---\ncreated_datetime: 2023-01-01T12:00:00Z\n---\n# Dated",
+    )
+    .unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let get = |state: AppState, uri: &'static str| async move {
+        let app = Router::new().nest("/pages", pages_router()).with_state(state);
+        let response = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+    };
+
+    let (_, json) = get(state.clone(), "/pages/dated").await;
+    assert_eq!(json["created_datetime"], "2023-01-01 12:00:00");
+
+    let (_, json) = get(state.clone(), "/pages/dated?datetime_format=rfc3339").await;
+    assert_eq!(json["created_datetime"], "2023-01-01T12:00:00Z");
+
+    let (_, json) = get(state.clone(), "/pages?datetime_format=rfc3339&sort=identifier").await;
+    let dated = json.as_array().unwrap().iter().find(|p| p["identifier"] == "dated").unwrap();
+    assert_eq!(dated["created_datetime"], "2023-01-01T12:00:00Z");
+
+    let (status, _) = get(state.clone(), "/pages/dated?datetime_format=epoch").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // the configured default applies when the client doesn't ask
+    let mut config = (*state.config).clone();
+    config.json_datetime_format = chasqui_core::features::pages::model::DatetimeFormat::Rfc3339;
+    let state = AppState {
+        config: Arc::new(config),
+        ..state
+    };
+    let (_, json) = get(state.clone(), "/pages/dated").await;
+    assert_eq!(json["created_datetime"], "2023-01-01T12:00:00Z");
+    let (_, json) = get(state, "/pages/dated?datetime_format=legacy").await;
+    assert_eq!(json["created_datetime"], "2023-01-01 12:00:00");
+}
+
+#[tokio::test]
+async fn test_missing_page_serves_content_not_found_page() {
+    let (state, _dir) = setup_api_test_state().await;
+
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .route("/p/{*identifier}", axum::routing::get(html_page_handler))
+        .with_state(state.clone());
+    let get = |uri: &'static str, accept: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).header("accept", accept).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        }
+    };
+
+    // without a 404 page the miss stays bare
+    let (status, body) = get("/pages/nowhere", "application/json").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body.is_empty());
+
+    fs::write(
+        state.config.pages_dir.join("404.md"),
+        "---\nname: Lost\n---\n# Lost at sea",
+    )
+    .unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let (status, body) = get("/pages/nowhere", "application/json").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["identifier"], "404");
+
+    let (status, body) = get("/pages/nowhere", "text/html").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body.contains("<h1>Lost at sea</h1>"), "{}", body);
+
+    let (status, body) = get("/p/nowhere", "text/html").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body.contains("<title>Lost</title>"), "{}", body);
+}
+
+#[tokio::test]
+async fn test_cache_control_headers() {
+    let (state, _dir) = setup_api_test_state().await;
+    fs::write(
+        state.config.pages_dir.join("ancient.md"),
+        "---\nmodified_datetime: 2001-01-01T00:00:00Z\n---\n# Ancient",
+    )
+    .unwrap();
+    state.sync_service.full_sync().await.unwrap();
+
+    let mut config = (*state.config).clone();
+    config.page_cache_max_age = 120;
+    config.stable_page_after_days = 365;
+    config.stable_page_max_age = 604800;
+    config.list_cache_max_age = 30;
+    config.admin_token = "s3cret".into();
+    let state = AppState {
+        config: Arc::new(config),
+        ..state
+    };
+
+    let admin = Router::new()
+        .route("/admin/manifest", axum::routing::get(manifest_handler))
+        .layer(axum::middleware::from_fn(no_store));
+    let app = Router::new()
+        .nest("/pages", pages_router())
+        .route("/feed.xml", axum::routing::get(feed_handler))
+        .merge(admin)
+        .with_state(state);
+
+    let cache_control = |uri: &'static str, etag: Option<String>| {
+        let app = app.clone();
+        async move {
+            let mut request = Request::builder().uri(uri).header("authorization", "Bearer s3cret");
+            if let Some(etag) = etag {
+                request = request.header("if-none-match", etag);
+            }
+            let response = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+            (
+                response.status(),
+                response.headers().get("cache-control").map(|v| v.to_str().unwrap().to_string()),
+                response.headers().get("etag").map(|v| v.to_str().unwrap().to_string()),
+            )
+        }
+    };
+
+    let (status, header, etag) = cache_control("/pages/api-test", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(header.as_deref(), Some("public, max-age=120"));
+
+    // revalidating keeps the lifetime
+    let (status, header, _) = cache_control("/pages/api-test", etag).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+    assert_eq!(header.as_deref(), Some("public, max-age=120"));
+
+    let (_, header, _) = cache_control("/pages/ancient", None).await;
+    assert_eq!(header.as_deref(), Some("public, max-age=604800"));
+
+    let (_, header, _) = cache_control("/pages", None).await;
+    assert_eq!(header.as_deref(), Some("public, max-age=30"));
+    let (_, header, _) = cache_control("/feed.xml", None).await;
+    assert_eq!(header.as_deref(), Some("public, max-age=30"));
+
+    let (status, header, _) = cache_control("/admin/manifest", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(header.as_deref(), Some("no-store"));
+}
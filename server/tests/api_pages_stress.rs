@@ -24,14 +24,66 @@ async fn setup_stress_state(page_count: usize) -> AppState {
         images_dir: PathBuf::from("/content"),
         audio_dir: PathBuf::from("/content"),
         videos_dir: PathBuf::from("/content"),
+        extra_content_dirs: Vec::new(),
         page_strip_extension: false,
         asset_strip_extension: false,
         serve_home: true,
         home_identifier: "index".into(),
+        folder_index_pages: false,
+        resolve_html_links: false,
         webhook_url: "".into(),
         webhook_secret: "".into(),
+        webhook_payload: None,
         port: 3000,
         nginx_media_prefixes: false,
+        site_url: "http://localhost:3000".into(),
+        watch_debounce_ms: 1500,
+        cors_allowed_origins: Vec::new(),
+        slugify_identifiers: false,
+        admin_token: "".into(),
+        required_frontmatter: Vec::new(),
+        html_template_path: None,
+        created_datetime_aliases: Vec::new(),
+        modified_datetime_aliases: Vec::new(),
+        max_request_body_bytes: 1024 * 1024,
+        max_list_pages: 1000,
+        soft_delete: false,
+        tombstone_retention_days: 30,
+        git_repo: None,
+        git_ref: "HEAD".into(),
+        git_poll_secs: 30,
+        sqlite_journal_mode: "WAL".to_string(),
+        sqlite_synchronous: "NORMAL".to_string(),
+        sqlite_busy_timeout_ms: 5000,
+        sqlite_cache_size: -64000,
+        sqlite_foreign_keys: true,
+        base_path: String::new(),
+        compiled_cache_entries: 0,
+        normalize_slashes: false,
+        strict_links: false,
+        max_batch_pages: 50,
+        content_extensions: vec!["md".to_string()],
+        json_datetime_format: Default::default(),
+        not_found_identifier: "404".into(),
+        dedupe_page_bodies: false,
+        page_cache_max_age: 300,
+        stable_page_after_days: 0,
+        stable_page_max_age: 86400,
+        list_cache_max_age: 60,
+        max_file_bytes: 10_485_760,
+        max_write_connections: 1,
+        passthrough_extensions: Vec::new(),
+        create_content_dirs: false,
+        content_dir_retries: 0,
+        collision_policy: Default::default(),
+        link_check_interval_secs: 0,
+        link_check_concurrency: 4,
+        link_check_timeout_secs: 10,
+        webhook_coalesce: true,
+        html_layouts_dir: None,
+        track_accessed_datetime: false,
+        identifier_strategy: Default::default(),
+        hide_unpublished_pages: false,
     });
 
     for i in 0..page_count {
@@ -54,6 +106,8 @@ async fn setup_stress_state(page_count: usize) -> AppState {
     AppState {
         sync_service: Arc::new(service),
         config,
+        search_index: Default::default(),
+        link_health: Default::default(),
     }
 }
 
@@ -64,7 +118,12 @@ async fn test_api_hammer_random_access() {
     let request_count = 10000;
 
     let state = setup_stress_state(page_count).await;
-    let app = Arc::new(pages_router().with_state(state));
+    // with the access log on, as in production
+    let app = Arc::new(
+        pages_router()
+            .layer(axum::middleware::from_fn(chasqui_server::telemetry::access_log))
+            .with_state(state),
+    );
 
     let mut set = JoinSet::new();
     let start = Instant::now();
@@ -76,6 +135,7 @@ async fn test_api_hammer_random_access() {
             let uri = format!("/post-{}", random_id as usize % page_count);
 
             let local_app = app_clone.as_ref().clone();
+            let sent = Instant::now();
             let response = local_app
                 .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
                 .await
@@ -85,12 +145,16 @@ async fn test_api_hammer_random_access() {
             if status != 200 {
                 panic!("Hammer failed with status {}. URI: {}", status, uri);
             }
+            sent.elapsed()
         });
     }
 
+    let mut latencies = Vec::with_capacity(request_count);
     while let Some(res) = set.join_next().await {
-        res.expect("Worker task panicked during hammer test");
+        latencies.push(res.expect("Worker task panicked during hammer test"));
     }
+    latencies.sort();
+    let percentile = |p: usize| latencies[(latencies.len() * p / 100).min(latencies.len() - 1)];
 
     let duration = start.elapsed();
     println!("\nNUCLEAR RANDOM ACCESS TEST RESULT:");
@@ -100,6 +164,7 @@ async fn test_api_hammer_random_access() {
         "Requests per second: {:.2}",
         request_count as f64 / duration.as_secs_f64()
     );
+    println!("Latency p50: {:?}, p99: {:?}", percentile(50), percentile(99));
 }
 
 #[tokio::test]
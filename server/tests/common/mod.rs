@@ -13,14 +13,66 @@ pub fn mock_config(temp_path: PathBuf) -> Arc<ChasquiConfig> {
         images_dir: temp_path.join("images"),
         audio_dir: temp_path.join("audio"),
         videos_dir: temp_path.join("videos"),
+        extra_content_dirs: Vec::new(),
         page_strip_extension: true,
         asset_strip_extension: false,
         serve_home: true,
         home_identifier: "index".into(),
+        folder_index_pages: false,
+        resolve_html_links: false,
         webhook_url: "http://localhost/build".into(),
         webhook_secret: "secret".into(),
+        webhook_payload: None,
         port: 3000,
         nginx_media_prefixes: false,
+        site_url: "http://localhost:3000".into(),
+        watch_debounce_ms: 1500,
+        cors_allowed_origins: Vec::new(),
+        slugify_identifiers: false,
+        admin_token: "".into(),
+        required_frontmatter: Vec::new(),
+        html_template_path: None,
+        created_datetime_aliases: Vec::new(),
+        modified_datetime_aliases: Vec::new(),
+        max_request_body_bytes: 1024 * 1024,
+        max_list_pages: 1000,
+        soft_delete: false,
+        tombstone_retention_days: 30,
+        git_repo: None,
+        git_ref: "HEAD".into(),
+        git_poll_secs: 30,
+        sqlite_journal_mode: "WAL".to_string(),
+        sqlite_synchronous: "NORMAL".to_string(),
+        sqlite_busy_timeout_ms: 5000,
+        sqlite_cache_size: -64000,
+        sqlite_foreign_keys: true,
+        base_path: String::new(),
+        compiled_cache_entries: 0,
+        normalize_slashes: false,
+        strict_links: false,
+        max_batch_pages: 50,
+        content_extensions: vec!["md".to_string()],
+        json_datetime_format: Default::default(),
+        not_found_identifier: "404".into(),
+        dedupe_page_bodies: false,
+        page_cache_max_age: 300,
+        stable_page_after_days: 0,
+        stable_page_max_age: 86400,
+        list_cache_max_age: 60,
+        max_file_bytes: 10_485_760,
+        max_write_connections: 1,
+        passthrough_extensions: Vec::new(),
+        create_content_dirs: false,
+        content_dir_retries: 0,
+        collision_policy: Default::default(),
+        link_check_interval_secs: 0,
+        link_check_concurrency: 4,
+        link_check_timeout_secs: 10,
+        webhook_coalesce: true,
+        html_layouts_dir: None,
+        track_accessed_datetime: false,
+        identifier_strategy: Default::default(),
+        hide_unpublished_pages: false,
     })
 }
 
@@ -50,9 +102,6 @@ pub async fn setup_service_with_options(opts: TestOptions) -> (
     Arc<ChasquiConfig>,
     SqliteRepository,
 ) {
-    let repo = chasqui_db::testutil::create_test_repository().await;
-    let reader = MockContentReader::new();
-    let notifier = MockBuildNotifier::new();
     let config = Arc::new(ChasquiConfig {
         database_url: "".into(),
         max_connections: 1,
@@ -60,16 +109,99 @@ pub async fn setup_service_with_options(opts: TestOptions) -> (
         images_dir: opts.images_dir,
         audio_dir: opts.audio_dir,
         videos_dir: opts.videos_dir,
+        extra_content_dirs: Vec::new(),
         page_strip_extension: true,
         asset_strip_extension: false,
         serve_home: true,
         home_identifier: "index".into(),
+        folder_index_pages: false,
+        resolve_html_links: false,
         webhook_url: "".into(),
         webhook_secret: "".into(),
+        webhook_payload: None,
         port: 3000,
         nginx_media_prefixes: false,
+        site_url: "http://localhost:3000".into(),
+        watch_debounce_ms: 1500,
+        cors_allowed_origins: Vec::new(),
+        slugify_identifiers: false,
+        admin_token: "".into(),
+        required_frontmatter: Vec::new(),
+        html_template_path: None,
+        created_datetime_aliases: Vec::new(),
+        modified_datetime_aliases: Vec::new(),
+        max_request_body_bytes: 1024 * 1024,
+        max_list_pages: 1000,
+        soft_delete: false,
+        tombstone_retention_days: 30,
+        git_repo: None,
+        git_ref: "HEAD".into(),
+        git_poll_secs: 30,
+        sqlite_journal_mode: "WAL".to_string(),
+        sqlite_synchronous: "NORMAL".to_string(),
+        sqlite_busy_timeout_ms: 5000,
+        sqlite_cache_size: -64000,
+        sqlite_foreign_keys: true,
+        base_path: String::new(),
+        compiled_cache_entries: 0,
+        normalize_slashes: false,
+        strict_links: false,
+        max_batch_pages: 50,
+        content_extensions: vec!["md".to_string()],
+        json_datetime_format: Default::default(),
+        not_found_identifier: "404".into(),
+        dedupe_page_bodies: false,
+        page_cache_max_age: 300,
+        stable_page_after_days: 0,
+        stable_page_max_age: 86400,
+        list_cache_max_age: 60,
+        max_file_bytes: 10_485_760,
+        max_write_connections: 1,
+        passthrough_extensions: Vec::new(),
+        create_content_dirs: false,
+        content_dir_retries: 0,
+        collision_policy: Default::default(),
+        link_check_interval_secs: 0,
+        link_check_concurrency: 4,
+        link_check_timeout_secs: 10,
+        webhook_coalesce: true,
+        html_layouts_dir: None,
+        track_accessed_datetime: false,
+        identifier_strategy: Default::default(),
+        hide_unpublished_pages: false,
     });
 
+    boot_service(config).await
+}
+
+/// `setup_service` over the `mock_config` layout under `/content`, with
+/// `configure` applied first. The service boots with no files, so tests add
+/// theirs to the reader and `full_sync`.
+#[allow(dead_code)] // not every test binary uses it
+pub async fn setup_service_with_config(configure: impl FnOnce(&mut ChasquiConfig)) -> (
+    Arc<SyncService>,
+    MockContentReader,
+    MockBuildNotifier,
+    Arc<ChasquiConfig>,
+    SqliteRepository,
+) {
+    let mut config = (*mock_config(PathBuf::from("/content"))).clone();
+    configure(&mut config);
+    boot_service(Arc::new(config)).await
+}
+
+#[allow(dead_code)]
+async fn boot_service(config: Arc<ChasquiConfig>) -> (
+    Arc<SyncService>,
+    MockContentReader,
+    MockBuildNotifier,
+    Arc<ChasquiConfig>,
+    SqliteRepository,
+) {
+    let repo = chasqui_db::testutil::create_test_repository().await;
+    let reader = MockContentReader::new();
+    let notifier = MockBuildNotifier::new();
+
     let service = SyncService::new(
         repo.clone(),
         Arc::new(reader.clone()),
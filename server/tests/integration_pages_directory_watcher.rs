@@ -111,6 +111,35 @@ async fn test_watcher_worker_redundant_commands() {
     assert_eq!(*notifier.call_count.lock().unwrap(), 1);
 }
 
+#[tokio::test]
+async fn test_watcher_worker_skips_build_when_nothing_was_written() {
+    let (service, reader, notifier, config, _repo) = setup_watcher_service().await;
+    let (tx, rx) = mpsc::channel(100);
+    let full_sync_flag = Arc::new(AtomicBool::new(false));
+
+    tokio::spawn(run_watcher_worker(service.clone(), rx, full_sync_flag));
+
+    let path = PathBuf::from("/content/touched.md");
+    let send = || tx.send(SyncCommand::SingleFile(path.clone(), config.pages_dir.clone(), FeatureType::Page));
+
+    reader.add_file("/content/touched.md", "# Content");
+    send().await.unwrap();
+    sleep(Duration::from_millis(2500)).await;
+    assert_eq!(*notifier.call_count.lock().unwrap(), 1);
+
+    // an mtime-only touch leaves the hash, and so the stored page, as it was
+    let touched = chrono::NaiveDate::from_ymd_opt(2030, 1, 1).unwrap().and_hms_opt(0, 0, 0);
+    reader.add_file_with_metadata("/content/touched.md", "# Content", touched, None);
+    send().await.unwrap();
+    sleep(Duration::from_millis(2500)).await;
+    assert_eq!(*notifier.call_count.lock().unwrap(), 1);
+
+    reader.add_file("/content/touched.md", "# Edited");
+    send().await.unwrap();
+    sleep(Duration::from_millis(2500)).await;
+    assert_eq!(*notifier.call_count.lock().unwrap(), 2);
+}
+
 #[tokio::test]
 async fn test_watcher_worker_add_delete_recreate_cancellation() {
     let (service, reader, _notifier, config, _repo) = setup_watcher_service().await;
@@ -140,4 +169,139 @@ async fn test_watcher_worker_add_delete_recreate_cancellation() {
     let feature = service.get_feature_by_identifier("flicker").await.unwrap();
     let page = if let chasqui_core::features::model::Feature::Page(p) = feature { p } else { panic!("Expected page") };
     assert_eq!(page.md_content.trim(), "# Version 2");
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_watcher_worker_directory_rename() {
+    let (service, reader, _notifier, _config, repo) = setup_watcher_service().await;
+
+    reader.add_file("/content/old/a.md", "# A");
+    reader.add_file("/content/old/nested/b.md", "# B");
+    service.full_sync().await.unwrap();
+    assert!(service.get_feature_by_identifier("old/nested/b").await.is_some());
+
+    let (tx, rx) = mpsc::channel(100);
+    let full_sync_flag = Arc::new(AtomicBool::new(false));
+    tokio::spawn(run_watcher_worker(service.clone(), rx, full_sync_flag));
+
+    reader.remove_file("/content/old/a.md");
+    reader.remove_file("/content/old/nested/b.md");
+    reader.add_file("/content/new/a.md", "# A");
+    reader.add_file("/content/new/nested/b.md", "# B");
+
+    tx.send(SyncCommand::DeleteFile(PathBuf::from("/content/old")))
+        .await
+        .unwrap();
+    tx.send(SyncCommand::RescanDirectory(PathBuf::from("/content/new")))
+        .await
+        .unwrap();
+
+    sleep(Duration::from_millis(2500)).await;
+
+    assert_eq!(service.get_all_features_by_type(FeatureType::Page).await.len(), 2);
+    assert!(service.get_feature_by_identifier("new/a").await.is_some());
+    assert!(service.get_feature_by_identifier("new/nested/b").await.is_some());
+    assert!(service.get_feature_by_identifier("old/a").await.is_none());
+    assert!(repo.get_page_by_filename("old/nested/b.md").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_watcher_worker_records_batch_metrics() {
+    let handle = chasqui_server::telemetry::install_recorder();
+    let (service, reader, _notifier, config, _repo) = setup_watcher_service().await;
+    let (tx, rx) = mpsc::channel(100);
+    let full_sync_flag = Arc::new(AtomicBool::new(false));
+
+    tokio::spawn(run_watcher_worker(service.clone(), rx, full_sync_flag.clone()));
+
+    let path = PathBuf::from("/content/metered.md");
+    reader.add_file("/content/metered.md", "# Content");
+    for _ in 0..5 {
+        tx.send(SyncCommand::SingleFile(path.clone(), config.pages_dir.clone(), FeatureType::Page))
+            .await
+            .unwrap();
+    }
+    sleep(Duration::from_millis(2500)).await;
+
+    full_sync_flag.store(true, Ordering::SeqCst);
+    tx.send(SyncCommand::SingleFile(path, config.pages_dir.clone(), FeatureType::Page))
+        .await
+        .unwrap();
+    sleep(Duration::from_millis(2500)).await;
+
+    let text = handle.render();
+    assert!(text.contains("chasqui_watcher_events_total"), "{}", text);
+    assert!(text.contains("chasqui_watcher_coalesced_events_total"), "{}", text);
+    assert!(text.contains("chasqui_watcher_full_sync_fallbacks_total"), "{}", text);
+    assert!(text.contains("chasqui_watcher_batch_size"), "{}", text);
+}
+
+#[tokio::test]
+async fn test_watcher_worker_retries_half_written_files_once() {
+    let (service, reader, _notifier, config, _repo) = setup_watcher_service().await;
+    let (tx, rx) = mpsc::channel(100);
+    let full_sync_flag = Arc::new(AtomicBool::new(false));
+
+    tokio::spawn(run_watcher_worker(service.clone(), rx, full_sync_flag));
+
+    // an atomic save whose `Create` beat the content flush
+    reader.add_file("/content/saved.md", "");
+    reader.add_file("/content/empty.md", "");
+    for path in ["/content/saved.md", "/content/empty.md"] {
+        tx.send(SyncCommand::SingleFile(PathBuf::from(path), config.pages_dir.clone(), FeatureType::Page))
+            .await
+            .unwrap();
+    }
+
+    sleep(Duration::from_millis(2000)).await;
+    assert!(service.get_page_by_filename("saved.md").await.is_none());
+    assert!(service.get_page_by_filename("empty.md").await.is_none());
+
+    reader.add_file("/content/saved.md", "# Flushed");
+    sleep(Duration::from_millis(2000)).await;
+
+    let saved = service.get_page_by_filename("saved.md").await.unwrap();
+    assert!(saved.md_content.contains("Flushed"));
+    // still empty after the retry, so it really is an empty page
+    assert!(service.get_page_by_filename("empty.md").await.is_some());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_mount_paths_follow_symlinked_content_root() {
+    use chasqui_server::watcher::watcher::MountPaths;
+    use std::os::unix::fs::symlink;
+
+    let dir = tempfile::tempdir().unwrap();
+    let real = dir.path().join("real");
+    let link = dir.path().join("link");
+    std::fs::create_dir_all(real.join("md/blog")).unwrap();
+    symlink(&real, &link).unwrap();
+    std::fs::write(real.join("md/blog/post.md"), "# Post").unwrap();
+    let real = std::fs::canonicalize(&real).unwrap();
+
+    // configured through the symlink, events reported under the real path
+    let via_link = MountPaths::new([link.join("md")]);
+    assert_eq!(
+        via_link.to_mount_path(&real.join("md/blog/post.md")),
+        link.join("md/blog/post.md")
+    );
+    assert_eq!(
+        via_link.to_mount_path(&link.join("md/blog/post.md")),
+        link.join("md/blog/post.md")
+    );
+
+    // configured canonical, as `from_env` does, events under the symlink
+    let canonical = MountPaths::new([real.join("md")]);
+    assert_eq!(
+        canonical.to_mount_path(&link.join("md/blog/post.md")),
+        real.join("md/blog/post.md")
+    );
+    // deleted files no longer resolve, but their directory does
+    assert_eq!(
+        canonical.to_mount_path(&link.join("md/blog/gone.md")),
+        real.join("md/blog/gone.md")
+    );
+
+    let outside = dir.path().join("elsewhere/post.md");
+    assert_eq!(canonical.to_mount_path(&outside), outside);
+}
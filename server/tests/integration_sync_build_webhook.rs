@@ -77,4 +77,160 @@ async fn test_webhook_called_after_watcher_batch() {
 
     // Verify that the webhook notification was sent
     assert!(*notifier.call_count.lock().unwrap() >= 1);
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_webhook_receives_changed_and_deleted_identifiers() {
+    let repo = chasqui_db::testutil::create_test_repository().await;
+    let reader = MockContentReader::new();
+    let notifier = MockBuildNotifier::new();
+    let config = mock_config(PathBuf::from("/content"));
+
+    reader.add_file("/content/md/keep.md", "# Keep");
+    reader.add_file("/content/md/doomed.md", "# Doomed");
+    let service = SyncService::new(
+        repo,
+        Arc::new(reader.clone()),
+        Box::new(notifier.clone()),
+        config.clone(),
+    )
+    .await
+    .unwrap();
+
+    service.notify_build().await.unwrap();
+    let initial = notifier.last_changes.lock().unwrap().clone().unwrap();
+    assert_eq!(initial.changed.iter().collect::<Vec<_>>(), vec!["doomed", "keep"]);
+
+    // a failed notification keeps its changes for the next one
+    reader.add_file("/content/md/keep.md", "# Kept, edited");
+    service
+        .process_batch(
+            vec![(PathBuf::from("/content/md/keep.md"), config.pages_dir.clone(), FeatureType::Page)],
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+    notifier.set_fail(true);
+    assert!(service.notify_build().await.is_err());
+    notifier.set_fail(false);
+
+    reader.remove_file("/content/md/doomed.md");
+    service
+        .process_batch(Vec::new(), vec![PathBuf::from("/content/md/doomed.md")])
+        .await
+        .unwrap();
+    service.notify_build().await.unwrap();
+
+    let changes = notifier.last_changes.lock().unwrap().clone().unwrap();
+    assert_eq!(changes.changed.iter().collect::<Vec<_>>(), vec!["keep"]);
+    assert_eq!(changes.deleted.iter().collect::<Vec<_>>(), vec!["doomed"]);
+
+    service.notify_build().await.unwrap();
+    assert!(notifier.last_changes.lock().unwrap().as_ref().unwrap().is_empty());
+}
+
+#[test]
+fn test_webhook_body_fills_changes_placeholder() {
+    use chasqui_core::notifier::BuildChanges;
+    use chasqui_server::services::webhook_body;
+    use serde_json::json;
+
+    let mut changes = BuildChanges::default();
+    changes.record_changed("post".to_string());
+    changes.record_deleted("old".to_string());
+
+    assert_eq!(
+        webhook_body(None, &changes),
+        json!({"changed": ["post"], "deleted": ["old"]})
+    );
+
+    let payload = json!({"event_type": "content_updated", "client_payload": "{{changes}}"});
+    assert_eq!(
+        webhook_body(Some(&payload), &changes),
+        json!({
+            "event_type": "content_updated",
+            "client_payload": {"changed": ["post"], "deleted": ["old"]}
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_coalescing_notifier_merges_notifications_sent_mid_build() {
+    use chasqui_core::notifier::{BuildChanges, ContentBuildNotifier};
+    use chasqui_server::services::CoalescingBuildNotifier;
+
+    let inner = MockBuildNotifier::new();
+    inner.set_latency(Duration::from_millis(200));
+    let notifier = Arc::new(CoalescingBuildNotifier::new(inner.clone()));
+
+    let changed = |identifier: &str| {
+        let mut changes = BuildChanges::default();
+        changes.record_changed(identifier.to_string());
+        changes
+    };
+
+    let first = {
+        let notifier = notifier.clone();
+        tokio::spawn(async move { notifier.notify(&changed("first")).await })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // these land while the first is in flight and return without waiting
+    for identifier in ["second", "third", "fourth"] {
+        timeout(Duration::from_millis(50), notifier.notify(&changed(identifier)))
+            .await
+            .expect("a coalesced notification should not wait for the build")
+            .unwrap();
+    }
+
+    first.await.unwrap().unwrap();
+    assert_eq!(*inner.call_count.lock().unwrap(), 2);
+    let follow_up = inner.last_changes.lock().unwrap().clone().unwrap();
+    assert_eq!(follow_up.changed.iter().collect::<Vec<_>>(), vec!["fourth", "second", "third"]);
+
+    // a failed notification's changes go back to its caller rather than
+    // being kept for the next one as well
+    inner.set_fail(true);
+    assert!(notifier.notify(&changed("failed")).await.is_err());
+    inner.set_fail(false);
+    notifier.notify(&changed("later")).await.unwrap();
+    let next = inner.last_changes.lock().unwrap().clone().unwrap();
+    assert_eq!(next.changed.iter().collect::<Vec<_>>(), vec!["later"]);
+
+    // a notification dropped mid-build doesn't hold back the ones after it
+    let dropped = timeout(Duration::from_millis(50), notifier.notify(&changed("dropped"))).await;
+    assert!(dropped.is_err());
+    notifier.notify(&changed("after")).await.unwrap();
+    assert_eq!(*inner.call_count.lock().unwrap(), 4);
+}
+
+#[tokio::test]
+async fn test_failed_coalesced_build_is_retried_once_by_the_service() {
+    use chasqui_server::services::CoalescingBuildNotifier;
+
+    let repo = chasqui_db::testutil::create_test_repository().await;
+    let reader = MockContentReader::new();
+    let inner = MockBuildNotifier::new();
+    let service = SyncService::new(
+        repo,
+        Arc::new(reader.clone()),
+        Box::new(CoalescingBuildNotifier::new(inner.clone())),
+        mock_config(PathBuf::from("/content")),
+    )
+    .await
+    .unwrap();
+
+    reader.add_file("/content/md/post.md", "# Post");
+    inner.set_fail(true);
+    service.full_sync().await.unwrap();
+    assert!(service.notify_build().await.is_err());
+
+    inner.set_fail(false);
+    service.notify_build().await.unwrap();
+    let retried = inner.last_changes.lock().unwrap().clone().unwrap();
+    assert_eq!(retried.changed.iter().collect::<Vec<_>>(), vec!["post"]);
+
+    // delivered once, so nothing is left to send again
+    service.notify_build().await.unwrap();
+    let after = inner.last_changes.lock().unwrap().clone().unwrap();
+    assert!(after.is_empty());
+}
@@ -1,19 +1,22 @@
 mod common;
 
-use chasqui_core::config::ChasquiConfig;
+use chasqui_core::config::{CollisionPolicy, IdentifierStrategy};
 use chasqui_core::features::model::{Feature, FeatureType};
+use chasqui_core::features::pages::model::JsonPage;
+use chasqui_core::parser::markdown::render_html;
+use chasqui_server::features::factory::FeatureFactory;
+use chasqui_server::features::pages::{feed, sitemap};
+use chasqui_server::services::sync::manifest::{Manifest, ManifestClaim};
 use chasqui_server::services::sync::SyncService;
 use chasqui_server::testutil::{MockBuildNotifier, MockContentReader, BlockingReader};
-use common::{mock_config, setup_service};
+use common::{mock_config, setup_service, setup_service_with_config};
 use chrono::NaiveDate;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 #[tokio::test]
 async fn test_sync_service_discovery_and_ingestion() {
-    let (service, reader, _notifier, _config, _repo) = setup_service().await;
-    let content_dir = PathBuf::from("/content");
-    let config = mock_config(content_dir.clone());
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|_| {}).await;
 
     reader.add_file(
         "/content/md/post1.md",
@@ -24,14 +27,7 @@ identifier: hello
     );
     reader.add_file("/content/md/post2.md", "# Post 2 with [link](post1.md)");
 
-    let service = SyncService::new(
-        _repo.clone(),
-        Arc::new(reader.clone()),
-        Box::new(_notifier.clone()),
-        config.clone(),
-    )
-    .await
-    .expect("Failed to create service");
+    service.full_sync().await.unwrap();
 
     let pages = service.get_all_features_by_type(FeatureType::Page).await;
     assert_eq!(pages.len(), 2);
@@ -47,18 +43,7 @@ identifier: hello
 
 #[tokio::test]
 async fn test_sync_service_link_validation() {
-    let (service, reader, _notifier, _config, repo) = setup_service().await;
-    let content_dir = PathBuf::from("/content");
-    let config = mock_config(content_dir.clone());
-
-    let service = SyncService::new(
-        repo.clone(),
-        Arc::new(reader.clone()),
-        Box::new(_notifier.clone()),
-        config.clone(),
-    )
-    .await
-    .unwrap();
+    let (service, reader, _notifier, config, _repo) = setup_service_with_config(|_| {}).await;
 
     reader.add_file("/content/md/a.md", "[Go to B](b.md)");
     reader.add_file("/content/md/b.md", "[Go to A](a.md)");
@@ -107,17 +92,7 @@ New location",
 
 #[tokio::test]
 async fn test_sync_service_identifier_collision_reject_both() {
-    let (service, reader, _notifier, _config, repo) = setup_service().await;
-    let config = mock_config(PathBuf::from("/content"));
-
-    let service = SyncService::new(
-        repo.clone(),
-        Arc::new(reader.clone()),
-        Box::new(_notifier.clone()),
-        config.clone(),
-    )
-    .await
-    .unwrap();
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|_| {}).await;
 
     reader.add_file(
         "/content/md/a.md",
@@ -140,10 +115,269 @@ identifier: collision
     assert_eq!(pages.len(), 0);
 }
 
+#[tokio::test]
+async fn test_sync_service_identifier_collision_append_suffix() {
+    let (service, reader, _notifier, _config, repo) = setup_service_with_config(|config| {
+        config.collision_policy = CollisionPolicy::AppendSuffix;
+    })
+    .await;
+
+    // listed out of filename order; suffixes still follow the filenames
+    for name in ["c", "a", "b"] {
+        reader.add_file(
+            &format!("/content/md/{}.md", name),
+            &format!("---\nidentifier: shared\n---\n# {}", name.to_uppercase()),
+        );
+    }
+
+    service.full_sync().await.unwrap();
+
+    let identifier_of = |filename: &'static str| {
+        let service = &service;
+        async move { service.get_page_by_filename(filename).await.unwrap().identifier }
+    };
+    assert_eq!(identifier_of("a.md").await, "shared");
+    assert_eq!(identifier_of("b.md").await, "shared-2");
+    assert_eq!(identifier_of("c.md").await, "shared-3");
+    assert_eq!(
+        repo.get_page_by_identifier("shared-2").await.unwrap().unwrap().filename,
+        "b.md"
+    );
+
+    // an edit keeps the suffix the file already holds
+    reader.add_file("/content/md/b.md", "---\nidentifier: shared\n---\n# B edited");
+    service.full_sync().await.unwrap();
+    assert_eq!(identifier_of("b.md").await, "shared-2");
+    assert_eq!(identifier_of("c.md").await, "shared-3");
+
+    // a later claimant that sorts first takes the bare identifier, and the
+    // rest move up as if they had all been synced together
+    reader.add_file("/content/md/0.md", "---\nidentifier: shared\n---\n# Zero");
+    service.full_sync().await.unwrap();
+    assert_eq!(identifier_of("0.md").await, "shared");
+    assert_eq!(identifier_of("a.md").await, "shared-2");
+    assert_eq!(identifier_of("b.md").await, "shared-3");
+    assert_eq!(identifier_of("c.md").await, "shared-4");
+    assert_eq!(
+        repo.get_page_by_identifier("shared-4").await.unwrap().unwrap().filename,
+        "c.md"
+    );
+
+    // and they move back down when it goes
+    reader.remove_file("/content/md/0.md");
+    service
+        .process_batch(vec![], vec![PathBuf::from("/content/md/0.md")])
+        .await
+        .unwrap();
+    assert_eq!(identifier_of("a.md").await, "shared");
+    assert_eq!(identifier_of("b.md").await, "shared-2");
+    assert_eq!(identifier_of("c.md").await, "shared-3");
+    assert!(repo.get_page_by_identifier("shared-4").await.unwrap().is_none());
+    assert_eq!(
+        service.get_feature_by_identifier("shared").await.map(|f| match f {
+            Feature::Page(p) => p.filename,
+            _ => String::new(),
+        }),
+        Some("a.md".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_full_sync_spans_several_chunks() {
+    let (service, reader, _notifier, _config, _repo) = setup_service().await;
+    for i in 0..2500 {
+        reader.add_file(&format!("/content/md/page{}.md", i), "# Page");
+    }
+
+    let report = service.full_sync().await.unwrap();
+    assert_eq!(report.changes, 2500);
+    assert_eq!(report.written.changed.len(), 2500);
+    assert_eq!(service.status().await.page_count, 2500);
+}
+
+#[tokio::test]
+async fn test_full_sync_deletes_files_no_longer_discovered() {
+    let (service, reader, _notifier, _config, repo) = setup_service_with_config(|_| {}).await;
+    reader.add_file("/content/md/kept.md", "# Kept");
+    reader.add_file("/content/md/gone.md", "# Gone");
+    reader.add_file("/content/md/old/moved.md", "# Moved");
+    service.full_sync().await.unwrap();
+
+    // removed, and moved to another directory, with no watcher event for either
+    reader.remove_file("/content/md/gone.md");
+    reader.remove_file("/content/md/old/moved.md");
+    reader.add_file("/content/md/new/moved.md", "# Moved");
+    let dry_run = service.dry_run_sync().await.unwrap();
+    assert_eq!(dry_run.deletions, vec!["gone.md", "old/moved.md"]);
+
+    let report = service.full_sync().await.unwrap();
+    assert_eq!(report.deletions, 2);
+    assert!(service.get_page_by_filename("gone.md").await.is_none());
+    assert!(service.get_page_by_filename("old/moved.md").await.is_none());
+    assert!(service.get_page_by_filename("new/moved.md").await.is_some());
+    assert!(service.get_page_by_filename("kept.md").await.is_some());
+    assert!(repo.get_page_by_filename("gone.md").await.unwrap().is_none());
+    assert!(repo.get_page_by_filename("old/moved.md").await.unwrap().is_none());
+    assert!(report.written.deleted.contains("gone"));
+    assert!(service.dry_run_sync().await.unwrap().deletions.is_empty());
+}
+
+#[tokio::test]
+async fn test_full_sync_resolves_links_across_the_whole_tree() {
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|_| {}).await;
+    // a ring of links, so however the walk orders them some point at pages
+    // it finds much later
+    let count = 2500;
+    for i in 0..count {
+        reader.add_file(
+            &format!("/content/md/page{}.md", i),
+            &format!("---\nidentifier: p{}\n---\n[next](page{}.md)", i, (i + 1) % count),
+        );
+    }
+
+    service.full_sync().await.unwrap();
+    for i in 0..count {
+        let page = service.get_page_by_filename(&format!("page{}.md", i)).await.unwrap();
+        let expected = format!("[next](/p{})", (i + 1) % count);
+        assert!(page.md_content.contains(&expected), "{}", page.md_content);
+    }
+}
+
+#[tokio::test]
+async fn test_full_sync_rejects_collisions_across_the_whole_tree() {
+    let (service, reader, _notifier, _config, repo) = setup_service_with_config(|_| {}).await;
+    // pairs of claimants, so however the walk orders them some pairs are
+    // found far apart
+    let pairs = 1300;
+    for i in 0..pairs {
+        for side in ["a", "b"] {
+            reader.add_file(
+                &format!("/content/md/{}{}.md", side, i),
+                &format!("---\nidentifier: shared{}\n---\n# Shared", i),
+            );
+        }
+    }
+
+    let report = service.full_sync().await.unwrap();
+    assert_eq!(report.collisions, pairs * 2);
+    assert_eq!(service.status().await.page_count, 0);
+    for i in 0..pairs {
+        let identifier = format!("shared{}", i);
+        assert!(service.get_feature_by_identifier(&identifier).await.is_none());
+        assert!(repo.get_page_by_identifier(&identifier).await.unwrap().is_none());
+    }
+}
+
+async fn service_with_strategy(
+    strategy: IdentifierStrategy,
+    collision_policy: CollisionPolicy,
+    files: &[(&str, &str)],
+) -> Arc<SyncService> {
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|config| {
+        config.identifier_strategy = strategy;
+        config.collision_policy = collision_policy;
+    })
+    .await;
+
+    for (path, content) in files {
+        reader.add_file(path, content);
+    }
+
+    service.full_sync().await.unwrap();
+    service
+}
+
+#[tokio::test]
+async fn test_identifier_strategy_full_path() {
+    let service = service_with_strategy(
+        IdentifierStrategy::FullPath,
+        CollisionPolicy::Reject,
+        &[("/content/md/blog/2024-01-31-post.md", "# Post")],
+    )
+    .await;
+
+    let page = service.get_page_by_filename("blog/2024-01-31-post.md").await.unwrap();
+    assert_eq!(page.identifier, "blog/2024-01-31-post");
+}
+
+#[tokio::test]
+async fn test_identifier_strategy_stem() {
+    let service = service_with_strategy(
+        IdentifierStrategy::Stem,
+        CollisionPolicy::AppendSuffix,
+        &[
+            ("/content/md/blog/post.md", "# Blog post"),
+            ("/content/md/notes/post.md", "# Notes post"),
+            ("/content/md/guides/setup.md", "# Setup"),
+            ("/content/md/named.md", "---\nidentifier: docs/named\n---\n# Named"),
+        ],
+    )
+    .await;
+
+    let identifier_of = |filename: &'static str| {
+        let service = &service;
+        async move { service.get_page_by_filename(filename).await.unwrap().identifier }
+    };
+    assert_eq!(identifier_of("guides/setup.md").await, "setup");
+    // stems shared across dirs go through the collision policy
+    assert_eq!(identifier_of("blog/post.md").await, "post");
+    assert_eq!(identifier_of("notes/post.md").await, "post-2");
+    // an identifier in the frontmatter still wins
+    assert_eq!(identifier_of("named.md").await, "docs/named");
+}
+
+#[tokio::test]
+async fn test_identifier_strategy_stem_collisions_rejected() {
+    let service = service_with_strategy(
+        IdentifierStrategy::Stem,
+        CollisionPolicy::Reject,
+        &[
+            ("/content/md/blog/post.md", "# Blog post"),
+            ("/content/md/notes/post.md", "# Notes post"),
+        ],
+    )
+    .await;
+
+    assert!(service.get_all_features_by_type(FeatureType::Page).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_identifier_strategy_jekyll_date() {
+    let service = service_with_strategy(
+        IdentifierStrategy::JekyllDate,
+        CollisionPolicy::Reject,
+        &[
+            ("/content/md/blog/2024-01-31-hello-world.md", "# Hello"),
+            (
+                "/content/md/blog/2024-02-01-dated.md",
+                "---\ncreated_datetime: 2023-06-01\n---\n# Dated",
+            ),
+            ("/content/md/blog/2024-13-01-not-a-date.md", "# Not a date"),
+            ("/content/md/about.md", "# About"),
+        ],
+    )
+    .await;
+
+    let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(0, 0, 0);
+
+    let hello = service.get_page_by_filename("blog/2024-01-31-hello-world.md").await.unwrap();
+    assert_eq!(hello.identifier, "blog/hello-world");
+    assert_eq!(hello.created_datetime, date(2024, 1, 31));
+
+    // the frontmatter's date beats the filename's
+    let dated = service.get_page_by_filename("blog/2024-02-01-dated.md").await.unwrap();
+    assert_eq!(dated.identifier, "blog/dated");
+    assert_eq!(dated.created_datetime, date(2023, 6, 1));
+
+    // names without a valid date prefix are kept whole
+    let invalid = service.get_page_by_filename("blog/2024-13-01-not-a-date.md").await.unwrap();
+    assert_eq!(invalid.identifier, "blog/2024-13-01-not-a-date");
+    assert_eq!(service.get_page_by_filename("about.md").await.unwrap().identifier, "about");
+}
+
 #[tokio::test]
 async fn test_sync_service_datetime_resolution() {
-    let (service, reader, _notifier, _config, repo) = setup_service().await;
-    let config = mock_config(PathBuf::from("/content"));
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|_| {}).await;
 
     let time_a = NaiveDate::from_ymd_opt(2026, 1, 1)
         .unwrap()
@@ -155,14 +389,6 @@ async fn test_sync_service_datetime_resolution() {
         .unwrap();
     let time_b_str = "2026-12-25T00:00:00Z";
 
-    let service = SyncService::new(
-        repo.clone(),
-        Arc::new(reader.clone()),
-        Box::new(_notifier.clone()),
-        config.clone(),
-    )
-    .await
-    .unwrap();
 
     reader.add_file_with_metadata(
         "/content/md/fs_only.md",
@@ -212,18 +438,7 @@ modified_datetime: {}
 
 #[tokio::test]
 async fn test_sync_prevent_identity_hijack() {
-    let (service, reader, _notifier, _config, repo) = setup_service().await;
-    let content_dir = PathBuf::from("/content");
-    let config = mock_config(content_dir.clone());
-
-    let service = SyncService::new(
-        repo.clone(),
-        Arc::new(reader.clone()),
-        Box::new(_notifier.clone()),
-        config.clone(),
-    )
-    .await
-    .unwrap();
+    let (service, reader, _notifier, config, _repo) = setup_service_with_config(|_| {}).await;
 
     reader.add_binary_file("/content/images/logo.png", vec![0, 1, 2, 3]);
     service.full_sync().await.unwrap();
@@ -264,18 +479,7 @@ identifier: logo.png
 
 #[tokio::test]
 async fn test_sync_rejects_jailbreak_identifier() {
-    let (service, reader, _notifier, _config, repo) = setup_service().await;
-    let content_dir = PathBuf::from("/content");
-    let config = mock_config(content_dir.clone());
-
-    let service = SyncService::new(
-        repo.clone(),
-        Arc::new(reader.clone()),
-        Box::new(_notifier.clone()),
-        config.clone(),
-    )
-    .await
-    .unwrap();
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|_| {}).await;
 
     reader.add_file(
         "/content/md/malicious.md",
@@ -291,9 +495,10 @@ async fn test_sync_rejects_jailbreak_identifier() {
 
 #[tokio::test]
 async fn test_sync_queues_updates() {
-    let (service, inner_reader, notifier, _config, repo) = setup_service().await;
-    let content_dir = PathBuf::from("/content");
-    let config = mock_config(content_dir.clone());
+    let repo = chasqui_db::testutil::create_test_repository().await;
+    let inner_reader = MockContentReader::new();
+    let notifier = MockBuildNotifier::new();
+    let config = mock_config(PathBuf::from("/content"));
 
     let barrier = Arc::new(tokio::sync::Barrier::new(3));
     let blocking_reader = Arc::new(BlockingReader::new(inner_reader.clone(), barrier.clone()));
@@ -343,4 +548,1013 @@ async fn test_sync_queues_updates() {
 
     let pages = service.get_all_features_by_type(FeatureType::Page).await;
     assert_eq!(pages.len(), 20, "Should have synced all 20 files across both concurrent sync triggers");
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_sync_description_falls_back_to_excerpt() {
+    let (service, reader, _notifier, _config, repo) = setup_service().await;
+
+    reader.add_file(
+        "/content/explicit.md",
+        "---\ndescription: Hand written\n---\n# Title\n\nBody text.",
+    );
+    reader.add_file(
+        "/content/auto.md",
+        "# Title\n\nFirst *paragraph* of the post.\n\nSecond paragraph.",
+    );
+
+    service.full_sync().await.unwrap();
+
+    let explicit = if let Some(Feature::Page(p)) = service.get_feature_by_identifier("explicit").await {
+        p
+    } else {
+        panic!("Expected explicit page")
+    };
+    assert_eq!(explicit.description, Some("Hand written".to_string()));
+
+    let stored = repo.get_page_by_filename("auto.md").await.unwrap().unwrap();
+    assert_eq!(
+        stored.description,
+        Some("First paragraph of the post.".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_sync_slugified_identifiers() {
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|config| {
+        config.slugify_identifiers = true;
+    })
+    .await;
+
+
+    reader.add_file("/content/md/cool.md", "---\nidentifier: My Cool Post!\n---\n# Cool");
+    reader.add_file("/content/md/symbols.md", "---\nidentifier: \"!!!\"\n---\n# Symbols");
+    reader.add_file("/content/md/dup_a.md", "---\nidentifier: Same Title\n---\n# A");
+    reader.add_file("/content/md/dup_b.md", "---\nidentifier: same-title!\n---\n# B");
+
+    service.full_sync().await.unwrap();
+
+    assert!(service.get_feature_by_identifier("my-cool-post").await.is_some());
+    assert!(service.get_feature_by_identifier("symbols").await.is_some());
+    // both slugify to "same-title", so the collision policy rejects both
+    assert!(service.get_feature_by_identifier("same-title").await.is_none());
+    assert!(service.get_feature_by_identifier("dup_a").await.is_none());
+}
+
+#[tokio::test]
+async fn test_identifier_lookup_ignores_case_in_cache_and_repo() {
+    let (service, reader, _notifier, _config, repo) = setup_service_with_config(|_| {}).await;
+
+    reader.add_file("/content/md/about.md", "---\nidentifier: About-Us\n---\n# About");
+    reader.add_file("/content/md/Team.md", "# Team");
+
+    service.full_sync().await.unwrap();
+
+    for lookup in ["about-us", "About-Us", "ABOUT-US", "team", "Team", "TEAM"] {
+        let cached = match service.get_feature_by_identifier(lookup).await {
+            Some(Feature::Page(page)) => Some(page.identifier),
+            _ => None,
+        };
+        let stored = repo
+            .get_page_by_identifier(lookup)
+            .await
+            .unwrap()
+            .map(|page| page.identifier);
+        assert!(cached.is_some(), "{} not found", lookup);
+        assert_eq!(cached, stored, "cache and repo disagree on {}", lookup);
+    }
+
+    // `_` is not a wildcard
+    assert!(repo.get_page_by_identifier("t_am").await.unwrap().is_none());
+    assert!(service.get_feature_by_identifier("t_am").await.is_none());
+}
+
+#[tokio::test]
+async fn test_sync_status_tracks_in_progress_and_last_sync() {
+    let repo = chasqui_db::testutil::create_test_repository().await;
+    let inner_reader = MockContentReader::new();
+    let notifier = MockBuildNotifier::new();
+    let config = mock_config(PathBuf::from("/content"));
+
+    let barrier = Arc::new(tokio::sync::Barrier::new(2));
+    let blocking_reader = Arc::new(BlockingReader::new(inner_reader.clone(), barrier.clone()));
+
+    let service = Arc::new(
+        SyncService::new(repo.clone(), blocking_reader.clone(), Box::new(notifier.clone()), config)
+            .await
+            .unwrap(),
+    );
+
+    let initial = service.status().await;
+    assert!(initial.last_sync.is_some(), "Boot sync should record a timestamp");
+    assert!(!initial.sync_in_progress);
+    assert_eq!(initial.page_count, 0);
+
+    inner_reader.add_file("/content/md/slow.md", "# Slow");
+    blocking_reader.block_at("slow.md");
+
+    let service_clone = service.clone();
+    let sync = tokio::spawn(async move { service_clone.full_sync().await });
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    assert!(service.status().await.sync_in_progress);
+
+    barrier.wait().await;
+    sync.await.unwrap().unwrap();
+
+    let after = service.status().await;
+    assert!(!after.sync_in_progress);
+    assert_eq!(after.page_count, 1);
+    assert!(after.last_sync >= initial.last_sync);
+}
+
+#[tokio::test]
+async fn test_sync_multiple_content_roots() {
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|config| {
+        config.extra_content_dirs = vec![PathBuf::from("/docs")];
+    })
+    .await;
+
+    reader.add_file("/content/md/blog-post.md", "# Blog");
+    reader.add_file("/docs/md/guide/install.md", "# Install");
+    reader.add_file("/docs/images/diagram.png", "fake-image");
+    reader.add_file("/content/md/dup.md", "---\nidentifier: shared\n---\n# Blog");
+    reader.add_file("/docs/md/other.md", "---\nidentifier: shared\n---\n# Docs");
+    reader.add_file("/content/md/index.md", "---\nidentifier: from-content\n---\n# A");
+    reader.add_file("/docs/md/index.md", "---\nidentifier: from-docs\n---\n# B");
+
+    service.full_sync().await.unwrap();
+
+    assert!(service.get_feature_by_identifier("blog-post").await.is_some());
+    assert!(service.get_feature_by_identifier("guide/install").await.is_some());
+    assert!(service.get_feature_by_identifier("diagram.png").await.is_some());
+
+    // identifiers must be unique across all roots
+    assert!(service.get_feature_by_identifier("shared").await.is_none());
+
+    // but each root has its own paths, so both `index.md` files sync
+    let from_content = service.get_page_by_filename("index.md").await.unwrap();
+    let from_docs = service.get_page_by_filename("@docs/index.md").await.unwrap();
+    assert_eq!(from_content.identifier, "from-content");
+    assert_eq!(from_docs.identifier, "from-docs");
+    assert_eq!(from_docs.file_path, PathBuf::from("/docs/md/index.md"));
+    let install = service.get_page_by_filename("@docs/guide/install.md").await;
+    assert_eq!(install.unwrap().identifier, "guide/install");
+
+    reader.remove_file("/docs/md/index.md");
+    service
+        .process_batch(vec![], vec![PathBuf::from("/docs/md/index.md")])
+        .await
+        .unwrap();
+    assert!(service.get_feature_by_identifier("from-docs").await.is_none());
+    assert!(service.get_feature_by_identifier("from-content").await.is_some());
+
+    service
+        .process_batch(vec![], vec![PathBuf::from("/docs/md/guide")])
+        .await
+        .unwrap();
+    assert!(service.get_feature_by_identifier("guide/install").await.is_none());
+    assert!(service.get_feature_by_identifier("blog-post").await.is_some());
+}
+
+#[tokio::test]
+async fn test_sync_folder_index_pages() {
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|config| {
+        config.folder_index_pages = true;
+    })
+    .await;
+
+    reader.add_file("/content/md/index.md", "# Home");
+    reader.add_file("/content/md/blog/index.md", "# Blog");
+    reader.add_file("/content/md/index-of-things.md", "# Things");
+    reader.add_file(
+        "/content/md/links.md",
+        "[home](index.md) [file](blog/index.md) [folder](blog/) [things](index-of-things.md)",
+    );
+
+    service.full_sync().await.unwrap();
+
+    assert!(service.get_feature_by_identifier("index").await.is_some());
+    assert!(service.get_feature_by_identifier("blog").await.is_some());
+    assert!(service.get_feature_by_identifier("blog/index").await.is_none());
+    assert!(service.get_feature_by_identifier("index-of-things").await.is_some());
+
+    let links = if let Some(Feature::Page(p)) = service.get_feature_by_identifier("links").await {
+        p
+    } else {
+        panic!("Expected links page")
+    };
+    assert!(links.md_content.contains("[home](/)"), "{}", links.md_content);
+    assert!(links.md_content.contains("[file](/blog)"), "{}", links.md_content);
+    assert!(links.md_content.contains("[folder](/blog)"), "{}", links.md_content);
+    assert!(links.md_content.contains("[things](/index-of-things)"), "{}", links.md_content);
+}
+
+#[tokio::test]
+async fn test_folder_links_stay_literal_without_folder_index_pages() {
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|config| {
+        config.folder_index_pages = false;
+    })
+    .await;
+
+    reader.add_file("/content/md/blog/index.md", "# Blog");
+    reader.add_file("/content/md/links.md", "[file](blog/index.md) [folder](blog/)");
+
+    service.full_sync().await.unwrap();
+
+    assert!(service.get_feature_by_identifier("blog/index").await.is_some());
+    assert!(service.get_feature_by_identifier("blog").await.is_none());
+
+    let links = service.get_page_by_filename("links.md").await.unwrap();
+    assert!(links.md_content.contains("[file](/blog/index)"), "{}", links.md_content);
+    assert!(links.md_content.contains("[folder](blog/)"), "{}", links.md_content);
+}
+
+#[tokio::test]
+async fn test_links_to_the_home_page_point_at_the_root() {
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|config| {
+        config.home_identifier = "welcome".to_string();
+    })
+    .await;
+
+    reader.add_file("/content/md/start.md", "---\nidentifier: Welcome\naliases: [home]\n---\n# Home");
+    reader.add_file(
+        "/content/md/links.md",
+        "[file](start.md#intro) [id](welcome#intro) [case](Welcome) [alias](home#top) [here](#local) [other](links)",
+    );
+
+    service.full_sync().await.unwrap();
+
+    let links = if let Some(Feature::Page(p)) = service.get_feature_by_identifier("links").await {
+        p
+    } else {
+        panic!("Expected links page")
+    };
+    // filename, identifier in any case and alias all reach the root, keeping the fragment
+    assert!(links.md_content.contains("[file](/#intro)"), "{}", links.md_content);
+    assert!(links.md_content.contains("[id](/#intro)"), "{}", links.md_content);
+    assert!(links.md_content.contains("[case](/)"), "{}", links.md_content);
+    assert!(links.md_content.contains("[alias](/#top)"), "{}", links.md_content);
+    assert!(links.md_content.contains("[here](#local)"), "{}", links.md_content);
+    assert!(links.md_content.contains("[other](/links)"), "{}", links.md_content);
+}
+
+#[tokio::test]
+async fn test_base_path_prefixes_page_urls() {
+    let (service, reader, _notifier, config, _repo) = setup_service_with_config(|config| {
+        config.base_path = "/docs".to_string();
+        config.site_url = "https://example.com".to_string();
+    })
+    .await;
+
+    reader.add_file("/content/md/index.md", "# Home");
+    reader.add_file("/content/md/getting-started.md", "# Start");
+    reader.add_file(
+        "/content/md/links.md",
+        "[home](index.md) [start](getting-started.md#setup) [out](https://example.org)",
+    );
+
+    service.full_sync().await.unwrap();
+
+    // identifiers stay unprefixed; only the URLs built from them change
+    assert!(service.get_feature_by_identifier("getting-started").await.is_some());
+
+    let links = if let Some(Feature::Page(p)) = service.get_feature_by_identifier("links").await {
+        p
+    } else {
+        panic!("Expected links page")
+    };
+    assert!(links.md_content.contains("[home](/docs/)"), "{}", links.md_content);
+    assert!(links.md_content.contains("[start](/docs/getting-started#setup)"), "{}", links.md_content);
+    assert!(links.md_content.contains("[out](https://example.org)"), "{}", links.md_content);
+
+    let pages = service.get_all_pages().await;
+    let sitemap = sitemap::build_sitemap(&pages, &config);
+    assert!(sitemap.contains("<loc>https://example.com/docs/</loc>"), "{}", sitemap);
+    assert!(sitemap.contains("<loc>https://example.com/docs/getting-started</loc>"));
+    assert!(!sitemap.contains("/docs/index<"));
+
+    let feed = feed::build_feed(&pages, &config, "Docs");
+    assert!(feed.contains("<link>https://example.com/docs/</link>"), "{}", feed);
+    assert!(feed.contains("<link>https://example.com/docs/getting-started</link>"));
+}
+
+#[tokio::test]
+async fn test_compiled_cache_reuses_bodies_until_links_move() {
+    let reader = MockContentReader::new();
+    let mut config = (*mock_config(PathBuf::from("/content"))).clone();
+    config.compiled_cache_entries = 8;
+    let mount = config.pages_dir.clone();
+
+    reader.add_file("/content/md/a.md", "See [b](b.md)");
+    let claim = |filename: &str, identifier: &str| ManifestClaim {
+        feature_type: FeatureType::Page,
+        filename: filename.to_string(),
+        path: mount.join(filename),
+        mount_path: mount.clone(),
+        identifier: Some(identifier.to_string()),
+        aliases: Vec::new(),
+        content_hash: String::new(),
+    };
+
+    let mut manifest = Manifest::new();
+    manifest.register_claim(claim("a.md", "a"));
+    manifest.register_claim(claim("b.md", "b"));
+
+    let factory = FeatureFactory::new(
+        Arc::new(tokio::sync::RwLock::new(Manifest::new())),
+        Arc::new(reader.clone()),
+        Arc::new(config),
+    );
+    let build = |manifest: Manifest| {
+        let factory = &factory;
+        let reader = reader.clone();
+        async move {
+            match factory
+                .get_feature_from_file_with_manifest(claim("a.md", "a"), &manifest, &reader, None)
+                .await
+                .unwrap()
+            {
+                Feature::Page(page) => page.md_content,
+                _ => panic!("Expected a page"),
+            }
+        }
+    };
+
+    assert!(build(manifest.snapshot()).await.contains("(/b)"));
+    assert_eq!(factory.compiled_pages.len(), 1);
+    assert!(build(manifest.snapshot()).await.contains("(/b)"));
+    assert_eq!(factory.compiled_pages.len(), 1);
+
+    // the cached body is stale once its link target is renamed
+    manifest.remove_by_filename("b.md");
+    manifest.register_claim(claim("b.md", "bee"));
+    assert!(build(manifest.snapshot()).await.contains("(/bee)"));
+    assert_eq!(factory.compiled_pages.len(), 1);
+}
+
+#[tokio::test]
+async fn test_unchanged_body_reuses_previous_page_until_links_move() {
+    let reader = MockContentReader::new();
+    let mut config = (*mock_config(PathBuf::from("/content"))).clone();
+    config.compiled_cache_entries = 0;
+    let mount = config.pages_dir.clone();
+
+    reader.add_file("/content/md/a.md", "See [b](b.md) and ![pic](pic.png)");
+    let claim = |filename: &str, identifier: &str| ManifestClaim {
+        feature_type: FeatureType::Page,
+        filename: filename.to_string(),
+        path: mount.join(filename),
+        mount_path: mount.clone(),
+        identifier: Some(identifier.to_string()),
+        aliases: Vec::new(),
+        content_hash: String::new(),
+    };
+
+    let mut manifest = Manifest::new();
+    manifest.register_claim(claim("a.md", "a"));
+    manifest.register_claim(claim("b.md", "b"));
+
+    let factory = FeatureFactory::new(
+        Arc::new(tokio::sync::RwLock::new(Manifest::new())),
+        Arc::new(reader.clone()),
+        Arc::new(config),
+    );
+    let build = |manifest: Manifest, previous: Option<Feature>| {
+        let factory = &factory;
+        let reader = reader.clone();
+        async move {
+            match factory
+                .get_feature_from_file_with_manifest(claim("a.md", "a"), &manifest, &reader, previous.as_ref())
+                .await
+                .unwrap()
+            {
+                Feature::Page(page) => page,
+                _ => panic!("Expected a page"),
+            }
+        }
+    };
+
+    let mut previous = build(manifest.snapshot(), None).await;
+    assert!(previous.md_content.contains("(/b)"), "{}", previous.md_content);
+
+    // marked, so a page built from it shows it wasn't compiled again
+    previous.md_content.push_str("\n\nfrom the previous build");
+    let reused = build(manifest.snapshot(), Some(Feature::Page(previous.clone()))).await;
+    assert!(reused.md_content.contains("from the previous build"), "{}", reused.md_content);
+
+    // a frontmatter edit leaves the body alone
+    reader.add_file("/content/md/a.md", "---\ntags: [x]\n---\nSee [b](b.md) and ![pic](pic.png)");
+    let retagged = build(manifest.snapshot(), Some(Feature::Page(previous.clone()))).await;
+    assert!(retagged.md_content.contains("from the previous build"), "{}", retagged.md_content);
+
+    // but not once its link target is renamed
+    manifest.remove_by_filename("b.md");
+    manifest.register_claim(claim("b.md", "bee"));
+    let relinked = build(manifest.snapshot(), Some(Feature::Page(previous))).await;
+    assert!(relinked.md_content.contains("(/bee)"), "{}", relinked.md_content);
+    assert!(!relinked.md_content.contains("from the previous build"));
+}
+
+#[tokio::test]
+async fn test_sync_frontmatter_only_change_keeps_body_hash() {
+    let (service, reader, _notifier, config, _repo) = setup_service().await;
+    let change = || {
+        vec![(
+            PathBuf::from("/content/post.md"),
+            config.pages_dir.clone(),
+            FeatureType::Page,
+        )]
+    };
+    let get_post = || async {
+        match service.get_page_by_filename("post.md").await {
+            Some(p) => p,
+            None => panic!("Expected post page"),
+        }
+    };
+
+    reader.add_file(
+        "/content/post.md",
+        "---\nmodified_datetime: 2024-01-01\n---\n# Body",
+    );
+    service.process_batch(change(), vec![]).await.unwrap();
+    let original = get_post().await;
+
+    reader.add_file(
+        "/content/post.md",
+        "---\nmodified_datetime: 2024-02-01\n---\n# Body",
+    );
+    service.process_batch(change(), vec![]).await.unwrap();
+    let retimed = get_post().await;
+
+    assert_ne!(retimed.content_hash, original.content_hash);
+    assert_eq!(retimed.body_hash, original.body_hash);
+    assert_eq!(retimed.md_content, original.md_content);
+    assert_eq!(
+        retimed.modified_datetime,
+        NaiveDate::from_ymd_opt(2024, 2, 1).and_then(|d| d.and_hms_opt(0, 0, 0))
+    );
+
+    reader.add_file(
+        "/content/post.md",
+        "---\nmodified_datetime: 2024-02-01\n---\n# New Body",
+    );
+    service.process_batch(change(), vec![]).await.unwrap();
+    let rewritten = get_post().await;
+
+    assert_ne!(rewritten.body_hash, original.body_hash);
+    assert!(rewritten.md_content.contains("# New Body"));
+}
+
+#[tokio::test]
+async fn test_sync_frontmatter_only_change_relinks_moved_targets() {
+    let (service, reader, _notifier, config, _repo) = setup_service().await;
+    let change = |path: &str| vec![(PathBuf::from(path), config.pages_dir.clone(), FeatureType::Page)];
+
+    reader.add_file("/content/target.md", "---\nidentifier: old-name\n---\n# Target");
+    reader.add_file("/content/post.md", "---\ntags: [a]\n---\n[target](target.md)");
+    service.process_batch(change("/content/target.md"), vec![]).await.unwrap();
+    service.process_batch(change("/content/post.md"), vec![]).await.unwrap();
+    let post = service.get_page_by_filename("post.md").await.unwrap();
+    assert!(post.md_content.contains("[target](/old-name)"), "{}", post.md_content);
+
+    reader.add_file("/content/target.md", "---\nidentifier: new-name\n---\n# Target");
+    service.process_batch(change("/content/target.md"), vec![]).await.unwrap();
+
+    // same body, so only the links can tell the stored one is stale
+    reader.add_file("/content/post.md", "---\ntags: [b]\n---\n[target](target.md)");
+    service.process_batch(change("/content/post.md"), vec![]).await.unwrap();
+    let retagged = service.get_page_by_filename("post.md").await.unwrap();
+    assert_eq!(retagged.body_hash, post.body_hash);
+    assert!(retagged.md_content.contains("[target](/new-name)"), "{}", retagged.md_content);
+}
+
+#[tokio::test]
+async fn test_sync_metadata_only_touch_keeps_modified_datetime() {
+    let (service, reader, _notifier, config, _repo) = setup_service().await;
+    let change = || {
+        vec![(
+            PathBuf::from("/content/post.md"),
+            config.pages_dir.clone(),
+            FeatureType::Page,
+        )]
+    };
+    let at = |day| NaiveDate::from_ymd_opt(2024, 3, day).and_then(|d| d.and_hms_opt(12, 0, 0));
+
+    reader.add_file_with_metadata("/content/post.md", "# Body", at(1), None);
+    service.process_batch(change(), vec![]).await.unwrap();
+
+    // a bumped mtime and a new tag, but the same body
+    reader.add_file_with_metadata("/content/post.md", "---\ntags: [news]\n---\n# Body", at(2), None);
+    service.process_batch(change(), vec![]).await.unwrap();
+    let touched = service.get_page_by_filename("post.md").await.unwrap();
+    assert_eq!(touched.tags, vec!["news"]);
+    assert_eq!(touched.modified_datetime, at(1));
+
+    reader.add_file_with_metadata("/content/post.md", "---\ntags: [news]\n---\n# Edited", at(3), None);
+    service.process_batch(change(), vec![]).await.unwrap();
+    let edited = service.get_page_by_filename("post.md").await.unwrap();
+    assert_eq!(edited.modified_datetime, at(3));
+}
+
+#[tokio::test]
+async fn test_sync_skips_pages_failing_required_frontmatter() {
+    let (service, reader, _notifier, _config, repo) = setup_service_with_config(|config| {
+        config.required_frontmatter = vec!["name".to_string(), "tags".to_string()];
+    })
+    .await;
+
+    reader.add_file("/content/md/complete.md", "---\nname: Complete\ntags: [news]\n---\n# Ok");
+    reader.add_file("/content/md/untagged.md", "---\nname: Untagged\n---\n# Missing tags");
+    reader.add_file("/content/md/bare.md", "# No frontmatter");
+
+    service.full_sync().await.unwrap();
+
+    assert!(service.get_feature_by_identifier("complete").await.is_some());
+    assert!(service.get_feature_by_identifier("untagged").await.is_none());
+    assert!(service.get_feature_by_identifier("bare").await.is_none());
+    assert!(repo.get_page_by_filename("untagged.md").await.unwrap().is_none());
+
+    let report = service.full_sync().await.unwrap();
+    let mut invalid: Vec<(&str, &str)> = report
+        .invalid
+        .iter()
+        .map(|i| (i.filename.as_str(), i.reason.as_str()))
+        .collect();
+    invalid.sort();
+    assert_eq!(
+        invalid,
+        vec![
+            ("bare.md", "missing required frontmatter: name, tags"),
+            ("untagged.md", "missing required frontmatter: tags"),
+        ]
+    );
+    assert_eq!(report.failed, 0);
+}
+
+#[tokio::test]
+async fn test_include_directives_splice_snippets() {
+    let (service, reader, _notifier, _config, repo) = setup_service_with_config(|_| {}).await;
+
+    reader.add_file("/content/md/shared/warning.md", "---\nname: Warning\n---\n> **Careful**\n\n{{include: nested}}");
+    reader.add_file("/content/md/shared/nested.md", "---\nidentifier: nested\n---\nnested text");
+    reader.add_file("/content/md/shared/tip.md", "A tip, see [intro](intro.md).");
+    reader.add_file("/content/md/intro.md", "# Intro");
+    reader.add_file(
+        "/content/md/guide.md",
+        "# Guide\n\n{{include: shared/warning.md}}\n\n{{include: ./shared/tip.md}}\n\n`{{include: shared/tip.md}}`\n\nThe end.",
+    );
+    reader.add_file("/content/md/a.md", "A\n\n{{include: b.md}}");
+    reader.add_file("/content/md/b.md", "B\n\n{{include: a.md}}");
+    reader.add_file("/content/md/dangling.md", "{{include: missing.md}}\n\n{{include: ../../etc/passwd}}");
+    service.full_sync().await.unwrap();
+
+    let guide = repo.get_page_by_identifier("guide").await.unwrap().unwrap();
+    // the directive inside a paragraph of its own is spliced, frontmatter
+    // and all nested includes resolved; the one in inline code is not
+    assert!(guide.md_content.contains("**Careful**"), "{}", guide.md_content);
+    assert!(!guide.md_content.contains("Warning"));
+    assert!(guide.md_content.contains("nested text"));
+    assert!(guide.md_content.contains("A tip"));
+    assert!(guide.md_content.contains("`{{include: shared/tip.md}}`"));
+    assert!(guide.md_content.contains("The end."));
+    // links in a snippet are compiled along with the page
+    assert!(guide.md_content.contains("(/intro)"));
+
+    // A includes B includes A: the cycle stops at the repeat
+    let a = repo.get_page_by_identifier("a").await.unwrap().unwrap();
+    assert!(a.md_content.contains("B"));
+    assert!(a.md_content.contains("include failed: a.md"));
+
+    let dangling = repo.get_page_by_identifier("dangling").await.unwrap().unwrap();
+    assert!(dangling.md_content.contains("include failed: missing.md"));
+    assert!(dangling.md_content.contains("include failed: ../../etc/passwd"));
+
+    // strict mode rejects a page whose includes don't all resolve
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|config| {
+        config.strict_links = true;
+    })
+    .await;
+    reader.add_file("/content/md/snippet.md", "snippet");
+    reader.add_file("/content/md/fine.md", "{{include: snippet.md}}");
+    reader.add_file("/content/md/broken.md", "{{include: missing.md}}");
+    service.full_sync().await.unwrap();
+
+    assert!(service.get_feature_by_identifier("fine").await.is_some());
+    assert!(service.get_feature_by_identifier("broken").await.is_none());
+    let report = service.full_sync().await.unwrap();
+    assert_eq!(report.invalid.len(), 1);
+    assert_eq!(report.invalid[0].reason, "broken includes: missing.md (not found)");
+}
+
+#[tokio::test]
+async fn test_snippet_changes_rebuild_including_pages() {
+    let (service, reader, _notifier, config, repo) = setup_service_with_config(|_| {}).await;
+    let change = |path: &str| vec![(PathBuf::from(path), config.pages_dir.clone(), FeatureType::Page)];
+
+    reader.add_file("/content/md/shared/warning.md", "> Careful\n\n{{include: ./nested.md}}");
+    reader.add_file("/content/md/shared/nested.md", "nested text");
+    reader.add_file("/content/md/guide.md", "# Guide\n\n{{include: shared/warning.md}}");
+    service.full_sync().await.unwrap();
+    let guide = || async { repo.get_page_by_identifier("guide").await.unwrap().unwrap().md_content };
+    assert!(guide().await.contains("nested text"));
+
+    // only the snippet is in the batch, as from a watcher event
+    reader.add_file("/content/md/shared/warning.md", "> Very careful\n\n{{include: ./nested.md}}");
+    let report = service.process_batch(change("/content/md/shared/warning.md"), vec![]).await.unwrap();
+    assert!(guide().await.contains("Very careful"), "{}", guide().await);
+    assert!(report.written.changed.contains("guide"));
+    assert!(service.get_page_by_filename("guide.md").await.unwrap().md_content.contains("Very careful"));
+
+    // a snippet nested in another reaches the page too
+    reader.add_file("/content/md/shared/nested.md", "nested text, edited");
+    service.process_batch(change("/content/md/shared/nested.md"), vec![]).await.unwrap();
+    assert!(guide().await.contains("nested text, edited"), "{}", guide().await);
+
+    // and so does one going away
+    reader.remove_file("/content/md/shared/warning.md");
+    service
+        .process_batch(vec![], vec![PathBuf::from("/content/md/shared/warning.md")])
+        .await
+        .unwrap();
+    assert!(guide().await.contains("include failed: shared/warning.md"), "{}", guide().await);
+}
+
+#[tokio::test]
+async fn test_strict_links_reject_pages_with_broken_links() {
+    let (service, reader, notifier, config, repo) = setup_service_with_config(|config| {
+        config.strict_links = true;
+    })
+    .await;
+
+    reader.add_file("/content/md/target.md", "# Target");
+    reader.add_file(
+        "/content/md/good.md",
+        "[ok](target.md) [out](https://example.com/x.md) [mail](mailto:a@b.c) [top](#top) ![img](pic.png)",
+    );
+    reader.add_file("/content/md/bad.md", "[ok](target.md) [dead](missing.md) [gone](/nowhere)");
+
+    service.full_sync().await.unwrap();
+
+    assert!(service.get_feature_by_identifier("good").await.is_some());
+    assert!(service.get_feature_by_identifier("bad").await.is_none());
+
+    let report = service.full_sync().await.unwrap();
+    let invalid: Vec<(&str, &str)> = report
+        .invalid
+        .iter()
+        .map(|i| (i.filename.as_str(), i.reason.as_str()))
+        .collect();
+    assert_eq!(invalid, vec![("bad.md", "broken links: missing.md, /nowhere")]);
+
+    // lenient mode keeps passing the links through
+    let mut lenient_config = (*config).clone();
+    lenient_config.strict_links = false;
+    let lenient = SyncService::new(
+        repo.clone(),
+        Arc::new(reader.clone()),
+        Box::new(notifier.clone()),
+        Arc::new(lenient_config),
+    )
+    .await
+    .unwrap();
+    let bad = lenient.get_page_by_filename("bad.md").await.unwrap();
+    assert!(bad.md_content.contains("(missing.md)"), "{}", bad.md_content);
+}
+
+#[tokio::test]
+async fn test_dry_run_categorizes_failures() {
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|config| {
+        config.required_frontmatter = vec!["name".to_string()];
+    })
+    .await;
+
+    reader.add_file("/content/md/complete.md", "---\nname: Complete\n---\n# Ok");
+
+    service.full_sync().await.unwrap();
+
+    reader.add_file("/content/md/bare.md", "# No frontmatter");
+
+    let report = service.dry_run_sync().await.unwrap();
+    let failed: Vec<(&str, &str)> = report
+        .failed
+        .iter()
+        .map(|f| (f.filename.as_str(), f.kind))
+        .collect();
+    assert_eq!(failed, vec![("bare.md", "validation")]);
+    assert!(report.failed[0].error.contains("missing required frontmatter: name"));
+}
+
+#[tokio::test]
+async fn test_unsynced_dry_run_checks_every_file() {
+    let repo = chasqui_db::testutil::create_test_repository().await;
+    let reader = MockContentReader::new();
+
+    reader.add_file("/content/md/fine.md", "# Fine");
+    reader.add_file("/content/md/dangling.md", "[gone](missing.md) [ok](fine.md)");
+    reader.add_file("/content/md/first.md", "---\nidentifier: same\n---\n# One");
+    reader.add_file("/content/md/second.md", "---\nidentifier: same\n---\n# Two");
+
+    let service = SyncService::unsynced(
+        repo.clone(),
+        Arc::new(reader.clone()),
+        Box::new(MockBuildNotifier::new()),
+        mock_config(PathBuf::from("/content")),
+    );
+    let report = service.dry_run_sync().await.unwrap();
+
+    // nothing was synced, so every file counts rather than only changed ones
+    let mut inserts = report.inserts.clone();
+    inserts.sort();
+    assert_eq!(inserts, vec!["dangling.md", "fine.md"]);
+    let mut collisions = report.collisions.clone();
+    collisions.sort();
+    assert_eq!(collisions, vec!["first.md", "second.md"]);
+    let broken: Vec<(&str, &str)> = report
+        .broken_links
+        .iter()
+        .map(|b| (b.filename.as_str(), b.link.as_str()))
+        .collect();
+    assert_eq!(broken, vec![("dangling.md", "missing.md")]);
+    assert!(report.has_problems());
+    assert!(service.get_all_pages().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_sync_reads_sidecar_metadata_for_pages_without_frontmatter() {
+    let (service, reader, _notifier, _config, _repo) = setup_service().await;
+
+    reader.add_file("/content/generated.md", "# Generated\n\nNo frontmatter here.");
+    reader.add_file(
+        "/content/generated.md.meta.yaml",
+        "identifier: gen\nname: Generated Page\ntags: [auto]\nsource: pipeline",
+    );
+    // frontmatter in the page wins over its sidecar
+    reader.add_file("/content/written.md", "---\nname: Written\n---\n# Written");
+    reader.add_file("/content/written.md.meta.yaml", "name: Ignored");
+    reader.add_file("/content/plain.md", "# Plain");
+    service.full_sync().await.unwrap();
+
+    let generated = service.get_page_by_filename("generated.md").await.unwrap();
+    assert_eq!(generated.identifier, "gen");
+    assert_eq!(generated.name.as_deref(), Some("Generated Page"));
+    assert_eq!(generated.tags, vec!["auto"]);
+    assert_eq!(generated.metadata["source"], "pipeline");
+    assert!(!generated.md_content.contains("identifier"));
+
+    let written = service.get_page_by_filename("written.md").await.unwrap();
+    assert_eq!(written.name.as_deref(), Some("Written"));
+    assert!(service.get_page_by_filename("plain.md").await.is_some());
+
+    // only the sidecar changed, which still resyncs the page
+    reader.add_file("/content/generated.md.meta.yaml", "identifier: gen\nname: Renamed");
+    service.full_sync().await.unwrap();
+    let generated = service.get_page_by_filename("generated.md").await.unwrap();
+    assert_eq!(generated.name.as_deref(), Some("Renamed"));
+    assert!(generated.tags.is_empty());
+}
+
+#[tokio::test]
+async fn test_sync_links_preserve_fragments() {
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|config| {
+        config.nginx_media_prefixes = true;
+    })
+    .await;
+
+    reader.add_file("/content/md/target.md", "---\nidentifier: target-id\n---\n# Target");
+    reader.add_file("/content/md/guides/sibling.md", "# Sibling");
+    reader.add_file(
+        "/content/md/guides/intro.md",
+        "[id](target-id#section) [rel](./sibling.md#part-2) [abs](/target.md#top) \
+         [local](#only) [nested](target-id#a#b) [dotted](target-id#v1.mp3)",
+    );
+
+    service.full_sync().await.unwrap();
+
+    let intro = service.get_page_by_filename("guides/intro.md").await.unwrap();
+    let content = &intro.md_content;
+    assert!(content.contains("[id](/target-id#section)"), "{}", content);
+    assert!(content.contains("[rel](/guides/sibling#part-2)"), "{}", content);
+    assert!(content.contains("[abs](/target-id#top)"), "{}", content);
+    assert!(content.contains("[local](#only)"), "{}", content);
+    assert!(content.contains("[nested](/target-id#a#b)"), "{}", content);
+    assert!(content.contains("[dotted](/target-id#v1.mp3)"), "{}", content);
+}
+
+#[tokio::test]
+async fn test_sync_html_link_resolution_toggle() {
+    for resolve_html_links in [false, true] {
+        let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|config| {
+            config.resolve_html_links = resolve_html_links;
+        })
+        .await;
+
+        reader.add_file("/content/md/about.md", "# About");
+        reader.add_file("/content/md/docs/guide.md", "---\nidentifier: the-guide\n---\n# Guide");
+        reader.add_file(
+            "/content/md/docs/links.md",
+            "[html](/about.html) [md](/about.md) [bare](about) \
+             [rel](./guide.html#setup) [missing](/nowhere.html)",
+        );
+
+        service.full_sync().await.unwrap();
+
+        let links = service.get_page_by_filename("docs/links.md").await.unwrap();
+        let content = &links.md_content;
+
+        assert!(content.contains("[md](/about)"), "{}", content);
+        assert!(content.contains("[bare](/about)"), "{}", content);
+        assert!(content.contains("[missing](/nowhere.html)"), "{}", content);
+        if resolve_html_links {
+            assert!(content.contains("[html](/about)"), "{}", content);
+            assert!(content.contains("[rel](/the-guide#setup)"), "{}", content);
+        } else {
+            assert!(content.contains("[html](/about.html)"), "{}", content);
+            assert!(content.contains("[rel](./guide.html#setup)"), "{}", content);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_sync_datetime_aliases_and_formats() {
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|config| {
+        config.created_datetime_aliases = vec!["date".to_string(), "published".to_string()];
+        config.modified_datetime_aliases = vec!["last_modified_at".to_string()];
+    })
+    .await;
+
+    let fs_time = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    let at = |y, m, d, h, min| NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0);
+
+    let files = [
+        ("jekyll", "date: 2024-01-31 09:30:00 +0100\nlast_modified_at: 2024-02-01 10:00:00"),
+        ("published", "published: Tue, 5 Mar 2024 08:15:00 +0000"),
+        ("explicit", "created_datetime: 2024-04-01\ndate: 2023-01-01"),
+    ];
+    for (name, frontmatter) in files {
+        reader.add_file_with_metadata(
+            &format!("/content/md/{}.md", name),
+            &format!("---\n{}\n---\n# Title", frontmatter),
+            Some(fs_time),
+            Some(fs_time),
+        );
+    }
+
+    service.full_sync().await.unwrap();
+
+    let jekyll = service.get_page_by_filename("jekyll.md").await.unwrap();
+    assert_eq!(jekyll.created_datetime, at(2024, 1, 31, 8, 30));
+    assert_eq!(jekyll.modified_datetime, at(2024, 2, 1, 10, 0));
+
+    let published = service.get_page_by_filename("published.md").await.unwrap();
+    assert_eq!(published.created_datetime, at(2024, 3, 5, 8, 15));
+    assert_eq!(published.modified_datetime, Some(fs_time));
+
+    let explicit = service.get_page_by_filename("explicit.md").await.unwrap();
+    assert_eq!(explicit.created_datetime, at(2024, 4, 1, 0, 0));
+}
+
+#[tokio::test]
+async fn test_sync_page_aliases_share_identifier_namespace() {
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|_| {}).await;
+
+    reader.add_file(
+        "/content/md/moved.md",
+        "---\nidentifier: new-home\naliases: [old-home, Old Home, new-home]\n---\n# Moved",
+    );
+    reader.add_file("/content/md/linker.md", "[home](old-home#top)");
+    service.full_sync().await.unwrap();
+
+    // aliases are sanitized like identifiers, so the two spellings fold into
+    // one and the page's own identifier is dropped
+    let page = service.get_page_by_filename("moved.md").await.unwrap();
+    assert_eq!(page.aliases, vec!["old-home"]);
+    assert_eq!(service.resolve_alias("old-home").await.as_deref(), Some("new-home"));
+    assert!(service.get_feature_by_identifier("old-home").await.is_none());
+
+    // links to an alias point straight at the canonical identifier
+    let linker = service.get_page_by_filename("linker.md").await.unwrap();
+    assert!(linker.md_content.contains("(/new-home#top)"), "{}", linker.md_content);
+
+    // an alias already held by another page rejects the newcomer
+    reader.add_file("/content/md/squatter.md", "---\nidentifier: old-home\n---\n# Squat");
+    let report = service.full_sync().await.unwrap();
+    assert_eq!(report.collisions, 1);
+    assert!(service.get_page_by_filename("squatter.md").await.is_none());
+
+    // within one batch, an identifier and an alias clash like two identifiers
+    reader.remove_file("/content/md/squatter.md");
+    reader.add_file("/content/md/a.md", "---\nidentifier: shared\n---\n# A");
+    reader.add_file("/content/md/b.md", "---\naliases: [shared]\n---\n# B");
+    service.full_sync().await.unwrap();
+    assert!(service.get_page_by_filename("a.md").await.is_none());
+    assert!(service.get_page_by_filename("b.md").await.is_none());
+
+    // dropping an alias frees it
+    reader.add_file("/content/md/moved.md", "---\nidentifier: new-home\n---\n# Moved");
+    service.full_sync().await.unwrap();
+    assert_eq!(service.resolve_alias("old-home").await, None);
+}
+
+#[tokio::test]
+async fn test_malformed_frontmatter_is_reported_not_fatal() {
+    let (service, reader, _notifier, _config, repo) = setup_service().await;
+
+    reader.add_file("/content/broken.md", "---\ntitle: [unclosed\n---\nStill here.");
+    reader.add_file("/content/fine.md", "---\nname: Fine\n---\nAll good.");
+
+    let report = service.full_sync().await.unwrap();
+    assert!(report.invalid.is_empty());
+    assert_eq!(report.frontmatter_errors.len(), 1);
+    assert_eq!(report.frontmatter_errors[0].filename, "broken.md");
+
+    let broken = service.get_page_by_filename("broken.md").await.unwrap();
+    assert!(broken.md_content.contains("Still here."));
+    assert!(JsonPage::from(&broken).has_frontmatter_error);
+
+    let fine = service.get_page_by_filename("fine.md").await.unwrap();
+    assert!(!JsonPage::from(&fine).has_frontmatter_error);
+
+    // the flag survives a round trip through the database
+    let stored = repo.get_page_by_filename("broken.md").await.unwrap().unwrap();
+    assert!(stored.frontmatter_error.is_some());
+}
+
+#[tokio::test]
+async fn test_content_extensions_decide_what_is_a_page() {
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|config| {
+        config.content_extensions = vec!["md".to_string(), "markdown".to_string()];
+    })
+    .await;
+
+    reader.add_file("/content/md/short.md", "# Short");
+    reader.add_file("/content/md/long.markdown", "# Long");
+    reader.add_file("/content/md/widget.mdx", "# Widget");
+
+    service.full_sync().await.unwrap();
+
+    let mut identifiers: Vec<String> = service
+        .get_all_pages()
+        .await
+        .into_iter()
+        .map(|p| p.identifier)
+        .collect();
+    identifiers.sort();
+    assert_eq!(identifiers, vec!["long", "short"]);
+
+    // the watcher filters events through the same check
+    assert!(service.identify_mount(&PathBuf::from("/content/md/new.MARKDOWN")).is_some());
+    assert!(service.identify_mount(&PathBuf::from("/content/md/widget.mdx")).is_none());
+}
+
+#[tokio::test]
+async fn test_links_resolve_through_content_extensions() {
+    let (service, reader, _notifier, _config, _repo) = setup_service_with_config(|config| {
+        config.content_extensions = vec!["markdown".to_string()];
+        config.folder_index_pages = true;
+        config.resolve_html_links = true;
+    })
+    .await;
+
+    reader.add_file("/content/md/about.markdown", "# About");
+    reader.add_file("/content/md/blog/index.markdown", "# Blog");
+    reader.add_file(
+        "/content/md/links.markdown",
+        "[html](/about.html) [folder](blog/) [missing](/gone.markdown) [asset](/gone.pdf)",
+    );
+
+    service.full_sync().await.unwrap();
+
+    let links = service.get_page_by_filename("links.markdown").await.unwrap();
+    assert!(links.md_content.contains("[html](/about)"), "{}", links.md_content);
+    assert!(links.md_content.contains("[folder](/blog)"), "{}", links.md_content);
+
+    let broken: Vec<String> = service
+        .dry_run_sync()
+        .await
+        .unwrap()
+        .broken_links
+        .into_iter()
+        .map(|b| b.link)
+        .collect();
+    assert_eq!(broken, vec!["/gone.markdown"]);
+}
+
+#[tokio::test]
+async fn test_sync_keeps_more_delimiter_for_excerpt() {
+    let (service, reader, _notifier, _config, _repo) = setup_service().await;
+    reader.add_file(
+        "/content/post.md",
+        "Lead with a [link](./other.md).\n\n<!-- more -->\n\nThe rest.",
+    );
+    reader.add_file("/content/other.md", "# Other");
+    service.full_sync().await.unwrap();
+
+    let page = service.get_page_by_filename("post.md").await.unwrap();
+    let json = JsonPage::from(&page);
+    assert_eq!(
+        json.excerpt_html.as_deref(),
+        Some("<p>Lead with a <a href=\"/other\">link</a>.</p>\n")
+    );
+    assert!(render_html(&page.md_content).contains("The rest."));
+}
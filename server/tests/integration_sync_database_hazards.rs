@@ -1,7 +1,7 @@
 mod common;
 
 use chasqui_core::features::model::FeatureType;
-use chasqui_core::io::{ContentMetadata, ContentReader, SyncFile};
+use chasqui_core::io::{ContentMetadata, ContentReader, PathStream, SyncFile};
 use chasqui_server::services::sync::SyncService;
 use chasqui_server::testutil::{MockBuildNotifier, MockContentReader};
 use common::mock_config;
@@ -50,8 +50,8 @@ impl ContentReader for SlowContentReader {
     async fn list_files_by_extension(&self, root: &Path, ext: String) {
         self.inner.list_files_by_extension(root, ext).await
     }
-    async fn list_markdown_files(&self, root: &Path) -> Result<Vec<PathBuf>> {
-        self.inner.list_markdown_files(root).await
+    fn stream_markdown_files<'a>(&'a self, root: &'a Path, extensions: &'a [String]) -> PathStream<'a> {
+        self.inner.stream_markdown_files(root, extensions)
     }
 }
 
@@ -5,7 +5,8 @@ use chasqui_core::features::model::{Feature, FeatureType};
 use chasqui_server::services::sync::SyncService;
 use chasqui_server::testutil::{MockBuildNotifier};
 use chasqui_core::io::local::LocalContentReader;
-use common::{mock_config, setup_service};
+use chasqui_core::io::DEFAULT_MAX_FILE_BYTES;
+use common::mock_config;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -39,18 +40,71 @@ async fn test_sync_handles_symlink() {
         images_dir: content_dir.join("images"),
         audio_dir: content_dir.join("audio"),
         videos_dir: content_dir.join("videos"),
+        extra_content_dirs: Vec::new(),
         page_strip_extension: true,
         asset_strip_extension: false,
         serve_home: true,
         home_identifier: "index".into(),
+        folder_index_pages: false,
+        resolve_html_links: false,
         webhook_url: "".into(),
         webhook_secret: "".into(),
+        webhook_payload: None,
         port: 3000,
         nginx_media_prefixes: false,
+        site_url: "http://localhost:3000".into(),
+        watch_debounce_ms: 1500,
+        cors_allowed_origins: Vec::new(),
+        slugify_identifiers: false,
+        admin_token: "".into(),
+        required_frontmatter: Vec::new(),
+        html_template_path: None,
+        created_datetime_aliases: Vec::new(),
+        modified_datetime_aliases: Vec::new(),
+        max_request_body_bytes: 1024 * 1024,
+        max_list_pages: 1000,
+        soft_delete: false,
+        tombstone_retention_days: 30,
+        git_repo: None,
+        git_ref: "HEAD".into(),
+        git_poll_secs: 30,
+        sqlite_journal_mode: "WAL".to_string(),
+        sqlite_synchronous: "NORMAL".to_string(),
+        sqlite_busy_timeout_ms: 5000,
+        sqlite_cache_size: -64000,
+        sqlite_foreign_keys: true,
+        base_path: String::new(),
+        compiled_cache_entries: 0,
+        normalize_slashes: false,
+        strict_links: false,
+        max_batch_pages: 50,
+        content_extensions: vec!["md".to_string()],
+        json_datetime_format: Default::default(),
+        not_found_identifier: "404".into(),
+        dedupe_page_bodies: false,
+        page_cache_max_age: 300,
+        stable_page_after_days: 0,
+        stable_page_max_age: 86400,
+        list_cache_max_age: 60,
+        max_file_bytes: 10_485_760,
+        max_write_connections: 1,
+        passthrough_extensions: Vec::new(),
+        create_content_dirs: false,
+        content_dir_retries: 0,
+        collision_policy: Default::default(),
+        link_check_interval_secs: 0,
+        link_check_concurrency: 4,
+        link_check_timeout_secs: 10,
+        webhook_coalesce: true,
+        html_layouts_dir: None,
+        track_accessed_datetime: false,
+        identifier_strategy: Default::default(),
+        hide_unpublished_pages: false,
     });
 
     let reader = Arc::new(LocalContentReader {
         root_path: PathBuf::from("/"),
+        max_file_bytes: DEFAULT_MAX_FILE_BYTES,
     });
 
     let service = timeout(
@@ -69,4 +123,77 @@ async fn test_sync_handles_symlink() {
     // Symlink handling varies by filesystem; just verify it doesn't crash
     let pages = service.get_all_features_by_type(FeatureType::Page).await;
     assert!(pages.len() >= 1);
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_sync_skips_files_over_max_size() {
+    let dir = tempdir().unwrap();
+    let md_dir = dir.path().join("md");
+    fs::create_dir_all(&md_dir).unwrap();
+    fs::write(md_dir.join("small.md"), "# Small").unwrap();
+    fs::write(md_dir.join("huge.md"), format!("# Huge\n{}", "x".repeat(2048))).unwrap();
+
+    let repo = chasqui_db::testutil::create_test_repository().await;
+    let notifier = MockBuildNotifier::new();
+    let reader = Arc::new(LocalContentReader {
+        root_path: dir.path().to_path_buf(),
+        max_file_bytes: 1024,
+    });
+    let service = SyncService::new(repo.clone(), reader, Box::new(notifier), mock_config(dir.path().to_path_buf()))
+        .await
+        .unwrap();
+
+    let report = service.full_sync().await.unwrap();
+    assert_eq!(report.failed, 1);
+    assert!(service.get_feature_by_identifier("small").await.is_some());
+    assert!(service.get_feature_by_identifier("huge").await.is_none());
+    assert!(repo.get_page_by_filename("huge.md").await.unwrap().is_none());
+}
+
+async fn local_service(root: &std::path::Path, track_accessed_datetime: bool) -> (SyncService, chasqui_db::SqliteRepository) {
+    let repo = chasqui_db::testutil::create_test_repository().await;
+    let notifier = MockBuildNotifier::new();
+    let mut config = (*mock_config(root.to_path_buf())).clone();
+    config.track_accessed_datetime = track_accessed_datetime;
+    let reader = Arc::new(LocalContentReader {
+        root_path: root.to_path_buf(),
+        max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+    });
+    let service = SyncService::new(repo.clone(), reader, Box::new(notifier), Arc::new(config))
+        .await
+        .unwrap();
+    (service, repo)
+}
+
+#[tokio::test]
+async fn test_sync_keeps_access_time_only_when_tracked() {
+    let dir = tempdir().unwrap();
+    let md_dir = dir.path().join("md");
+    fs::create_dir_all(&md_dir).unwrap();
+    let path = md_dir.join("stale.md");
+    fs::write(&path, "# Stale").unwrap();
+
+    // older than the ctime, so a plain read would bump it on relatime mounts
+    fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_times(fs::FileTimes::new().set_accessed(std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_000)))
+        .unwrap();
+    let expected = chrono::DateTime::from_timestamp(1_700_000_000, 0).map(|dt| dt.naive_utc());
+
+    let (service, repo) = local_service(dir.path(), false).await;
+    service.full_sync().await.unwrap();
+    let page = repo.get_page_by_filename("stale.md").await.unwrap().unwrap();
+    assert_eq!(page.accessed_datetime, None);
+
+    // every sync of an edit reads the file again, which mustn't count as an access
+    let (service, repo) = local_service(dir.path(), true).await;
+    for body in ["# Stale", "# Stale, edited", "# Stale, edited again"] {
+        fs::write(&path, body).unwrap();
+        service.full_sync().await.unwrap();
+
+        let page = repo.get_page_by_filename("stale.md").await.unwrap().unwrap();
+        assert!(page.md_content.contains(body), "{}", page.md_content);
+        assert_eq!(page.accessed_datetime, expected);
+    }
+}